@@ -233,7 +233,7 @@ where
     pub(super) const unsafe fn into_internal_array(
         self,
     ) -> InternalArray<T, TAllocator> {
-        let internal = core::ptr::read(&self.internal);
+        let internal = core::ptr::read(&raw const self.internal);
         core::mem::forget(self);
         internal
     }
@@ -128,7 +128,7 @@ fn test_rand_array() {
     for _ in 0..10 {
         let mut rng = rand::thread_rng();
         let start = rng.gen_range(10000..20000) as u32;
-        let end = rng.gen_range(0..5000) + 15000 as u32;
+        let end = rng.gen_range(0..5000) + 15000_u32;
 
         let mut dyn_array = DynamicArray::new().unwrap();
         let mut expected = Vec::new();
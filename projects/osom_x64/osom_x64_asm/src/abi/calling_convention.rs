@@ -0,0 +1,144 @@
+use osom_x64_encoder::models::{GPR, XMM};
+
+use super::{system_v, win64};
+
+/// Represents a calling convention, i.e. how arguments are passed and which
+/// registers have to be preserved by the callee.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum CallingConvention {
+    /// See [`system_v`].
+    SystemV,
+
+    /// See [`win64`].
+    Win64,
+}
+
+impl CallingConvention {
+    /// Returns registers holding integer and pointer arguments, in order.
+    ///
+    /// # Notes
+    /// See [`CallingConvention::assigns_registers_by_position`].
+    #[must_use]
+    #[inline(always)]
+    pub const fn integer_arguments(self) -> &'static [GPR] {
+        match self {
+            CallingConvention::SystemV => &system_v::INTEGER_ARGUMENTS,
+            CallingConvention::Win64 => &win64::INTEGER_ARGUMENTS,
+        }
+    }
+
+    /// Returns registers holding floating point arguments, in order.
+    #[must_use]
+    #[inline(always)]
+    pub const fn float_arguments(self) -> &'static [XMM] {
+        match self {
+            CallingConvention::SystemV => &system_v::FLOAT_ARGUMENTS,
+            CallingConvention::Win64 => &win64::FLOAT_ARGUMENTS,
+        }
+    }
+
+    /// Returns the register holding integer and pointer results.
+    #[must_use]
+    #[inline(always)]
+    pub const fn integer_return(self) -> GPR {
+        GPR::RAX
+    }
+
+    /// Returns the register holding floating point results.
+    #[must_use]
+    #[inline(always)]
+    pub const fn float_return(self) -> XMM {
+        XMM::XMM0
+    }
+
+    /// Returns general purpose registers the callee has to preserve, in the
+    /// order they are saved by prologues.
+    #[must_use]
+    #[inline(always)]
+    pub const fn callee_saved(self) -> &'static [GPR] {
+        match self {
+            CallingConvention::SystemV => &system_v::CALLEE_SAVED,
+            CallingConvention::Win64 => &win64::CALLEE_SAVED,
+        }
+    }
+
+    /// Returns XMM registers the callee has to preserve, in the order they are
+    /// saved by prologues.
+    #[must_use]
+    #[inline(always)]
+    pub const fn callee_saved_xmm(self) -> &'static [XMM] {
+        match self {
+            CallingConvention::SystemV => &system_v::CALLEE_SAVED_XMM,
+            CallingConvention::Win64 => &win64::CALLEE_SAVED_XMM,
+        }
+    }
+
+    /// Returns `true` if arguments are assigned to registers by their position,
+    /// i.e. the `n`-th argument always uses the `n`-th integer or float register,
+    /// and the unused register of the other class is skipped.
+    #[must_use]
+    #[inline(always)]
+    pub const fn assigns_registers_by_position(self) -> bool {
+        matches!(self, CallingConvention::Win64)
+    }
+
+    /// Returns the size of the area the caller reserves above the return address
+    /// for the callee, before any stack arguments.
+    #[must_use]
+    #[inline(always)]
+    pub const fn shadow_space_size(self) -> u32 {
+        match self {
+            CallingConvention::SystemV => 0,
+            CallingConvention::Win64 => win64::SHADOW_SPACE_SIZE,
+        }
+    }
+
+    /// Returns `true` if `reg` (of any size) has to be preserved by the callee.
+    #[must_use]
+    pub const fn is_callee_saved(self, reg: GPR) -> bool {
+        let callee_saved = self.callee_saved();
+        let mut idx = 0;
+        while idx < callee_saved.len() {
+            if callee_saved[idx].index() == reg.index() {
+                return true;
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    /// Returns `true` if `reg` has to be preserved by the callee.
+    #[must_use]
+    pub const fn is_callee_saved_xmm(self, reg: XMM) -> bool {
+        let callee_saved = self.callee_saved_xmm();
+        let mut idx = 0;
+        while idx < callee_saved.len() {
+            if callee_saved[idx].index() == reg.index() {
+                return true;
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    /// Returns the size of the area below RSP usable without adjusting RSP.
+    #[must_use]
+    #[inline(always)]
+    pub const fn red_zone_size(self) -> u32 {
+        match self {
+            CallingConvention::SystemV => system_v::RED_ZONE_SIZE,
+            CallingConvention::Win64 => 0,
+        }
+    }
+
+    /// Returns the alignment of RSP required right before `call` instructions.
+    #[must_use]
+    #[inline(always)]
+    pub const fn stack_alignment(self) -> u32 {
+        match self {
+            CallingConvention::SystemV => system_v::STACK_ALIGNMENT,
+            CallingConvention::Win64 => win64::STACK_ALIGNMENT,
+        }
+    }
+}
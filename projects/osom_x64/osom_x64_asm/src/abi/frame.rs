@@ -0,0 +1,347 @@
+use osom_x64_encoder::{
+    encoder::{arith, errors::EncodingError, misc, mov, sse, stack},
+    models::{MachineSize, Memory, GPR, XMM},
+};
+
+use super::{FunctionDescription, ValueClass};
+use crate::{unwind::CfiDirective, Assembler, AssemblerError};
+
+/// Size of a stack slot, of the return address and of each pushed register.
+const SLOT_SIZE: u32 = 8;
+
+/// Size of a stack slot holding an XMM register.
+const XMM_SLOT_SIZE: u64 = 16;
+
+/// Location of a function argument right after the prologue.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ArgumentLocation {
+    Gpr(GPR),
+    Xmm(XMM),
+
+    /// RSP based stack slot.
+    Stack(Memory),
+}
+
+/// Stack frame of a function, computed from [`FunctionDescription`].
+///
+/// The frame looks as follows, from higher to lower addresses:
+/// 1. stack arguments, the shadow space (Win64 only) and the return address,
+/// 2. RBP, if the frame pointer was requested,
+/// 3. clobbered callee saved general purpose registers,
+/// 4. padding keeping RSP aligned,
+/// 5. 16-byte aligned slots of clobbered callee saved XMM registers,
+/// 6. local variables,
+/// 7. the shadow space for callees (Win64 only, if the function makes calls).
+///
+/// # Notes
+/// Leaf functions whose locals fit in the red zone (System V only) and which
+/// save no XMM registers do not adjust RSP at all, and keep locals below it
+/// instead.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct FrameLayout {
+    frame_pointer: bool,
+    saved: Vec<GPR>,
+    saved_xmm: Vec<(XMM, i32)>,
+    stack_adjustment: u32,
+    locals_offset: i32,
+    arguments: Vec<ArgumentLocation>,
+}
+
+impl FrameLayout {
+    /// Computes the frame of `function`.
+    ///
+    /// # Errors
+    /// * [`AssemblerError::Encoding`] with `InvalidRegisterSize` if any clobbered
+    ///   register is not a general purpose one.
+    /// * [`AssemblerError::FrameTooLarge`] if the frame does not fit in 31 bits.
+    pub fn new(function: &FunctionDescription) -> Result<Self, AssemblerError> {
+        let convention = function.convention();
+        for reg in function.clobbered() {
+            if matches!(reg.size(), MachineSize::None) {
+                return Err(EncodingError::InvalidRegisterSize.into());
+            }
+        }
+
+        let frame_pointer = function.frame_pointer();
+        let saved: Vec<GPR> = convention
+            .callee_saved()
+            .iter()
+            .copied()
+            .filter(|reg| !(frame_pointer && *reg == GPR::RBP))
+            .filter(|reg| {
+                function
+                    .clobbered()
+                    .iter()
+                    .any(|c| c.index() == reg.index())
+            })
+            .collect();
+
+        let saved_xmm: Vec<XMM> = convention
+            .callee_saved_xmm()
+            .iter()
+            .copied()
+            .filter(|reg| {
+                function
+                    .clobbered_xmm()
+                    .iter()
+                    .any(|c| c.index() == reg.index())
+            })
+            .collect();
+
+        let pushes = saved.len() as u64 + u64::from(frame_pointer);
+        let locals_size = u64::from(function.locals_size());
+        let uses_red_zone = !function.makes_calls()
+            && saved_xmm.is_empty()
+            && locals_size <= u64::from(convention.red_zone_size());
+
+        let mut xmm_offsets = Vec::with_capacity(saved_xmm.len());
+        let (stack_adjustment, locals_offset) = if uses_red_zone {
+            (0, -i64::from(function.locals_size()))
+        } else {
+            let shadow_space = if function.makes_calls() {
+                convention.shadow_space_size()
+            } else {
+                0
+            };
+            let mut body_size = u64::from(shadow_space) + locals_size;
+            if !saved_xmm.is_empty() {
+                body_size = body_size.next_multiple_of(XMM_SLOT_SIZE);
+                for _ in &saved_xmm {
+                    xmm_offsets.push(body_size);
+                    body_size += XMM_SLOT_SIZE;
+                }
+            }
+
+            // RSP is misaligned by the return address on entry. Pushes and the
+            // adjustment together have to compensate for that.
+            let alignment = u64::from(convention.stack_alignment());
+            let mut adjustment = body_size.next_multiple_of(alignment);
+            if (u64::from(SLOT_SIZE) * (pushes + 1)) % alignment != 0 {
+                adjustment += u64::from(SLOT_SIZE);
+            }
+            (adjustment, i64::from(shadow_space))
+        };
+
+        let frame_size = u64::from(SLOT_SIZE) * (pushes + 1) + stack_adjustment;
+        let Ok(stack_adjustment) = u32::try_from(stack_adjustment) else {
+            return Err(AssemblerError::FrameTooLarge);
+        };
+        let Ok(locals_offset) = i32::try_from(locals_offset) else {
+            return Err(AssemblerError::FrameTooLarge);
+        };
+        let saved_xmm = saved_xmm
+            .into_iter()
+            .zip(xmm_offsets)
+            .map(|(reg, offset)| Ok((reg, i32::try_from(offset)?)))
+            .collect::<Result<Vec<_>, core::num::TryFromIntError>>()
+            .map_err(|_| AssemblerError::FrameTooLarge)?;
+
+        let arguments = place_arguments(function, frame_size)?;
+        Ok(Self {
+            frame_pointer,
+            saved,
+            saved_xmm,
+            stack_adjustment,
+            locals_offset,
+            arguments,
+        })
+    }
+
+    /// Returns callee saved registers preserved by the prologue, in push order.
+    /// Does not include RBP pushed as the frame pointer.
+    #[must_use]
+    #[inline(always)]
+    pub fn saved_registers(&self) -> &[GPR] {
+        &self.saved
+    }
+
+    /// Returns callee saved XMM registers preserved by the prologue.
+    pub fn saved_xmm_registers(&self) -> impl Iterator<Item = XMM> + '_ {
+        self.saved_xmm.iter().map(|(reg, _)| *reg)
+    }
+
+    /// Returns the number of bytes subtracted from RSP after pushing registers.
+    #[must_use]
+    #[inline(always)]
+    pub const fn stack_adjustment(&self) -> u32 {
+        self.stack_adjustment
+    }
+
+    /// Returns locations of the function's arguments, valid after the prologue.
+    #[must_use]
+    #[inline(always)]
+    pub fn arguments(&self) -> &[ArgumentLocation] {
+        &self.arguments
+    }
+
+    /// Returns the RSP based address of the local variable at `offset` within
+    /// the locals area.
+    ///
+    /// # Errors
+    /// [`AssemblerError::FrameTooLarge`] if the address is not representable.
+    pub fn local(&self, offset: u32) -> Result<Memory, AssemblerError> {
+        let displacement = i64::from(self.locals_offset) + i64::from(offset);
+        let Ok(displacement) = i32::try_from(displacement) else {
+            return Err(AssemblerError::FrameTooLarge);
+        };
+        Ok(Memory::based(GPR::RSP, displacement)?)
+    }
+
+    /// Appends the prologue: frame pointer setup, pushes of saved registers, the
+    /// stack adjustment and stores of saved XMM registers. Each instruction
+    /// changing the frame is followed by the matching [`CfiDirective`].
+    ///
+    /// # Notes
+    /// Saved XMM registers are not described by the recorded directives.
+    ///
+    /// # Errors
+    /// Propagates encoding errors.
+    pub fn emit_prologue(&self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        let mut cfa_offset = SLOT_SIZE;
+        if self.frame_pointer {
+            asm.emit(stack::encode_push(GPR::RBP)?);
+            cfa_offset += SLOT_SIZE;
+            asm.emit_cfi(CfiDirective::DefCfaOffset(cfa_offset));
+            asm.emit_cfi(saved_at(GPR::RBP, cfa_offset));
+            asm.emit(mov::encode_mov_reg_reg(GPR::RBP, GPR::RSP)?);
+            asm.emit_cfi(CfiDirective::DefCfaRegister(GPR::RBP));
+        }
+        for reg in &self.saved {
+            asm.emit(stack::encode_push(*reg)?);
+            cfa_offset += SLOT_SIZE;
+            if !self.frame_pointer {
+                asm.emit_cfi(CfiDirective::DefCfaOffset(cfa_offset));
+            }
+            asm.emit_cfi(saved_at(*reg, cfa_offset));
+        }
+        if self.stack_adjustment != 0 {
+            asm.emit(arith::encode_sub_reg_imm(GPR::RSP, self.adjustment_imm()?)?);
+            if !self.frame_pointer {
+                asm.emit_cfi(CfiDirective::DefCfaOffset(self.frame_size()?));
+            }
+        }
+        for (reg, offset) in &self.saved_xmm {
+            let slot = Memory::based(GPR::RSP, *offset)?;
+            asm.emit(sse::encode_movaps_mem_xmm(slot, *reg));
+        }
+        Ok(())
+    }
+
+    /// Appends the epilogue undoing the prologue, followed by `ret`. The frame
+    /// description is remembered before the epilogue and restored after `ret`,
+    /// so that code following it, e.g. another epilogue, stays described.
+    ///
+    /// # Errors
+    /// Propagates encoding errors.
+    pub fn emit_epilogue(&self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit_cfi(CfiDirective::RememberState);
+        for (reg, offset) in &self.saved_xmm {
+            let slot = Memory::based(GPR::RSP, *offset)?;
+            asm.emit(sse::encode_movaps_xmm_mem(*reg, slot));
+        }
+        let mut cfa_offset = SLOT_SIZE * (self.pushes() + 1);
+        if self.stack_adjustment != 0 {
+            asm.emit(arith::encode_add_reg_imm(GPR::RSP, self.adjustment_imm()?)?);
+            if !self.frame_pointer {
+                asm.emit_cfi(CfiDirective::DefCfaOffset(cfa_offset));
+            }
+        }
+        for reg in self.saved.iter().rev() {
+            asm.emit(stack::encode_pop(*reg)?);
+            cfa_offset -= SLOT_SIZE;
+            if !self.frame_pointer {
+                asm.emit_cfi(CfiDirective::DefCfaOffset(cfa_offset));
+            }
+            asm.emit_cfi(CfiDirective::Restore(*reg));
+        }
+        if self.frame_pointer {
+            asm.emit(stack::encode_pop(GPR::RBP)?);
+            asm.emit_cfi(CfiDirective::DefCfa {
+                register: GPR::RSP,
+                offset: SLOT_SIZE,
+            });
+            asm.emit_cfi(CfiDirective::Restore(GPR::RBP));
+        }
+        asm.emit(misc::encode_ret());
+        asm.emit_cfi(CfiDirective::RestoreState);
+        Ok(())
+    }
+
+    /// Returns the number of pushed registers, including RBP.
+    #[inline(always)]
+    fn pushes(&self) -> u32 {
+        #[allow(clippy::cast_possible_truncation)]
+        let saved = self.saved.len() as u32;
+        saved + u32::from(self.frame_pointer)
+    }
+
+    /// Returns the distance between RSP after the prologue and the CFA.
+    #[inline(always)]
+    fn frame_size(&self) -> Result<u32, AssemblerError> {
+        (SLOT_SIZE * (self.pushes() + 1))
+            .checked_add(self.stack_adjustment)
+            .ok_or(AssemblerError::FrameTooLarge)
+    }
+
+    #[inline(always)]
+    fn adjustment_imm(&self) -> Result<i32, AssemblerError> {
+        i32::try_from(self.stack_adjustment).map_err(|_| AssemblerError::FrameTooLarge)
+    }
+}
+
+/// Describes `reg` as pushed when the CFA was `cfa_offset` bytes above RSP.
+#[allow(clippy::cast_possible_wrap)]
+fn saved_at(register: GPR, cfa_offset: u32) -> CfiDirective {
+    CfiDirective::Offset {
+        register,
+        offset: -(cfa_offset as i32),
+    }
+}
+
+/// Assigns registers to arguments, and RSP based slots to the remaining ones.
+/// `frame_size` is the distance between RSP after the prologue and the return
+/// address' end.
+fn place_arguments(
+    function: &FunctionDescription,
+    frame_size: u64,
+) -> Result<Vec<ArgumentLocation>, AssemblerError> {
+    let convention = function.convention();
+    let integers = convention.integer_arguments();
+    let floats = convention.float_arguments();
+    let mut next_integer = 0;
+    let mut next_float = 0;
+    let mut stack_offset = frame_size + u64::from(convention.shadow_space_size());
+
+    let mut result = Vec::with_capacity(function.arguments().len());
+    for (position, class) in function.arguments().iter().enumerate() {
+        if convention.assigns_registers_by_position() {
+            next_integer = position;
+            next_float = position;
+        }
+        let register = match class {
+            ValueClass::Integer => {
+                next_integer += 1;
+                integers
+                    .get(next_integer - 1)
+                    .map(|reg| ArgumentLocation::Gpr(*reg))
+            }
+            ValueClass::Float => {
+                next_float += 1;
+                floats
+                    .get(next_float - 1)
+                    .map(|reg| ArgumentLocation::Xmm(*reg))
+            }
+        };
+        let location = if let Some(location) = register {
+            location
+        } else {
+            let Ok(displacement) = i32::try_from(stack_offset) else {
+                return Err(AssemblerError::FrameTooLarge);
+            };
+            stack_offset += u64::from(SLOT_SIZE);
+            ArgumentLocation::Stack(Memory::based(GPR::RSP, displacement)?)
+        };
+        result.push(location);
+    }
+    Ok(result)
+}
@@ -0,0 +1,134 @@
+use osom_x64_encoder::models::{GPR, XMM};
+
+use super::CallingConvention;
+
+/// Class of a value passed in registers.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ValueClass {
+    /// Integers and pointers of at most 64 bits, passed in general purpose registers.
+    Integer,
+
+    /// `f32` and `f64` values, passed in XMM registers.
+    Float,
+}
+
+/// Describes a function to generate a stack frame for. See
+/// [`FrameLayout`][super::FrameLayout].
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct FunctionDescription {
+    convention: CallingConvention,
+    arguments: Vec<ValueClass>,
+    clobbered: Vec<GPR>,
+    clobbered_xmm: Vec<XMM>,
+    locals_size: u32,
+    makes_calls: bool,
+    frame_pointer: bool,
+}
+
+impl FunctionDescription {
+    /// Creates a description of a leaf function without arguments and locals.
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(convention: CallingConvention) -> Self {
+        Self {
+            convention,
+            arguments: Vec::new(),
+            clobbered: Vec::new(),
+            clobbered_xmm: Vec::new(),
+            locals_size: 0,
+            makes_calls: false,
+            frame_pointer: false,
+        }
+    }
+
+    /// Sets classes of the function's arguments, in order.
+    #[must_use]
+    pub fn with_arguments(mut self, arguments: &[ValueClass]) -> Self {
+        self.arguments = arguments.to_vec();
+        self
+    }
+
+    /// Marks `registers` as modified by the function body. Only the callee saved
+    /// ones among them are preserved by the prologue.
+    #[must_use]
+    pub fn with_clobbered(mut self, registers: &[GPR]) -> Self {
+        self.clobbered.extend_from_slice(registers);
+        self
+    }
+
+    /// Marks XMM `registers` as modified by the function body. Only the callee
+    /// saved ones among them are preserved by the prologue.
+    #[must_use]
+    pub fn with_clobbered_xmm(mut self, registers: &[XMM]) -> Self {
+        self.clobbered_xmm.extend_from_slice(registers);
+        self
+    }
+
+    /// Sets the number of bytes reserved for local variables.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_locals_size(mut self, locals_size: u32) -> Self {
+        self.locals_size = locals_size;
+        self
+    }
+
+    /// Marks the function as calling other functions. Such functions keep RSP
+    /// aligned, cannot use the red zone and reserve the shadow space for callees.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_calls(mut self) -> Self {
+        self.makes_calls = true;
+        self
+    }
+
+    /// Requests the classic `push rbp; mov rbp, rsp` frame.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_frame_pointer(mut self) -> Self {
+        self.frame_pointer = true;
+        self
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn convention(&self) -> CallingConvention {
+        self.convention
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn arguments(&self) -> &[ValueClass] {
+        &self.arguments
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn clobbered(&self) -> &[GPR] {
+        &self.clobbered
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn clobbered_xmm(&self) -> &[XMM] {
+        &self.clobbered_xmm
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn locals_size(&self) -> u32 {
+        self.locals_size
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn makes_calls(&self) -> bool {
+        self.makes_calls
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn frame_pointer(&self) -> bool {
+        self.frame_pointer
+    }
+}
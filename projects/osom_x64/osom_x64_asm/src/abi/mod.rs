@@ -0,0 +1,11 @@
+//! Calling conventions and generation of function prologues and epilogues.
+
+mod calling_convention;
+mod frame;
+mod function;
+pub mod system_v;
+pub mod win64;
+
+pub use calling_convention::*;
+pub use frame::*;
+pub use function::*;
@@ -0,0 +1,33 @@
+//! System V AMD64 calling convention, used on Linux, macOS and BSDs.
+
+use osom_x64_encoder::models::{GPR, XMM};
+
+/// Registers holding the first six integer and pointer arguments, in order.
+pub const INTEGER_ARGUMENTS: [GPR; 6] =
+    [GPR::RDI, GPR::RSI, GPR::RDX, GPR::RCX, GPR::R8, GPR::R9];
+
+/// Registers holding the first eight floating point arguments, in order.
+pub const FLOAT_ARGUMENTS: [XMM; 8] = [
+    XMM::XMM0,
+    XMM::XMM1,
+    XMM::XMM2,
+    XMM::XMM3,
+    XMM::XMM4,
+    XMM::XMM5,
+    XMM::XMM6,
+    XMM::XMM7,
+];
+
+/// General purpose registers that have to be preserved across calls. RSP is
+/// preserved implicitly and thus not listed.
+pub const CALLEE_SAVED: [GPR; 6] =
+    [GPR::RBX, GPR::RBP, GPR::R12, GPR::R13, GPR::R14, GPR::R15];
+
+/// XMM registers that have to be preserved across calls. There are none.
+pub const CALLEE_SAVED_XMM: [XMM; 0] = [];
+
+/// Size of the area below RSP that leaf functions may use without adjusting RSP.
+pub const RED_ZONE_SIZE: u32 = 128;
+
+/// Alignment of RSP right before each `call` instruction.
+pub const STACK_ALIGNMENT: u32 = 16;
@@ -0,0 +1,46 @@
+//! Microsoft x64 calling convention, used on Windows.
+//!
+//! Unlike System V, argument registers are assigned by position: the third
+//! argument goes to R8 or XMM2 regardless of the classes of preceding ones.
+
+use osom_x64_encoder::models::{GPR, XMM};
+
+/// Registers holding the first four integer and pointer arguments, by position.
+pub const INTEGER_ARGUMENTS: [GPR; 4] = [GPR::RCX, GPR::RDX, GPR::R8, GPR::R9];
+
+/// Registers holding the first four floating point arguments, by position.
+pub const FLOAT_ARGUMENTS: [XMM; 4] = [XMM::XMM0, XMM::XMM1, XMM::XMM2, XMM::XMM3];
+
+/// General purpose registers that have to be preserved across calls. RSP is
+/// preserved implicitly and thus not listed.
+pub const CALLEE_SAVED: [GPR; 8] = [
+    GPR::RBX,
+    GPR::RBP,
+    GPR::RDI,
+    GPR::RSI,
+    GPR::R12,
+    GPR::R13,
+    GPR::R14,
+    GPR::R15,
+];
+
+/// XMM registers that have to be preserved across calls.
+pub const CALLEE_SAVED_XMM: [XMM; 10] = [
+    XMM::XMM6,
+    XMM::XMM7,
+    XMM::XMM8,
+    XMM::XMM9,
+    XMM::XMM10,
+    XMM::XMM11,
+    XMM::XMM12,
+    XMM::XMM13,
+    XMM::XMM14,
+    XMM::XMM15,
+];
+
+/// Size of the area the caller reserves right above the return address, where
+/// the callee may spill its register arguments.
+pub const SHADOW_SPACE_SIZE: u32 = 32;
+
+/// Alignment of RSP right before each `call` instruction.
+pub const STACK_ALIGNMENT: u32 = 16;
@@ -0,0 +1,135 @@
+use core::ops::Range;
+
+use crate::{unwind::CfiDirective, Label, PatchPoint, Relocation, Section, Symbol};
+
+/// The result of [`Assembler::finish`][crate::Assembler::finish]: the machine code
+/// followed by the constant pool, and the final offsets of all labels.
+///
+/// # Notes
+/// Offsets are relative to the start of the code. Constants aligned to more than
+/// one byte are only aligned in memory if the code itself is placed at an address
+/// aligned to at least 16 bytes.
+pub struct AssembledCode {
+    bytes: Vec<u8>,
+    code_len: u32,
+    label_offsets: Vec<Option<u32>>,
+    patch_points: Vec<PatchPoint>,
+    cfi_directives: Vec<(u32, CfiDirective)>,
+    symbols: Vec<String>,
+    relocations: Vec<Relocation>,
+    sections: Vec<(String, Range<u32>)>,
+}
+
+impl AssembledCode {
+    #[must_use]
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) const fn new(
+        bytes: Vec<u8>,
+        code_len: u32,
+        label_offsets: Vec<Option<u32>>,
+        patch_points: Vec<PatchPoint>,
+        cfi_directives: Vec<(u32, CfiDirective)>,
+        symbols: Vec<String>,
+        relocations: Vec<Relocation>,
+        sections: Vec<(String, Range<u32>)>,
+    ) -> Self {
+        Self {
+            bytes,
+            code_len,
+            label_offsets,
+            patch_points,
+            cfi_directives,
+            symbols,
+            relocations,
+            sections,
+        }
+    }
+
+    /// Returns the whole assembled image: code followed by the constant pool.
+    #[must_use]
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the code part of the image, i.e. all sections without the constant
+    /// pool.
+    #[must_use]
+    #[inline(always)]
+    pub fn code(&self) -> &[u8] {
+        &self.bytes[..self.code_len as usize]
+    }
+
+    /// Returns the offset of `label`, or [`None`] if it was never bound.
+    #[must_use]
+    #[inline(always)]
+    pub fn label_offset(&self, label: Label) -> Option<u32> {
+        self.label_offsets
+            .get(label.index() as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// Returns patchable instructions in the order they were emitted.
+    #[must_use]
+    #[inline(always)]
+    pub fn patch_points(&self) -> &[PatchPoint] {
+        &self.patch_points
+    }
+
+    /// Returns call frame information recorded with
+    /// [`Assembler::emit_cfi`][crate::Assembler::emit_cfi], with offsets of the
+    /// positions the directives take effect at, sorted by offset. Directives at
+    /// the same offset keep the order they were emitted in.
+    #[must_use]
+    #[inline(always)]
+    pub fn cfi_directives(&self) -> &[(u32, CfiDirective)] {
+        &self.cfi_directives
+    }
+
+    /// Returns the name of `section`.
+    ///
+    /// # Panics
+    /// When `section` was created by another assembler.
+    #[must_use]
+    #[inline(always)]
+    pub fn section_name(&self, section: Section) -> &str {
+        &self.sections[section.index() as usize].0
+    }
+
+    /// Returns offsets of the start and the end of `section`.
+    ///
+    /// # Panics
+    /// When `section` was created by another assembler.
+    #[must_use]
+    #[inline(always)]
+    pub fn section_range(&self, section: Section) -> Range<u32> {
+        self.sections[section.index() as usize].1.clone()
+    }
+
+    /// Returns the name of `symbol`.
+    ///
+    /// # Panics
+    /// When `symbol` was declared by another assembler.
+    #[must_use]
+    #[inline(always)]
+    pub fn symbol_name(&self, symbol: Symbol) -> &str {
+        &self.symbols[symbol.index() as usize]
+    }
+
+    /// Returns references to external symbols, sorted by offset. They are left
+    /// for the consumer of the code to resolve, e.g. by
+    /// [`ExecutableCode::link`][crate::ExecutableCode::link] or an ELF writer.
+    #[must_use]
+    #[inline(always)]
+    pub fn relocations(&self) -> &[Relocation] {
+        &self.relocations
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.bytes
+    }
+}
@@ -0,0 +1,1375 @@
+use std::collections::HashMap;
+
+use osom_x64_encoder::{
+    encoder::{
+        arith, call, errors::EncodingError, jcc, jmp, lea, misc, mov, sse,
+        EncodedInstruction,
+    },
+    formatter::Syntax,
+    models::{
+        Condition, CpuFeatures, CpuRequirement, Displacement, MachineSize, Memory,
+        Scale, GPR, XMM,
+    },
+};
+
+use crate::{
+    constant_pool::{ConstantPool, CONSTANT_POOL_ALIGNMENT},
+    fragment::{padding, Fragment},
+    listing::{Listing, ListingBuilder},
+    peephole::{find_rewrite, Rewrite},
+    unwind::CfiDirective,
+    AssembledCode, AssemblerError, Label, PatchKind, PatchPoint, PeepholeConfig,
+    PeepholeStatistics, Relocation, RelocationKind, Section, SectionKind, Symbol,
+};
+
+/// The biggest alignment accepted by [`Assembler::emit_align`] and constants.
+pub const MAX_ALIGNMENT: u32 = 4096;
+
+/// Maximal size of a single NOP produced by the encoder.
+const MAX_NOP_SIZE: u32 = 9;
+
+/// Size of a single jump table entry, i.e. of a 32-bit offset.
+const JUMP_TABLE_ENTRY_SIZE: usize = 4;
+
+/// Alignment of [`Section::TEXT`].
+const TEXT_ALIGNMENT: u32 = 16;
+
+/// `[rip]` operand of instructions whose displacement is patched later.
+pub(crate) const RIP_PLACEHOLDER: Memory =
+    match Memory::new(GPR::RIP, GPR::NO_REG, Scale::Scale1, Displacement::ZERO) {
+        Ok(memory) => memory,
+        Err(_) => panic!("Invalid RIP-relative placeholder"),
+    };
+
+#[derive(Clone, Copy)]
+enum LabelPosition {
+    Unbound,
+
+    /// Bound right before the fragment with the given index in the section.
+    Code {
+        section: u32,
+        fragment: u32,
+    },
+
+    /// Bound to the constant pool entry with the given index.
+    Constant(u32),
+}
+
+/// Fragments of a [`Section`] together with its properties.
+struct SectionState {
+    name: String,
+    kind: SectionKind,
+    alignment: u32,
+    fragments: Vec<Fragment>,
+}
+
+/// Jump table occupying a constant pool entry, filled in [`Assembler::finish`].
+struct JumpTable {
+    entry: u32,
+    targets: Vec<Label>,
+}
+
+/// Assembles a single function: streams of instructions and data referencing
+/// each other through [`Label`]s, followed by the function's constant pool.
+///
+/// Instructions are appended to the current [`Section`], initially
+/// [`Section::TEXT`]. Sections are placed in the image one after another, in
+/// the order they were created unless set with [`Assembler::set_section_order`].
+///
+/// # Notes
+/// Jumps to labels are emitted in their short form whenever the target is
+/// within `rel8` range, and in the long form otherwise. This is decided in
+/// [`Assembler::finish`], once all labels are known.
+pub struct Assembler {
+    sections: Vec<SectionState>,
+    current_section: u32,
+    section_order: Vec<u32>,
+    data: Vec<u8>,
+    labels: Vec<LabelPosition>,
+    constant_pool: ConstantPool,
+    jump_tables: Vec<JumpTable>,
+    patch_points: Vec<Label>,
+    cfi_directives: Vec<(Label, CfiDirective)>,
+    symbols: Vec<String>,
+    label_names: HashMap<Label, String>,
+    comments: Vec<(Label, String)>,
+    cpu_features: Option<CpuFeatures>,
+
+    /// The error of the first emitted fragment not allowed by `cpu_features`,
+    /// recomputed whenever they change.
+    cpu_features_error: Option<AssemblerError>,
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Assembler {
+    #[must_use]
+    pub fn new() -> Self {
+        let text = SectionState {
+            name: ".text".to_owned(),
+            kind: SectionKind::Code,
+            alignment: TEXT_ALIGNMENT,
+            fragments: Vec::new(),
+        };
+        Self {
+            sections: vec![text],
+            current_section: Section::TEXT.index(),
+            section_order: vec![Section::TEXT.index()],
+            data: Vec::new(),
+            labels: Vec::new(),
+            constant_pool: ConstantPool::new(),
+            jump_tables: Vec::new(),
+            patch_points: Vec::new(),
+            cfi_directives: Vec::new(),
+            symbols: Vec::new(),
+            label_names: HashMap::new(),
+            comments: Vec::new(),
+            cpu_features: None,
+            cpu_features_error: None,
+        }
+    }
+
+    /// Creates a new, unbound [`Label`].
+    ///
+    /// # Panics
+    /// When more than [`u32::MAX`] labels are created.
+    #[must_use]
+    pub fn create_label(&mut self) -> Label {
+        let index = u32::try_from(self.labels.len()).expect("Too many labels.");
+        self.labels.push(LabelPosition::Unbound);
+        Label::new(index)
+    }
+
+    /// Binds `label` to the current position.
+    ///
+    /// # Errors
+    /// [`AssemblerError::LabelAlreadyBound`] if `label` was already bound.
+    ///
+    /// # Panics
+    /// When `label` was created by another assembler.
+    pub fn bind_label(&mut self, label: Label) -> Result<(), AssemblerError> {
+        let current = self.current_position();
+        let position = &mut self.labels[label.index() as usize];
+        if !matches!(position, LabelPosition::Unbound) {
+            return Err(AssemblerError::LabelAlreadyBound(label));
+        }
+        *position = current;
+        Ok(())
+    }
+
+    /// Names `label` in the [`Listing`] produced by
+    /// [`Assembler::finish_with_listing`]. Unnamed labels are listed as `.L<index>`,
+    /// and only when referenced.
+    #[inline(always)]
+    pub fn set_label_name(&mut self, label: Label, name: &str) {
+        self.label_names.insert(label, name.to_owned());
+    }
+
+    /// Restricts instructions to those available with `features`, e.g. as
+    /// returned by [`CpuFeatures::detect`]. By default any instruction is
+    /// accepted.
+    ///
+    /// # Notes
+    /// Instructions are checked by their [`CpuRequirement`], as set by the
+    /// encoders. Instructions of unknown requirement, e.g. built from raw bytes,
+    /// and raw bytes emitted into code sections are rejected, unless classified
+    /// with [`EncodedInstruction::with_cpu_requirement`]. The first rejected
+    /// instruction fails [`Assembler::finish`], use [`Assembler::try_emit`] to
+    /// detect it right away.
+    ///
+    /// # Errors
+    /// The error of the first already emitted instruction not allowed by
+    /// `features`, see [`Assembler::try_emit`]. The restriction is applied
+    /// nevertheless.
+    pub fn set_cpu_features(
+        &mut self,
+        features: CpuFeatures,
+    ) -> Result<(), AssemblerError> {
+        self.cpu_features = Some(features);
+        let first_error = self.sections.iter().find_map(|section| {
+            section.fragments.iter().find_map(|fragment| {
+                check_cpu_features(features, section.kind, fragment)
+            })
+        });
+        // Errors are relative to the current restriction only, e.g. widening
+        // it accepts instructions rejected before.
+        self.cpu_features_error = first_error;
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates a new, empty section placed after all existing ones.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidAlignment`] if `alignment` is not a power of two
+    /// or exceeds [`MAX_ALIGNMENT`].
+    ///
+    /// # Panics
+    /// When more than [`u32::MAX`] sections are created.
+    pub fn create_section(
+        &mut self,
+        name: &str,
+        kind: SectionKind,
+        alignment: u32,
+    ) -> Result<Section, AssemblerError> {
+        validate_alignment(alignment)?;
+        let index = u32::try_from(self.sections.len()).expect("Too many sections.");
+        self.sections.push(SectionState {
+            name: name.to_owned(),
+            kind,
+            alignment,
+            fragments: Vec::new(),
+        });
+        self.section_order.push(index);
+        Ok(Section::new(index))
+    }
+
+    /// Makes `section` the current one, i.e. the one receiving emitted
+    /// instructions and data, and labels bound from now on.
+    ///
+    /// # Panics
+    /// When `section` was created by another assembler.
+    #[inline(always)]
+    pub fn switch_section(&mut self, section: Section) {
+        assert!(
+            (section.index() as usize) < self.sections.len(),
+            "Section created by another assembler."
+        );
+        self.current_section = section.index();
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn current_section(&self) -> Section {
+        Section::new(self.current_section)
+    }
+
+    /// Sets the order of sections in the final image. Sections created later
+    /// are placed after the given ones.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidSectionOrder`] if `order` does not contain each
+    /// existing section exactly once.
+    pub fn set_section_order(
+        &mut self,
+        order: &[Section],
+    ) -> Result<(), AssemblerError> {
+        let mut seen = vec![false; self.sections.len()];
+        for section in order {
+            match seen.get_mut(section.index() as usize) {
+                Some(seen @ false) => *seen = true,
+                _ => return Err(AssemblerError::InvalidSectionOrder),
+            }
+        }
+        if order.len() != self.sections.len() {
+            return Err(AssemblerError::InvalidSectionOrder);
+        }
+        self.section_order = order.iter().map(|section| section.index()).collect();
+        Ok(())
+    }
+
+    /// Returns the position right after the last fragment of the current section.
+    #[inline(always)]
+    fn current_position(&self) -> LabelPosition {
+        #[allow(clippy::cast_possible_truncation)]
+        LabelPosition::Code {
+            section: self.current_section,
+            fragment: self.fragments().len() as u32,
+        }
+    }
+
+    /// Returns fragments of the current section.
+    #[inline(always)]
+    fn fragments(&self) -> &Vec<Fragment> {
+        &self.sections[self.current_section as usize].fragments
+    }
+
+    #[inline(always)]
+    fn push_fragment(&mut self, fragment: Fragment) {
+        if let Some(features) = self.cpu_features {
+            let kind = self.sections[self.current_section as usize].kind;
+            if let Some(error) = check_cpu_features(features, kind, &fragment) {
+                self.cpu_features_error.get_or_insert(error);
+            }
+        }
+        self.sections[self.current_section as usize]
+            .fragments
+            .push(fragment);
+    }
+
+    /// Records `directive` as taking effect at the current position, i.e. after
+    /// the last emitted instruction. The directives are available, resolved to
+    /// offsets, through [`AssembledCode::cfi_directives`].
+    ///
+    /// # Panics
+    /// When more than [`u32::MAX`] labels are created.
+    pub fn emit_cfi(&mut self, directive: CfiDirective) {
+        let label = self.create_label();
+        self.labels[label.index() as usize] = self.current_position();
+        self.cfi_directives.push((label, directive));
+    }
+
+    /// Attaches `text` to the next instruction or data emitted in the current
+    /// section, as its comment in the [`Listing`] produced by
+    /// [`Assembler::finish_with_listing`]. Comments attached to the same
+    /// instruction are joined.
+    ///
+    /// # Panics
+    /// When more than [`u32::MAX`] labels are created.
+    pub fn emit_comment(&mut self, text: &str) {
+        let label = self.create_label();
+        self.labels[label.index() as usize] = self.current_position();
+        self.comments.push((label, text.to_owned()));
+    }
+
+    /// Appends already encoded instruction.
+    ///
+    /// # Notes
+    /// An instruction not allowed by [`Assembler::set_cpu_features`] fails
+    /// [`Assembler::finish`].
+    #[inline(always)]
+    pub fn emit(&mut self, instruction: EncodedInstruction) {
+        self.push_fragment(Fragment::Instruction(instruction));
+    }
+
+    /// Appends already encoded instruction, if allowed by
+    /// [`Assembler::set_cpu_features`].
+    ///
+    /// # Errors
+    /// * [`AssemblerError::Encoding`] with [`EncodingError::UnsupportedCpuFeature`]
+    ///   if the instruction requires a missing feature.
+    /// * [`AssemblerError::UnknownCpuRequirement`] if the requirement of the
+    ///   instruction is not known.
+    ///
+    /// Nothing is emitted on error.
+    pub fn try_emit(
+        &mut self,
+        instruction: EncodedInstruction,
+    ) -> Result<(), AssemblerError> {
+        let fragment = Fragment::Instruction(instruction);
+        if let Some(features) = self.cpu_features {
+            let kind = self.sections[self.current_section as usize].kind;
+            if let Some(error) = check_cpu_features(features, kind, &fragment) {
+                return Err(error);
+            }
+        }
+        self.push_fragment(fragment);
+        Ok(())
+    }
+
+    /// Appends `jmp target`.
+    #[inline(always)]
+    pub fn emit_jmp(&mut self, target: Label) {
+        self.push_fragment(Fragment::Jump {
+            cond: None,
+            target,
+            is_long: false,
+        });
+    }
+
+    /// Appends `jcc target`.
+    #[inline(always)]
+    pub fn emit_jcc(&mut self, cond: Condition, target: Label) {
+        self.push_fragment(Fragment::Jump {
+            cond: Some(cond),
+            target,
+            is_long: false,
+        });
+    }
+
+    /// Appends an instruction with a RIP-relative memory operand, whose
+    /// displacement is patched to point at `target`.
+    ///
+    /// # Notes
+    /// The displacement has to occupy the last 4 bytes of `instruction`, i.e.
+    /// the instruction cannot have an immediate.
+    #[inline(always)]
+    pub(crate) fn emit_rip_relative(
+        &mut self,
+        instruction: EncodedInstruction,
+        target: Label,
+    ) {
+        self.push_fragment(Fragment::RipRelative {
+            instruction,
+            target,
+        });
+    }
+
+    /// Appends `lea dst, [rip + target]`.
+    ///
+    /// # Errors
+    /// See [`lea::encode_lea`].
+    pub fn emit_lea_label(
+        &mut self,
+        dst: GPR,
+        target: Label,
+    ) -> Result<(), AssemblerError> {
+        let instruction = lea::encode_lea(dst, RIP_PLACEHOLDER)?;
+        self.emit_rip_relative(instruction, target);
+        Ok(())
+    }
+
+    /// Appends `movsd dst, qword ptr [rip + target]`.
+    #[inline(always)]
+    pub fn emit_movsd_label(&mut self, dst: XMM, target: Label) {
+        let instruction = sse::encode_movsd_xmm_mem(dst, RIP_PLACEHOLDER);
+        self.emit_rip_relative(instruction, target);
+    }
+
+    /// Appends `movss dst, dword ptr [rip + target]`.
+    #[inline(always)]
+    pub fn emit_movss_label(&mut self, dst: XMM, target: Label) {
+        let instruction = sse::encode_movss_xmm_mem(dst, RIP_PLACEHOLDER);
+        self.emit_rip_relative(instruction, target);
+    }
+
+    /// Appends `jmp rel32` to `target`, which can be redirected after the code is
+    /// mapped. Returns the index of its [`PatchPoint`] in
+    /// [`AssembledCode::patch_points`].
+    ///
+    /// # Notes
+    /// The jump is always emitted in its long form and may be preceded by NOPs,
+    /// see [`PatchPoint`].
+    pub fn emit_patchable_jmp(&mut self, target: Label) -> usize {
+        let instruction = jmp::encode_jmp_rel32(0);
+        self.emit_patchable(PatchKind::Jump, instruction, Some(target))
+    }
+
+    /// Appends `movabs dst, imm`, whose immediate can be replaced after the code
+    /// is mapped. Returns the index of its [`PatchPoint`] in
+    /// [`AssembledCode::patch_points`].
+    ///
+    /// # Notes
+    /// The instruction may be preceded by NOPs, see [`PatchPoint`].
+    ///
+    /// # Errors
+    /// See [`mov::encode_movabs_reg_imm`].
+    pub fn emit_patchable_mov_imm64(
+        &mut self,
+        dst: GPR,
+        imm: i64,
+    ) -> Result<usize, AssemblerError> {
+        let instruction = mov::encode_movabs_reg_imm(dst, imm)?;
+        Ok(self.emit_patchable(PatchKind::MovImm64, instruction, None))
+    }
+
+    fn emit_patchable(
+        &mut self,
+        kind: PatchKind,
+        instruction: EncodedInstruction,
+        target: Option<Label>,
+    ) -> usize {
+        let label = self.create_label();
+        self.labels[label.index() as usize] = self.current_position();
+        self.push_fragment(Fragment::Patchable {
+            kind,
+            instruction,
+            target,
+        });
+        self.patch_points.push(label);
+        self.patch_points.len() - 1
+    }
+
+    /// Returns the [`Symbol`] named `name`, declaring it on first use.
+    ///
+    /// # Panics
+    /// When more than [`u32::MAX`] symbols are declared.
+    pub fn declare_symbol(&mut self, name: &str) -> Symbol {
+        let index = self
+            .symbols
+            .iter()
+            .position(|symbol| symbol == name)
+            .unwrap_or_else(|| {
+                self.symbols.push(name.to_owned());
+                self.symbols.len() - 1
+            });
+        Symbol::new(u32::try_from(index).expect("Too many symbols."))
+    }
+
+    /// Appends `instruction` whose last bytes are a field referencing `symbol`,
+    /// resolved according to `kind` once the symbol's address is known. See
+    /// [`AssembledCode::relocations`].
+    ///
+    /// # Notes
+    /// PC-relative fields have to end the instruction, i.e. the instruction
+    /// cannot have an immediate. Current content of the field is ignored.
+    #[inline(always)]
+    pub fn emit_relocated(
+        &mut self,
+        instruction: EncodedInstruction,
+        kind: RelocationKind,
+        symbol: Symbol,
+    ) {
+        self.push_fragment(Fragment::Relocated {
+            instruction,
+            kind,
+            symbol,
+        });
+    }
+
+    /// Appends `call symbol`.
+    #[inline(always)]
+    pub fn emit_call_symbol(&mut self, symbol: Symbol) {
+        let instruction = call::encode_call_rel32(0);
+        self.emit_relocated(instruction, RelocationKind::Plt32, symbol);
+    }
+
+    /// Appends `jmp symbol`, e.g. for tail calls.
+    #[inline(always)]
+    pub fn emit_jmp_symbol(&mut self, symbol: Symbol) {
+        let instruction = jmp::encode_jmp_rel32(0);
+        self.emit_relocated(instruction, RelocationKind::Plt32, symbol);
+    }
+
+    /// Appends `lea dst, [rip + symbol]`. The symbol has to be within 2GB of the
+    /// code.
+    ///
+    /// # Errors
+    /// See [`lea::encode_lea`].
+    pub fn emit_lea_symbol(
+        &mut self,
+        dst: GPR,
+        symbol: Symbol,
+    ) -> Result<(), AssemblerError> {
+        let instruction = lea::encode_lea(dst, RIP_PLACEHOLDER)?;
+        self.emit_relocated(instruction, RelocationKind::Pc32, symbol);
+        Ok(())
+    }
+
+    /// Appends `movabs dst, symbol`, loading the absolute address of `symbol`.
+    ///
+    /// # Errors
+    /// See [`mov::encode_movabs_reg_imm`].
+    pub fn emit_mov_symbol_address(
+        &mut self,
+        dst: GPR,
+        symbol: Symbol,
+    ) -> Result<(), AssemblerError> {
+        let instruction = mov::encode_movabs_reg_imm(dst, 0)?;
+        self.emit_relocated(instruction, RelocationKind::Abs64, symbol);
+        Ok(())
+    }
+
+    /// Appends `mov dst, qword ptr [rip + symbol@GOTPCREL]`, loading the address
+    /// of `symbol` from the global offset table.
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a 64-bit register.
+    /// * See [`mov::encode_mov_reg_mem`].
+    pub fn emit_load_symbol_address(
+        &mut self,
+        dst: GPR,
+        symbol: Symbol,
+    ) -> Result<(), AssemblerError> {
+        if dst.size() != MachineSize::QWord {
+            return Err(EncodingError::InvalidRegisterSize.into());
+        }
+        let instruction = mov::encode_mov_reg_mem(dst, RIP_PLACEHOLDER)?;
+        self.emit_relocated(instruction, RelocationKind::GotPc32, symbol);
+        Ok(())
+    }
+
+    /// Appends raw bytes to the instruction stream.
+    ///
+    /// # Panics
+    /// When the total size of emitted data exceeds [`u32::MAX`].
+    pub fn emit_bytes(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+
+        let start = u32::try_from(self.data.len()).expect("Too much data.");
+        let len = u32::try_from(bytes.len()).expect("Too much data.");
+        self.data.extend_from_slice(bytes);
+        self.push_fragment(Fragment::Data { start, len });
+    }
+
+    /// Appends a byte, i.e. `db value`.
+    #[inline(always)]
+    pub fn emit_db(&mut self, value: u8) {
+        self.emit_bytes(&[value]);
+    }
+
+    /// Appends a little-endian word, i.e. `dw value`.
+    #[inline(always)]
+    pub fn emit_dw(&mut self, value: u16) {
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Appends a little-endian double word, i.e. `dd value`.
+    #[inline(always)]
+    pub fn emit_dd(&mut self, value: u32) {
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Appends a little-endian quad word, i.e. `dq value`.
+    #[inline(always)]
+    pub fn emit_dq(&mut self, value: u64) {
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Appends IEEE 754 single precision float.
+    #[inline(always)]
+    pub fn emit_f32(&mut self, value: f32) {
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Appends IEEE 754 double precision float.
+    #[inline(always)]
+    pub fn emit_f64(&mut self, value: f64) {
+        self.emit_bytes(&value.to_le_bytes());
+    }
+
+    /// Pads the current section up to the next multiple of `alignment`, with NOPs
+    /// in [`SectionKind::Code`] sections and zeros in [`SectionKind::Data`] ones.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidAlignment`] if `alignment` is not a power of two
+    /// or exceeds [`MAX_ALIGNMENT`].
+    pub fn emit_align(&mut self, alignment: u32) -> Result<(), AssemblerError> {
+        validate_alignment(alignment)?;
+        if alignment > 1 {
+            self.push_fragment(Fragment::Align { alignment });
+        }
+        Ok(())
+    }
+
+    /// Returns a label pointing to `value` in the constant pool. Equal constants
+    /// share the same label.
+    #[must_use]
+    #[inline(always)]
+    pub fn constant_f64(&mut self, value: f64) -> Label {
+        self.constant_bytes_unchecked(&value.to_le_bytes(), 8)
+    }
+
+    /// Returns a label pointing to `value` in the constant pool. Equal constants
+    /// share the same label.
+    #[must_use]
+    #[inline(always)]
+    pub fn constant_f32(&mut self, value: f32) -> Label {
+        self.constant_bytes_unchecked(&value.to_le_bytes(), 4)
+    }
+
+    /// Returns a label pointing to `value` in the constant pool. Equal constants
+    /// share the same label.
+    #[must_use]
+    #[inline(always)]
+    pub fn constant_u64(&mut self, value: u64) -> Label {
+        self.constant_bytes_unchecked(&value.to_le_bytes(), 8)
+    }
+
+    /// Returns a label pointing to `bytes` placed in the constant pool at an offset
+    /// aligned to `alignment`. Equal constants share the same label.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidAlignment`] if `alignment` is not a power of two
+    /// or exceeds [`MAX_ALIGNMENT`].
+    pub fn constant_bytes(
+        &mut self,
+        bytes: &[u8],
+        alignment: u32,
+    ) -> Result<Label, AssemblerError> {
+        validate_alignment(alignment)?;
+        Ok(self.constant_bytes_unchecked(bytes, alignment))
+    }
+
+    fn constant_bytes_unchecked(&mut self, bytes: &[u8], alignment: u32) -> Label {
+        if let Some(label) = self.constant_pool.find(bytes, alignment) {
+            return label;
+        }
+
+        let label = self.create_label();
+        let index = self.constant_pool.push(bytes, alignment, label);
+        self.labels[label.index() as usize] = LabelPosition::Constant(index);
+        label
+    }
+
+    /// Returns a label pointing to a jump table in the constant pool, i.e. to
+    /// 32-bit offsets of `targets` relative to the start of the table. Unlike
+    /// other constants, jump tables are never shared.
+    ///
+    /// # Panics
+    /// When the table exceeds [`u32::MAX`] bytes.
+    #[must_use]
+    pub fn jump_table(&mut self, targets: &[Label]) -> Label {
+        let len = targets
+            .len()
+            .checked_mul(JUMP_TABLE_ENTRY_SIZE)
+            .filter(|len| u32::try_from(*len).is_ok())
+            .expect("Jump table too large.");
+        let label = self.create_label();
+        #[allow(clippy::cast_possible_truncation)]
+        let alignment = JUMP_TABLE_ENTRY_SIZE as u32;
+        let entry = self.constant_pool.reserve(len, alignment, label);
+        self.labels[label.index() as usize] = LabelPosition::Constant(entry);
+        self.jump_tables.push(JumpTable {
+            entry,
+            targets: targets.to_vec(),
+        });
+        label
+    }
+
+    /// Appends an indirect jump through `table` created with
+    /// [`Assembler::jump_table`], to the target with the given `index`:
+    ///
+    /// ```text
+    /// lea scratch, [rip + table]
+    /// movsxd index, dword ptr [scratch + index * 4]
+    /// add scratch, index
+    /// jmp scratch
+    /// ```
+    ///
+    /// # Notes
+    /// `index` is not checked against the length of the table. Both `index` and
+    /// `scratch` are clobbered.
+    ///
+    /// # Errors
+    /// * [`AssemblerError::InvalidSwitch`] if `index` and `scratch` are the same
+    ///   register.
+    /// * [`AssemblerError::Encoding`] if `index` or `scratch` is not a 64-bit
+    ///   register, or `index` is `rsp`.
+    pub fn emit_jump_table_dispatch(
+        &mut self,
+        index: GPR,
+        table: Label,
+        scratch: GPR,
+    ) -> Result<(), AssemblerError> {
+        let [load, add, jump] = encode_jump_table_dispatch(index, scratch)?;
+        self.emit_lea_label(scratch, table)?;
+        self.emit(load);
+        self.emit(add);
+        self.emit(jump);
+        Ok(())
+    }
+
+    /// Runs the peephole optimizer over instructions emitted so far, applying
+    /// rules enabled in `config` until none of them matches. Should be called
+    /// right before [`Assembler::finish`].
+    ///
+    /// # Notes
+    /// Only instructions added with [`Assembler::emit`] and jumps to labels are
+    /// rewritten. Rules changing status flags apply only when the flags are
+    /// overwritten before being read, or reach `ret`. Removing such an
+    /// instruction never exposes older flags to a reader, thus all rewrites
+    /// found in a single round can be applied together.
+    pub fn optimize(&mut self, config: PeepholeConfig) -> PeepholeStatistics {
+        let mut statistics = PeepholeStatistics::default();
+        for section in 0..self.sections.len() {
+            #[allow(clippy::cast_possible_truncation)]
+            let section = section as u32;
+            loop {
+                let fragments = &self.sections[section as usize].fragments;
+                let mut rewrites = Vec::new();
+                for index in 0..fragments.len() {
+                    let targets_next = |label: Label| {
+                        matches!(
+                            self.labels[label.index() as usize],
+                            LabelPosition::Code { section: label_section, fragment }
+                                if label_section == section
+                                    && fragment as usize == index + 1
+                        )
+                    };
+                    if let Some((rule, rewrite)) =
+                        find_rewrite(fragments, index, config, targets_next)
+                    {
+                        statistics.record(rule);
+                        rewrites.push((index, rewrite));
+                    }
+                }
+                if rewrites.is_empty() {
+                    break;
+                }
+                self.apply_rewrites(section, rewrites);
+            }
+        }
+        statistics
+    }
+
+    /// Applies `rewrites` to `section`, sorted by fragment index, and moves
+    /// labels bound to removed fragments to the following ones.
+    fn apply_rewrites(&mut self, section: u32, rewrites: Vec<(usize, Rewrite)>) {
+        let fragments = &mut self.sections[section as usize].fragments;
+        let old_fragments = core::mem::take(fragments);
+        let mut new_positions = Vec::with_capacity(old_fragments.len() + 1);
+        let mut rewrites = rewrites.into_iter().peekable();
+        for (index, fragment) in old_fragments.into_iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            new_positions.push(fragments.len() as u32);
+            match rewrites.next_if(|(rewritten, _)| *rewritten == index) {
+                Some((_, Rewrite::Remove)) => {}
+                Some((_, Rewrite::Replace(instruction))) => {
+                    fragments.push(Fragment::Instruction(instruction));
+                }
+                None => fragments.push(fragment),
+            }
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        new_positions.push(fragments.len() as u32);
+
+        for label in &mut self.labels {
+            if let LabelPosition::Code {
+                section: label_section,
+                fragment,
+            } = label
+            {
+                if *label_section == section {
+                    *fragment = new_positions[*fragment as usize];
+                }
+            }
+        }
+    }
+
+    /// Resolves all labels and produces the final code, i.e. all sections in
+    /// their order, followed by the constant pool aligned to 16 bytes.
+    ///
+    /// # Errors
+    /// * [`AssemblerError::UnboundLabel`] if any referenced label was never bound.
+    /// * [`AssemblerError::CodeTooLarge`] if the code exceeds 2GB.
+    /// * [`AssemblerError::Encoding`] with [`EncodingError::UnsupportedCpuFeature`]
+    ///   or [`AssemblerError::UnknownCpuRequirement`] if an instruction is not
+    ///   allowed by [`Assembler::set_cpu_features`].
+    pub fn finish(mut self) -> Result<AssembledCode, AssemblerError> {
+        let layout = self.resolve()?;
+        let bytes = self.write(&layout);
+        Ok(self.into_code(&layout, bytes))
+    }
+
+    /// Same as [`Assembler::finish`], additionally producing a [`Listing`] of the
+    /// final code, with instructions in the given `syntax`.
+    ///
+    /// # Errors
+    /// Same as [`Assembler::finish`].
+    pub fn finish_with_listing(
+        mut self,
+        syntax: Syntax,
+    ) -> Result<(AssembledCode, Listing), AssemblerError> {
+        let layout = self.resolve()?;
+        let bytes = self.write(&layout);
+        let listing = self.listing(&layout, &bytes, syntax);
+        Ok((self.into_code(&layout, bytes), listing))
+    }
+
+    /// Validates labels and relaxes jumps, returning the final layout.
+    fn resolve(&mut self) -> Result<Layout, AssemblerError> {
+        if let Some(error) = self.cpu_features_error {
+            return Err(error);
+        }
+
+        let fragment_targets = self
+            .sections
+            .iter()
+            .flat_map(|section| &section.fragments)
+            .filter_map(Fragment::target);
+        let table_targets = self.jump_tables.iter().flat_map(|table| &table.targets);
+        for target in fragment_targets.chain(table_targets.copied()) {
+            if matches!(self.labels[target.index() as usize], LabelPosition::Unbound) {
+                return Err(AssemblerError::UnboundLabel(target));
+            }
+        }
+
+        let mut layout = Layout::default();
+        loop {
+            layout.compute(self)?;
+            if !self.relax(&layout) {
+                break;
+            }
+        }
+        Ok(layout)
+    }
+
+    fn into_code(self, layout: &Layout, bytes: Vec<u8>) -> AssembledCode {
+        let label_offsets = (0..self.labels.len())
+            .map(|index| layout.label_offset(&self, index))
+            .collect();
+        let patch_points = self
+            .patch_points
+            .iter()
+            .map(|label| {
+                let LabelPosition::Code { section, fragment } =
+                    self.labels[label.index() as usize]
+                else {
+                    unreachable!("Patch points are bound to code.");
+                };
+                let Fragment::Patchable {
+                    kind, instruction, ..
+                } = &self.sections[section as usize].fragments[fragment as usize]
+                else {
+                    unreachable!("Patchable fragments are never rewritten.");
+                };
+                let end =
+                    layout.fragment_offsets[section as usize][fragment as usize + 1];
+                PatchPoint::new(*kind, end - u32::from(instruction.len()))
+            })
+            .collect();
+        let mut relocations = Vec::new();
+        for section in &self.section_order {
+            let fragments = &self.sections[*section as usize].fragments;
+            let offsets = &layout.fragment_offsets[*section as usize];
+            for (index, fragment) in fragments.iter().enumerate() {
+                if let Fragment::Relocated { kind, symbol, .. } = fragment {
+                    let offset = offsets[index + 1] - kind.size();
+                    let addend = if kind.is_pc_relative() {
+                        -i64::from(kind.size())
+                    } else {
+                        0
+                    };
+                    relocations.push(Relocation::new(*kind, offset, *symbol, addend));
+                }
+            }
+        }
+        let mut cfi_directives: Vec<_> = self
+            .cfi_directives
+            .iter()
+            .map(|(label, directive)| {
+                let offset = layout.offset_of(&self.labels, *label);
+                (offset, *directive)
+            })
+            .collect();
+        cfi_directives.sort_by_key(|(offset, _)| *offset);
+        let sections = self
+            .sections
+            .into_iter()
+            .zip(&layout.fragment_offsets)
+            .map(|(section, offsets)| {
+                let range = offsets[0]..offsets[offsets.len() - 1];
+                (section.name, range)
+            })
+            .collect();
+        AssembledCode::new(
+            bytes,
+            layout.code_end,
+            label_offsets,
+            patch_points,
+            cfi_directives,
+            self.symbols,
+            relocations,
+            sections,
+        )
+    }
+
+    /// Returns labels shown in a [`Listing`], i.e. named and referenced ones.
+    fn listed_labels(&self) -> ListedLabels {
+        let mut listed = vec![false; self.labels.len()];
+        let fragment_targets = self
+            .sections
+            .iter()
+            .flat_map(|section| &section.fragments)
+            .filter_map(Fragment::target);
+        let table_targets = self.jump_tables.iter().flat_map(|table| &table.targets);
+        for label in fragment_targets
+            .chain(table_targets.copied())
+            .chain(self.label_names.keys().copied())
+        {
+            listed[label.index() as usize] = true;
+        }
+        let mut result = ListedLabels::default();
+        for (index, position) in self.labels.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            let label = Label::new(index as u32);
+            match *position {
+                _ if !listed[index] => {}
+                LabelPosition::Code { section, fragment } => {
+                    result
+                        .code
+                        .entry((section, fragment))
+                        .or_default()
+                        .push(label);
+                }
+                LabelPosition::Constant(entry) => {
+                    result.constants.entry(entry).or_default().push(label);
+                }
+                LabelPosition::Unbound => {}
+            }
+        }
+        result
+    }
+
+    /// Lists `bytes` written for `layout`, with named and referenced labels.
+    fn listing(&self, layout: &Layout, bytes: &[u8], syntax: Syntax) -> Listing {
+        let label_name = |label: Label| match self.label_names.get(&label) {
+            Some(name) => name.clone(),
+            None => format!(".L{}", label.index()),
+        };
+        let ListedLabels {
+            code: code_labels,
+            constants: constant_labels,
+        } = self.listed_labels();
+        let mut comments: HashMap<(u32, u32), Vec<&str>> = HashMap::new();
+        for (label, text) in &self.comments {
+            if let LabelPosition::Code { section, fragment } =
+                self.labels[label.index() as usize]
+            {
+                comments.entry((section, fragment)).or_default().push(text);
+            }
+        }
+
+        let mut builder = ListingBuilder::new(syntax);
+        let mut end = 0;
+        for section in &self.section_order {
+            let offsets = &layout.fragment_offsets[*section as usize];
+            let state = &self.sections[*section as usize];
+            let list_range = |builder: &mut ListingBuilder,
+                              start: u32,
+                              end: u32,
+                              target: Option<&str>| {
+                let range = &bytes[start as usize..end as usize];
+                match state.kind {
+                    SectionKind::Code => builder.code(start, range, target),
+                    SectionKind::Data => builder.data(start, range),
+                }
+            };
+            list_range(&mut builder, end, offsets[0], None);
+            builder.section(&state.name, offsets[0]);
+            for index in 0..offsets.len() {
+                #[allow(clippy::cast_possible_truncation)]
+                let position = (*section, index as u32);
+                for label in code_labels.get(&position).into_iter().flatten() {
+                    builder.label(label_name(*label), offsets[index]);
+                }
+                for text in comments.get(&position).into_iter().flatten() {
+                    builder.comment(text);
+                }
+                let Some(fragment) = state.fragments.get(index) else {
+                    break;
+                };
+                let target = match fragment {
+                    Fragment::Relocated { symbol, .. } => {
+                        Some(self.symbols[symbol.index() as usize].clone())
+                    }
+                    _ => fragment.target().map(label_name),
+                };
+                let (start, fragment_end) = (offsets[index], offsets[index + 1]);
+                if matches!(fragment, Fragment::Data { .. }) {
+                    builder.data(start, &bytes[start as usize..fragment_end as usize]);
+                } else {
+                    list_range(&mut builder, start, fragment_end, target.as_deref());
+                }
+            }
+            builder.flush_comment();
+            end = offsets[offsets.len() - 1];
+        }
+
+        if let Some(first) = layout.constant_offsets.first() {
+            builder.data(end, &bytes[end as usize..*first as usize]);
+            builder.section(".rodata", *first);
+            let ends = layout.constant_offsets[1..]
+                .iter()
+                .chain([&layout.total_len]);
+            for (entry, (start, end)) in
+                layout.constant_offsets.iter().zip(ends).enumerate()
+            {
+                #[allow(clippy::cast_possible_truncation)]
+                let entry = entry as u32;
+                for label in constant_labels.get(&entry).into_iter().flatten() {
+                    builder.label(label_name(*label), *start);
+                }
+                builder.data(*start, &bytes[*start as usize..*end as usize]);
+            }
+        }
+        builder.finish()
+    }
+
+    /// Switches short jumps whose targets are out of `rel8` range to their long
+    /// form. Returns `true` if anything changed.
+    fn relax(&mut self, layout: &Layout) -> bool {
+        let mut changed = false;
+        for (section, offsets) in self.sections.iter_mut().zip(&layout.fragment_offsets)
+        {
+            for (index, fragment) in section.fragments.iter_mut().enumerate() {
+                if let Fragment::Jump {
+                    target,
+                    is_long: is_long @ false,
+                    ..
+                } = fragment
+                {
+                    let end = offsets[index + 1];
+                    let target = layout.offset_of(&self.labels, *target);
+                    let rel = i64::from(target) - i64::from(end);
+                    if i8::try_from(rel).is_err() {
+                        *is_long = true;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    fn write(&self, layout: &Layout) -> Vec<u8> {
+        let mut output = Vec::with_capacity(layout.total_len as usize);
+        for section in &self.section_order {
+            let offsets = &layout.fragment_offsets[*section as usize];
+            let section = &self.sections[*section as usize];
+            write_padding(&mut output, section.kind, offsets[0]);
+            for (index, fragment) in section.fragments.iter().enumerate() {
+                self.write_fragment(
+                    &mut output,
+                    layout,
+                    section.kind,
+                    fragment,
+                    offsets[index + 1],
+                );
+            }
+        }
+
+        self.constant_pool
+            .write(&layout.constant_offsets, &mut output);
+        for table in &self.jump_tables {
+            let start = layout.constant_offsets[table.entry as usize];
+            for (index, target) in table.targets.iter().enumerate() {
+                let target = layout.offset_of(&self.labels, *target);
+                #[allow(clippy::cast_possible_truncation)]
+                let rel = (i64::from(target) - i64::from(start)) as i32;
+                let position = start as usize + index * JUMP_TABLE_ENTRY_SIZE;
+                output[position..position + JUMP_TABLE_ENTRY_SIZE]
+                    .copy_from_slice(&rel.to_le_bytes());
+            }
+        }
+        output
+    }
+
+    /// Appends `fragment` ending at `end` to `output`.
+    fn write_fragment(
+        &self,
+        output: &mut Vec<u8>,
+        layout: &Layout,
+        kind: SectionKind,
+        fragment: &Fragment,
+        end: u32,
+    ) {
+        let rel_to = |target: Label| {
+            let target = layout.offset_of(&self.labels, target);
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                (i64::from(target) - i64::from(end)) as i32
+            }
+        };
+
+        match fragment {
+            Fragment::Instruction(instruction) => {
+                output.extend_from_slice(instruction.as_slice());
+            }
+            Fragment::Data { start, len } => {
+                let range = *start as usize..(*start + *len) as usize;
+                output.extend_from_slice(&self.data[range]);
+            }
+            Fragment::Align { .. } => write_padding(output, kind, end),
+            Fragment::Jump {
+                cond,
+                target,
+                is_long,
+            } => {
+                let rel = rel_to(*target);
+                let instruction = match (cond, is_long) {
+                    (None, true) => jmp::encode_jmp_rel32(rel),
+                    (Some(cond), true) => jcc::encode_jcc_rel32(*cond, rel),
+                    #[allow(clippy::cast_possible_truncation)]
+                    (None, false) => jmp::encode_jmp_rel8(rel as i8),
+                    #[allow(clippy::cast_possible_truncation)]
+                    (Some(cond), false) => jcc::encode_jcc_rel8(*cond, rel as i8),
+                };
+                output.extend_from_slice(instruction.as_slice());
+            }
+            Fragment::RipRelative {
+                instruction,
+                target,
+            } => {
+                let slice = instruction.as_slice();
+                let disp_start = output.len() + slice.len() - 4;
+                output.extend_from_slice(slice);
+                output[disp_start..].copy_from_slice(&rel_to(*target).to_le_bytes());
+            }
+            Fragment::Relocated {
+                instruction, kind, ..
+            } => {
+                let slice = instruction.as_slice();
+                output.extend_from_slice(slice);
+                let field_start = output.len() - kind.size() as usize;
+                output[field_start..].fill(0);
+            }
+            Fragment::Patchable {
+                instruction,
+                target,
+                ..
+            } => {
+                let slice = instruction.as_slice();
+                #[allow(clippy::cast_possible_truncation)]
+                write_nops(output, end - output.len() as u32 - slice.len() as u32);
+                output.extend_from_slice(slice);
+                if let Some(target) = target {
+                    let rel_start = output.len() - 4;
+                    output[rel_start..].copy_from_slice(&rel_to(*target).to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Labels shown in a [`Listing`], by position.
+#[derive(Default)]
+struct ListedLabels {
+    /// Labels bound before the given fragment of the given section.
+    code: HashMap<(u32, u32), Vec<Label>>,
+
+    /// Labels bound to the given constant pool entry.
+    constants: HashMap<u32, Vec<Label>>,
+}
+
+/// Offsets of all fragments and constants for the current state of jumps.
+#[derive(Default)]
+struct Layout {
+    /// Offset of each fragment of each section, plus the end of the section as
+    /// the last element.
+    fragment_offsets: Vec<Vec<u32>>,
+    constant_offsets: Vec<u32>,
+    code_end: u32,
+    total_len: u32,
+}
+
+impl Layout {
+    fn compute(&mut self, assembler: &Assembler) -> Result<(), AssemblerError> {
+        const LIMIT: u64 = i32::MAX as u64;
+
+        self.fragment_offsets
+            .resize_with(assembler.sections.len(), Vec::new);
+        let mut offset: u64 = 0;
+        for section in &assembler.section_order {
+            let offsets = &mut self.fragment_offsets[*section as usize];
+            let section = &assembler.sections[*section as usize];
+            offsets.clear();
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                offset += u64::from(padding(offset as u32, section.alignment));
+            }
+            for fragment in &section.fragments {
+                #[allow(clippy::cast_possible_truncation)]
+                let current = offset as u32;
+                offsets.push(current);
+                offset += u64::from(fragment.size(current));
+                if offset > LIMIT {
+                    return Err(AssemblerError::CodeTooLarge);
+                }
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            offsets.push(offset as u32);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            self.code_end = offset as u32;
+        }
+        let total_len = assembler
+            .constant_pool
+            .layout(self.code_end, &mut self.constant_offsets);
+        if total_len > LIMIT {
+            return Err(AssemblerError::CodeTooLarge);
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            self.total_len = total_len as u32;
+        }
+        Ok(())
+    }
+
+    fn offset_of(&self, labels: &[LabelPosition], label: Label) -> u32 {
+        match labels[label.index() as usize] {
+            LabelPosition::Code { section, fragment } => {
+                self.fragment_offsets[section as usize][fragment as usize]
+            }
+            LabelPosition::Constant(index) => self.constant_offsets[index as usize],
+            LabelPosition::Unbound => unreachable!("Labels are validated in finish()."),
+        }
+    }
+
+    fn label_offset(&self, assembler: &Assembler, index: usize) -> Option<u32> {
+        match assembler.labels[index] {
+            LabelPosition::Unbound => None,
+            #[allow(clippy::cast_possible_truncation)]
+            _ => Some(self.offset_of(&assembler.labels, Label::new(index as u32))),
+        }
+    }
+}
+
+/// Pads `output` up to `end` with NOPs in code, and with zeros in data.
+#[allow(clippy::cast_possible_truncation)]
+fn write_padding(output: &mut Vec<u8>, kind: SectionKind, end: u32) {
+    match kind {
+        SectionKind::Code => write_nops(output, end - output.len() as u32),
+        SectionKind::Data => output.resize(end as usize, 0),
+    }
+}
+
+fn write_nops(output: &mut Vec<u8>, mut size: u32) {
+    while size > 0 {
+        let chunk = size.min(MAX_NOP_SIZE);
+        #[allow(clippy::cast_possible_truncation)]
+        let nop = misc::encode_nop(chunk as u8).unwrap();
+        output.extend_from_slice(nop.as_slice());
+        size -= chunk;
+    }
+}
+
+/// Encodes the part of [`Assembler::emit_jump_table_dispatch`] following `lea`,
+/// validating the operands.
+pub(crate) fn encode_jump_table_dispatch(
+    index: GPR,
+    scratch: GPR,
+) -> Result<[EncodedInstruction; 3], AssemblerError> {
+    if index.size() != MachineSize::QWord || scratch.size() != MachineSize::QWord {
+        return Err(EncodingError::InvalidRegisterSize.into());
+    }
+    if index == scratch {
+        return Err(AssemblerError::InvalidSwitch);
+    }
+    let entry = Memory::new(scratch, index, Scale::Scale4, Displacement::ZERO)?;
+    Ok([
+        mov::encode_movsxd_reg_mem(index, entry)?,
+        arith::encode_add_reg_reg(scratch, index)?,
+        jmp::encode_jmp_reg(scratch)?,
+    ])
+}
+
+/// Returns the error if `fragment` in a section of the given `kind` is not
+/// allowed by `features`.
+fn check_cpu_features(
+    features: CpuFeatures,
+    kind: SectionKind,
+    fragment: &Fragment,
+) -> Option<AssemblerError> {
+    let requirement = match fragment {
+        Fragment::Instruction(instruction)
+        | Fragment::RipRelative { instruction, .. }
+        | Fragment::Relocated { instruction, .. }
+        | Fragment::Patchable { instruction, .. } => instruction.cpu_requirement(),
+        Fragment::Data { .. } if kind == SectionKind::Code => CpuRequirement::Unknown,
+        Fragment::Data { .. } | Fragment::Align { .. } | Fragment::Jump { .. } => {
+            CpuRequirement::NoFeature
+        }
+    };
+    match requirement {
+        _ if requirement.is_met_by(features) => None,
+        CpuRequirement::Feature(feature) => {
+            Some(EncodingError::UnsupportedCpuFeature(feature).into())
+        }
+        _ => Some(AssemblerError::UnknownCpuRequirement),
+    }
+}
+
+#[inline(always)]
+const fn validate_alignment(alignment: u32) -> Result<(), AssemblerError> {
+    if alignment.is_power_of_two() && alignment <= MAX_ALIGNMENT {
+        Ok(())
+    } else {
+        Err(AssemblerError::InvalidAlignment)
+    }
+}
+
+const _CHECK: () = const {
+    assert!(CONSTANT_POOL_ALIGNMENT <= MAX_ALIGNMENT);
+};
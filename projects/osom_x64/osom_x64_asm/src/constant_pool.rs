@@ -0,0 +1,114 @@
+use crate::{fragment::padding, Label};
+
+/// Alignment of the whole constant pool, relative to the start of the code.
+pub(crate) const CONSTANT_POOL_ALIGNMENT: u32 = 16;
+
+struct Entry {
+    start: u32,
+    len: u32,
+    alignment: u32,
+    label: Label,
+
+    /// Whether equal constants may reuse the entry. Entries patched after the
+    /// layout, like jump tables, are never shared.
+    is_shared: bool,
+}
+
+/// Deduplicated constants placed after the code.
+pub(crate) struct ConstantPool {
+    data: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl ConstantPool {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn bytes(&self, entry: &Entry) -> &[u8] {
+        &self.data[entry.start as usize..(entry.start + entry.len) as usize]
+    }
+
+    /// Returns the label of an existing constant with the same bytes and alignment.
+    pub(crate) fn find(&self, bytes: &[u8], alignment: u32) -> Option<Label> {
+        self.entries
+            .iter()
+            .find(|entry| {
+                entry.is_shared
+                    && entry.alignment == alignment
+                    && self.bytes(entry) == bytes
+            })
+            .map(|entry| entry.label)
+    }
+
+    /// Adds new constant and returns its index in the pool.
+    pub(crate) fn push(&mut self, bytes: &[u8], alignment: u32, label: Label) -> u32 {
+        self.push_entry(bytes, alignment, label, true)
+    }
+
+    /// Adds zeroed space of `len` bytes, never shared with other constants, and
+    /// returns its index in the pool. The content is expected to be patched
+    /// once the layout is known.
+    pub(crate) fn reserve(&mut self, len: usize, alignment: u32, label: Label) -> u32 {
+        self.push_entry(&vec![0; len], alignment, label, false)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_entry(
+        &mut self,
+        bytes: &[u8],
+        alignment: u32,
+        label: Label,
+        is_shared: bool,
+    ) -> u32 {
+        let entry = Entry {
+            start: self.data.len() as u32,
+            len: bytes.len() as u32,
+            alignment,
+            label,
+            is_shared,
+        };
+        self.data.extend_from_slice(bytes);
+        self.entries.push(entry);
+        (self.entries.len() - 1) as u32
+    }
+
+    /// Computes offsets of all constants, given that the pool starts right
+    /// after the code ending at `code_end`. Returns the end of the pool.
+    pub(crate) fn layout(&self, code_end: u32, offsets: &mut Vec<u32>) -> u64 {
+        offsets.clear();
+        let mut offset = u64::from(code_end);
+        if self.is_empty() {
+            return offset;
+        }
+
+        offset += u64::from(padding(code_end, CONSTANT_POOL_ALIGNMENT));
+        for entry in &self.entries {
+            #[allow(clippy::cast_possible_truncation)]
+            let current = offset as u32;
+            offset += u64::from(padding(current, entry.alignment));
+            #[allow(clippy::cast_possible_truncation)]
+            offsets.push(offset as u32);
+            offset += u64::from(entry.len);
+        }
+        offset
+    }
+
+    /// Writes the pool into `output`, which has to end at the `code_end` passed
+    /// to [`ConstantPool::layout`]. Padding is filled with zeros.
+    pub(crate) fn write(&self, offsets: &[u32], output: &mut Vec<u8>) {
+        for (entry, offset) in self.entries.iter().zip(offsets) {
+            output.resize(*offset as usize, 0);
+            output.extend_from_slice(self.bytes(entry));
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Mnemonic functions called by the [`x64!`][crate::x64] macro. Each of them
+//! takes the assembler and a tuple of operands, and is only implemented for
+//! operand combinations the encoder supports.
+
+use osom_x64_encoder::{
+    encoder::{arith, lea, misc, mov, sse, stack},
+    models::{ArithmeticOp, Condition},
+};
+
+use super::{Gpr, IntoMemory, RipLabel, Xmm};
+use crate::{assembler::RIP_PLACEHOLDER, Assembler, AssemblerError, Label};
+
+/// Operands accepted by [`mov()`].
+pub trait MovOperands {
+    /// Appends `mov` with these operands.
+    ///
+    /// # Errors
+    /// Propagates encoding errors, e.g. size mismatch of registers.
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError>;
+}
+
+impl MovOperands for (Gpr, Gpr) {
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit(mov::encode_mov_reg_reg(self.0 .0, self.1 .0)?);
+        Ok(())
+    }
+}
+
+impl MovOperands for (Gpr, i64) {
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit(mov::encode_mov_reg_imm(self.0 .0, self.1)?);
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> MovOperands for (Gpr, [M; 1]) {
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit(mov::encode_mov_reg_mem(
+            self.0 .0,
+            self.1[0].into_memory()?,
+        )?);
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> MovOperands for ([M; 1], Gpr) {
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit(mov::encode_mov_mem_reg(
+            self.0[0].into_memory()?,
+            self.1 .0,
+        )?);
+        Ok(())
+    }
+}
+
+impl MovOperands for (Gpr, [RipLabel; 1]) {
+    fn emit_mov(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        let instruction = mov::encode_mov_reg_mem(self.0 .0, RIP_PLACEHOLDER)?;
+        asm.emit_rip_relative(instruction, self.1[0].0);
+        Ok(())
+    }
+}
+
+/// Operands accepted by arithmetic mnemonics, e.g. [`add`] or [`cmp`].
+pub trait ArithOperands {
+    /// Appends `op` with these operands.
+    ///
+    /// # Errors
+    /// Propagates encoding errors, e.g. size mismatch of registers.
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError>;
+}
+
+impl ArithOperands for (Gpr, Gpr) {
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError> {
+        asm.emit(arith::encode_arith_reg_reg(op, self.0 .0, self.1 .0)?);
+        Ok(())
+    }
+}
+
+impl ArithOperands for (Gpr, i32) {
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError> {
+        asm.emit(arith::encode_arith_reg_imm(op, self.0 .0, self.1)?);
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> ArithOperands for (Gpr, [M; 1]) {
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError> {
+        let memory = self.1[0].into_memory()?;
+        asm.emit(arith::encode_arith_reg_mem(op, self.0 .0, memory)?);
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> ArithOperands for ([M; 1], Gpr) {
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError> {
+        let memory = self.0[0].into_memory()?;
+        asm.emit(arith::encode_arith_mem_reg(op, memory, self.1 .0)?);
+        Ok(())
+    }
+}
+
+impl ArithOperands for (Gpr, [RipLabel; 1]) {
+    fn emit_arith(
+        self,
+        asm: &mut Assembler,
+        op: ArithmeticOp,
+    ) -> Result<(), AssemblerError> {
+        let memory = RIP_PLACEHOLDER;
+        let instruction = arith::encode_arith_reg_mem(op, self.0 .0, memory)?;
+        asm.emit_rip_relative(instruction, self.1[0].0);
+        Ok(())
+    }
+}
+
+/// Operands accepted by [`lea()`].
+pub trait LeaOperands {
+    /// Appends `lea` with these operands.
+    ///
+    /// # Errors
+    /// Propagates encoding errors, e.g. invalid register size.
+    fn emit_lea(self, asm: &mut Assembler) -> Result<(), AssemblerError>;
+}
+
+impl<M: IntoMemory> LeaOperands for (Gpr, [M; 1]) {
+    fn emit_lea(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit(lea::encode_lea(self.0 .0, self.1[0].into_memory()?)?);
+        Ok(())
+    }
+}
+
+impl LeaOperands for (Gpr, [RipLabel; 1]) {
+    fn emit_lea(self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        asm.emit_lea_label(self.0 .0, self.1[0].0)
+    }
+}
+
+/// Operands accepted by [`movsd`] and [`movss`].
+pub trait ScalarMoveOperands {
+    /// Appends `movsd` if `is_double` is set, and `movss` otherwise.
+    ///
+    /// # Errors
+    /// Propagates errors of memory operands.
+    fn emit_scalar_move(
+        self,
+        asm: &mut Assembler,
+        is_double: bool,
+    ) -> Result<(), AssemblerError>;
+}
+
+impl ScalarMoveOperands for (Xmm, Xmm) {
+    fn emit_scalar_move(
+        self,
+        asm: &mut Assembler,
+        is_double: bool,
+    ) -> Result<(), AssemblerError> {
+        let (dst, src) = (self.0 .0, self.1 .0);
+        asm.emit(if is_double {
+            sse::encode_movsd_xmm_xmm(dst, src)
+        } else {
+            sse::encode_movss_xmm_xmm(dst, src)
+        });
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> ScalarMoveOperands for (Xmm, [M; 1]) {
+    fn emit_scalar_move(
+        self,
+        asm: &mut Assembler,
+        is_double: bool,
+    ) -> Result<(), AssemblerError> {
+        let (dst, src) = (self.0 .0, self.1[0].into_memory()?);
+        asm.emit(if is_double {
+            sse::encode_movsd_xmm_mem(dst, src)
+        } else {
+            sse::encode_movss_xmm_mem(dst, src)
+        });
+        Ok(())
+    }
+}
+
+impl<M: IntoMemory> ScalarMoveOperands for ([M; 1], Xmm) {
+    fn emit_scalar_move(
+        self,
+        asm: &mut Assembler,
+        is_double: bool,
+    ) -> Result<(), AssemblerError> {
+        let (dst, src) = (self.0[0].into_memory()?, self.1 .0);
+        asm.emit(if is_double {
+            sse::encode_movsd_mem_xmm(dst, src)
+        } else {
+            sse::encode_movss_mem_xmm(dst, src)
+        });
+        Ok(())
+    }
+}
+
+impl ScalarMoveOperands for (Xmm, [RipLabel; 1]) {
+    fn emit_scalar_move(
+        self,
+        asm: &mut Assembler,
+        is_double: bool,
+    ) -> Result<(), AssemblerError> {
+        let (dst, target) = (self.0 .0, self.1[0].0);
+        if is_double {
+            asm.emit_movsd_label(dst, target);
+        } else {
+            asm.emit_movss_label(dst, target);
+        }
+        Ok(())
+    }
+}
+
+/// Appends `mov`.
+///
+/// # Errors
+/// See [`MovOperands::emit_mov`].
+#[inline(always)]
+pub fn mov<T: MovOperands>(
+    asm: &mut Assembler,
+    operands: T,
+) -> Result<(), AssemblerError> {
+    operands.emit_mov(asm)
+}
+
+/// Appends `lea`.
+///
+/// # Errors
+/// See [`LeaOperands::emit_lea`].
+#[inline(always)]
+pub fn lea<T: LeaOperands>(
+    asm: &mut Assembler,
+    operands: T,
+) -> Result<(), AssemblerError> {
+    operands.emit_lea(asm)
+}
+
+/// Appends `movsd`.
+///
+/// # Errors
+/// See [`ScalarMoveOperands::emit_scalar_move`].
+#[inline(always)]
+pub fn movsd<T: ScalarMoveOperands>(
+    asm: &mut Assembler,
+    operands: T,
+) -> Result<(), AssemblerError> {
+    operands.emit_scalar_move(asm, true)
+}
+
+/// Appends `movss`.
+///
+/// # Errors
+/// See [`ScalarMoveOperands::emit_scalar_move`].
+#[inline(always)]
+pub fn movss<T: ScalarMoveOperands>(
+    asm: &mut Assembler,
+    operands: T,
+) -> Result<(), AssemblerError> {
+    operands.emit_scalar_move(asm, false)
+}
+
+/// Appends `push reg`.
+///
+/// # Errors
+/// Propagates encoding errors, i.e. `reg` not being a 64-bit register.
+#[inline(always)]
+pub fn push(asm: &mut Assembler, (reg,): (Gpr,)) -> Result<(), AssemblerError> {
+    asm.emit(stack::encode_push(reg.0)?);
+    Ok(())
+}
+
+/// Appends `pop reg`.
+///
+/// # Errors
+/// Propagates encoding errors, i.e. `reg` not being a 64-bit register.
+#[inline(always)]
+pub fn pop(asm: &mut Assembler, (reg,): (Gpr,)) -> Result<(), AssemblerError> {
+    asm.emit(stack::encode_pop(reg.0)?);
+    Ok(())
+}
+
+/// Appends `ret`.
+///
+/// # Errors
+/// Never fails, returns [`Result`] for uniformity with other mnemonics.
+#[inline(always)]
+pub fn ret(asm: &mut Assembler, (): ()) -> Result<(), AssemblerError> {
+    asm.emit(misc::encode_ret());
+    Ok(())
+}
+
+/// Appends a single byte `nop`.
+///
+/// # Errors
+/// Never fails, returns [`Result`] for uniformity with other mnemonics.
+#[inline(always)]
+pub fn nop(asm: &mut Assembler, (): ()) -> Result<(), AssemblerError> {
+    asm.emit(misc::encode_nop(1)?);
+    Ok(())
+}
+
+/// Appends `jmp target`.
+///
+/// # Errors
+/// Never fails, returns [`Result`] for uniformity with other mnemonics.
+#[inline(always)]
+pub fn jmp(asm: &mut Assembler, (target,): (Label,)) -> Result<(), AssemblerError> {
+    asm.emit_jmp(target);
+    Ok(())
+}
+
+/// Appends `jcc target` with an explicit condition, e.g. `jcc Condition::Less, done`.
+///
+/// # Errors
+/// Never fails, returns [`Result`] for uniformity with other mnemonics.
+#[inline(always)]
+pub fn jcc(
+    asm: &mut Assembler,
+    (cond, target): (Condition, Label),
+) -> Result<(), AssemblerError> {
+    asm.emit_jcc(cond, target);
+    Ok(())
+}
+
+macro_rules! arith_mnemonics {
+    ( $( $name: ident => $op: ident ),* $(,)? ) => {
+        $(
+            #[doc = concat!("Appends `", stringify!($name), "`.")]
+            ///
+            /// # Errors
+            /// See [`ArithOperands::emit_arith`].
+            #[inline(always)]
+            pub fn $name<T: ArithOperands>(
+                asm: &mut Assembler,
+                operands: T,
+            ) -> Result<(), AssemblerError> {
+                operands.emit_arith(asm, ArithmeticOp::$op)
+            }
+        )*
+    };
+}
+
+arith_mnemonics!(
+    add => Add,
+    or => Or,
+    adc => Adc,
+    sbb => Sbb,
+    and => And,
+    sub => Sub,
+    xor => Xor,
+    cmp => Cmp,
+);
+
+macro_rules! jcc_mnemonics {
+    ( $( $name: ident => $cond: ident ),* $(,)? ) => {
+        $(
+            #[doc = concat!("Appends `", stringify!($name), " target`.")]
+            ///
+            /// # Errors
+            /// Never fails, returns [`Result`] for uniformity with other mnemonics.
+            #[inline(always)]
+            pub fn $name(asm: &mut Assembler, (target,): (Label,)) -> Result<(), AssemblerError> {
+                asm.emit_jcc(Condition::$cond, target);
+                Ok(())
+            }
+        )*
+    };
+}
+
+jcc_mnemonics!(
+    ja => Above,
+    jae => AboveOrEqual,
+    jb => Below,
+    jbe => BelowOrEqual,
+    jc => Carry,
+    jnc => NotCarry,
+    je => Equal,
+    jne => NotEqual,
+    jg => Greater,
+    jge => GreaterOrEqual,
+    jl => Less,
+    jle => LessOrEqual,
+    jo => Overflow,
+    jno => NotOverflow,
+    jp => Parity,
+    jnp => NotParity,
+    js => Sign,
+    jns => NotSign,
+    jz => Zero,
+    jnz => NotZero,
+);
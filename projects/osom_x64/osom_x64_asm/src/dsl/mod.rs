@@ -0,0 +1,68 @@
+//! Typed instruction DSL, see the [`x64!`][crate::x64] macro.
+//!
+//! Registers are lowercase constants in [`regs`], memory operands are single
+//! element arrays of address expressions (e.g. `[rbp - 8]`), immediates are
+//! plain integers and branch targets are [`Label`][crate::Label]s. Each mnemonic
+//! is a function accepting a tuple of operands, implemented only for the
+//! combinations the encoder supports. Passing e.g. an XMM register to `add`
+//! is therefore a compile time error.
+
+mod instructions;
+mod operands;
+pub mod regs;
+
+pub use instructions::*;
+pub use operands::*;
+
+/// Appends instructions written in Intel syntax to an [`Assembler`][crate::Assembler].
+/// Evaluates to `Result<(), AssemblerError>`, stopping at the first instruction
+/// that fails to encode.
+///
+/// Instructions are separated by `;`, labels are [`Label`][crate::Label]
+/// variables bound with `name:`.
+///
+/// ```
+/// use osom_x64_asm::{x64, Assembler, AssemblerError};
+///
+/// fn build() -> Result<(), AssemblerError> {
+///     let mut asm = Assembler::new();
+///     let done = asm.create_label();
+///     x64!(asm =>
+///         mov rax, [rdi + rsi * 8 + 16];
+///         add rax, 8;
+///         cmp rax, rdx;
+///         jne done;
+///         xor eax, eax;
+///     done:
+///         ret;
+///     )?;
+///     let code = asm.finish()?;
+///     assert!(!code.code().is_empty());
+///     Ok(())
+/// }
+/// # build().unwrap();
+/// ```
+#[macro_export]
+macro_rules! x64 {
+    (@munch $asm: ident;) => {};
+    (@munch $asm: ident; $label: ident : $($rest: tt)*) => {
+        $asm.bind_label($label)?;
+        $crate::x64!(@munch $asm; $($rest)*);
+    };
+    (@munch $asm: ident; $mnemonic: ident $($operand: expr),* ; $($rest: tt)*) => {
+        $crate::dsl::$mnemonic($asm, ($($operand,)*))?;
+        $crate::x64!(@munch $asm; $($rest)*);
+    };
+    ($asm: expr => $($body: tt)*) => {{
+        #[allow(unused_imports)]
+        use $crate::dsl::regs::*;
+
+        let asm: &mut $crate::Assembler = &mut $asm;
+        #[allow(clippy::redundant_closure_call)]
+        let result = (|| -> ::core::result::Result<(), $crate::AssemblerError> {
+            $crate::x64!(@munch asm; $($body)*);
+            Ok(())
+        })();
+        result
+    }};
+}
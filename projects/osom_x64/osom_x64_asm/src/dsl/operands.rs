@@ -0,0 +1,249 @@
+use core::ops::{Add, Mul, Sub};
+
+use osom_x64_encoder::{
+    encoder::errors::EncodingError,
+    models::{Displacement, Memory, Scale, GPR, XMM},
+};
+
+use crate::{AssemblerError, Label};
+
+/// General purpose register operand of the [`x64!`][crate::x64] macro.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Gpr(pub GPR);
+
+/// XMM register operand of the [`x64!`][crate::x64] macro.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Xmm(pub XMM);
+
+/// The instruction pointer. Only meaningful as `rip + label`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Rip;
+
+/// Result of `rip + label`, used as `[rip + label]` memory operand.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct RipLabel(pub Label);
+
+/// Memory address expression, built with `+`, `-` and `*` operators from
+/// registers and integers, e.g. `rbp - 8` or `rax + rcx * 8 + 16`.
+///
+/// # Notes
+/// The expression is validated only when converted to [`Memory`], so that
+/// invalid combinations (e.g. two indexes or scale of 3) surface as
+/// [`EncodingError::InvalidMemoryOperand`] at runtime.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct MemExpr {
+    base: Option<GPR>,
+    index: Option<(GPR, i32)>,
+    displacement: i64,
+    is_valid: bool,
+}
+
+impl MemExpr {
+    const EMPTY: Self = Self {
+        base: None,
+        index: None,
+        displacement: 0,
+        is_valid: true,
+    };
+
+    #[inline(always)]
+    const fn with_displacement(mut self, displacement: i64) -> Self {
+        self.displacement += displacement;
+        self
+    }
+
+    /// Adds `reg` as the base, or as the index if the base is already taken.
+    const fn with_register(mut self, reg: GPR, factor: i32) -> Self {
+        if factor == 1 && self.base.is_none() {
+            self.base = Some(reg);
+        } else if self.index.is_none() {
+            self.index = Some((reg, factor));
+        } else {
+            self.is_valid = false;
+        }
+        self
+    }
+
+    const fn merge(self, other: Self) -> Self {
+        let mut result = self.with_displacement(other.displacement);
+        result.is_valid &= other.is_valid;
+        if let Some(base) = other.base {
+            result = result.with_register(base, 1);
+        }
+        if let Some((index, factor)) = other.index {
+            result = result.with_register(index, factor);
+        }
+        result
+    }
+
+    /// Converts the expression into a [`Memory`] operand.
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidMemoryOperand`] if the expression does not form
+    ///   a valid `base + index * scale + displacement` operand.
+    /// * [`EncodingError::ArgumentOutOfRange`] if the displacement does not fit
+    ///   in 32 bits.
+    pub fn to_memory(self) -> Result<Memory, EncodingError> {
+        if !self.is_valid {
+            return Err(EncodingError::InvalidMemoryOperand);
+        }
+        let (index, scale) = match self.index {
+            None => (GPR::NO_REG, Scale::Scale1),
+            Some((index, 1)) => (index, Scale::Scale1),
+            Some((index, 2)) => (index, Scale::Scale2),
+            Some((index, 4)) => (index, Scale::Scale4),
+            Some((index, 8)) => (index, Scale::Scale8),
+            Some(_) => return Err(EncodingError::InvalidMemoryOperand),
+        };
+        let base = self.base.unwrap_or(GPR::NO_REG);
+        Memory::new(base, index, scale, Displacement::new(self.displacement))
+    }
+}
+
+impl From<Gpr> for MemExpr {
+    #[inline(always)]
+    fn from(value: Gpr) -> Self {
+        MemExpr::EMPTY.with_register(value.0, 1)
+    }
+}
+
+impl Add<i32> for Gpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: i32) -> MemExpr {
+        MemExpr::from(self).with_displacement(rhs.into())
+    }
+}
+
+impl Sub<i32> for Gpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn sub(self, rhs: i32) -> MemExpr {
+        MemExpr::from(self).with_displacement(-i64::from(rhs))
+    }
+}
+
+impl Mul<i32> for Gpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn mul(self, rhs: i32) -> MemExpr {
+        MemExpr::EMPTY.with_register(self.0, rhs)
+    }
+}
+
+impl Add<Gpr> for Gpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: Gpr) -> MemExpr {
+        MemExpr::from(self).with_register(rhs.0, 1)
+    }
+}
+
+impl Add<MemExpr> for Gpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: MemExpr) -> MemExpr {
+        MemExpr::from(self).merge(rhs)
+    }
+}
+
+impl Add<i32> for MemExpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: i32) -> MemExpr {
+        self.with_displacement(rhs.into())
+    }
+}
+
+impl Sub<i32> for MemExpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn sub(self, rhs: i32) -> MemExpr {
+        self.with_displacement(-i64::from(rhs))
+    }
+}
+
+impl Add<Gpr> for MemExpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: Gpr) -> MemExpr {
+        self.with_register(rhs.0, 1)
+    }
+}
+
+impl Add<MemExpr> for MemExpr {
+    type Output = MemExpr;
+
+    #[inline(always)]
+    fn add(self, rhs: MemExpr) -> MemExpr {
+        self.merge(rhs)
+    }
+}
+
+impl Add<Label> for Rip {
+    type Output = RipLabel;
+
+    #[inline(always)]
+    fn add(self, rhs: Label) -> RipLabel {
+        RipLabel(rhs)
+    }
+}
+
+/// Content of a memory operand written in square brackets, e.g. `[rbp - 8]`.
+/// Implemented for registers and address expressions.
+pub trait IntoMemory: Copy {
+    /// Converts the operand into [`Memory`].
+    ///
+    /// # Errors
+    /// See [`MemExpr::to_memory`].
+    fn into_memory(self) -> Result<Memory, AssemblerError>;
+}
+
+impl IntoMemory for Gpr {
+    #[inline(always)]
+    fn into_memory(self) -> Result<Memory, AssemblerError> {
+        Ok(MemExpr::from(self).to_memory()?)
+    }
+}
+
+impl IntoMemory for MemExpr {
+    #[inline(always)]
+    fn into_memory(self) -> Result<Memory, AssemblerError> {
+        Ok(self.to_memory()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dsl::regs::*;
+
+    #[rstest]
+    #[case(rbp - 8, Memory::based(GPR::RBP, -8))]
+    #[case(rax + rcx * 8 + 16, Memory::new(GPR::RAX, GPR::RCX, Scale::Scale8, Displacement::new(16)))]
+    #[case(rcx * 4 + rax, Memory::new(GPR::RAX, GPR::RCX, Scale::Scale4, Displacement::ZERO))]
+    #[case(r9 * 2 - 1, Memory::new(GPR::NO_REG, GPR::R9, Scale::Scale2, Displacement::new(-1)))]
+    #[case(rsi + rdi, Memory::new(GPR::RSI, GPR::RDI, Scale::Scale1, Displacement::ZERO))]
+    #[case(rax + rcx * 3, Err(EncodingError::InvalidMemoryOperand))]
+    #[case(rax + rcx + rdx, Err(EncodingError::InvalidMemoryOperand))]
+    #[case(rax + i32::MAX + 1, Err(EncodingError::ArgumentOutOfRange))]
+    #[case(eax + 1, Err(EncodingError::InvalidMemoryOperand))]
+    fn test_mem_expr(
+        #[case] expr: MemExpr,
+        #[case] expected: Result<Memory, EncodingError>,
+    ) {
+        assert_eq!(expr.to_memory(), expected);
+    }
+}
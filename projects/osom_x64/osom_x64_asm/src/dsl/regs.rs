@@ -0,0 +1,50 @@
+//! Lowercase register names used by the [`x64!`][crate::x64] macro.
+#![allow(non_upper_case_globals)]
+
+use osom_x64_encoder::models::{GPR, XMM};
+
+use super::{Gpr, Rip, Xmm};
+
+macro_rules! regs {
+    ( $wrapper: ident, $model: ident: $( $name: ident => $field: ident ),* $(,)? ) => {
+        $(
+            pub const $name: $wrapper = $wrapper($model::$field);
+        )*
+    };
+}
+
+/// The instruction pointer, only usable in `[rip + label]` memory operands.
+pub const rip: Rip = Rip;
+
+regs!(Gpr, GPR:
+    al => AL, cl => CL, dl => DL, bl => BL, spl => SPL, bpl => BPL, sil => SIL, dil => DIL,
+    r8b => R8B, r9b => R9B, r10b => R10B, r11b => R11B,
+    r12b => R12B, r13b => R13B, r14b => R14B, r15b => R15B,
+);
+
+regs!(Gpr, GPR:
+    ax => AX, cx => CX, dx => DX, bx => BX, sp => SP, bp => BP, si => SI, di => DI,
+    r8w => R8W, r9w => R9W, r10w => R10W, r11w => R11W,
+    r12w => R12W, r13w => R13W, r14w => R14W, r15w => R15W,
+);
+
+regs!(Gpr, GPR:
+    eax => EAX, ecx => ECX, edx => EDX, ebx => EBX,
+    esp => ESP, ebp => EBP, esi => ESI, edi => EDI,
+    r8d => R8D, r9d => R9D, r10d => R10D, r11d => R11D,
+    r12d => R12D, r13d => R13D, r14d => R14D, r15d => R15D,
+);
+
+regs!(Gpr, GPR:
+    rax => RAX, rcx => RCX, rdx => RDX, rbx => RBX,
+    rsp => RSP, rbp => RBP, rsi => RSI, rdi => RDI,
+    r8 => R8, r9 => R9, r10 => R10, r11 => R11,
+    r12 => R12, r13 => R13, r14 => R14, r15 => R15,
+);
+
+regs!(Xmm, XMM:
+    xmm0 => XMM0, xmm1 => XMM1, xmm2 => XMM2, xmm3 => XMM3,
+    xmm4 => XMM4, xmm5 => XMM5, xmm6 => XMM6, xmm7 => XMM7,
+    xmm8 => XMM8, xmm9 => XMM9, xmm10 => XMM10, xmm11 => XMM11,
+    xmm12 => XMM12, xmm13 => XMM13, xmm14 => XMM14, xmm15 => XMM15,
+);
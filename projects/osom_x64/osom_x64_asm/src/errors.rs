@@ -0,0 +1,74 @@
+use osom_x64_encoder::encoder::errors::EncodingError;
+
+use crate::Label;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum AssemblerError {
+    /// [`Label`] was bound twice.
+    LabelAlreadyBound(Label),
+
+    /// [`Label`] was referenced, but never bound.
+    UnboundLabel(Label),
+
+    /// Alignment is not a power of two, or is too big.
+    InvalidAlignment,
+
+    /// The assembled code would not be addressable with 32-bit offsets.
+    CodeTooLarge,
+
+    /// Stack frame does not fit in 31 bits.
+    FrameTooLarge,
+
+    /// Section order does not list every section exactly once.
+    InvalidSectionOrder,
+
+    /// Switch has duplicate case values, or uses the same register as its index
+    /// and scratch.
+    InvalidSwitch,
+
+    /// IR instruction has an unsupported operation, uses a virtual register of a
+    /// wrong class or one created by another function, or refers to a label not
+    /// bound exactly once. Also returned for functions not ending with `ret` or
+    /// `jmp`.
+    InvalidIr,
+
+    /// Instruction of unknown [`CpuRequirement`], or raw bytes in a code section,
+    /// were emitted while instructions are restricted with
+    /// [`Assembler::set_cpu_features`].
+    ///
+    /// [`CpuRequirement`]: osom_x64_encoder::models::CpuRequirement
+    /// [`Assembler::set_cpu_features`]: crate::Assembler::set_cpu_features
+    UnknownCpuRequirement,
+
+    /// Underlying encoder rejected an instruction.
+    Encoding(EncodingError),
+}
+
+impl From<EncodingError> for AssemblerError {
+    fn from(value: EncodingError) -> Self {
+        Self::Encoding(value)
+    }
+}
+
+/// Error returned by [`ExecutableCode`][crate::ExecutableCode].
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum PatchError {
+    /// The operating system refused to map the code.
+    MappingFailed,
+
+    /// The operating system refused to change the protection of a page.
+    ProtectionFailed,
+
+    /// [`PatchPoint`][crate::PatchPoint] does not exist, or is of a different
+    /// [`PatchKind`][crate::PatchKind].
+    InvalidPatchPoint,
+
+    /// Jump target is not within `rel32` range of the patched instruction, or a
+    /// PC-relative relocation cannot reach its symbol.
+    TargetOutOfRange,
+
+    /// Relocation references a symbol missing from the
+    /// [`SymbolTable`][crate::SymbolTable].
+    UndefinedSymbol,
+}
@@ -0,0 +1,345 @@
+use core::{
+    ffi::c_void,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{
+    unwind::CfiDirective, AssembledCode, PatchError, PatchKind, PatchPoint,
+    RelocationKind, Symbol, SymbolTable, PATCH_WINDOW_SIZE,
+};
+
+/// Size of a global offset table slot, holding a symbol's address.
+const GOT_SLOT_SIZE: usize = 8;
+
+/// Size of a stub jumping through a global offset table slot.
+const STUB_SIZE: usize = 8;
+
+mod sys {
+    use core::ffi::c_void;
+
+    pub const PROT_READ: i32 = 1;
+    pub const PROT_WRITE: i32 = 2;
+    pub const PROT_EXEC: i32 = 4;
+    pub const MAP_PRIVATE: i32 = 0x02;
+    pub const MAP_ANONYMOUS: i32 = 0x20;
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+    pub const SC_PAGESIZE: i32 = 30;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> i32;
+        pub fn munmap(addr: *mut c_void, len: usize) -> i32;
+        pub fn sysconf(name: i32) -> i64;
+    }
+}
+
+/// [`AssembledCode`] copied into its own readable and executable mapping, with
+/// its relocations resolved, together with its [`PatchPoint`]s.
+///
+/// # Notes
+/// Patching temporarily makes the page holding the patched field writable, in
+/// addition to executable, so that other threads may keep running the code.
+/// The field is then replaced with a single atomic store of the aligned 8-byte
+/// window containing it, thus a concurrently executing thread sees either the
+/// old or the new instruction, never a mix of both. Patches are serialized.
+pub struct ExecutableCode {
+    address: NonNull<u8>,
+    len: usize,
+    code_len: usize,
+    mapping_len: usize,
+    page_size: usize,
+    patch_points: Vec<PatchPoint>,
+    cfi_directives: Vec<(u32, CfiDirective)>,
+    patch_lock: Mutex<()>,
+}
+
+// The mapping is owned exclusively and only modified under `patch_lock`.
+unsafe impl Send for ExecutableCode {}
+unsafe impl Sync for ExecutableCode {}
+
+impl ExecutableCode {
+    /// Maps a copy of `code`.
+    ///
+    /// # Errors
+    /// * [`PatchError::MappingFailed`] if the memory cannot be mapped, or made
+    ///   executable.
+    /// * [`PatchError::UndefinedSymbol`] if `code` has any relocations, see
+    ///   [`ExecutableCode::link`].
+    #[inline(always)]
+    pub fn new(code: &AssembledCode) -> Result<Self, PatchError> {
+        Self::link(code, &SymbolTable::new())
+    }
+
+    /// Maps a copy of `code`, resolving its [`Relocation`][crate::Relocation]s with addresses from
+    /// `symbols`.
+    ///
+    /// # Notes
+    /// Symbols referenced through [`RelocationKind::GotPc32`] or
+    /// [`RelocationKind::Plt32`] get a slot in a global offset table mapped right
+    /// after the code. Calls and jumps to symbols out of `rel32` range are
+    /// redirected to stubs jumping through these slots, placed after the table.
+    ///
+    /// # Errors
+    /// * [`PatchError::MappingFailed`] if the memory cannot be mapped, or made
+    ///   executable.
+    /// * [`PatchError::UndefinedSymbol`] if a referenced symbol is not defined in
+    ///   `symbols`.
+    /// * [`PatchError::TargetOutOfRange`] if a [`RelocationKind::Pc32`] symbol is
+    ///   more than 2GB away from its reference.
+    pub fn link(
+        code: &AssembledCode,
+        symbols: &SymbolTable,
+    ) -> Result<Self, PatchError> {
+        let bytes = code.as_slice();
+        let mut targets = Vec::with_capacity(code.relocations().len());
+        let mut slots: Vec<(Symbol, u64)> = Vec::new();
+        for relocation in code.relocations() {
+            let symbol = relocation.symbol();
+            let Some(target) = symbols.address(code.symbol_name(symbol)) else {
+                return Err(PatchError::UndefinedSymbol);
+            };
+            let needs_slot = matches!(
+                relocation.kind(),
+                RelocationKind::GotPc32 | RelocationKind::Plt32
+            );
+            let slot = needs_slot.then(|| {
+                slots
+                    .iter()
+                    .position(|(slotted, _)| *slotted == symbol)
+                    .unwrap_or_else(|| {
+                        slots.push((symbol, target));
+                        slots.len() - 1
+                    })
+            });
+            targets.push((target, slot));
+        }
+        let got_offset = bytes.len().next_multiple_of(GOT_SLOT_SIZE);
+        let stubs_offset = got_offset + GOT_SLOT_SIZE * slots.len();
+        let image_len = stubs_offset + STUB_SIZE * slots.len();
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let page_size = unsafe { sys::sysconf(sys::SC_PAGESIZE) } as usize;
+        let mapping_len = image_len.max(1);
+        let address = unsafe {
+            sys::mmap(
+                core::ptr::null_mut(),
+                mapping_len,
+                sys::PROT_READ | sys::PROT_WRITE,
+                sys::MAP_PRIVATE | sys::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if address == sys::MAP_FAILED {
+            return Err(PatchError::MappingFailed);
+        }
+
+        let result = Self {
+            address: unsafe { NonNull::new_unchecked(address.cast()) },
+            len: bytes.len(),
+            code_len: code.code().len(),
+            mapping_len,
+            page_size,
+            patch_points: code.patch_points().to_vec(),
+            cfi_directives: code.cfi_directives().to_vec(),
+            patch_lock: Mutex::new(()),
+        };
+        let image =
+            unsafe { core::slice::from_raw_parts_mut(address.cast(), image_len) };
+        image[..bytes.len()].copy_from_slice(bytes);
+
+        let base = address as u64;
+        let slot_address =
+            |index: usize| base + (got_offset + index * GOT_SLOT_SIZE) as u64;
+        let stub_address =
+            |index: usize| base + (stubs_offset + index * STUB_SIZE) as u64;
+        for (index, (_, target)) in slots.iter().enumerate() {
+            let slot = got_offset + index * GOT_SLOT_SIZE;
+            image[slot..slot + GOT_SLOT_SIZE].copy_from_slice(&target.to_le_bytes());
+
+            // jmp qword ptr [rip + slot], padded with int3.
+            let stub = stubs_offset + index * STUB_SIZE;
+            let rel = relative(slot_address(index), stub_address(index) + 6)
+                .ok_or(PatchError::TargetOutOfRange)?;
+            image[stub..stub + 2].copy_from_slice(&[0xFF, 0x25]);
+            image[stub + 2..stub + 6].copy_from_slice(&rel.to_le_bytes());
+            image[stub + 6..stub + STUB_SIZE].fill(0xCC);
+        }
+
+        for (relocation, (target, slot)) in code.relocations().iter().zip(targets) {
+            let field = relocation.offset() as usize;
+            let place = (base + field as u64).wrapping_add_signed(-relocation.addend());
+            let rel = match (relocation.kind(), slot) {
+                (RelocationKind::Abs64, _) => {
+                    let value = target.wrapping_add_signed(relocation.addend());
+                    image[field..field + 8].copy_from_slice(&value.to_le_bytes());
+                    continue;
+                }
+                (RelocationKind::Plt32, Some(slot)) => relative(target, place)
+                    .or_else(|| relative(stub_address(slot), place)),
+                (RelocationKind::GotPc32, Some(slot)) => {
+                    relative(slot_address(slot), place)
+                }
+                _ => relative(target, place),
+            };
+            let rel = rel.ok_or(PatchError::TargetOutOfRange)?;
+            image[field..field + 4].copy_from_slice(&rel.to_le_bytes());
+        }
+
+        unsafe {
+            let protection = sys::PROT_READ | sys::PROT_EXEC;
+            if sys::mprotect(address, mapping_len, protection) != 0 {
+                return Err(PatchError::MappingFailed);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the address of the start of the code.
+    #[must_use]
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *const u8 {
+        self.address.as_ptr()
+    }
+
+    /// Returns the size of the code, including the constant pool.
+    #[must_use]
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the code, without the constant pool.
+    #[must_use]
+    #[inline(always)]
+    pub const fn code_len(&self) -> usize {
+        self.code_len
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn patch_points(&self) -> &[PatchPoint] {
+        &self.patch_points
+    }
+
+    /// See [`AssembledCode::cfi_directives`].
+    #[must_use]
+    #[inline(always)]
+    pub fn cfi_directives(&self) -> &[(u32, CfiDirective)] {
+        &self.cfi_directives
+    }
+
+    /// Returns the start of the code as a function pointer.
+    ///
+    /// # Safety
+    /// `F` has to be a function pointer type matching the code, including its
+    /// calling convention.
+    ///
+    /// # Panics
+    /// When `F` is not pointer sized.
+    #[must_use]
+    pub unsafe fn entry<F: Copy>(&self) -> F {
+        assert_eq!(size_of::<F>(), size_of::<*const u8>());
+        let address = self.as_ptr();
+        unsafe { core::mem::transmute_copy(&address) }
+    }
+
+    /// Redirects the [`PatchKind::Jump`] patch point with the given `index` to
+    /// `target`.
+    ///
+    /// # Errors
+    /// * [`PatchError::InvalidPatchPoint`] if there is no such jump.
+    /// * [`PatchError::TargetOutOfRange`] if `target` is more than 2GB away.
+    /// * [`PatchError::ProtectionFailed`] if the page cannot be made writable.
+    pub fn patch_jump(&self, index: usize, target: *const u8) -> Result<(), PatchError> {
+        let point = self.patch_point(index, PatchKind::Jump)?;
+        let end = self.as_ptr() as usize + point.end_offset() as usize;
+        #[allow(clippy::cast_possible_wrap)]
+        let rel = (target as usize).wrapping_sub(end) as isize;
+        let Ok(rel) = i32::try_from(rel) else {
+            return Err(PatchError::TargetOutOfRange);
+        };
+        self.patch_field(point, &rel.to_le_bytes())
+    }
+
+    /// Replaces the immediate of the [`PatchKind::MovImm64`] patch point with
+    /// the given `index`.
+    ///
+    /// # Errors
+    /// * [`PatchError::InvalidPatchPoint`] if there is no such instruction.
+    /// * [`PatchError::ProtectionFailed`] if the page cannot be made writable.
+    pub fn patch_immediate(&self, index: usize, value: i64) -> Result<(), PatchError> {
+        let point = self.patch_point(index, PatchKind::MovImm64)?;
+        self.patch_field(point, &value.to_le_bytes())
+    }
+
+    fn patch_point(
+        &self,
+        index: usize,
+        kind: PatchKind,
+    ) -> Result<PatchPoint, PatchError> {
+        match self.patch_points.get(index) {
+            Some(point) if point.kind() == kind => Ok(*point),
+            _ => Err(PatchError::InvalidPatchPoint),
+        }
+    }
+
+    fn patch_field(&self, point: PatchPoint, bytes: &[u8]) -> Result<(), PatchError> {
+        const WINDOW_SIZE: usize = PATCH_WINDOW_SIZE as usize;
+
+        let _guard = self
+            .patch_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let field_offset = point.field_offset() as usize;
+        let shift = field_offset % WINDOW_SIZE;
+        debug_assert!(shift + bytes.len() <= WINDOW_SIZE);
+        let window = unsafe { self.address.as_ptr().add(field_offset - shift) };
+        let page = unsafe { window.sub(window as usize % self.page_size) };
+
+        let writable = sys::PROT_READ | sys::PROT_WRITE | sys::PROT_EXEC;
+        if unsafe { sys::mprotect(page.cast(), self.page_size, writable) } != 0 {
+            return Err(PatchError::ProtectionFailed);
+        }
+
+        // The mapping is page aligned, thus the window is naturally aligned.
+        let atomic = unsafe { AtomicU64::from_ptr(window.cast()) };
+        let mut content = atomic.load(Ordering::Acquire).to_le_bytes();
+        content[shift..shift + bytes.len()].copy_from_slice(bytes);
+        atomic.store(u64::from_le_bytes(content), Ordering::Release);
+
+        let executable = sys::PROT_READ | sys::PROT_EXEC;
+        if unsafe { sys::mprotect(page.cast(), self.page_size, executable) } != 0 {
+            return Err(PatchError::ProtectionFailed);
+        }
+        Ok(())
+    }
+}
+
+/// Returns `target - place` if it fits in `rel32`.
+#[allow(clippy::cast_possible_wrap)]
+fn relative(target: u64, place: u64) -> Option<i32> {
+    i32::try_from(target.wrapping_sub(place) as i64).ok()
+}
+
+impl Drop for ExecutableCode {
+    fn drop(&mut self) {
+        unsafe { sys::munmap(self.address.as_ptr().cast::<c_void>(), self.mapping_len) };
+    }
+}
@@ -0,0 +1,96 @@
+use osom_x64_encoder::{
+    constants::{JCC_REL32_SIZE, JCC_REL8_SIZE, JMP_REL32_SIZE, JMP_REL8_SIZE},
+    encoder::EncodedInstruction,
+    models::Condition,
+};
+
+use crate::{Label, PatchKind, RelocationKind, Symbol};
+
+/// A piece of the instruction stream, as recorded by the assembler.
+pub(crate) enum Fragment {
+    /// Fully encoded instruction, no fixups needed.
+    Instruction(EncodedInstruction),
+
+    /// Raw bytes, stored in the assembler's data buffer.
+    Data { start: u32, len: u32 },
+
+    /// Padding up to the next multiple of `alignment`, filled with NOPs.
+    Align { alignment: u32 },
+
+    /// `jmp` (if `cond` is [`None`]) or `jcc` to `target`. Starts as the short
+    /// form and is switched to the long form if the target does not fit in `rel8`.
+    Jump {
+        cond: Option<Condition>,
+        target: Label,
+        is_long: bool,
+    },
+
+    /// Instruction whose last 4 bytes are RIP-relative displacement to `target`.
+    RipRelative {
+        instruction: EncodedInstruction,
+        target: Label,
+    },
+
+    /// Instruction whose last bytes are a field of the given `kind`, referencing
+    /// an external `symbol`.
+    Relocated {
+        instruction: EncodedInstruction,
+        kind: RelocationKind,
+        symbol: Symbol,
+    },
+
+    /// Instruction of the given `kind`, preceded by NOPs so that its patched
+    /// field fits in a single aligned window. For [`PatchKind::Jump`] the field
+    /// is initially `rel32` to `target`.
+    Patchable {
+        kind: PatchKind,
+        instruction: EncodedInstruction,
+        target: Option<Label>,
+    },
+}
+
+impl Fragment {
+    /// Returns the size of the fragment if it starts at `offset`.
+    pub(crate) fn size(&self, offset: u32) -> u32 {
+        match self {
+            Fragment::Instruction(instruction)
+            | Fragment::RipRelative { instruction, .. }
+            | Fragment::Relocated { instruction, .. } => u32::from(instruction.len()),
+            Fragment::Data { len, .. } => *len,
+            Fragment::Align { alignment } => padding(offset, *alignment),
+            Fragment::Patchable {
+                kind, instruction, ..
+            } => kind.padding(offset) + u32::from(instruction.len()),
+            Fragment::Jump { cond, is_long, .. } => {
+                let size = match (cond, is_long) {
+                    (None, false) => JMP_REL8_SIZE,
+                    (None, true) => JMP_REL32_SIZE,
+                    (Some(_), false) => JCC_REL8_SIZE,
+                    (Some(_), true) => JCC_REL32_SIZE,
+                };
+                #[allow(clippy::cast_possible_truncation)]
+                {
+                    size as u32
+                }
+            }
+        }
+    }
+
+    /// Returns the label referenced by the fragment, if any.
+    pub(crate) const fn target(&self) -> Option<Label> {
+        match self {
+            Fragment::Jump { target, .. } | Fragment::RipRelative { target, .. } => {
+                Some(*target)
+            }
+            Fragment::Patchable { target, .. } => *target,
+            _ => None,
+        }
+    }
+}
+
+/// Returns the number of bytes needed to move `offset` to the next multiple
+/// of `alignment`, which has to be a power of two.
+#[inline(always)]
+pub(crate) const fn padding(offset: u32, alignment: u32) -> u32 {
+    offset.wrapping_neg() & (alignment - 1)
+}
@@ -0,0 +1,307 @@
+use osom_x64_encoder::models::{GPR, XMM};
+
+use crate::abi::{CallingConvention, ValueClass};
+
+use super::{liveness::position, IrFunction, IrInstruction, LiveInterval, VReg};
+
+/// General purpose registers handed out by the allocator, in preference order.
+/// RSP and RBP are never allocated, R10 and R11 are reserved as scratch.
+pub const ALLOCATABLE_GPRS: [GPR; 12] = [
+    GPR::RAX,
+    GPR::RCX,
+    GPR::RDX,
+    GPR::RSI,
+    GPR::RDI,
+    GPR::R8,
+    GPR::R9,
+    GPR::RBX,
+    GPR::R12,
+    GPR::R13,
+    GPR::R14,
+    GPR::R15,
+];
+
+/// XMM registers handed out by the allocator, in preference order. XMM15 is
+/// reserved as scratch.
+pub const ALLOCATABLE_XMMS: [XMM; 15] = [
+    XMM::XMM0,
+    XMM::XMM1,
+    XMM::XMM2,
+    XMM::XMM3,
+    XMM::XMM4,
+    XMM::XMM5,
+    XMM::XMM6,
+    XMM::XMM7,
+    XMM::XMM8,
+    XMM::XMM9,
+    XMM::XMM10,
+    XMM::XMM11,
+    XMM::XMM12,
+    XMM::XMM13,
+    XMM::XMM14,
+];
+
+/// Size of a single spill slot.
+pub const SPILL_SLOT_SIZE: u32 = 8;
+
+/// Location assigned to a virtual register for its whole live interval.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Location {
+    Gpr(GPR),
+    Xmm(XMM),
+
+    /// Spill slot with the given index, in the locals area of the frame.
+    Stack(u32),
+}
+
+/// Result of the linear-scan register allocation of an [`IrFunction`].
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Allocation {
+    locations: Vec<Option<Location>>,
+    spill_slot_count: u32,
+}
+
+impl Allocation {
+    /// Returns the location of `reg`, or `None` if `reg` is never referenced.
+    #[must_use]
+    #[inline(always)]
+    pub fn location(&self, reg: VReg) -> Option<Location> {
+        self.locations.get(reg.index() as usize).copied().flatten()
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn spill_slot_count(&self) -> u32 {
+        self.spill_slot_count
+    }
+
+    /// Returns general purpose registers assigned to any virtual register.
+    #[must_use]
+    pub fn used_gprs(&self) -> Vec<GPR> {
+        ALLOCATABLE_GPRS
+            .into_iter()
+            .filter(|reg| self.locations.contains(&Some(Location::Gpr(*reg))))
+            .collect()
+    }
+
+    /// Returns XMM registers assigned to any virtual register.
+    #[must_use]
+    pub fn used_xmms(&self) -> Vec<XMM> {
+        ALLOCATABLE_XMMS
+            .into_iter()
+            .filter(|reg| self.locations.contains(&Some(Location::Xmm(*reg))))
+            .collect()
+    }
+}
+
+/// Bit set of registers, indexed by the register's encoding.
+type RegisterMask = u16;
+
+#[inline(always)]
+const fn bit(index: u8) -> RegisterMask {
+    1 << index
+}
+
+/// Returns registers each interval cannot be assigned to, and the register it
+/// would prefer.
+fn constraints(
+    function: &IrFunction,
+    intervals: &[LiveInterval],
+) -> (Vec<RegisterMask>, Vec<Option<u8>>) {
+    let mut forbidden = vec![0; intervals.len()];
+    let mut hints = vec![None; intervals.len()];
+
+    // `div` and `idiv` clobber RAX and RDX, shifts need the count in CL. No value
+    // live across such an instruction can stay in those registers.
+    for (index, instruction) in function.instructions().iter().enumerate() {
+        let clobbered = match instruction {
+            IrInstruction::Div { .. } | IrInstruction::Rem { .. } => {
+                bit(GPR::RAX.index()) | bit(GPR::RDX.index())
+            }
+            IrInstruction::Shift { .. } => bit(GPR::RCX.index()),
+            _ => continue,
+        };
+        let position = position(index);
+        for (interval, mask) in intervals.iter().zip(forbidden.iter_mut()) {
+            if matches!(interval.vreg.class(), ValueClass::Integer)
+                && interval.contains(position)
+            {
+                *mask |= clobbered;
+            }
+        }
+    }
+
+    // Arguments may stay in their own registers, but cannot take registers
+    // still holding other arguments at the entry.
+    let (integers, floats) = argument_registers(function);
+    for (argument, location) in function.arguments().iter().zip(argument_hints(function))
+    {
+        let Some(interval) = intervals.iter().position(|i| i.vreg == *argument) else {
+            continue;
+        };
+        let mask = match argument.class() {
+            ValueClass::Integer => integers,
+            ValueClass::Float => floats,
+        };
+        forbidden[interval] |= mask & !location.map_or(0, bit);
+        if location.is_some_and(|index| forbidden[interval] & bit(index) == 0) {
+            hints[interval] = location;
+        }
+    }
+    (forbidden, hints)
+}
+
+/// Returns masks of integer and float argument registers of the convention.
+fn argument_registers(function: &IrFunction) -> (RegisterMask, RegisterMask) {
+    let convention = function.convention();
+    let integers = convention
+        .integer_arguments()
+        .iter()
+        .fold(0, |mask, reg| mask | bit(reg.index()));
+    let floats = convention
+        .float_arguments()
+        .iter()
+        .fold(0, |mask, reg| mask | bit(reg.index()));
+    (integers, floats)
+}
+
+/// Returns the index of the register each argument is passed in, if any.
+pub(crate) fn argument_hints(function: &IrFunction) -> Vec<Option<u8>> {
+    let convention = function.convention();
+    let mut next_integer = 0;
+    let mut next_float = 0;
+    let mut result = Vec::with_capacity(function.arguments().len());
+    for (position, argument) in function.arguments().iter().enumerate() {
+        if convention.assigns_registers_by_position() {
+            next_integer = position;
+            next_float = position;
+        }
+        let register = match argument.class() {
+            ValueClass::Integer => {
+                next_integer += 1;
+                integer_argument(convention, next_integer - 1)
+            }
+            ValueClass::Float => {
+                next_float += 1;
+                float_argument(convention, next_float - 1)
+            }
+        };
+        result.push(register);
+    }
+    result
+}
+
+#[inline(always)]
+fn integer_argument(convention: CallingConvention, index: usize) -> Option<u8> {
+    convention.integer_arguments().get(index).map(GPR::index)
+}
+
+#[inline(always)]
+fn float_argument(convention: CallingConvention, index: usize) -> Option<u8> {
+    convention.float_arguments().get(index).map(XMM::index)
+}
+
+struct Active {
+    interval: usize,
+    register: u8,
+}
+
+/// Runs the linear scan over intervals of a single register class.
+///
+/// Whenever no register is free, the interval ending last among the current
+/// one and active ones whose register the current one may take is spilled.
+fn linear_scan(
+    intervals: &[LiveInterval],
+    forbidden: &[RegisterMask],
+    hints: &[Option<u8>],
+    class: ValueClass,
+    locations: &mut [Option<Location>],
+    spill_slot_count: &mut u32,
+) {
+    let registers: Vec<u8> = match class {
+        ValueClass::Integer => ALLOCATABLE_GPRS.iter().map(GPR::index).collect(),
+        ValueClass::Float => ALLOCATABLE_XMMS.iter().map(XMM::index).collect(),
+    };
+    let assign = |register: u8| match class {
+        ValueClass::Integer => {
+            let index = ALLOCATABLE_GPRS.iter().position(|r| r.index() == register);
+            Location::Gpr(ALLOCATABLE_GPRS[index.unwrap()])
+        }
+        ValueClass::Float => {
+            let index = ALLOCATABLE_XMMS.iter().position(|r| r.index() == register);
+            Location::Xmm(ALLOCATABLE_XMMS[index.unwrap()])
+        }
+    };
+    let mut spill = |locations: &mut [Option<Location>], interval: &LiveInterval| {
+        locations[interval.vreg.index() as usize] =
+            Some(Location::Stack(*spill_slot_count));
+        *spill_slot_count += 1;
+    };
+
+    let mut active: Vec<Active> = Vec::new();
+    for (current, interval) in intervals.iter().enumerate() {
+        if interval.vreg.class() != class {
+            continue;
+        }
+        active.retain(|a| intervals[a.interval].end >= interval.start);
+
+        let occupied = active.iter().fold(0, |mask, a| mask | bit(a.register));
+        let unavailable = occupied | forbidden[current];
+        let free = hints[current]
+            .into_iter()
+            .chain(registers.iter().copied())
+            .find(|register| unavailable & bit(*register) == 0);
+        if let Some(register) = free {
+            locations[interval.vreg.index() as usize] = Some(assign(register));
+            active.push(Active {
+                interval: current,
+                register,
+            });
+            continue;
+        }
+
+        let victim = active
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| forbidden[current] & bit(a.register) == 0)
+            .max_by_key(|(_, a)| intervals[a.interval].end)
+            .map(|(index, _)| index);
+        match victim {
+            Some(victim) if intervals[active[victim].interval].end > interval.end => {
+                let register = active[victim].register;
+                spill(locations, &intervals[active[victim].interval]);
+                locations[interval.vreg.index() as usize] = Some(assign(register));
+                active[victim] = Active {
+                    interval: current,
+                    register,
+                };
+            }
+            _ => spill(locations, interval),
+        }
+    }
+}
+
+/// Assigns locations to all virtual registers with intervals. The function has
+/// to be valid.
+pub(crate) fn allocate_registers(
+    function: &IrFunction,
+    intervals: &[LiveInterval],
+) -> Allocation {
+    let (forbidden, hints) = constraints(function, intervals);
+    let mut locations = vec![None; function.vreg_count()];
+    let mut spill_slot_count = 0;
+    for class in [ValueClass::Integer, ValueClass::Float] {
+        linear_scan(
+            intervals,
+            &forbidden,
+            &hints,
+            class,
+            &mut locations,
+            &mut spill_slot_count,
+        );
+    }
+    Allocation {
+        locations,
+        spill_slot_count,
+    }
+}
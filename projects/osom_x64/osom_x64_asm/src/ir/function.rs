@@ -0,0 +1,186 @@
+use crate::{
+    abi::{CallingConvention, ValueClass},
+    Assembler, AssemblerError,
+};
+
+use super::{
+    allocator::allocate_registers, liveness::compute_live_intervals,
+    lowering::lower_function, Allocation, IrInstruction, IrLabel, LiveInterval, VReg,
+};
+
+/// Leaf function built of [`IrInstruction`]s over virtual registers.
+///
+/// # Notes
+/// Argument registers of the calling convention hold arguments only at the
+/// function's entry. The allocator copies them elsewhere if needed.
+#[derive(PartialEq, Clone, Debug)]
+pub struct IrFunction {
+    convention: CallingConvention,
+    classes: Vec<ValueClass>,
+    arguments: Vec<VReg>,
+    label_count: u32,
+    instructions: Vec<IrInstruction>,
+}
+
+impl IrFunction {
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(convention: CallingConvention) -> Self {
+        Self {
+            convention,
+            classes: Vec::new(),
+            arguments: Vec::new(),
+            label_count: 0,
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Creates a new virtual register of the given `class`.
+    ///
+    /// # Panics
+    /// When the function runs out of `u32` indexes.
+    pub fn create_vreg(&mut self, class: ValueClass) -> VReg {
+        let index =
+            u32::try_from(self.classes.len()).expect("Too many virtual registers");
+        self.classes.push(class);
+        VReg::new(index, class)
+    }
+
+    /// Creates a virtual register holding the next argument of the function.
+    ///
+    /// # Panics
+    /// When the function runs out of `u32` indexes.
+    pub fn add_argument(&mut self, class: ValueClass) -> VReg {
+        let reg = self.create_vreg(class);
+        self.arguments.push(reg);
+        reg
+    }
+
+    /// Creates a new label, to be bound with [`IrFunction::bind`].
+    pub fn create_label(&mut self) -> IrLabel {
+        let label = IrLabel::new(self.label_count);
+        self.label_count += 1;
+        label
+    }
+
+    /// Binds `label` to the next pushed instruction.
+    #[inline(always)]
+    pub fn bind(&mut self, label: IrLabel) {
+        self.push(IrInstruction::Bind(label));
+    }
+
+    /// Appends `instruction` to the function's body. Operands are validated
+    /// when the function is allocated or compiled.
+    #[inline(always)]
+    pub fn push(&mut self, instruction: IrInstruction) {
+        self.instructions.push(instruction);
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn convention(&self) -> CallingConvention {
+        self.convention
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn arguments(&self) -> &[VReg] {
+        &self.arguments
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn instructions(&self) -> &[IrInstruction] {
+        &self.instructions
+    }
+
+    /// Returns the number of virtual registers, including arguments.
+    #[must_use]
+    #[inline(always)]
+    pub fn vreg_count(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Returns the virtual register with the given `index`.
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline(always)]
+    pub(crate) fn vreg(&self, index: usize) -> VReg {
+        VReg::new(index as u32, self.classes[index])
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn label_count(&self) -> u32 {
+        self.label_count
+    }
+
+    /// Computes live intervals of all virtual registers that are referenced.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidIr`] if the function is malformed.
+    pub fn live_intervals(&self) -> Result<Vec<LiveInterval>, AssemblerError> {
+        self.validate()?;
+        Ok(compute_live_intervals(self))
+    }
+
+    /// Assigns a physical register or a stack slot to each referenced virtual
+    /// register.
+    ///
+    /// # Errors
+    /// [`AssemblerError::InvalidIr`] if the function is malformed.
+    pub fn allocate(&self) -> Result<Allocation, AssemblerError> {
+        self.validate()?;
+        Ok(allocate_registers(self, &compute_live_intervals(self)))
+    }
+
+    /// Allocates registers and appends the function, together with its prologue
+    /// and epilogues, to `asm`.
+    ///
+    /// # Errors
+    /// * [`AssemblerError::InvalidIr`] if the function is malformed.
+    /// * [`AssemblerError::FrameTooLarge`] if spill slots do not fit in the frame.
+    pub fn compile(&self, asm: &mut Assembler) -> Result<(), AssemblerError> {
+        let allocation = self.allocate()?;
+        lower_function(self, &allocation, asm)
+    }
+
+    fn validate(&self) -> Result<(), AssemblerError> {
+        let is_known = |reg: VReg| {
+            self.classes
+                .get(reg.index() as usize)
+                .is_some_and(|class| *class == reg.class())
+        };
+
+        let mut bound = vec![false; self.label_count as usize];
+        for instruction in &self.instructions {
+            let defined = instruction.defined().into_iter();
+            if !instruction.has_valid_operands()
+                || !defined
+                    .chain(instruction.used().into_iter().flatten())
+                    .all(is_known)
+            {
+                return Err(AssemblerError::InvalidIr);
+            }
+            if let IrInstruction::Bind(label) = instruction {
+                match bound.get_mut(label.index() as usize) {
+                    Some(is_bound @ false) => *is_bound = true,
+                    _ => return Err(AssemblerError::InvalidIr),
+                }
+            }
+        }
+
+        let targets_bound = self
+            .instructions
+            .iter()
+            .filter_map(IrInstruction::jump_target)
+            .all(|label| bound.get(label.index() as usize) == Some(&true));
+        let ends_with_terminator = self
+            .instructions
+            .last()
+            .is_some_and(IrInstruction::is_terminator);
+        if !targets_bound || !ends_with_terminator {
+            return Err(AssemblerError::InvalidIr);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use osom_x64_encoder::models::{ArithmeticOp, Condition, FloatArithmeticOp, ShiftOp};
+
+use crate::abi::ValueClass;
+
+/// Virtual register, created by
+/// [`IrFunction::create_vreg`][super::IrFunction::create_vreg]. Integer virtual
+/// registers hold 64-bit values, float ones hold `f64` values.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct VReg {
+    index: u32,
+    class: ValueClass,
+}
+
+impl VReg {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(index: u32, class: ValueClass) -> Self {
+        Self { index, class }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn class(self) -> ValueClass {
+        self.class
+    }
+}
+
+/// Position in an [`IrFunction`][super::IrFunction], created by
+/// [`IrFunction::create_label`][super::IrFunction::create_label].
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct IrLabel {
+    index: u32,
+}
+
+impl IrLabel {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(index: u32) -> Self {
+        Self { index }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+}
+
+/// Three-address instruction over virtual registers. Unless stated otherwise
+/// all operands are integer ones.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum IrInstruction {
+    /// `dst = imm`.
+    MovImm {
+        dst: VReg,
+        imm: i64,
+    },
+
+    /// `dst = value`, with float `dst`. The value is kept in the constant pool.
+    MovF64 {
+        dst: VReg,
+        value: f64,
+    },
+
+    /// `dst = src`, with operands of any, but the same, class.
+    Mov {
+        dst: VReg,
+        src: VReg,
+    },
+
+    /// `dst = lhs op rhs`, wrapping. The IR does not model flags, thus `op` cannot
+    /// be [`ArithmeticOp::Adc`], [`ArithmeticOp::Sbb`] or [`ArithmeticOp::Cmp`].
+    Arith {
+        op: ArithmeticOp,
+        dst: VReg,
+        lhs: VReg,
+        rhs: VReg,
+    },
+
+    /// `dst = lhs * rhs`, wrapping.
+    Imul {
+        dst: VReg,
+        lhs: VReg,
+        rhs: VReg,
+    },
+
+    /// Signed `dst = lhs / rhs`, rounding towards zero.
+    Div {
+        dst: VReg,
+        lhs: VReg,
+        rhs: VReg,
+    },
+
+    /// Signed `dst = lhs % rhs`, with the sign of `lhs`.
+    Rem {
+        dst: VReg,
+        lhs: VReg,
+        rhs: VReg,
+    },
+
+    /// `dst = lhs op amount`, with `amount` masked to its lowest 6 bits.
+    Shift {
+        op: ShiftOp,
+        dst: VReg,
+        lhs: VReg,
+        amount: VReg,
+    },
+
+    /// Reads 8 bytes at `base + offset` into `dst` of any class.
+    Load {
+        dst: VReg,
+        base: VReg,
+        offset: i32,
+    },
+
+    /// Writes `src` of any class into 8 bytes at `base + offset`.
+    Store {
+        base: VReg,
+        offset: i32,
+        src: VReg,
+    },
+
+    /// `dst = lhs op rhs`, with float operands. Only double precision `op`s
+    /// are supported.
+    FloatArith {
+        op: FloatArithmeticOp,
+        dst: VReg,
+        lhs: VReg,
+        rhs: VReg,
+    },
+
+    /// Binds the label to the next instruction.
+    Bind(IrLabel),
+
+    Jmp(IrLabel),
+
+    /// Jumps to `target` if `cmp lhs, rhs` satisfies `cond`.
+    Branch {
+        cond: Condition,
+        lhs: VReg,
+        rhs: VReg,
+        target: IrLabel,
+    },
+
+    /// Returns from the function, with `value` of any class in the return
+    /// register of the calling convention.
+    Ret {
+        value: Option<VReg>,
+    },
+}
+
+impl IrInstruction {
+    /// Returns the virtual register written by the instruction.
+    #[must_use]
+    pub const fn defined(&self) -> Option<VReg> {
+        match *self {
+            Self::MovImm { dst, .. }
+            | Self::MovF64 { dst, .. }
+            | Self::Mov { dst, .. }
+            | Self::Arith { dst, .. }
+            | Self::Imul { dst, .. }
+            | Self::Div { dst, .. }
+            | Self::Rem { dst, .. }
+            | Self::Shift { dst, .. }
+            | Self::Load { dst, .. }
+            | Self::FloatArith { dst, .. } => Some(dst),
+            Self::Store { .. }
+            | Self::Bind(_)
+            | Self::Jmp(_)
+            | Self::Branch { .. }
+            | Self::Ret { .. } => None,
+        }
+    }
+
+    /// Returns virtual registers read by the instruction.
+    #[must_use]
+    pub const fn used(&self) -> [Option<VReg>; 2] {
+        match *self {
+            Self::MovImm { .. } | Self::MovF64 { .. } | Self::Bind(_) | Self::Jmp(_) => {
+                [None, None]
+            }
+            Self::Mov { src, .. } => [Some(src), None],
+            Self::Load { base, .. } => [Some(base), None],
+            Self::Ret { value } => [value, None],
+            Self::Arith { lhs, rhs, .. }
+            | Self::Imul { lhs, rhs, .. }
+            | Self::Div { lhs, rhs, .. }
+            | Self::Rem { lhs, rhs, .. }
+            | Self::FloatArith { lhs, rhs, .. }
+            | Self::Branch { lhs, rhs, .. } => [Some(lhs), Some(rhs)],
+            Self::Shift { lhs, amount, .. } => [Some(lhs), Some(amount)],
+            Self::Store { base, src, .. } => [Some(base), Some(src)],
+        }
+    }
+
+    /// Returns `true` if control never falls through to the next instruction.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_terminator(&self) -> bool {
+        matches!(self, Self::Jmp(_) | Self::Ret { .. })
+    }
+
+    /// Returns the label the instruction may jump to.
+    #[must_use]
+    #[inline(always)]
+    pub const fn jump_target(&self) -> Option<IrLabel> {
+        match *self {
+            Self::Jmp(target) | Self::Branch { target, .. } => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Checks whether operands have classes the instruction expects.
+    pub(crate) const fn has_valid_operands(&self) -> bool {
+        const fn is_integer(reg: VReg) -> bool {
+            matches!(reg.class(), ValueClass::Integer)
+        }
+        const fn is_float(reg: VReg) -> bool {
+            matches!(reg.class(), ValueClass::Float)
+        }
+
+        match *self {
+            Self::MovImm { dst, .. } => is_integer(dst),
+            Self::MovF64 { dst, .. } => is_float(dst),
+            Self::Mov { dst, src } => is_integer(dst) == is_integer(src),
+            Self::Arith { op, dst, lhs, rhs } => {
+                matches!(
+                    op,
+                    ArithmeticOp::Add
+                        | ArithmeticOp::Or
+                        | ArithmeticOp::And
+                        | ArithmeticOp::Sub
+                        | ArithmeticOp::Xor
+                ) && is_integer(dst)
+                    && is_integer(lhs)
+                    && is_integer(rhs)
+            }
+            Self::Imul { dst, lhs, rhs }
+            | Self::Div { dst, lhs, rhs }
+            | Self::Rem { dst, lhs, rhs }
+            | Self::Shift {
+                dst,
+                lhs,
+                amount: rhs,
+                ..
+            } => is_integer(dst) && is_integer(lhs) && is_integer(rhs),
+            Self::Load { base, .. } | Self::Store { base, .. } => is_integer(base),
+            Self::FloatArith { op, dst, lhs, rhs } => {
+                op.is_double() && is_float(dst) && is_float(lhs) && is_float(rhs)
+            }
+            Self::Branch { lhs, rhs, .. } => is_integer(lhs) && is_integer(rhs),
+            Self::Bind(_) | Self::Jmp(_) | Self::Ret { .. } => true,
+        }
+    }
+}
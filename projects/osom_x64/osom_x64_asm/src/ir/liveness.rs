@@ -0,0 +1,168 @@
+use super::{IrFunction, IrInstruction, VReg};
+
+/// Range of positions at which a virtual register may hold a live value.
+///
+/// Position `0` is the function's entry, where arguments are defined, and
+/// position `i + 1` is the `i`-th instruction. Both ends are inclusive.
+///
+/// # Notes
+/// Intervals do not have holes: a value live in two distant parts of the
+/// function occupies its location in between as well.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct LiveInterval {
+    pub vreg: VReg,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl LiveInterval {
+    #[must_use]
+    #[inline(always)]
+    pub const fn contains(&self, position: u32) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+/// Returns the position of the `index`-th instruction.
+#[inline(always)]
+pub(crate) fn position(index: usize) -> u32 {
+    u32::try_from(index + 1).expect("Too many IR instructions")
+}
+
+struct BasicBlock {
+    start: usize,
+    end: usize,
+    successors: Vec<usize>,
+}
+
+/// Splits instructions into basic blocks: a new block starts at every label
+/// and after every jump.
+fn split_blocks(instructions: &[IrInstruction], label_count: u32) -> Vec<BasicBlock> {
+    let mut is_leader = vec![false; instructions.len() + 1];
+    is_leader[0] = true;
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            IrInstruction::Bind(_) => is_leader[index] = true,
+            IrInstruction::Jmp(_)
+            | IrInstruction::Branch { .. }
+            | IrInstruction::Ret { .. } => is_leader[index + 1] = true,
+            _ => {}
+        }
+    }
+
+    let starts: Vec<usize> = (0..instructions.len())
+        .filter(|index| is_leader[*index])
+        .collect();
+    let mut label_blocks = vec![usize::MAX; label_count as usize];
+    for (block, start) in starts.iter().enumerate() {
+        if let IrInstruction::Bind(label) = instructions[*start] {
+            label_blocks[label.index() as usize] = block;
+        }
+    }
+
+    let mut blocks = Vec::with_capacity(starts.len());
+    for (block, start) in starts.iter().enumerate() {
+        let end = starts.get(block + 1).copied().unwrap_or(instructions.len());
+        let last = &instructions[end - 1];
+        let mut successors = Vec::with_capacity(2);
+        if let Some(target) = last.jump_target() {
+            successors.push(label_blocks[target.index() as usize]);
+        }
+        if !last.is_terminator() && end < instructions.len() {
+            successors.push(block + 1);
+        }
+        blocks.push(BasicBlock {
+            start: *start,
+            end,
+            successors,
+        });
+    }
+    blocks
+}
+
+/// Computes intervals of all referenced virtual registers, sorted by start.
+/// The function has to be valid.
+pub(crate) fn compute_live_intervals(function: &IrFunction) -> Vec<LiveInterval> {
+    let instructions = function.instructions();
+    let vreg_count = function.vreg_count();
+    let blocks = split_blocks(instructions, function.label_count());
+
+    // Upward exposed uses and definitions of each block.
+    let mut uses = vec![vec![false; vreg_count]; blocks.len()];
+    let mut defs = vec![vec![false; vreg_count]; blocks.len()];
+    for (block, info) in blocks.iter().enumerate() {
+        for instruction in &instructions[info.start..info.end] {
+            for reg in instruction.used().into_iter().flatten() {
+                let index = reg.index() as usize;
+                if !defs[block][index] {
+                    uses[block][index] = true;
+                }
+            }
+            if let Some(reg) = instruction.defined() {
+                defs[block][reg.index() as usize] = true;
+            }
+        }
+    }
+
+    let mut live_in = uses.clone();
+    let mut live_out = vec![vec![false; vreg_count]; blocks.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in (0..blocks.len()).rev() {
+            for successor in &blocks[block].successors {
+                for index in 0..vreg_count {
+                    if live_in[*successor][index] && !live_out[block][index] {
+                        live_out[block][index] = true;
+                        if !defs[block][index] && !live_in[block][index] {
+                            live_in[block][index] = true;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut ranges: Vec<Option<(u32, u32)>> = vec![None; vreg_count];
+    let mut extend = |reg: usize, position: u32| {
+        let range = ranges[reg].get_or_insert((position, position));
+        range.0 = range.0.min(position);
+        range.1 = range.1.max(position);
+    };
+
+    for reg in function.arguments() {
+        extend(reg.index() as usize, 0);
+    }
+    for (block, info) in blocks.iter().enumerate() {
+        for index in 0..vreg_count {
+            if live_in[block][index] {
+                extend(index, position(info.start));
+            }
+            if live_out[block][index] {
+                extend(index, position(info.end - 1));
+            }
+        }
+    }
+    for (index, instruction) in instructions.iter().enumerate() {
+        let referenced = instruction.defined().into_iter();
+        for reg in referenced.chain(instruction.used().into_iter().flatten()) {
+            extend(reg.index() as usize, position(index));
+        }
+    }
+
+    let mut intervals: Vec<LiveInterval> = ranges
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, range)| {
+            let (start, end) = range?;
+            Some(LiveInterval {
+                vreg: function.vreg(index),
+                start,
+                end,
+            })
+        })
+        .collect();
+    intervals.sort_by_key(|interval| (interval.start, interval.vreg.index()));
+    intervals
+}
@@ -0,0 +1,380 @@
+use osom_x64_encoder::{
+    encoder::{arith, mov, muldiv, shift, sse},
+    models::{Memory, GPR, XMM},
+};
+
+use crate::{
+    abi::{ArgumentLocation, FrameLayout, FunctionDescription, ValueClass},
+    Assembler, AssemblerError, Label,
+};
+
+use super::{
+    allocator::SPILL_SLOT_SIZE, Allocation, IrFunction, IrInstruction, Location, VReg,
+};
+
+/// Scratch register for the first operand of an instruction.
+const SCRATCH: GPR = GPR::R11;
+
+/// Scratch register for memory bases.
+const SCRATCH_BASE: GPR = GPR::R10;
+
+const SCRATCH_XMM: XMM = XMM::XMM15;
+
+/// Location of a virtual register, with spill slots resolved to memory.
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Operand {
+    Gpr(GPR),
+    Xmm(XMM),
+    Memory(Memory),
+}
+
+struct Lowering<'a> {
+    allocation: &'a Allocation,
+    layout: FrameLayout,
+    labels: Vec<Label>,
+    asm: &'a mut Assembler,
+}
+
+impl Lowering<'_> {
+    fn operand(&self, reg: VReg) -> Result<Operand, AssemblerError> {
+        let location = self.allocation.location(reg);
+        match location.ok_or(AssemblerError::InvalidIr)? {
+            Location::Gpr(reg) => Ok(Operand::Gpr(reg)),
+            Location::Xmm(reg) => Ok(Operand::Xmm(reg)),
+            Location::Stack(slot) => {
+                let offset = slot * SPILL_SLOT_SIZE;
+                Ok(Operand::Memory(self.layout.local(offset)?))
+            }
+        }
+    }
+
+    /// Returns the register `reg` lives in, loading it into `scratch` if spilled.
+    fn load_gpr(&mut self, reg: VReg, scratch: GPR) -> Result<GPR, AssemblerError> {
+        match self.operand(reg)? {
+            Operand::Gpr(reg) => Ok(reg),
+            Operand::Memory(memory) => {
+                self.asm.emit(mov::encode_mov_reg_mem(scratch, memory)?);
+                Ok(scratch)
+            }
+            Operand::Xmm(_) => Err(AssemblerError::InvalidIr),
+        }
+    }
+
+    fn load_xmm(&mut self, reg: VReg) -> Result<XMM, AssemblerError> {
+        match self.operand(reg)? {
+            Operand::Xmm(reg) => Ok(reg),
+            Operand::Memory(memory) => {
+                self.asm
+                    .emit(sse::encode_movsd_xmm_mem(SCRATCH_XMM, memory));
+                Ok(SCRATCH_XMM)
+            }
+            Operand::Gpr(_) => Err(AssemblerError::InvalidIr),
+        }
+    }
+
+    /// Returns the register to compute `dst` in: its own one, unless it holds
+    /// `source` read later by the instruction.
+    fn target_gpr(&self, dst: VReg, source: VReg) -> Result<GPR, AssemblerError> {
+        match self.operand(dst)? {
+            Operand::Gpr(reg) if self.operand(source)? != Operand::Gpr(reg) => Ok(reg),
+            _ => Ok(SCRATCH),
+        }
+    }
+
+    fn target_xmm(&self, dst: VReg, source: VReg) -> Result<XMM, AssemblerError> {
+        match self.operand(dst)? {
+            Operand::Xmm(reg) if self.operand(source)? != Operand::Xmm(reg) => Ok(reg),
+            _ => Ok(SCRATCH_XMM),
+        }
+    }
+
+    fn move_to_gpr(&mut self, dst: GPR, src: VReg) -> Result<(), AssemblerError> {
+        match self.operand(src)? {
+            Operand::Gpr(reg) if reg == dst => {}
+            Operand::Gpr(reg) => self.asm.emit(mov::encode_mov_reg_reg(dst, reg)?),
+            Operand::Memory(memory) => {
+                self.asm.emit(mov::encode_mov_reg_mem(dst, memory)?);
+            }
+            Operand::Xmm(_) => return Err(AssemblerError::InvalidIr),
+        }
+        Ok(())
+    }
+
+    fn move_to_xmm(&mut self, dst: XMM, src: VReg) -> Result<(), AssemblerError> {
+        match self.operand(src)? {
+            Operand::Xmm(reg) if reg == dst => {}
+            Operand::Xmm(reg) => self.asm.emit(sse::encode_movaps_xmm_xmm(dst, reg)),
+            Operand::Memory(memory) => {
+                self.asm.emit(sse::encode_movsd_xmm_mem(dst, memory));
+            }
+            Operand::Gpr(_) => return Err(AssemblerError::InvalidIr),
+        }
+        Ok(())
+    }
+
+    fn store_gpr(&mut self, dst: VReg, src: GPR) -> Result<(), AssemblerError> {
+        match self.operand(dst)? {
+            Operand::Gpr(reg) if reg == src => {}
+            Operand::Gpr(reg) => self.asm.emit(mov::encode_mov_reg_reg(reg, src)?),
+            Operand::Memory(memory) => {
+                self.asm.emit(mov::encode_mov_mem_reg(memory, src)?);
+            }
+            Operand::Xmm(_) => return Err(AssemblerError::InvalidIr),
+        }
+        Ok(())
+    }
+
+    fn store_xmm(&mut self, dst: VReg, src: XMM) -> Result<(), AssemblerError> {
+        match self.operand(dst)? {
+            Operand::Xmm(reg) if reg == src => {}
+            Operand::Xmm(reg) => self.asm.emit(sse::encode_movaps_xmm_xmm(reg, src)),
+            Operand::Memory(memory) => {
+                self.asm.emit(sse::encode_movsd_mem_xmm(memory, src));
+            }
+            Operand::Gpr(_) => return Err(AssemblerError::InvalidIr),
+        }
+        Ok(())
+    }
+
+    /// Copies arguments from where the caller put them to their locations.
+    fn emit_arguments(&mut self, function: &IrFunction) -> Result<(), AssemblerError> {
+        let arguments: Vec<(VReg, ArgumentLocation)> = function
+            .arguments()
+            .iter()
+            .copied()
+            .zip(self.layout.arguments().iter().copied())
+            .filter(|(argument, _)| self.allocation.location(*argument).is_some())
+            .collect();
+
+        // The allocator keeps arguments away from registers of other arguments,
+        // so register arguments can be moved in any order. Stack arguments go
+        // last, as they may need scratch registers.
+        for (argument, location) in &arguments {
+            match location {
+                ArgumentLocation::Gpr(reg) => self.store_gpr(*argument, *reg)?,
+                ArgumentLocation::Xmm(reg) => self.store_xmm(*argument, *reg)?,
+                ArgumentLocation::Stack(_) => {}
+            }
+        }
+        for (argument, location) in &arguments {
+            let ArgumentLocation::Stack(memory) = *location else {
+                continue;
+            };
+            match (argument.class(), self.operand(*argument)?) {
+                (ValueClass::Integer, Operand::Gpr(reg)) => {
+                    self.asm.emit(mov::encode_mov_reg_mem(reg, memory)?);
+                }
+                (ValueClass::Integer, _) => {
+                    self.asm.emit(mov::encode_mov_reg_mem(SCRATCH, memory)?);
+                    self.store_gpr(*argument, SCRATCH)?;
+                }
+                (ValueClass::Float, _) => {
+                    let reg = self.target_xmm(*argument, *argument)?;
+                    self.asm.emit(sse::encode_movsd_xmm_mem(reg, memory));
+                    self.store_xmm(*argument, reg)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn emit_instruction(
+        &mut self,
+        instruction: &IrInstruction,
+    ) -> Result<(), AssemblerError> {
+        match *instruction {
+            IrInstruction::MovImm { dst, imm } => {
+                let reg = self.target_gpr(dst, dst)?;
+                self.asm.emit(mov::encode_mov_reg_imm(reg, imm)?);
+                self.store_gpr(dst, reg)?;
+            }
+            IrInstruction::MovF64 { dst, value } => {
+                let reg = self.target_xmm(dst, dst)?;
+                let constant = self.asm.constant_f64(value);
+                self.asm.emit_movsd_label(reg, constant);
+                self.store_xmm(dst, reg)?;
+            }
+            IrInstruction::Mov { dst, src } => match dst.class() {
+                ValueClass::Integer => {
+                    let reg = self.load_gpr(src, SCRATCH)?;
+                    self.store_gpr(dst, reg)?;
+                }
+                ValueClass::Float => {
+                    let reg = self.load_xmm(src)?;
+                    self.store_xmm(dst, reg)?;
+                }
+            },
+            IrInstruction::Arith { op, dst, lhs, rhs } => {
+                let reg = self.target_gpr(dst, rhs)?;
+                self.move_to_gpr(reg, lhs)?;
+                let encoded = match self.operand(rhs)? {
+                    Operand::Gpr(src) => arith::encode_arith_reg_reg(op, reg, src)?,
+                    Operand::Memory(src) => arith::encode_arith_reg_mem(op, reg, src)?,
+                    Operand::Xmm(_) => return Err(AssemblerError::InvalidIr),
+                };
+                self.asm.emit(encoded);
+                self.store_gpr(dst, reg)?;
+            }
+            IrInstruction::Imul { dst, lhs, rhs } => {
+                let reg = self.target_gpr(dst, rhs)?;
+                self.move_to_gpr(reg, lhs)?;
+                let encoded = match self.operand(rhs)? {
+                    Operand::Gpr(src) => muldiv::encode_imul_reg_reg(reg, src)?,
+                    Operand::Memory(src) => muldiv::encode_imul_reg_mem(reg, src)?,
+                    Operand::Xmm(_) => return Err(AssemblerError::InvalidIr),
+                };
+                self.asm.emit(encoded);
+                self.store_gpr(dst, reg)?;
+            }
+            IrInstruction::Div { dst, lhs, rhs }
+            | IrInstruction::Rem { dst, lhs, rhs } => {
+                // The allocator keeps all values live here out of RAX and RDX.
+                self.move_to_gpr(GPR::RAX, lhs)?;
+                self.asm.emit(muldiv::encode_cqo());
+                let divisor = self.load_gpr(rhs, SCRATCH)?;
+                self.asm.emit(muldiv::encode_idiv(divisor)?);
+                let result = if matches!(instruction, IrInstruction::Div { .. }) {
+                    GPR::RAX
+                } else {
+                    GPR::RDX
+                };
+                self.store_gpr(dst, result)?;
+            }
+            IrInstruction::Shift {
+                op,
+                dst,
+                lhs,
+                amount,
+            } => {
+                // The allocator keeps all values live here out of RCX.
+                self.move_to_gpr(GPR::RCX, amount)?;
+                let reg = self.target_gpr(dst, dst)?;
+                self.move_to_gpr(reg, lhs)?;
+                self.asm.emit(shift::encode_shift_reg_cl(op, reg)?);
+                self.store_gpr(dst, reg)?;
+            }
+            IrInstruction::Load { dst, base, offset } => {
+                let base = self.load_gpr(base, SCRATCH_BASE)?;
+                let memory = Memory::based(base, offset)?;
+                match dst.class() {
+                    ValueClass::Integer => {
+                        let reg = self.target_gpr(dst, dst)?;
+                        self.asm.emit(mov::encode_mov_reg_mem(reg, memory)?);
+                        self.store_gpr(dst, reg)?;
+                    }
+                    ValueClass::Float => {
+                        let reg = self.target_xmm(dst, dst)?;
+                        self.asm.emit(sse::encode_movsd_xmm_mem(reg, memory));
+                        self.store_xmm(dst, reg)?;
+                    }
+                }
+            }
+            IrInstruction::Store { base, offset, src } => {
+                let base = self.load_gpr(base, SCRATCH_BASE)?;
+                let memory = Memory::based(base, offset)?;
+                match src.class() {
+                    ValueClass::Integer => {
+                        let reg = self.load_gpr(src, SCRATCH)?;
+                        self.asm.emit(mov::encode_mov_mem_reg(memory, reg)?);
+                    }
+                    ValueClass::Float => {
+                        let reg = self.load_xmm(src)?;
+                        self.asm.emit(sse::encode_movsd_mem_xmm(memory, reg));
+                    }
+                }
+            }
+            IrInstruction::FloatArith { op, dst, lhs, rhs } => {
+                let reg = self.target_xmm(dst, rhs)?;
+                self.move_to_xmm(reg, lhs)?;
+                let encoded = match self.operand(rhs)? {
+                    Operand::Xmm(src) => sse::encode_float_arith_xmm_xmm(op, reg, src),
+                    Operand::Memory(src) => {
+                        sse::encode_float_arith_xmm_mem(op, reg, src)
+                    }
+                    Operand::Gpr(_) => return Err(AssemblerError::InvalidIr),
+                };
+                self.asm.emit(encoded);
+                self.store_xmm(dst, reg)?;
+            }
+            IrInstruction::Bind(label) => {
+                self.asm.bind_label(self.labels[label.index() as usize])?;
+            }
+            IrInstruction::Jmp(label) => {
+                self.asm.emit_jmp(self.labels[label.index() as usize]);
+            }
+            IrInstruction::Branch {
+                cond,
+                lhs,
+                rhs,
+                target,
+            } => {
+                let lhs = self.load_gpr(lhs, SCRATCH)?;
+                let encoded = match self.operand(rhs)? {
+                    Operand::Gpr(rhs) => arith::encode_cmp_reg_reg(lhs, rhs)?,
+                    Operand::Memory(rhs) => arith::encode_cmp_reg_mem(lhs, rhs)?,
+                    Operand::Xmm(_) => return Err(AssemblerError::InvalidIr),
+                };
+                self.asm.emit(encoded);
+                self.asm
+                    .emit_jcc(cond, self.labels[target.index() as usize]);
+            }
+            IrInstruction::Ret { value } => {
+                match value.map(|value| (value, value.class())) {
+                    Some((value, ValueClass::Integer)) => {
+                        self.move_to_gpr(GPR::RAX, value)?;
+                    }
+                    Some((value, ValueClass::Float)) => {
+                        self.move_to_xmm(XMM::XMM0, value)?;
+                    }
+                    None => {}
+                }
+                self.layout.emit_epilogue(self.asm)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lowers a valid `function` according to `allocation`.
+pub(crate) fn lower_function(
+    function: &IrFunction,
+    allocation: &Allocation,
+    asm: &mut Assembler,
+) -> Result<(), AssemblerError> {
+    let has_floats = (0..function.vreg_count())
+        .any(|index| matches!(function.vreg(index).class(), ValueClass::Float));
+    let mut clobbered_xmm = allocation.used_xmms();
+    if has_floats {
+        clobbered_xmm.push(SCRATCH_XMM);
+    }
+
+    let arguments: Vec<ValueClass> =
+        function.arguments().iter().map(|reg| reg.class()).collect();
+    let Some(locals_size) = allocation.spill_slot_count().checked_mul(SPILL_SLOT_SIZE)
+    else {
+        return Err(AssemblerError::FrameTooLarge);
+    };
+    let description = FunctionDescription::new(function.convention())
+        .with_arguments(&arguments)
+        .with_clobbered(&allocation.used_gprs())
+        .with_clobbered(&[SCRATCH, SCRATCH_BASE])
+        .with_clobbered_xmm(&clobbered_xmm)
+        .with_locals_size(locals_size);
+
+    let labels = (0..function.label_count())
+        .map(|_| asm.create_label())
+        .collect();
+    let mut lowering = Lowering {
+        allocation,
+        layout: FrameLayout::new(&description)?,
+        labels,
+        asm,
+    };
+    lowering.layout.emit_prologue(lowering.asm)?;
+    lowering.emit_arguments(function)?;
+    for instruction in function.instructions() {
+        lowering.emit_instruction(instruction)?;
+    }
+    Ok(())
+}
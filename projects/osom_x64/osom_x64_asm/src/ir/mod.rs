@@ -0,0 +1,58 @@
+//! Intermediate representation over virtual registers.
+//!
+//! An [`IrFunction`] is a list of three-address [`IrInstruction`]s over an
+//! unlimited number of [`VReg`]s. [`IrFunction::compile`] computes
+//! [`LiveInterval`]s, assigns each virtual register a physical register or a
+//! stack slot with a linear scan, and lowers the function to the [`Assembler`],
+//! together with the prologue and epilogues generated by
+//! [`FrameLayout`][crate::abi::FrameLayout].
+//!
+//! # Notes
+//! Instructions with fixed register operands are handled by the allocator:
+//! values live across `div` are kept out of RAX and RDX, and values live
+//! across shifts are kept out of RCX. R10, R11 and XMM15 are never allocated,
+//! and serve as scratch registers for spilled operands.
+//!
+//! # Examples
+//! ```
+//! use osom_x64_asm::{
+//!     abi::{CallingConvention, ValueClass},
+//!     ir::{IrFunction, IrInstruction},
+//!     Assembler, AssemblerError,
+//! };
+//! use osom_x64_encoder::models::ArithmeticOp;
+//!
+//! fn main() -> Result<(), AssemblerError> {
+//!     let mut function = IrFunction::new(CallingConvention::SystemV);
+//!     let a = function.add_argument(ValueClass::Integer);
+//!     let b = function.add_argument(ValueClass::Integer);
+//!     let sum = function.create_vreg(ValueClass::Integer);
+//!     function.push(IrInstruction::Arith {
+//!         op: ArithmeticOp::Add,
+//!         dst: sum,
+//!         lhs: a,
+//!         rhs: b,
+//!     });
+//!     function.push(IrInstruction::Ret { value: Some(sum) });
+//!
+//!     let mut asm = Assembler::new();
+//!     function.compile(&mut asm)?;
+//!     let code = asm.finish()?;
+//!     assert!(!code.as_slice().is_empty());
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`Assembler`]: crate::Assembler
+mod allocator;
+mod function;
+mod instruction;
+mod liveness;
+mod lowering;
+
+pub use allocator::{
+    Allocation, Location, ALLOCATABLE_GPRS, ALLOCATABLE_XMMS, SPILL_SLOT_SIZE,
+};
+pub use function::*;
+pub use instruction::*;
+pub use liveness::LiveInterval;
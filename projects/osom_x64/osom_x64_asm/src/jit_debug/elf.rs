@@ -0,0 +1,272 @@
+//! Minimal in-memory ELF object describing JIT code, as expected by GDB.
+
+const HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: usize = 64;
+const SYMBOL_SIZE: usize = 24;
+
+const ET_REL: u16 = 1;
+const EM_X86_64: u16 = 62;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
+const SHF_ALLOC: u64 = 2;
+const SHF_EXECINSTR: u64 = 4;
+
+/// Global function, i.e. `STB_GLOBAL << 4 | STT_FUNC`.
+const GLOBAL_FUNCTION: u8 = 0x12;
+
+const TEXT_SECTION: u16 = 1;
+const SECTION_NAMES_SECTION: u16 = 4;
+const SECTION_COUNT: u16 = 6;
+const SECTION_NAMES: &[u8] = b"\0.text\0.symtab\0.strtab\0.shstrtab\0.eh_frame\0";
+
+/// Function symbol with an absolute address.
+pub(crate) struct ElfSymbol<'a> {
+    pub name: &'a str,
+    pub address: u64,
+    pub size: u64,
+}
+
+struct SectionHeader {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    address: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    info: u32,
+    alignment: u64,
+    entry_size: u64,
+}
+
+impl SectionHeader {
+    const NULL: Self = Self {
+        name: 0,
+        kind: 0,
+        flags: 0,
+        address: 0,
+        offset: 0,
+        size: 0,
+        link: 0,
+        info: 0,
+        alignment: 0,
+        entry_size: 0,
+    };
+
+    fn write(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.name.to_le_bytes());
+        output.extend_from_slice(&self.kind.to_le_bytes());
+        output.extend_from_slice(&self.flags.to_le_bytes());
+        output.extend_from_slice(&self.address.to_le_bytes());
+        output.extend_from_slice(&self.offset.to_le_bytes());
+        output.extend_from_slice(&self.size.to_le_bytes());
+        output.extend_from_slice(&self.link.to_le_bytes());
+        output.extend_from_slice(&self.info.to_le_bytes());
+        output.extend_from_slice(&self.alignment.to_le_bytes());
+        output.extend_from_slice(&self.entry_size.to_le_bytes());
+    }
+}
+
+/// Returns the offset of `name` in [`SECTION_NAMES`].
+#[allow(clippy::cast_possible_truncation)]
+fn section_name(name: &str) -> u32 {
+    let position = SECTION_NAMES
+        .windows(name.len() + 1)
+        .position(|window| {
+            &window[..name.len()] == name.as_bytes() && window[name.len()] == 0
+        })
+        .unwrap();
+    position as u32
+}
+
+fn align_to_8(output: &mut Vec<u8>) {
+    output.resize(output.len().next_multiple_of(8), 0);
+}
+
+/// Builds a relocatable ELF object with a `.text` section occupying
+/// `text_size` bytes at `text_address`, without its content, and `symbols`
+/// placed within it. `eh_frame` is stored as the `.eh_frame` section, and has
+/// to use absolute addresses.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn build_symbol_file(
+    text_address: u64,
+    text_size: u64,
+    symbols: &[ElfSymbol],
+    eh_frame: &[u8],
+) -> Vec<u8> {
+    let mut output = vec![0; HEADER_SIZE];
+
+    let section_names_offset = output.len() as u64;
+    output.extend_from_slice(SECTION_NAMES);
+
+    let names_offset = output.len() as u64;
+    let mut name_offsets = Vec::with_capacity(symbols.len());
+    output.push(0);
+    for symbol in symbols {
+        name_offsets.push((output.len() as u64 - names_offset) as u32);
+        output.extend_from_slice(symbol.name.as_bytes());
+        output.push(0);
+    }
+    let names_size = output.len() as u64 - names_offset;
+
+    align_to_8(&mut output);
+    let symbols_offset = output.len() as u64;
+    output.resize(output.len() + SYMBOL_SIZE, 0);
+    for (symbol, name) in symbols.iter().zip(name_offsets) {
+        output.extend_from_slice(&name.to_le_bytes());
+        output.push(GLOBAL_FUNCTION);
+        output.push(0);
+        output.extend_from_slice(&TEXT_SECTION.to_le_bytes());
+        output.extend_from_slice(&symbol.address.to_le_bytes());
+        output.extend_from_slice(&symbol.size.to_le_bytes());
+    }
+    let symbols_size = output.len() as u64 - symbols_offset;
+
+    align_to_8(&mut output);
+    let eh_frame_offset = output.len() as u64;
+    output.extend_from_slice(eh_frame);
+
+    align_to_8(&mut output);
+    let section_headers_offset = output.len() as u64;
+    let sections = [
+        SectionHeader::NULL,
+        SectionHeader {
+            name: section_name(".text"),
+            kind: SHT_NOBITS,
+            flags: SHF_ALLOC | SHF_EXECINSTR,
+            address: text_address,
+            size: text_size,
+            alignment: 16,
+            ..SectionHeader::NULL
+        },
+        SectionHeader {
+            name: section_name(".symtab"),
+            kind: SHT_SYMTAB,
+            offset: symbols_offset,
+            size: symbols_size,
+            link: 3,
+            // Index of the first non-local symbol.
+            info: 1,
+            alignment: 8,
+            entry_size: SYMBOL_SIZE as u64,
+            ..SectionHeader::NULL
+        },
+        SectionHeader {
+            name: section_name(".strtab"),
+            kind: SHT_STRTAB,
+            offset: names_offset,
+            size: names_size,
+            alignment: 1,
+            ..SectionHeader::NULL
+        },
+        SectionHeader {
+            name: section_name(".shstrtab"),
+            kind: SHT_STRTAB,
+            offset: section_names_offset,
+            size: SECTION_NAMES.len() as u64,
+            alignment: 1,
+            ..SectionHeader::NULL
+        },
+        SectionHeader {
+            name: section_name(".eh_frame"),
+            kind: SHT_PROGBITS,
+            flags: SHF_ALLOC,
+            offset: eh_frame_offset,
+            size: eh_frame.len() as u64,
+            alignment: 8,
+            ..SectionHeader::NULL
+        },
+    ];
+    for section in &sections {
+        section.write(&mut output);
+    }
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&[0x7F, b'E', b'L', b'F', 2, 1, 1, 0]);
+    header.extend_from_slice(&[0; 8]);
+    header.extend_from_slice(&ET_REL.to_le_bytes());
+    header.extend_from_slice(&EM_X86_64.to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes());
+    // Entry point and program headers offset.
+    header.extend_from_slice(&[0; 16]);
+    header.extend_from_slice(&section_headers_offset.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    // Program header size and count.
+    header.extend_from_slice(&[0; 4]);
+    header.extend_from_slice(&(SECTION_HEADER_SIZE as u16).to_le_bytes());
+    header.extend_from_slice(&SECTION_COUNT.to_le_bytes());
+    header.extend_from_slice(&SECTION_NAMES_SECTION.to_le_bytes());
+    output[..HEADER_SIZE].copy_from_slice(&header);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn read_offset(bytes: &[u8], offset: usize) -> usize {
+        usize::try_from(read_u64(bytes, offset)).unwrap()
+    }
+
+    #[test]
+    fn test_symbol_file() {
+        let symbols = [
+            ElfSymbol {
+                name: "first",
+                address: 0x1000,
+                size: 0x10,
+            },
+            ElfSymbol {
+                name: "second",
+                address: 0x1010,
+                size: 0x30,
+            },
+        ];
+        let eh_frame = [1, 2, 3, 4];
+        let file = build_symbol_file(0x1000, 0x40, &symbols, &eh_frame);
+        assert_eq!(&file[..4], b"\x7FELF");
+        assert_eq!(read_u16(&file, 16), ET_REL);
+        assert_eq!(read_u16(&file, 18), EM_X86_64);
+        assert_eq!(read_u16(&file, 60), SECTION_COUNT);
+
+        let section_headers = read_offset(&file, 40);
+        assert_eq!(
+            file.len(),
+            section_headers + SECTION_HEADER_SIZE * SECTION_COUNT as usize
+        );
+        let section = |index: usize| section_headers + index * SECTION_HEADER_SIZE;
+        assert_eq!(read_u64(&file, section(1) + 16), 0x1000);
+        assert_eq!(read_u64(&file, section(1) + 32), 0x40);
+
+        let symtab = read_offset(&file, section(2) + 24);
+        assert_eq!(read_u64(&file, section(2) + 32), 3 * SYMBOL_SIZE as u64);
+        let strtab = read_offset(&file, section(3) + 24);
+        for (index, symbol) in symbols.iter().enumerate() {
+            let entry = symtab + (index + 1) * SYMBOL_SIZE;
+            let name = u32::from_le_bytes(file[entry..entry + 4].try_into().unwrap());
+            let name = &file[strtab + name as usize..][..=symbol.name.len()];
+            assert_eq!(&name[..symbol.name.len()], symbol.name.as_bytes());
+            assert_eq!(name[symbol.name.len()], 0);
+            assert_eq!(file[entry + 4], GLOBAL_FUNCTION);
+            assert_eq!(read_u16(&file, entry + 6), TEXT_SECTION);
+            assert_eq!(read_u64(&file, entry + 8), symbol.address);
+            assert_eq!(read_u64(&file, entry + 16), symbol.size);
+        }
+
+        let eh_frame_offset = read_offset(&file, section(5) + 24);
+        assert_eq!(read_u64(&file, section(5) + 32), 4);
+        assert_eq!(&file[eh_frame_offset..eh_frame_offset + 4], &eh_frame);
+    }
+}
@@ -0,0 +1,171 @@
+//! Implementation of the GDB JIT compilation interface, see
+//! <https://sourceware.org/gdb/current/onlinedocs/gdb.html/JIT-Interface.html>.
+
+use core::ptr::{self, NonNull};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{unwind::EhFrameBuilder, ExecutableCode};
+
+use super::{
+    elf::{build_symbol_file, ElfSymbol},
+    JitSymbol,
+};
+
+const JIT_NOACTION: u32 = 0;
+const JIT_REGISTER_FN: u32 = 1;
+const JIT_UNREGISTER_FN: u32 = 2;
+
+#[repr(C)]
+struct JitCodeEntry {
+    next_entry: *mut JitCodeEntry,
+    prev_entry: *mut JitCodeEntry,
+    symfile_addr: *const u8,
+    symfile_size: u64,
+}
+
+#[repr(C)]
+struct JitDescriptor {
+    version: u32,
+    action_flag: u32,
+    relevant_entry: *mut JitCodeEntry,
+    first_entry: *mut JitCodeEntry,
+}
+
+/// Read by GDB to find registered symbol files.
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+static mut __jit_debug_descriptor: JitDescriptor = JitDescriptor {
+    version: 1,
+    action_flag: JIT_NOACTION,
+    relevant_entry: ptr::null_mut(),
+    first_entry: ptr::null_mut(),
+};
+
+/// GDB places a breakpoint here to get notified about changes of the
+/// descriptor.
+#[no_mangle]
+#[inline(never)]
+extern "C" fn __jit_debug_register_code() {
+    // Keeps the call, and the preceding writes, from being optimized out.
+    unsafe { core::arch::asm!("", options(nostack, preserves_flags)) };
+}
+
+/// Serializes modifications of [`__jit_debug_descriptor`].
+static DESCRIPTOR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Notifies GDB about `entry` with `action`, after the list was updated.
+///
+/// # Safety
+/// Has to be called with [`DESCRIPTOR_LOCK`] held.
+unsafe fn notify(entry: *mut JitCodeEntry, action: u32) {
+    let descriptor = ptr::addr_of_mut!(__jit_debug_descriptor);
+    unsafe {
+        (*descriptor).relevant_entry = entry;
+        (*descriptor).action_flag = action;
+        __jit_debug_register_code();
+        (*descriptor).action_flag = JIT_NOACTION;
+    }
+}
+
+/// Symbols of [`ExecutableCode`] registered with GDB, which shows their names
+/// in backtraces and accepts them in breakpoints. The symbols are unregistered
+/// on drop.
+///
+/// # Notes
+/// GDB reads the registered in-memory ELF object only when it is attached.
+/// The registration does not keep the code alive, it should be dropped
+/// together with the code.
+pub struct GdbRegistration {
+    entry: NonNull<JitCodeEntry>,
+    _symbol_file: Vec<u8>,
+}
+
+// The entry is only accessed under `DESCRIPTOR_LOCK`.
+unsafe impl Send for GdbRegistration {}
+unsafe impl Sync for GdbRegistration {}
+
+impl GdbRegistration {
+    /// Registers `symbols` of `code` with GDB, together with call frame
+    /// information recorded in `code`, if any.
+    ///
+    /// # Panics
+    /// When a symbol does not fit in `code`.
+    #[must_use]
+    pub fn register(code: &ExecutableCode, symbols: &[JitSymbol]) -> Self {
+        let elf_symbols: Vec<ElfSymbol> = symbols
+            .iter()
+            .map(|symbol| ElfSymbol {
+                name: symbol.name(),
+                address: symbol.address(code),
+                size: u64::from(symbol.size()),
+            })
+            .collect();
+        let eh_frame = if code.cfi_directives().is_empty() {
+            Vec::new()
+        } else {
+            let mut builder = EhFrameBuilder::new();
+            #[allow(clippy::cast_possible_truncation)]
+            builder.add_function(
+                code.as_ptr() as u64,
+                code.code_len() as u32,
+                code.cfi_directives(),
+            );
+            builder.finish()
+        };
+        let symbol_file = build_symbol_file(
+            code.as_ptr() as u64,
+            code.len() as u64,
+            &elf_symbols,
+            &eh_frame,
+        );
+        let entry = Box::into_raw(Box::new(JitCodeEntry {
+            next_entry: ptr::null_mut(),
+            prev_entry: ptr::null_mut(),
+            symfile_addr: symbol_file.as_ptr(),
+            symfile_size: symbol_file.len() as u64,
+        }));
+
+        let _guard = DESCRIPTOR_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        unsafe {
+            let descriptor = ptr::addr_of_mut!(__jit_debug_descriptor);
+            let next = (*descriptor).first_entry;
+            (*entry).next_entry = next;
+            if !next.is_null() {
+                (*next).prev_entry = entry;
+            }
+            (*descriptor).first_entry = entry;
+            notify(entry, JIT_REGISTER_FN);
+        }
+
+        Self {
+            entry: unsafe { NonNull::new_unchecked(entry) },
+            _symbol_file: symbol_file,
+        }
+    }
+}
+
+impl Drop for GdbRegistration {
+    fn drop(&mut self) {
+        let entry = self.entry.as_ptr();
+        let _guard = DESCRIPTOR_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        unsafe {
+            let descriptor = ptr::addr_of_mut!(__jit_debug_descriptor);
+            let next = (*entry).next_entry;
+            let prev = (*entry).prev_entry;
+            if prev.is_null() {
+                (*descriptor).first_entry = next;
+            } else {
+                (*prev).next_entry = next;
+            }
+            if !next.is_null() {
+                (*next).prev_entry = prev;
+            }
+            notify(entry, JIT_UNREGISTER_FN);
+            drop(Box::from_raw(entry));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Makes code produced by the assembler visible to debuggers and profilers.
+//!
+//! Nothing is registered automatically. Functions mapped with
+//! [`ExecutableCode`] can be described with [`JitSymbol`]s and announced to
+//! GDB with [`GdbRegistration`] (behind the `gdb-jit` feature), and to `perf`
+//! with [`PerfMap`].
+
+#[cfg(feature = "gdb-jit")]
+mod elf;
+#[cfg(feature = "gdb-jit")]
+mod gdb;
+mod perf_map;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "gdb-jit")))]
+#[cfg(feature = "gdb-jit")]
+pub use gdb::GdbRegistration;
+pub use perf_map::*;
+
+use crate::ExecutableCode;
+
+/// Named function within [`ExecutableCode`], given by its offset and size.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct JitSymbol<'a> {
+    name: &'a str,
+    offset: u32,
+    size: u32,
+}
+
+impl<'a> JitSymbol<'a> {
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(name: &'a str, offset: u32, size: u32) -> Self {
+        Self { name, offset, size }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the absolute address of the symbol within `code`.
+    ///
+    /// # Panics
+    /// When the symbol does not fit in `code`.
+    fn address(&self, code: &ExecutableCode) -> u64 {
+        let end = u64::from(self.offset) + u64::from(self.size);
+        assert!(end <= code.len() as u64, "Symbol exceeds the code.");
+        code.as_ptr() as u64 + u64::from(self.offset)
+    }
+}
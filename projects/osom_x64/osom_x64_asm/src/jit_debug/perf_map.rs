@@ -0,0 +1,63 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::ExecutableCode;
+
+use super::JitSymbol;
+
+/// Appends symbols to `/tmp/perf-<pid>.map`, read by `perf report` to name
+/// samples in JIT code.
+///
+/// # Notes
+/// `perf` reads the file after the process exits, thus entries are never
+/// removed. Code mapped later at the same address makes the older entries
+/// ambiguous.
+pub struct PerfMap {
+    file: File,
+}
+
+impl PerfMap {
+    /// Returns the path of the map of the current process.
+    #[must_use]
+    pub fn path() -> PathBuf {
+        PathBuf::from(format!("/tmp/perf-{}.map", std::process::id()))
+    }
+
+    /// Opens the map of the current process for appending, creating it when
+    /// needed.
+    ///
+    /// # Errors
+    /// When the file cannot be opened.
+    pub fn open() -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::path())?;
+        Ok(Self { file })
+    }
+
+    /// Appends an entry for each of `symbols` of `code`.
+    ///
+    /// # Errors
+    /// When the file cannot be written.
+    ///
+    /// # Panics
+    /// When a symbol does not fit in `code`.
+    pub fn add(
+        &mut self,
+        code: &ExecutableCode,
+        symbols: &[JitSymbol],
+    ) -> io::Result<()> {
+        let mut entries = Vec::new();
+        for symbol in symbols {
+            let address = symbol.address(code);
+            let name = symbol.name().replace('\n', " ");
+            writeln!(entries, "{address:x} {:x} {name}", symbol.size())?;
+        }
+        // A single write keeps entries of concurrent writers from interleaving.
+        self.file.write_all(&entries)
+    }
+}
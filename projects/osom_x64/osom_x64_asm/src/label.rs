@@ -0,0 +1,21 @@
+/// Represents a position in the assembled code, created by
+/// [`Assembler::create_label`][crate::Assembler::create_label].
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Label {
+    index: u32,
+}
+
+impl Label {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(index: u32) -> Self {
+        Self { index }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+}
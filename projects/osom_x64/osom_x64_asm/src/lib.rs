@@ -1,2 +1,34 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::inline_always, clippy::module_name_repetitions)]
+pub mod abi;
+mod assembled_code;
+mod assembler;
+mod constant_pool;
+pub mod dsl;
+mod errors;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod executable;
+mod fragment;
+pub mod ir;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub mod jit_debug;
+mod label;
+mod listing;
+mod patch;
+mod peephole;
+mod relocation;
+mod section;
+mod switch;
+pub mod unwind;
+
+pub use assembled_code::*;
+pub use assembler::*;
+pub use errors::*;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use executable::*;
+pub use label::*;
+pub use listing::{Listing, ListingLine};
+pub use patch::*;
+pub use peephole::{PeepholeConfig, PeepholeRule, PeepholeStatistics};
+pub use relocation::*;
+pub use section::*;
@@ -0,0 +1,193 @@
+use core::fmt::{self, Write};
+
+use osom_x64_encoder::{decoder::decode, formatter::Syntax};
+
+/// Number of bytes shown in a single line of a [`Listing`]. Longer instructions
+/// continue on the following lines, data is split into lines of that size.
+const BYTES_PER_LINE: usize = 8;
+
+/// Line of a [`Listing`].
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum ListingLine {
+    /// Start of a section, or of the constant pool.
+    Section { name: String, offset: u32 },
+
+    /// Label bound at `offset`.
+    Label { name: String, offset: u32 },
+
+    /// Single instruction, or a piece of data, with an optional comment given
+    /// with [`Assembler::emit_comment`][crate::Assembler::emit_comment].
+    Bytes {
+        offset: u32,
+        bytes: Vec<u8>,
+        text: String,
+        comment: Option<String>,
+    },
+
+    /// Comment not followed by any instruction in its section.
+    Comment(String),
+}
+
+/// Human readable dump of assembled code, produced by
+/// [`Assembler::finish_with_listing`][crate::Assembler::finish_with_listing].
+///
+/// Displays as a `.lst`-style text: each instruction in its own line, with its
+/// offset, bytes in hex, text and comment, and labels in their own lines.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Default)]
+pub struct Listing {
+    lines: Vec<ListingLine>,
+}
+
+impl Listing {
+    #[must_use]
+    #[inline(always)]
+    pub fn lines(&self) -> &[ListingLine] {
+        &self.lines
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BYTES_WIDTH: usize = BYTES_PER_LINE * 3;
+
+        for line in &self.lines {
+            match line {
+                ListingLine::Section { name, offset } => {
+                    writeln!(f, "{offset:08x}  section {name}")?;
+                }
+                ListingLine::Label { name, .. } => writeln!(f, "{name}:")?,
+                ListingLine::Bytes {
+                    offset,
+                    bytes,
+                    text,
+                    comment,
+                } => {
+                    let mut chunks = bytes.chunks(BYTES_PER_LINE);
+                    let first = hex(chunks.next().unwrap_or_default());
+                    write!(f, "{offset:08x}  {first:<BYTES_WIDTH$}    {text}")?;
+                    if let Some(comment) = comment {
+                        write!(f, "  ; {comment}")?;
+                    }
+                    writeln!(f)?;
+                    for (index, chunk) in chunks.enumerate() {
+                        let offset = *offset as usize + (index + 1) * BYTES_PER_LINE;
+                        writeln!(f, "{offset:08x}  {}", hex(chunk).trim_end())?;
+                    }
+                }
+                ListingLine::Comment(comment) => writeln!(f, "{:<10}; {comment}", "")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 3);
+    for byte in bytes {
+        let _ = write!(result, "{byte:02x} ");
+    }
+    result
+}
+
+/// Collects lines of a [`Listing`], rendering instructions in the given syntax.
+pub(crate) struct ListingBuilder {
+    syntax: Syntax,
+    lines: Vec<ListingLine>,
+    comment: Option<String>,
+}
+
+impl ListingBuilder {
+    #[inline(always)]
+    pub(crate) const fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            lines: Vec::new(),
+            comment: None,
+        }
+    }
+
+    pub(crate) fn section(&mut self, name: &str, offset: u32) {
+        self.lines.push(ListingLine::Section {
+            name: name.to_owned(),
+            offset,
+        });
+    }
+
+    pub(crate) fn label(&mut self, name: String, offset: u32) {
+        self.lines.push(ListingLine::Label { name, offset });
+    }
+
+    /// Attaches `comment` to the next instruction or data.
+    pub(crate) fn comment(&mut self, comment: &str) {
+        match &mut self.comment {
+            Some(pending) => {
+                pending.push_str("; ");
+                pending.push_str(comment);
+            }
+            None => self.comment = Some(comment.to_owned()),
+        }
+    }
+
+    /// Adds instructions in `bytes` starting at `offset`, with branch targets and
+    /// RIP-relative operands printed as `target`. Bytes not understood by the
+    /// decoder are added as data.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn code(&mut self, offset: u32, bytes: &[u8], target: Option<&str>) {
+        let mut position = 0;
+        while position < bytes.len() {
+            let Ok(decoded) = decode(&bytes[position..]) else {
+                self.data(offset + position as u32, &bytes[position..]);
+                return;
+            };
+            let len = usize::from(decoded.len());
+            let mut display = decoded.display(self.syntax);
+            if let Some(target) = target {
+                display = display.with_target_label(target);
+            }
+            self.push_bytes(
+                offset + position as u32,
+                &bytes[position..position + len],
+                display.to_string(),
+            );
+            position += len;
+        }
+    }
+
+    /// Adds `bytes` starting at `offset` as data directives.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn data(&mut self, offset: u32, bytes: &[u8]) {
+        let directive = match self.syntax {
+            Syntax::Intel => "db",
+            Syntax::Att => ".byte",
+        };
+        for (index, chunk) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let mut text = directive.to_owned();
+            let mut separator = " ";
+            for byte in chunk {
+                let _ = write!(text, "{separator}{byte:#04x}");
+                separator = ", ";
+            }
+            self.push_bytes(offset + (index * BYTES_PER_LINE) as u32, chunk, text);
+        }
+    }
+
+    pub(crate) fn finish(self) -> Listing {
+        Listing { lines: self.lines }
+    }
+
+    fn push_bytes(&mut self, offset: u32, bytes: &[u8], text: String) {
+        self.lines.push(ListingLine::Bytes {
+            offset,
+            bytes: bytes.to_vec(),
+            text,
+            comment: self.comment.take(),
+        });
+    }
+
+    /// Adds a pending comment, not followed by any instruction, as its own line.
+    pub(crate) fn flush_comment(&mut self) {
+        if let Some(comment) = self.comment.take() {
+            self.lines.push(ListingLine::Comment(comment));
+        }
+    }
+}
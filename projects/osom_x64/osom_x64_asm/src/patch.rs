@@ -0,0 +1,134 @@
+/// Size and alignment of the window rewritten by a single patch.
+pub const PATCH_WINDOW_SIZE: u32 = 8;
+
+/// Instruction recorded as a [`PatchPoint`].
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum PatchKind {
+    /// `jmp rel32`, patched by replacing the `rel32` field.
+    Jump = 0,
+
+    /// `movabs r64, imm64`, patched by replacing the `imm64` field.
+    MovImm64 = 1,
+}
+
+impl PatchKind {
+    /// Returns the offset of the patched field within the instruction.
+    #[must_use]
+    #[inline(always)]
+    pub const fn field_offset(self) -> u32 {
+        match self {
+            Self::Jump => 1,
+            Self::MovImm64 => 2,
+        }
+    }
+
+    /// Returns the size of the patched field.
+    #[must_use]
+    #[inline(always)]
+    pub const fn field_size(self) -> u32 {
+        match self {
+            Self::Jump => 4,
+            Self::MovImm64 => 8,
+        }
+    }
+
+    /// Returns the number of NOP bytes needed in front of the instruction
+    /// starting at `offset`, so that its field fits in a single naturally
+    /// aligned [`PATCH_WINDOW_SIZE`] window.
+    #[inline(always)]
+    pub(crate) const fn padding(self, offset: u32) -> u32 {
+        let field = offset.wrapping_add(self.field_offset()) % PATCH_WINDOW_SIZE;
+        if field + self.field_size() <= PATCH_WINDOW_SIZE {
+            0
+        } else {
+            PATCH_WINDOW_SIZE - field
+        }
+    }
+}
+
+/// Position of a patchable instruction in the assembled code, emitted with
+/// [`Assembler::emit_patchable_jmp`][crate::Assembler::emit_patchable_jmp] or
+/// [`Assembler::emit_patchable_mov_imm64`][crate::Assembler::emit_patchable_mov_imm64].
+///
+/// # Notes
+/// The patched field never crosses a naturally aligned [`PATCH_WINDOW_SIZE`]
+/// window, relative to the start of the code. Thus it can be rewritten with
+/// a single atomic store, as long as the code is placed at an address aligned
+/// to at least [`PATCH_WINDOW_SIZE`].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct PatchPoint {
+    kind: PatchKind,
+    offset: u32,
+}
+
+impl PatchPoint {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(kind: PatchKind, offset: u32) -> Self {
+        Self { kind, offset }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn kind(&self) -> PatchKind {
+        self.kind
+    }
+
+    /// Returns the offset of the instruction, relative to the start of the code.
+    #[must_use]
+    #[inline(always)]
+    pub const fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns the offset of the patched field, relative to the start of the code.
+    #[must_use]
+    #[inline(always)]
+    pub const fn field_offset(&self) -> u32 {
+        self.offset + self.kind.field_offset()
+    }
+
+    /// Returns the offset of the end of the instruction, i.e. the position
+    /// `rel32` of [`PatchKind::Jump`] is relative to.
+    #[must_use]
+    #[inline(always)]
+    pub const fn end_offset(&self) -> u32 {
+        self.field_offset() + self.kind.field_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(PatchKind::Jump, 0, 0)]
+    #[case(PatchKind::Jump, 3, 0)]
+    #[case(PatchKind::Jump, 4, 3)]
+    #[case(PatchKind::Jump, 7, 0)]
+    #[case(PatchKind::MovImm64, 6, 0)]
+    #[case(PatchKind::MovImm64, 7, 7)]
+    #[case(PatchKind::MovImm64, 0, 6)]
+    fn test_padding(
+        #[case] kind: PatchKind,
+        #[case] offset: u32,
+        #[case] expected: u32,
+    ) {
+        assert_eq!(kind.padding(offset), expected);
+    }
+
+    #[test]
+    fn test_field_fits_in_window() {
+        for kind in [PatchKind::Jump, PatchKind::MovImm64] {
+            for offset in 0..32 {
+                let field = offset + kind.padding(offset) + kind.field_offset();
+                let window = field / PATCH_WINDOW_SIZE;
+                let last = field + kind.field_size() - 1;
+                assert_eq!(last / PATCH_WINDOW_SIZE, window);
+            }
+        }
+    }
+}
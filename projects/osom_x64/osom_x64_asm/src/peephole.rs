@@ -0,0 +1,268 @@
+use osom_x64_encoder::{
+    decoder::{decode, Instruction},
+    encoder::{arith, EncodedInstruction},
+    models::{ArithmeticOp, Flags, MachineSize, GPR},
+};
+
+use crate::{fragment::Fragment, Label};
+
+/// Number of variants of [`PeepholeRule`].
+const RULE_COUNT: usize = 4;
+
+/// Single rewrite performed by [`Assembler::optimize`][crate::Assembler::optimize].
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum PeepholeRule {
+    /// Removes moves of a register to itself, e.g. `mov rax, rax`. Moves of 32-bit
+    /// registers are kept, as they clear the upper half of the register.
+    RedundantMove = 0,
+
+    /// Removes arithmetic not changing its destination, e.g. `add rax, 0` or
+    /// `and rax, -1`, if its flags are never read.
+    NeutralArithmetic = 1,
+
+    /// Replaces `mov r32, 0` and `mov r64, 0` with the shorter `xor r32, r32`, if
+    /// the flags it sets are never read.
+    ZeroIdiom = 2,
+
+    /// Removes `jmp` and `jcc` to the immediately following instruction.
+    JumpToNext = 3,
+}
+
+impl PeepholeRule {
+    pub const ALL: [Self; RULE_COUNT] = [
+        Self::RedundantMove,
+        Self::NeutralArithmetic,
+        Self::ZeroIdiom,
+        Self::JumpToNext,
+    ];
+
+    #[inline(always)]
+    const fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// Selects [`PeepholeRule`]s applied by
+/// [`Assembler::optimize`][crate::Assembler::optimize].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct PeepholeConfig {
+    enabled: u8,
+}
+
+impl Default for PeepholeConfig {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl PeepholeConfig {
+    /// Enables all rules.
+    #[must_use]
+    #[inline(always)]
+    pub const fn all() -> Self {
+        Self {
+            enabled: (1 << RULE_COUNT) - 1,
+        }
+    }
+
+    /// Disables all rules.
+    #[must_use]
+    #[inline(always)]
+    pub const fn none() -> Self {
+        Self { enabled: 0 }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_rule(self, rule: PeepholeRule) -> Self {
+        Self {
+            enabled: self.enabled | rule.mask(),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn without_rule(self, rule: PeepholeRule) -> Self {
+        Self {
+            enabled: self.enabled & !rule.mask(),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_enabled(&self, rule: PeepholeRule) -> bool {
+        self.enabled & rule.mask() != 0
+    }
+}
+
+/// Number of times each [`PeepholeRule`] was applied.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct PeepholeStatistics {
+    applied: [u32; RULE_COUNT],
+}
+
+impl PeepholeStatistics {
+    #[must_use]
+    #[inline(always)]
+    pub const fn applied(&self, rule: PeepholeRule) -> u32 {
+        self.applied[rule as usize]
+    }
+
+    /// Returns the number of all rewrites.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.applied.iter().sum()
+    }
+
+    #[inline(always)]
+    pub(crate) fn record(&mut self, rule: PeepholeRule) {
+        self.applied[rule as usize] += 1;
+    }
+}
+
+/// Change of a single fragment.
+pub(crate) enum Rewrite {
+    Remove,
+    Replace(EncodedInstruction),
+}
+
+/// How an instruction interacts with status flags set before it.
+enum FlagsUsage {
+    /// Flags are neither read nor written.
+    Preserved,
+
+    /// Flags are overwritten without being read, or are dead per the ABI.
+    Overwritten,
+
+    /// Flags may be read, or the control flow is unknown.
+    MaybeRead,
+}
+
+fn decode_fragment(fragment: &Fragment) -> Option<Instruction> {
+    match fragment {
+        Fragment::Instruction(instruction)
+        | Fragment::RipRelative { instruction, .. }
+        | Fragment::Relocated { instruction, .. } => decode(instruction.as_slice())
+            .ok()
+            .map(|decoded| decoded.instruction()),
+        _ => None,
+    }
+}
+
+fn flags_usage(fragment: &Fragment) -> FlagsUsage {
+    match fragment {
+        Fragment::Align { .. } => return FlagsUsage::Preserved,
+        Fragment::Data { .. } | Fragment::Jump { .. } | Fragment::Patchable { .. } => {
+            return FlagsUsage::MaybeRead
+        }
+        Fragment::Instruction(_)
+        | Fragment::RipRelative { .. }
+        | Fragment::Relocated { .. } => {}
+    }
+
+    let Some(instruction) = decode_fragment(fragment) else {
+        return FlagsUsage::MaybeRead;
+    };
+    // Flags are not preserved across calls, so they are dead at `ret`.
+    if instruction == Instruction::Ret {
+        return FlagsUsage::Overwritten;
+    }
+    let info = instruction.info();
+    if info.is_branch() || !info.flags_read().is_empty() {
+        FlagsUsage::MaybeRead
+    } else if info.flags_written().is_empty() {
+        FlagsUsage::Preserved
+    } else if info.flags_written() == Flags::STATUS {
+        FlagsUsage::Overwritten
+    } else {
+        // Flags not written by the instruction may still be read later.
+        FlagsUsage::MaybeRead
+    }
+}
+
+/// Returns `true` if flags set by the fragment at `index` are never read.
+fn flags_dead_after(fragments: &[Fragment], index: usize) -> bool {
+    for fragment in &fragments[index + 1..] {
+        match flags_usage(fragment) {
+            FlagsUsage::Preserved => {}
+            FlagsUsage::Overwritten => return true,
+            FlagsUsage::MaybeRead => return false,
+        }
+    }
+    false
+}
+
+/// Returns `true` if writing `reg` does not change anything but the register
+/// itself, i.e. `reg` is not a 32-bit register zeroing the upper half.
+#[inline(always)]
+fn keeps_upper_bits(reg: GPR) -> bool {
+    !matches!(reg.size(), MachineSize::DWord)
+}
+
+const fn is_neutral(op: ArithmeticOp, imm: i32) -> bool {
+    match op {
+        ArithmeticOp::Add | ArithmeticOp::Sub | ArithmeticOp::Or | ArithmeticOp::Xor => {
+            imm == 0
+        }
+        ArithmeticOp::And => imm == -1,
+        _ => false,
+    }
+}
+
+/// Finds a rewrite of the fragment at `index`. `targets_next` tells whether a
+/// label is bound right after the fragment.
+pub(crate) fn find_rewrite(
+    fragments: &[Fragment],
+    index: usize,
+    config: PeepholeConfig,
+    targets_next: impl Fn(Label) -> bool,
+) -> Option<(PeepholeRule, Rewrite)> {
+    let fragment = &fragments[index];
+    if let Fragment::Jump { target, .. } = fragment {
+        let rule = PeepholeRule::JumpToNext;
+        return (config.is_enabled(rule) && targets_next(*target))
+            .then_some((rule, Rewrite::Remove));
+    }
+    if !matches!(fragment, Fragment::Instruction(_)) {
+        return None;
+    }
+
+    let (rule, rewrite) = match decode_fragment(fragment)? {
+        Instruction::MovRegReg { dst, src } if dst == src && keeps_upper_bits(dst) => {
+            (PeepholeRule::RedundantMove, Rewrite::Remove)
+        }
+        Instruction::MovsdXmmXmm { dst, src }
+        | Instruction::MovssXmmXmm { dst, src }
+        | Instruction::MovapsXmmXmm { dst, src }
+            if dst == src =>
+        {
+            (PeepholeRule::RedundantMove, Rewrite::Remove)
+        }
+        Instruction::ArithRegImm { op, dst, imm }
+            if is_neutral(op, imm) && keeps_upper_bits(dst) =>
+        {
+            (PeepholeRule::NeutralArithmetic, Rewrite::Remove)
+        }
+        Instruction::MovRegImm { dst, imm: 0 }
+            if matches!(dst.size(), MachineSize::DWord | MachineSize::QWord) =>
+        {
+            let reg = dst.resize(MachineSize::DWord).ok()?;
+            let encoded = arith::encode_xor_reg_reg(reg, reg).ok()?;
+            (PeepholeRule::ZeroIdiom, Rewrite::Replace(encoded))
+        }
+        _ => return None,
+    };
+
+    if !config.is_enabled(rule) {
+        return None;
+    }
+    let writes_flags = matches!(
+        rule,
+        PeepholeRule::NeutralArithmetic | PeepholeRule::ZeroIdiom
+    );
+    if writes_flags && !flags_dead_after(fragments, index) {
+        return None;
+    }
+    Some((rule, rewrite))
+}
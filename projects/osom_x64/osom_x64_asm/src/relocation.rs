@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+/// External symbol, e.g. a function or a global variable whose address is not
+/// known while assembling. Created by
+/// [`Assembler::declare_symbol`][crate::Assembler::declare_symbol].
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Symbol {
+    index: u32,
+}
+
+impl Symbol {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(index: u32) -> Self {
+        Self { index }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+}
+
+/// How the value of a [`Relocation`] is computed. In descriptions below `S` is
+/// the symbol's address, `A` the addend and `P` the address of the field.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum RelocationKind {
+    /// 32-bit `S + A - P`, e.g. `lea rax, [rip + counter]`.
+    Pc32 = 0,
+
+    /// 32-bit `S + A - P` of a `call` or `jmp`. Targets out of `rel32` range
+    /// may be reached through a stub instead.
+    Plt32 = 1,
+
+    /// 64-bit `S + A`, e.g. `movabs rax, counter`.
+    Abs64 = 2,
+
+    /// 32-bit `G + A - P`, where `G` is the address of a global offset table
+    /// slot holding `S`, e.g. `mov rax, qword ptr [rip + counter@GOTPCREL]`.
+    GotPc32 = 3,
+}
+
+impl RelocationKind {
+    /// Returns the size of the relocated field in bytes.
+    #[must_use]
+    #[inline(always)]
+    pub const fn size(self) -> u32 {
+        match self {
+            Self::Abs64 => 8,
+            Self::Pc32 | Self::Plt32 | Self::GotPc32 => 4,
+        }
+    }
+
+    /// Returns `true` for relocations relative to the field's address.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_pc_relative(self) -> bool {
+        !matches!(self, Self::Abs64)
+    }
+
+    /// Returns the matching `R_X86_64_*` relocation type of ELF objects.
+    #[must_use]
+    #[inline(always)]
+    pub const fn elf_type(self) -> u32 {
+        match self {
+            Self::Abs64 => 1,
+            Self::Pc32 => 2,
+            Self::Plt32 => 4,
+            Self::GotPc32 => 9,
+        }
+    }
+}
+
+/// Reference to a [`Symbol`] from the field at `offset`, left unresolved in
+/// [`AssembledCode`][crate::AssembledCode]. The field is filled with zeros.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Relocation {
+    kind: RelocationKind,
+    offset: u32,
+    symbol: Symbol,
+    addend: i64,
+}
+
+impl Relocation {
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(
+        kind: RelocationKind,
+        offset: u32,
+        symbol: Symbol,
+        addend: i64,
+    ) -> Self {
+        Self {
+            kind,
+            offset,
+            symbol,
+            addend,
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn kind(&self) -> RelocationKind {
+        self.kind
+    }
+
+    /// Returns the offset of the relocated field from the start of the code.
+    #[must_use]
+    #[inline(always)]
+    pub const fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn symbol(&self) -> Symbol {
+        self.symbol
+    }
+
+    /// Returns the addend, with the same meaning as in ELF `RELA` entries. For
+    /// PC-relative relocations it accounts for the distance between the field
+    /// and the end of the instruction.
+    #[must_use]
+    #[inline(always)]
+    pub const fn addend(&self) -> i64 {
+        self.addend
+    }
+}
+
+/// Addresses of symbols by name, used to resolve [`Relocation`]s of JIT code.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    addresses: HashMap<String, u64>,
+}
+
+impl SymbolTable {
+    #[must_use]
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines `name` as `address`, replacing the previous definition.
+    pub fn define(&mut self, name: &str, address: *const u8) -> &mut Self {
+        self.addresses.insert(name.to_owned(), address as u64);
+        self
+    }
+
+    /// Returns the address of `name`, or [`None`] if it is not defined.
+    #[must_use]
+    #[inline(always)]
+    pub fn address(&self, name: &str) -> Option<u64> {
+        self.addresses.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_table() {
+        let value = 5u8;
+        let mut table = SymbolTable::new();
+        table
+            .define("value", &raw const value)
+            .define("null", core::ptr::null());
+        assert_eq!(table.address("value"), Some(&raw const value as u64));
+        assert_eq!(table.address("null"), Some(0));
+        assert_eq!(table.address("missing"), None);
+
+        table.define("null", &raw const value);
+        assert_eq!(table.address("null"), table.address("value"));
+    }
+}
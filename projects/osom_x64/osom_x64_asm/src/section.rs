@@ -0,0 +1,37 @@
+/// Named part of the assembled image, e.g. hot code, cold code or read-only
+/// data, created by [`Assembler::create_section`][crate::Assembler::create_section].
+/// Each section is a separate stream of instructions and data, placed in the
+/// image as a whole, at an offset aligned to the section's alignment.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Section {
+    index: u32,
+}
+
+impl Section {
+    /// The `.text` section, existing in every assembler and used by default.
+    pub const TEXT: Self = Self::new(0);
+
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const fn new(index: u32) -> Self {
+        Self { index }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+}
+
+/// Content of a [`Section`], deciding how alignment padding is filled.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum SectionKind {
+    /// Instructions, padded with NOPs.
+    Code = 0,
+
+    /// Data, padded with zeros.
+    Data = 1,
+}
@@ -0,0 +1,140 @@
+use osom_x64_encoder::{
+    encoder::{arith, errors::EncodingError, mov},
+    models::{Condition, MachineSize, GPR},
+};
+
+use crate::{assembler::encode_jump_table_dispatch, Assembler, AssemblerError, Label};
+
+/// Minimal number of cases dispatched through a jump table.
+const MIN_JUMP_TABLE_CASES: usize = 4;
+
+/// Maximal number of cases compared one by one at the leaves of the search tree.
+const MAX_LINEAR_CASES: usize = 3;
+
+impl Assembler {
+    /// Appends a multiway branch jumping to the label of the case equal to the
+    /// unsigned value of `index`, or to `default` if there is none. Cases can be
+    /// given in any order.
+    ///
+    /// # Notes
+    /// Dense cases, i.e. at least 4 cases covering at least half of the values
+    /// between the smallest and the biggest one, are dispatched through a jump
+    /// table with [`Assembler::emit_jump_table_dispatch`], after a single bounds
+    /// check. Sparse cases are found with binary search over `je` and `ja`
+    /// comparisons.
+    ///
+    /// Both `index` and `scratch` are clobbered, `scratch` only by jump tables.
+    ///
+    /// # Errors
+    /// * [`AssemblerError::InvalidSwitch`] if two cases have the same value, or
+    ///   `index` and `scratch` are the same register.
+    /// * [`AssemblerError::Encoding`] if `index` is not a 32-bit register,
+    ///   `scratch` is not a 64-bit register, or cases are dispatched through a
+    ///   jump table and `index` is `esp`.
+    ///
+    /// Nothing is emitted on error.
+    pub fn emit_switch(
+        &mut self,
+        index: GPR,
+        cases: &[(u32, Label)],
+        default: Label,
+        scratch: GPR,
+    ) -> Result<(), AssemblerError> {
+        if index.size() != MachineSize::DWord || scratch.size() != MachineSize::QWord {
+            return Err(EncodingError::InvalidRegisterSize.into());
+        }
+        if index.index() == scratch.index() {
+            return Err(AssemblerError::InvalidSwitch);
+        }
+
+        let mut cases = cases.to_vec();
+        cases.sort_unstable_by_key(|(value, _)| *value);
+        if cases.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(AssemblerError::InvalidSwitch);
+        }
+
+        match jump_table_span(&cases) {
+            Some(span) => self.emit_table_switch(index, &cases, span, default, scratch),
+            None => self.emit_search(index, &cases, default),
+        }
+    }
+
+    fn emit_table_switch(
+        &mut self,
+        index: GPR,
+        cases: &[(u32, Label)],
+        span: u32,
+        default: Label,
+        scratch: GPR,
+    ) -> Result<(), AssemblerError> {
+        // Rebasing wraps values below the first case around, so that a single
+        // unsigned comparison rejects them too. Both forms zero the upper half
+        // of the register, as required by the 64-bit dispatch.
+        let first = cases[0].0;
+        let rebase = if first == 0 {
+            mov::encode_mov_reg_reg(index, index)?
+        } else {
+            arith::encode_sub_reg_imm(index, first)?
+        };
+        let bounds_check = arith::encode_cmp_reg_imm(index, span)?;
+        // Everything is validated before emitting, so that a failure leaves
+        // neither a partial switch nor an orphan jump table behind.
+        let dispatch =
+            encode_jump_table_dispatch(index.resize(MachineSize::QWord)?, scratch)?;
+
+        let mut targets = vec![default; span as usize];
+        for &(value, target) in cases {
+            targets[(value - first) as usize] = target;
+        }
+        self.emit(rebase);
+        self.emit(bounds_check);
+        self.emit_jcc(Condition::AboveOrEqual, default);
+        let table = self.jump_table(&targets);
+        self.emit_lea_label(scratch, table)?;
+        for instruction in dispatch {
+            self.emit(instruction);
+        }
+        Ok(())
+    }
+
+    /// Emits binary search over sorted `cases`, falling back to `default`.
+    fn emit_search(
+        &mut self,
+        index: GPR,
+        cases: &[(u32, Label)],
+        default: Label,
+    ) -> Result<(), AssemblerError> {
+        let compare = |value: u32| arith::encode_cmp_reg_imm(index, value);
+
+        if cases.len() <= MAX_LINEAR_CASES {
+            for &(value, target) in cases {
+                self.emit(compare(value)?);
+                self.emit_jcc(Condition::Equal, target);
+            }
+            self.emit_jmp(default);
+            return Ok(());
+        }
+
+        let middle = cases.len() / 2;
+        let (value, target) = cases[middle];
+        let upper = self.create_label();
+        self.emit(compare(value)?);
+        self.emit_jcc(Condition::Equal, target);
+        self.emit_jcc(Condition::Above, upper);
+        self.emit_search(index, &cases[..middle], default)?;
+        self.bind_label(upper)?;
+        self.emit_search(index, &cases[middle + 1..], default)
+    }
+}
+
+/// Returns the number of jump table entries if sorted `cases` are dense enough.
+fn jump_table_span(cases: &[(u32, Label)]) -> Option<u32> {
+    if cases.len() < MIN_JUMP_TABLE_CASES {
+        return None;
+    }
+    let span = u64::from(cases[cases.len() - 1].0 - cases[0].0) + 1;
+    if span > 2 * cases.len() as u64 {
+        return None;
+    }
+    u32::try_from(span).ok()
+}
@@ -0,0 +1,42 @@
+use osom_x64_encoder::models::GPR;
+
+/// DWARF numbers of general purpose registers, indexed by their encoding.
+const DWARF_REGISTERS: [u8; 16] = [0, 2, 1, 3, 7, 6, 4, 5, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// DWARF number of the return address column.
+pub(crate) const RETURN_ADDRESS_REGISTER: u8 = 16;
+
+/// Returns the DWARF number of `reg`, regardless of its size.
+#[inline(always)]
+pub(crate) const fn dwarf_register(reg: GPR) -> u8 {
+    DWARF_REGISTERS[(reg.index() & 0b1111) as usize]
+}
+
+/// Change of the call frame description, taking effect at the position it was
+/// recorded at. The canonical frame address (CFA) is the value of RSP right
+/// before the `call` of the function, i.e. on entry the CFA is `rsp + 8`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum CfiDirective {
+    /// CFA is `register + offset`.
+    DefCfa { register: GPR, offset: u32 },
+
+    /// CFA is the current CFA register plus `offset`.
+    DefCfaOffset(u32),
+
+    /// CFA is `register` plus the current offset.
+    DefCfaRegister(GPR),
+
+    /// The caller's value of `register` is saved at `CFA + offset`. The offset
+    /// has to be a multiple of 8.
+    Offset { register: GPR, offset: i32 },
+
+    /// The caller's value of `register` is back in the register.
+    Restore(GPR),
+
+    /// Pushes the whole current description on a stack, e.g. before an
+    /// epilogue in the middle of the function.
+    RememberState,
+
+    /// Pops the description saved with [`CfiDirective::RememberState`].
+    RestoreState,
+}
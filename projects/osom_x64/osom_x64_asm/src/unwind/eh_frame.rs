@@ -0,0 +1,280 @@
+use super::{
+    cfi::{dwarf_register, RETURN_ADDRESS_REGISTER},
+    CfiDirective,
+};
+
+const DW_CFA_ADVANCE_LOC: u8 = 0x40;
+const DW_CFA_OFFSET: u8 = 0x80;
+const DW_CFA_RESTORE: u8 = 0xC0;
+const DW_CFA_NOP: u8 = 0x00;
+const DW_CFA_ADVANCE_LOC1: u8 = 0x02;
+const DW_CFA_ADVANCE_LOC2: u8 = 0x03;
+const DW_CFA_ADVANCE_LOC4: u8 = 0x04;
+const DW_CFA_REMEMBER_STATE: u8 = 0x0A;
+const DW_CFA_RESTORE_STATE: u8 = 0x0B;
+const DW_CFA_DEF_CFA: u8 = 0x0C;
+const DW_CFA_DEF_CFA_REGISTER: u8 = 0x0D;
+const DW_CFA_DEF_CFA_OFFSET: u8 = 0x0E;
+const DW_CFA_OFFSET_EXTENDED_SF: u8 = 0x11;
+
+/// Pointers are absolute, 8-byte values.
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+
+/// Data alignment factor of the CIE, i.e. the size of a stack slot.
+const DATA_ALIGNMENT: i64 = -8;
+
+/// Size of the CIE, which is always placed first.
+const CIE_SIZE: usize = 24;
+
+/// Builds the content of an `.eh_frame` section: a single CIE describing the
+/// state on function entry, followed by an FDE per function.
+///
+/// # Notes
+/// Function addresses are encoded as absolute pointers, thus the section is
+/// only valid for code placed at the given addresses, e.g. in memory or in ELF
+/// objects with absolute section addresses.
+pub struct EhFrameBuilder {
+    bytes: Vec<u8>,
+}
+
+impl Default for EhFrameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EhFrameBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut bytes = Vec::with_capacity(CIE_SIZE);
+        let length_start = start_entry(&mut bytes);
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.push(1);
+        bytes.extend_from_slice(b"zR\0");
+        write_uleb128(&mut bytes, 1);
+        write_sleb128(&mut bytes, DATA_ALIGNMENT);
+        write_uleb128(&mut bytes, RETURN_ADDRESS_REGISTER.into());
+        write_uleb128(&mut bytes, 1);
+        bytes.push(DW_EH_PE_ABSPTR);
+        // On entry the CFA is `rsp + 8`, with the return address right below it.
+        bytes.extend_from_slice(&[DW_CFA_DEF_CFA, 7, 8]);
+        bytes.push(DW_CFA_OFFSET | RETURN_ADDRESS_REGISTER);
+        write_uleb128(&mut bytes, 1);
+        finish_entry(&mut bytes, length_start);
+        debug_assert_eq!(bytes.len(), CIE_SIZE);
+        Self { bytes }
+    }
+
+    /// Appends the FDE of a function of `size` bytes at `address`, with
+    /// `directives` given together with their offsets from the function start.
+    ///
+    /// # Panics
+    /// When `directives` are not sorted by offset, or an offset exceeds `size`.
+    pub fn add_function(
+        &mut self,
+        address: u64,
+        size: u32,
+        directives: &[(u32, CfiDirective)],
+    ) -> &mut Self {
+        let bytes = &mut self.bytes;
+        let length_start = start_entry(bytes);
+        #[allow(clippy::cast_possible_truncation)]
+        let cie_pointer = bytes.len() as u32;
+        bytes.extend_from_slice(&cie_pointer.to_le_bytes());
+        bytes.extend_from_slice(&address.to_le_bytes());
+        bytes.extend_from_slice(&u64::from(size).to_le_bytes());
+        write_uleb128(bytes, 0);
+
+        let mut position = 0;
+        for (offset, directive) in directives {
+            assert!(
+                position <= *offset && *offset <= size,
+                "Directives out of order or outside of the function."
+            );
+            write_advance(bytes, offset - position);
+            write_directive(bytes, *directive);
+            position = *offset;
+        }
+        finish_entry(bytes, length_start);
+        self
+    }
+
+    /// Returns the section content, terminated with a zero-length entry.
+    #[must_use]
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.extend_from_slice(&0u32.to_le_bytes());
+        self.bytes
+    }
+}
+
+/// Reserves the length field of a CIE or FDE and returns its position.
+fn start_entry(bytes: &mut Vec<u8>) -> usize {
+    let start = bytes.len();
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    start
+}
+
+/// Pads the entry to 8 bytes and fills its length field.
+fn finish_entry(bytes: &mut Vec<u8>, length_start: usize) {
+    let content_start = length_start + 4;
+    let padded = (bytes.len() - length_start).next_multiple_of(8) + length_start;
+    bytes.resize(padded, DW_CFA_NOP);
+    #[allow(clippy::cast_possible_truncation)]
+    let length = (bytes.len() - content_start) as u32;
+    bytes[length_start..content_start].copy_from_slice(&length.to_le_bytes());
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_advance(bytes: &mut Vec<u8>, delta: u32) {
+    match delta {
+        0 => {}
+        1..0x40 => bytes.push(DW_CFA_ADVANCE_LOC | delta as u8),
+        0x40..0x100 => bytes.extend_from_slice(&[DW_CFA_ADVANCE_LOC1, delta as u8]),
+        0x100..0x1_0000 => {
+            bytes.push(DW_CFA_ADVANCE_LOC2);
+            bytes.extend_from_slice(&(delta as u16).to_le_bytes());
+        }
+        _ => {
+            bytes.push(DW_CFA_ADVANCE_LOC4);
+            bytes.extend_from_slice(&delta.to_le_bytes());
+        }
+    }
+}
+
+fn write_directive(bytes: &mut Vec<u8>, directive: CfiDirective) {
+    match directive {
+        CfiDirective::DefCfa { register, offset } => {
+            bytes.extend_from_slice(&[DW_CFA_DEF_CFA, dwarf_register(register)]);
+            write_uleb128(bytes, offset.into());
+        }
+        CfiDirective::DefCfaOffset(offset) => {
+            bytes.push(DW_CFA_DEF_CFA_OFFSET);
+            write_uleb128(bytes, offset.into());
+        }
+        CfiDirective::DefCfaRegister(register) => {
+            bytes
+                .extend_from_slice(&[DW_CFA_DEF_CFA_REGISTER, dwarf_register(register)]);
+        }
+        CfiDirective::Offset { register, offset } => {
+            let factored = i64::from(offset) / DATA_ALIGNMENT;
+            if let Ok(factored) = u64::try_from(factored) {
+                bytes.push(DW_CFA_OFFSET | dwarf_register(register));
+                write_uleb128(bytes, factored);
+            } else {
+                let register = dwarf_register(register);
+                bytes.extend_from_slice(&[DW_CFA_OFFSET_EXTENDED_SF, register]);
+                write_sleb128(bytes, factored);
+            }
+        }
+        CfiDirective::Restore(register) => {
+            bytes.push(DW_CFA_RESTORE | dwarf_register(register));
+        }
+        CfiDirective::RememberState => bytes.push(DW_CFA_REMEMBER_STATE),
+        CfiDirective::RestoreState => bytes.push(DW_CFA_RESTORE_STATE),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_uleb128(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn write_sleb128(bytes: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        let sign_bit = byte & 0x40 != 0;
+        if (value == 0 && !sign_bit) || (value == -1 && sign_bit) {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use osom_x64_encoder::models::GPR;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, &[0x00])]
+    #[case(127, &[0x7F])]
+    #[case(128, &[0x80, 0x01])]
+    #[case(624_485, &[0xE5, 0x8E, 0x26])]
+    fn test_uleb128(#[case] value: u64, #[case] expected: &[u8]) {
+        let mut bytes = Vec::new();
+        write_uleb128(&mut bytes, value);
+        assert_eq!(bytes, expected);
+    }
+
+    #[rstest]
+    #[case(0, &[0x00])]
+    #[case(-1, &[0x7F])]
+    #[case(63, &[0x3F])]
+    #[case(64, &[0xC0, 0x00])]
+    #[case(-8, &[0x78])]
+    #[case(-123_456, &[0xC0, 0xBB, 0x78])]
+    fn test_sleb128(#[case] value: i64, #[case] expected: &[u8]) {
+        let mut bytes = Vec::new();
+        write_sleb128(&mut bytes, value);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_eh_frame() {
+        let mut builder = EhFrameBuilder::new();
+        builder.add_function(
+            0x1000,
+            0x200,
+            &[
+                (1, CfiDirective::DefCfaOffset(16)),
+                (
+                    1,
+                    CfiDirective::Offset {
+                        register: GPR::RBX,
+                        offset: -16,
+                    },
+                ),
+                (0x101, CfiDirective::RememberState),
+                (0x102, CfiDirective::DefCfaOffset(8)),
+                (0x103, CfiDirective::RestoreState),
+            ],
+        );
+        let bytes = builder.finish();
+
+        #[rustfmt::skip]
+        let expected_cie: &[u8] = &[
+            20, 0, 0, 0, 0, 0, 0, 0, 1, b'z', b'R', 0, 1, 0x78, 16, 1, 0,
+            DW_CFA_DEF_CFA, 7, 8, DW_CFA_OFFSET | 16, 1, 0, 0,
+        ];
+        assert_eq!(&bytes[..CIE_SIZE], expected_cie);
+
+        #[rustfmt::skip]
+        let expected_fde: &[u8] = &[
+            36, 0, 0, 0, 28, 0, 0, 0,
+            0x00, 0x10, 0, 0, 0, 0, 0, 0,
+            0x00, 0x02, 0, 0, 0, 0, 0, 0,
+            0,
+            DW_CFA_ADVANCE_LOC | 1, DW_CFA_DEF_CFA_OFFSET, 16,
+            DW_CFA_OFFSET | 3, 2,
+            DW_CFA_ADVANCE_LOC2, 0, 1, DW_CFA_REMEMBER_STATE,
+            DW_CFA_ADVANCE_LOC | 1, DW_CFA_DEF_CFA_OFFSET, 8,
+            DW_CFA_ADVANCE_LOC | 1, DW_CFA_RESTORE_STATE,
+            DW_CFA_NOP,
+        ];
+        assert_eq!(&bytes[CIE_SIZE..bytes.len() - 4], expected_fde);
+        assert_eq!(&bytes[bytes.len() - 4..], &[0; 4]);
+    }
+}
@@ -0,0 +1,19 @@
+//! Call frame information, letting unwinders, profilers and debuggers walk
+//! through frames of generated functions.
+//!
+//! [`FrameLayout`][crate::abi::FrameLayout] records a [`CfiDirective`] with
+//! [`Assembler::emit_cfi`][crate::Assembler::emit_cfi] after every instruction
+//! of prologues and epilogues changing the frame. The directives, resolved to
+//! code offsets by [`AssembledCode::cfi_directives`][crate::AssembledCode::cfi_directives],
+//! are encoded into `.eh_frame` by [`EhFrameBuilder`], and can be registered with
+//! the system unwinder through [`FrameRegistration`].
+
+mod cfi;
+mod eh_frame;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod registration;
+
+pub use cfi::*;
+pub use eh_frame::*;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use registration::*;
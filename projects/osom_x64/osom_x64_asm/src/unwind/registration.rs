@@ -0,0 +1,88 @@
+use core::marker::PhantomData;
+
+use crate::ExecutableCode;
+
+use super::EhFrameBuilder;
+
+/// Registration of `.eh_frame` of in-memory code with the system unwinder, so
+/// that panics and C++ exceptions can propagate through generated frames, and
+/// backtraces can walk through them. The registration is removed on drop.
+///
+/// # Notes
+/// The registration relies on libgcc's `__register_frame`, which accepts a
+/// whole `.eh_frame` section.
+///
+/// The registration borrows the registered code, so that the code cannot be
+/// unmapped while the unwinder still refers to it:
+///
+/// ```compile_fail
+/// use osom_x64_asm::{unwind::FrameRegistration, Assembler, ExecutableCode};
+///
+/// let code = ExecutableCode::new(&Assembler::new().finish().unwrap()).unwrap();
+/// let registration = FrameRegistration::register(&code);
+/// drop(code);
+/// drop(registration);
+/// ```
+pub struct FrameRegistration<'a> {
+    eh_frame: Box<[u8]>,
+    code: PhantomData<&'a ExecutableCode>,
+}
+
+impl<'a> FrameRegistration<'a> {
+    /// Registers the CFI directives recorded in `code` as a single function
+    /// spanning all of it.
+    #[must_use]
+    pub fn register(code: &'a ExecutableCode) -> Self {
+        let mut builder = EhFrameBuilder::new();
+        #[allow(clippy::cast_possible_truncation)]
+        builder.add_function(
+            code.as_ptr() as u64,
+            code.code_len() as u32,
+            code.cfi_directives(),
+        );
+        // SAFETY: The builder produces a terminated section with a single FDE
+        // spanning exactly `code`.
+        unsafe { Self::from_eh_frame(builder.finish()) }
+    }
+
+    /// Registers an arbitrary `.eh_frame` section, e.g. produced by
+    /// [`EhFrameBuilder`] for multiple functions.
+    ///
+    /// # Safety
+    /// The system unwinder parses the section without any validation, thus:
+    /// * `eh_frame` has to be a well-formed `.eh_frame` section,
+    /// * it has to be terminated with a zero-length entry, as done by
+    ///   [`EhFrameBuilder::finish`],
+    /// * its FDEs have to cover only code that stays mapped, and keeps matching
+    ///   its CFI, until the registration is dropped. The lifetime `'a` is
+    ///   unconstrained, it is up to the caller to pick one enforcing that.
+    #[must_use]
+    pub unsafe fn from_eh_frame(eh_frame: Vec<u8>) -> Self {
+        let eh_frame = eh_frame.into_boxed_slice();
+        unsafe { sys::__register_frame(eh_frame.as_ptr()) };
+        Self {
+            eh_frame,
+            code: PhantomData,
+        }
+    }
+
+    /// Returns the registered section.
+    #[inline(always)]
+    #[must_use]
+    pub fn eh_frame(&self) -> &[u8] {
+        &self.eh_frame
+    }
+}
+
+impl Drop for FrameRegistration<'_> {
+    fn drop(&mut self) {
+        unsafe { sys::__deregister_frame(self.eh_frame.as_ptr()) };
+    }
+}
+
+mod sys {
+    extern "C" {
+        pub fn __register_frame(begin: *const u8);
+        pub fn __deregister_frame(begin: *const u8);
+    }
+}
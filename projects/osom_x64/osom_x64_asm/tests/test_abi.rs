@@ -0,0 +1,316 @@
+use osom_x64_asm::{
+    abi::{
+        ArgumentLocation, CallingConvention, FrameLayout, FunctionDescription,
+        ValueClass,
+    },
+    Assembler, AssemblerError,
+};
+use osom_x64_encoder::{
+    decoder::{Decoder, Instruction},
+    models::{ArithmeticOp, Memory, GPR, XMM},
+};
+
+fn prologue_and_epilogue(
+    layout: &FrameLayout,
+) -> Result<(Vec<Instruction>, Vec<Instruction>), AssemblerError> {
+    let decode = |asm: Assembler| -> Result<Vec<Instruction>, AssemblerError> {
+        let code = asm.finish()?;
+        Ok(Decoder::new(code.as_slice())
+            .map(|item| item.unwrap().1.instruction())
+            .collect())
+    };
+
+    let mut prologue = Assembler::new();
+    layout.emit_prologue(&mut prologue)?;
+    let mut epilogue = Assembler::new();
+    layout.emit_epilogue(&mut epilogue)?;
+    Ok((decode(prologue)?, decode(epilogue)?))
+}
+
+fn rsp(displacement: i32) -> Memory {
+    Memory::based(GPR::RSP, displacement).unwrap()
+}
+
+#[test]
+fn test_empty_leaf_frame() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::RAX, GPR::RCX, GPR::R11]);
+    let layout = FrameLayout::new(&function)?;
+    let (prologue, epilogue) = prologue_and_epilogue(&layout)?;
+    assert!(prologue.is_empty());
+    assert_eq!(epilogue, vec![Instruction::Ret]);
+    Ok(())
+}
+
+#[test]
+fn test_saves_only_clobbered_callee_saved() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::R12, GPR::RAX, GPR::EBX])
+        .with_locals_size(20)
+        .with_calls();
+    let layout = FrameLayout::new(&function)?;
+    assert_eq!(layout.saved_registers(), &[GPR::RBX, GPR::R12]);
+    // Return address and two pushes leave RSP misaligned by 8.
+    assert_eq!(layout.stack_adjustment(), 40);
+    assert_eq!(layout.local(8)?, rsp(8));
+
+    let (prologue, epilogue) = prologue_and_epilogue(&layout)?;
+    assert_eq!(
+        prologue,
+        vec![
+            Instruction::Push { reg: GPR::RBX },
+            Instruction::Push { reg: GPR::R12 },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Sub,
+                dst: GPR::RSP,
+                imm: 40
+            },
+        ]
+    );
+    assert_eq!(
+        epilogue,
+        vec![
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Add,
+                dst: GPR::RSP,
+                imm: 40
+            },
+            Instruction::Pop { reg: GPR::R12 },
+            Instruction::Pop { reg: GPR::RBX },
+            Instruction::Ret,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_frame_pointer() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::RBP, GPR::R15])
+        .with_frame_pointer()
+        .with_calls();
+    let layout = FrameLayout::new(&function)?;
+    assert_eq!(layout.saved_registers(), &[GPR::R15]);
+    assert_eq!(layout.stack_adjustment(), 8);
+
+    let (prologue, epilogue) = prologue_and_epilogue(&layout)?;
+    assert_eq!(
+        prologue,
+        vec![
+            Instruction::Push { reg: GPR::RBP },
+            Instruction::MovRegReg {
+                dst: GPR::RBP,
+                src: GPR::RSP
+            },
+            Instruction::Push { reg: GPR::R15 },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Sub,
+                dst: GPR::RSP,
+                imm: 8
+            },
+        ]
+    );
+    assert_eq!(
+        epilogue,
+        vec![
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Add,
+                dst: GPR::RSP,
+                imm: 8
+            },
+            Instruction::Pop { reg: GPR::R15 },
+            Instruction::Pop { reg: GPR::RBP },
+            Instruction::Ret,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_red_zone() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::RBX])
+        .with_locals_size(128);
+    let layout = FrameLayout::new(&function)?;
+    assert_eq!(layout.stack_adjustment(), 0);
+    assert_eq!(layout.local(0)?, rsp(-128));
+    assert_eq!(layout.local(120)?, rsp(-8));
+
+    let function = function.with_locals_size(129);
+    let layout = FrameLayout::new(&function)?;
+    assert_eq!(layout.stack_adjustment(), 144);
+    assert_eq!(layout.local(0)?, rsp(0));
+    Ok(())
+}
+
+#[test]
+fn test_argument_locations() -> Result<(), AssemblerError> {
+    use ValueClass::{Float, Integer};
+
+    let mut arguments = vec![Float, Integer];
+    arguments.extend([Integer; 6]);
+    arguments.extend([Float; 8]);
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_arguments(&arguments)
+        .with_clobbered(&[GPR::RBX])
+        .with_calls();
+    let layout = FrameLayout::new(&function)?;
+
+    // Return address, RBX and no padding.
+    let frame_size = 16;
+    let expected = [
+        ArgumentLocation::Xmm(XMM::XMM0),
+        ArgumentLocation::Gpr(GPR::RDI),
+        ArgumentLocation::Gpr(GPR::RSI),
+        ArgumentLocation::Gpr(GPR::RDX),
+        ArgumentLocation::Gpr(GPR::RCX),
+        ArgumentLocation::Gpr(GPR::R8),
+        ArgumentLocation::Gpr(GPR::R9),
+        ArgumentLocation::Stack(rsp(frame_size)),
+        ArgumentLocation::Xmm(XMM::XMM1),
+        ArgumentLocation::Xmm(XMM::XMM2),
+        ArgumentLocation::Xmm(XMM::XMM3),
+        ArgumentLocation::Xmm(XMM::XMM4),
+        ArgumentLocation::Xmm(XMM::XMM5),
+        ArgumentLocation::Xmm(XMM::XMM6),
+        ArgumentLocation::Xmm(XMM::XMM7),
+        ArgumentLocation::Stack(rsp(frame_size + 8)),
+    ];
+    assert_eq!(layout.arguments(), &expected);
+    Ok(())
+}
+
+#[test]
+fn test_callee_saved_queries() {
+    let convention = CallingConvention::SystemV;
+    assert!(convention.is_callee_saved(GPR::R13D));
+    assert!(!convention.is_callee_saved(GPR::RDI));
+    assert_eq!(convention.integer_return(), GPR::RAX);
+    assert_eq!(convention.red_zone_size(), 128);
+    assert_eq!(convention.stack_alignment(), 16);
+}
+
+#[test]
+fn test_win64_argument_locations() -> Result<(), AssemblerError> {
+    use ValueClass::{Float, Integer};
+
+    let function = FunctionDescription::new(CallingConvention::Win64)
+        .with_arguments(&[Float, Integer, Float, Integer, Integer, Float]);
+    let layout = FrameLayout::new(&function)?;
+
+    // Return address followed by the caller's shadow space.
+    let expected = [
+        ArgumentLocation::Xmm(XMM::XMM0),
+        ArgumentLocation::Gpr(GPR::RDX),
+        ArgumentLocation::Xmm(XMM::XMM2),
+        ArgumentLocation::Gpr(GPR::R9),
+        ArgumentLocation::Stack(rsp(40)),
+        ArgumentLocation::Stack(rsp(48)),
+    ];
+    assert_eq!(layout.arguments(), &expected);
+    Ok(())
+}
+
+#[test]
+fn test_win64_frame() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::Win64)
+        .with_clobbered(&[GPR::RSI, GPR::RDI, GPR::RAX])
+        .with_clobbered_xmm(&[XMM::XMM12, XMM::XMM2, XMM::XMM6])
+        .with_locals_size(8)
+        .with_calls();
+    let layout = FrameLayout::new(&function)?;
+    assert_eq!(layout.saved_registers(), &[GPR::RDI, GPR::RSI]);
+    assert_eq!(
+        layout.saved_xmm_registers().collect::<Vec<_>>(),
+        vec![XMM::XMM6, XMM::XMM12]
+    );
+    // Shadow space, locals, two XMM slots and padding.
+    assert_eq!(layout.stack_adjustment(), 88);
+    assert_eq!(layout.local(0)?, rsp(32));
+
+    let (prologue, epilogue) = prologue_and_epilogue(&layout)?;
+    assert_eq!(
+        prologue,
+        vec![
+            Instruction::Push { reg: GPR::RDI },
+            Instruction::Push { reg: GPR::RSI },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Sub,
+                dst: GPR::RSP,
+                imm: 88
+            },
+            Instruction::MovapsMemXmm {
+                dst: rsp(48),
+                src: XMM::XMM6
+            },
+            Instruction::MovapsMemXmm {
+                dst: rsp(64),
+                src: XMM::XMM12
+            },
+        ]
+    );
+    assert_eq!(
+        epilogue,
+        vec![
+            Instruction::MovapsXmmMem {
+                dst: XMM::XMM6,
+                src: rsp(48)
+            },
+            Instruction::MovapsXmmMem {
+                dst: XMM::XMM12,
+                src: rsp(64)
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Add,
+                dst: GPR::RSP,
+                imm: 88
+            },
+            Instruction::Pop { reg: GPR::RSI },
+            Instruction::Pop { reg: GPR::RDI },
+            Instruction::Ret,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_same_function_for_both_conventions() -> Result<(), AssemblerError> {
+    use ValueClass::{Float, Integer};
+
+    let describe = |convention| {
+        FunctionDescription::new(convention)
+            .with_arguments(&[Integer, Float, Integer])
+            .with_clobbered(&[GPR::RSI, GPR::RBX])
+            .with_clobbered_xmm(&[XMM::XMM7])
+    };
+
+    let system_v = FrameLayout::new(&describe(CallingConvention::SystemV))?;
+    assert_eq!(system_v.saved_registers(), &[GPR::RBX]);
+    assert_eq!(system_v.saved_xmm_registers().count(), 0);
+    assert_eq!(system_v.stack_adjustment(), 0);
+    assert_eq!(
+        system_v.arguments(),
+        &[
+            ArgumentLocation::Gpr(GPR::RDI),
+            ArgumentLocation::Xmm(XMM::XMM0),
+            ArgumentLocation::Gpr(GPR::RSI),
+        ]
+    );
+
+    let win64 = FrameLayout::new(&describe(CallingConvention::Win64))?;
+    assert_eq!(win64.saved_registers(), &[GPR::RBX, GPR::RSI]);
+    assert_eq!(
+        win64.saved_xmm_registers().collect::<Vec<_>>(),
+        vec![XMM::XMM7]
+    );
+    assert_eq!(win64.stack_adjustment(), 24);
+    assert_eq!(
+        win64.arguments(),
+        &[
+            ArgumentLocation::Gpr(GPR::RCX),
+            ArgumentLocation::Xmm(XMM::XMM1),
+            ArgumentLocation::Gpr(GPR::R8),
+        ]
+    );
+    Ok(())
+}
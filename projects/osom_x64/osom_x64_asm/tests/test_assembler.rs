@@ -0,0 +1,164 @@
+use osom_x64_asm::{Assembler, AssemblerError};
+use osom_x64_encoder::{
+    decoder::{decode, Decoder, Instruction},
+    encoder::misc,
+    models::{Condition, Memory, GPR, XMM},
+};
+
+#[test]
+fn test_short_and_long_jumps() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let start = asm.create_label();
+    let end = asm.create_label();
+    asm.bind_label(start)?;
+    asm.emit_jcc(Condition::NotEqual, end);
+    asm.emit_jmp(start);
+    asm.emit_bytes(&[0xCC; 200]);
+    asm.bind_label(end)?;
+    asm.emit(misc::encode_ret());
+
+    let code = asm.finish()?;
+    let bytes = code.as_slice();
+    assert_eq!(code.label_offset(start), Some(0));
+    assert_eq!(code.label_offset(end), Some(6 + 2 + 200));
+
+    let jcc = decode(bytes).unwrap();
+    assert_eq!(
+        jcc.instruction(),
+        Instruction::JccRel32 {
+            cond: Condition::NotEqual,
+            rel: 202
+        }
+    );
+    let jmp = decode(&bytes[6..]).unwrap();
+    assert_eq!(jmp.instruction(), Instruction::JmpRel8 { rel: -8 });
+    assert_eq!(bytes.last(), Some(&0xC3));
+    Ok(())
+}
+
+#[test]
+fn test_relaxation_cascades() -> Result<(), AssemblerError> {
+    // The first jump fits in rel8 only if the second one stays short,
+    // but the second one cannot.
+    let mut asm = Assembler::new();
+    let first = asm.create_label();
+    let second = asm.create_label();
+    asm.emit_jmp(first);
+    asm.emit_bytes(&[0x90; 123]);
+    asm.emit_jmp(second);
+    asm.bind_label(first)?;
+    asm.emit_bytes(&[0x90; 128]);
+    asm.bind_label(second)?;
+
+    let code = asm.finish()?;
+    let decoded: Vec<_> = Decoder::new(code.as_slice())
+        .map(|item| item.unwrap().1.instruction())
+        .filter(|instruction| !matches!(instruction, Instruction::Nop { .. }))
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            Instruction::JmpRel32 { rel: 128 },
+            Instruction::JmpRel32 { rel: 128 }
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unbound_and_rebound_labels() {
+    let mut asm = Assembler::new();
+    let label = asm.create_label();
+    asm.bind_label(label).unwrap();
+    assert_eq!(
+        asm.bind_label(label),
+        Err(AssemblerError::LabelAlreadyBound(label))
+    );
+
+    let unbound = asm.create_label();
+    asm.emit_jmp(unbound);
+    assert!(matches!(
+        asm.finish(),
+        Err(AssemblerError::UnboundLabel(l)) if l == unbound
+    ));
+}
+
+#[test]
+fn test_data_directives() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    asm.emit_db(0x01);
+    asm.emit_dw(0x0302);
+    asm.emit_dd(0x0706_0504);
+    asm.emit_dq(0x0F0E_0D0C_0B0A_0908);
+    asm.emit_bytes(b"osom");
+    asm.emit_f32(1.0);
+    asm.emit_f64(-2.0);
+
+    let code = asm.finish()?;
+    let mut expected: Vec<u8> = (1..16).collect();
+    expected.extend_from_slice(b"osom");
+    expected.extend_from_slice(&1.0f32.to_le_bytes());
+    expected.extend_from_slice(&(-2.0f64).to_le_bytes());
+    assert_eq!(code.as_slice(), expected.as_slice());
+    Ok(())
+}
+
+#[test]
+fn test_align_with_nops() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    asm.emit_db(0xCC);
+    asm.emit_align(32)?;
+    let label = asm.create_label();
+    asm.bind_label(label)?;
+    asm.emit(misc::encode_ret());
+    assert_eq!(asm.emit_align(3), Err(AssemblerError::InvalidAlignment));
+
+    let code = asm.finish()?;
+    assert_eq!(code.label_offset(label), Some(32));
+    let mut nops = Decoder::new(&code.as_slice()[1..32]).map(|item| item.unwrap().1);
+    assert!(nops.all(|nop| matches!(nop.instruction(), Instruction::Nop { .. })));
+    Ok(())
+}
+
+#[test]
+fn test_constant_pool() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let pi = asm.constant_f64(core::f64::consts::PI);
+    let one = asm.constant_f32(1.0);
+    let mask = asm.constant_bytes(&[0xFF; 16], 16)?;
+    assert_eq!(asm.constant_f64(core::f64::consts::PI), pi);
+
+    asm.emit_movsd_label(XMM::XMM0, pi);
+    asm.emit_movss_label(XMM::XMM1, one);
+    asm.emit_lea_label(GPR::RAX, mask)?;
+    asm.emit(misc::encode_ret());
+
+    let code = asm.finish()?;
+    let bytes = code.as_slice();
+    assert_eq!(code.code().len(), 8 + 8 + 7 + 1);
+    assert_eq!(code.label_offset(pi), Some(32));
+    assert_eq!(code.label_offset(one), Some(40));
+    assert_eq!(code.label_offset(mask), Some(48));
+    assert_eq!(&bytes[32..40], &core::f64::consts::PI.to_le_bytes());
+    assert_eq!(&bytes[40..44], &1.0f32.to_le_bytes());
+    assert_eq!(&bytes[48..64], &[0xFF; 16]);
+    assert!(bytes[24..32].iter().all(|byte| *byte == 0));
+
+    let movsd = decode(bytes).unwrap();
+    assert_eq!(
+        movsd.instruction(),
+        Instruction::MovsdXmmMem {
+            dst: XMM::XMM0,
+            src: Memory::rip_relative(32 - 8).unwrap()
+        }
+    );
+    let lea = decode(&bytes[16..]).unwrap();
+    assert_eq!(
+        lea.instruction(),
+        Instruction::Lea {
+            dst: GPR::RAX,
+            src: Memory::rip_relative(48 - 23).unwrap()
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use osom_x64_asm::{AssembledCode, Assembler, AssemblerError, SectionKind};
+use osom_x64_encoder::{
+    encoder::{bitcount, errors::EncodingError, misc, EncodedInstruction},
+    models::{BitCountOp, CpuFeature, CpuFeatures, CpuRequirement, GPR, XMM},
+};
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use osom_x64_asm::ExecutableCode;
+
+/// Assembles `tzcnt rax, rdi; lzcnt rax, rax; ret`, restricted to `features`
+/// if given.
+fn build(features: Option<CpuFeatures>) -> Result<AssembledCode, AssemblerError> {
+    let mut asm = Assembler::new();
+    if let Some(features) = features {
+        asm.set_cpu_features(features)?;
+    }
+    asm.emit(bitcount::encode_bit_count_reg_reg(
+        BitCountOp::Tzcnt,
+        GPR::RAX,
+        GPR::RDI,
+    )?);
+    asm.emit(bitcount::encode_bit_count_reg_reg(
+        BitCountOp::Lzcnt,
+        GPR::RAX,
+        GPR::RAX,
+    )?);
+    asm.emit(misc::encode_ret());
+    asm.finish()
+}
+
+#[test]
+fn test_unrestricted_by_default() {
+    assert!(build(None).is_ok());
+    assert!(build(Some(CpuFeatures::ALL)).is_ok());
+}
+
+#[test]
+fn test_missing_feature() {
+    let missing = |feature| {
+        Err(AssemblerError::Encoding(
+            EncodingError::UnsupportedCpuFeature(feature),
+        ))
+    };
+
+    assert_eq!(
+        build(Some(CpuFeatures::BASELINE)).map(|_| ()),
+        missing(CpuFeature::Bmi1)
+    );
+    let features = CpuFeatures::BASELINE.with(CpuFeature::Bmi1);
+    assert_eq!(
+        build(Some(features)).map(|_| ()),
+        missing(CpuFeature::Lzcnt)
+    );
+    assert!(build(Some(features.with(CpuFeature::Lzcnt))).is_ok());
+
+    let mut asm = Assembler::new();
+    asm.set_cpu_features(CpuFeatures::NONE).unwrap();
+    let constant = asm.constant_f64(1.0);
+    asm.emit_movsd_label(XMM::XMM0, constant);
+    asm.emit(misc::encode_ret());
+    assert_eq!(asm.finish().map(|_| ()), missing(CpuFeature::Sse2));
+}
+
+/// Returns `shlx rax, rdi, rsi` (BMI2), built from raw bytes.
+fn shlx() -> EncodedInstruction {
+    unsafe { EncodedInstruction::from_array_unchecked([0xC4, 0xE2, 0xC9, 0xF7, 0xC7]) }
+}
+
+#[test]
+fn test_unknown_requirement() {
+    let mut asm = Assembler::new();
+    asm.set_cpu_features(CpuFeatures::ALL).unwrap();
+    assert_eq!(
+        asm.try_emit(shlx()),
+        Err(AssemblerError::UnknownCpuRequirement)
+    );
+    asm.emit(misc::encode_ret());
+    assert!(asm.finish().is_ok());
+
+    let mut asm = Assembler::new();
+    asm.set_cpu_features(CpuFeatures::ALL).unwrap();
+    asm.emit(shlx());
+    asm.emit(misc::encode_ret());
+    assert_eq!(
+        asm.finish().map(|_| ()),
+        Err(AssemblerError::UnknownCpuRequirement)
+    );
+
+    let mut asm = Assembler::new();
+    asm.set_cpu_features(CpuFeatures::ALL).unwrap();
+    asm.emit_bytes(&[0x90]);
+    assert_eq!(
+        asm.finish().map(|_| ()),
+        Err(AssemblerError::UnknownCpuRequirement)
+    );
+
+    let mut asm = Assembler::new();
+    let data = asm.create_section(".data", SectionKind::Data, 8).unwrap();
+    asm.set_cpu_features(CpuFeatures::BASELINE).unwrap();
+    asm.switch_section(data);
+    asm.emit_dq(1);
+    assert!(asm.finish().is_ok());
+}
+
+#[test]
+fn test_classified_raw_instruction() {
+    let bmi2 = || shlx().with_cpu_requirement(CpuRequirement::Feature(CpuFeature::Bmi2));
+
+    let mut asm = Assembler::new();
+    asm.set_cpu_features(CpuFeatures::BASELINE).unwrap();
+    assert_eq!(
+        asm.try_emit(bmi2()),
+        Err(AssemblerError::Encoding(
+            EncodingError::UnsupportedCpuFeature(CpuFeature::Bmi2)
+        ))
+    );
+    asm.set_cpu_features(CpuFeatures::BASELINE.with(CpuFeature::Bmi2))
+        .unwrap();
+    assert_eq!(asm.try_emit(bmi2()), Ok(()));
+    asm.emit(misc::encode_ret());
+    assert!(asm.finish().is_ok());
+}
+
+#[test]
+fn test_emitted_before_restriction() {
+    let popcnt = || {
+        let mut asm = Assembler::new();
+        asm.emit(
+            bitcount::encode_bit_count_reg_reg(BitCountOp::Popcnt, GPR::RAX, GPR::RDI)
+                .unwrap(),
+        );
+        asm.emit(misc::encode_ret());
+        asm
+    };
+    let missing = Err(AssemblerError::Encoding(
+        EncodingError::UnsupportedCpuFeature(CpuFeature::Popcnt),
+    ));
+    let mut asm = popcnt();
+    assert_eq!(asm.set_cpu_features(CpuFeatures::BASELINE), missing);
+    assert_eq!(asm.finish().map(|_| ()), missing);
+
+    // Widening the restriction drops errors of the previous one.
+    let mut asm = popcnt();
+    assert_eq!(asm.set_cpu_features(CpuFeatures::BASELINE), missing);
+    assert_eq!(asm.set_cpu_features(CpuFeatures::ALL), Ok(()));
+    assert!(asm.finish().is_ok());
+
+    let mut asm = Assembler::new();
+    asm.emit(shlx());
+    assert_eq!(
+        asm.set_cpu_features(CpuFeatures::ALL),
+        Err(AssemblerError::UnknownCpuRequirement)
+    );
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_detected_features() {
+    let features = CpuFeatures::detect();
+    let result = build(Some(features));
+    if !features.contains(CpuFeature::Bmi1) || !features.contains(CpuFeature::Lzcnt) {
+        assert!(result.is_err());
+        return;
+    }
+
+    let code = result.unwrap();
+    let executable = ExecutableCode::new(&code).unwrap();
+    let entry: extern "sysv64" fn(u64) -> u64 = unsafe { executable.entry() };
+    assert_eq!(entry(1), 64);
+    assert_eq!(entry(1 << 40), 58);
+    assert_eq!(entry(0), 57);
+}
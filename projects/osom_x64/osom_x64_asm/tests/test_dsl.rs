@@ -0,0 +1,188 @@
+use osom_x64_asm::{x64, Assembler, AssemblerError};
+use osom_x64_encoder::{
+    decoder::{Decoder, Instruction},
+    encoder::errors::EncodingError,
+    models::{ArithmeticOp, Condition, Displacement, Memory, Scale, GPR, XMM},
+};
+
+fn decode_all(bytes: &[u8]) -> Vec<Instruction> {
+    Decoder::new(bytes)
+        .map(|item| item.unwrap().1.instruction())
+        .collect()
+}
+
+#[test]
+fn test_dsl_instructions() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    x64!(asm =>
+        mov rax, rbx;
+        mov ecx, -1;
+        mov r9, [rdi + rsi * 8 + 16];
+        mov [rbp - 8], r9;
+        lea rdx, [r12];
+        add rax, 8;
+        sub rsp, 0x1000;
+        xor eax, eax;
+        cmp cl, [rax];
+        and [rsp + 4], r15d;
+        movsd xmm0, [rsp];
+        movss [rax + rbx], xmm9;
+        movsd xmm1, xmm2;
+        nop;
+        ret;
+    )?;
+
+    let code = asm.finish()?;
+    let rsp = |disp| Memory::based(GPR::RSP, disp).unwrap();
+    assert_eq!(
+        decode_all(code.as_slice()),
+        vec![
+            Instruction::MovRegReg {
+                dst: GPR::RAX,
+                src: GPR::RBX
+            },
+            Instruction::MovRegImm {
+                dst: GPR::ECX,
+                imm: -1
+            },
+            Instruction::MovRegMem {
+                dst: GPR::R9,
+                src: Memory::new(
+                    GPR::RDI,
+                    GPR::RSI,
+                    Scale::Scale8,
+                    Displacement::new(16)
+                )
+                .unwrap()
+            },
+            Instruction::MovMemReg {
+                dst: Memory::based(GPR::RBP, -8).unwrap(),
+                src: GPR::R9
+            },
+            Instruction::Lea {
+                dst: GPR::RDX,
+                src: Memory::based(GPR::R12, 0).unwrap()
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Add,
+                dst: GPR::RAX,
+                imm: 8
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Sub,
+                dst: GPR::RSP,
+                imm: 0x1000
+            },
+            Instruction::ArithRegReg {
+                op: ArithmeticOp::Xor,
+                dst: GPR::EAX,
+                src: GPR::EAX
+            },
+            Instruction::ArithRegMem {
+                op: ArithmeticOp::Cmp,
+                dst: GPR::CL,
+                src: Memory::based(GPR::RAX, 0).unwrap()
+            },
+            Instruction::ArithMemReg {
+                op: ArithmeticOp::And,
+                dst: rsp(4),
+                src: GPR::R15D
+            },
+            Instruction::MovsdXmmMem {
+                dst: XMM::XMM0,
+                src: rsp(0)
+            },
+            Instruction::MovssMemXmm {
+                dst: Memory::new(GPR::RAX, GPR::RBX, Scale::Scale1, Displacement::ZERO)
+                    .unwrap(),
+                src: XMM::XMM9
+            },
+            Instruction::MovsdXmmXmm {
+                dst: XMM::XMM1,
+                src: XMM::XMM2
+            },
+            Instruction::Nop { size: 1 },
+            Instruction::Ret,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_dsl_labels() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let top = asm.create_label();
+    let done = asm.create_label();
+    let constant = asm.constant_u64(42);
+    x64!(asm =>
+    top:
+        mov rax, [rip + constant];
+        cmp rax, 0;
+        je done;
+        jcc Condition::Less, top;
+        jmp top;
+    done:
+        ret;
+    )?;
+
+    let code = asm.finish()?;
+    assert_eq!(code.label_offset(top), Some(0));
+    assert_eq!(code.label_offset(constant), Some(32));
+    assert_eq!(
+        decode_all(code.code()),
+        vec![
+            Instruction::MovRegMem {
+                dst: GPR::RAX,
+                src: Memory::rip_relative(32 - 7).unwrap()
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Cmp,
+                dst: GPR::RAX,
+                imm: 0
+            },
+            Instruction::JccRel8 {
+                cond: Condition::Equal,
+                rel: 4
+            },
+            Instruction::JccRel8 {
+                cond: Condition::Less,
+                rel: -15
+            },
+            Instruction::JmpRel8 { rel: -17 },
+            Instruction::Ret,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_dsl_errors() {
+    let mut asm = Assembler::new();
+    let result = x64!(asm => mov rax, ebx;);
+    assert_eq!(
+        result,
+        Err(AssemblerError::Encoding(
+            EncodingError::RegistersSizeMismatch
+        ))
+    );
+
+    let result = x64!(asm => add al, 256;);
+    assert_eq!(
+        result,
+        Err(AssemblerError::Encoding(EncodingError::ArgumentOutOfRange))
+    );
+
+    let result = x64!(asm => lea rax, [rax + rcx * 3];);
+    assert_eq!(
+        result,
+        Err(AssemblerError::Encoding(
+            EncodingError::InvalidMemoryOperand
+        ))
+    );
+
+    let label = asm.create_label();
+    let result = x64!(asm => ret; ret;);
+    assert_eq!(result, Ok(()));
+    let result = x64!(asm => label: ret; label: ret;);
+    assert_eq!(result, Err(AssemblerError::LabelAlreadyBound(label)));
+}
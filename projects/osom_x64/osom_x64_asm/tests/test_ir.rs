@@ -0,0 +1,673 @@
+use osom_x64_asm::{
+    abi::{CallingConvention, ValueClass},
+    ir::{IrFunction, IrInstruction, IrLabel, Location, VReg},
+    AssembledCode, Assembler, AssemblerError,
+};
+use osom_x64_encoder::models::{
+    ArithmeticOp, Condition, FloatArithmeticOp, ShiftOp, GPR,
+};
+use rand::Rng;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use osom_x64_asm::ExecutableCode;
+
+/// Reference semantics of the IR.
+mod interpreter {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug)]
+    pub enum Value {
+        Integer(i64),
+        Float(f64),
+    }
+
+    fn condition_holds(cond: Condition, lhs: i64, rhs: i64) -> bool {
+        let (ulhs, urhs) = (lhs as u64, rhs as u64);
+        match cond {
+            Condition::Equal | Condition::Zero => lhs == rhs,
+            Condition::NotEqual | Condition::NotZero => lhs != rhs,
+            Condition::Less => lhs < rhs,
+            Condition::LessOrEqual => lhs <= rhs,
+            Condition::Greater => lhs > rhs,
+            Condition::GreaterOrEqual => lhs >= rhs,
+            Condition::Below | Condition::Carry => ulhs < urhs,
+            Condition::BelowOrEqual => ulhs <= urhs,
+            Condition::Above => ulhs > urhs,
+            Condition::AboveOrEqual | Condition::NotCarry => ulhs >= urhs,
+            other => panic!("{other:?} is not supported by the interpreter"),
+        }
+    }
+
+    fn shift(op: ShiftOp, value: i64, amount: i64) -> i64 {
+        let amount = (amount & 63) as u32;
+        match op {
+            ShiftOp::Rol => value.rotate_left(amount),
+            ShiftOp::Ror => value.rotate_right(amount),
+            ShiftOp::Shl => value.wrapping_shl(amount),
+            ShiftOp::Shr => ((value as u64) >> amount) as i64,
+            ShiftOp::Sar => value >> amount,
+        }
+    }
+
+    /// Runs `function` with `arguments`, returning its result.
+    pub fn run(function: &IrFunction, arguments: &[Value]) -> Option<Value> {
+        let instructions = function.instructions();
+        let mut labels = vec![0; function.label_count() as usize];
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let IrInstruction::Bind(label) = instruction {
+                labels[label.index() as usize] = index;
+            }
+        }
+
+        let mut values = vec![Value::Integer(0); function.vreg_count()];
+        for (reg, value) in function.arguments().iter().zip(arguments) {
+            values[reg.index() as usize] = *value;
+        }
+        let int = |values: &[Value], reg: VReg| match values[reg.index() as usize] {
+            Value::Integer(value) => value,
+            Value::Float(_) => panic!("{reg:?} is not an integer"),
+        };
+        let float = |values: &[Value], reg: VReg| match values[reg.index() as usize] {
+            Value::Float(value) => value,
+            Value::Integer(_) => panic!("{reg:?} is not a float"),
+        };
+
+        let mut pc = 0;
+        loop {
+            let instruction = instructions[pc];
+            pc += 1;
+            let result = match instruction {
+                IrInstruction::MovImm { imm, .. } => Value::Integer(imm),
+                IrInstruction::MovF64 { value, .. } => Value::Float(value),
+                IrInstruction::Mov { src, .. } => values[src.index() as usize],
+                IrInstruction::Arith { op, lhs, rhs, .. } => {
+                    let (lhs, rhs) = (int(&values, lhs), int(&values, rhs));
+                    Value::Integer(match op {
+                        ArithmeticOp::Add => lhs.wrapping_add(rhs),
+                        ArithmeticOp::Sub => lhs.wrapping_sub(rhs),
+                        ArithmeticOp::And => lhs & rhs,
+                        ArithmeticOp::Or => lhs | rhs,
+                        ArithmeticOp::Xor => lhs ^ rhs,
+                        other => panic!("{other:?} is not supported"),
+                    })
+                }
+                IrInstruction::Imul { lhs, rhs, .. } => {
+                    Value::Integer(int(&values, lhs).wrapping_mul(int(&values, rhs)))
+                }
+                IrInstruction::Div { lhs, rhs, .. } => {
+                    Value::Integer(int(&values, lhs) / int(&values, rhs))
+                }
+                IrInstruction::Rem { lhs, rhs, .. } => {
+                    Value::Integer(int(&values, lhs) % int(&values, rhs))
+                }
+                IrInstruction::Shift {
+                    op, lhs, amount, ..
+                } => Value::Integer(shift(op, int(&values, lhs), int(&values, amount))),
+                IrInstruction::Load { dst, base, offset } => {
+                    let address = int(&values, base) + i64::from(offset);
+                    let bits = unsafe { (address as *const u64).read_unaligned() };
+                    match dst.class() {
+                        ValueClass::Integer => Value::Integer(bits as i64),
+                        ValueClass::Float => Value::Float(f64::from_bits(bits)),
+                    }
+                }
+                IrInstruction::Store { base, offset, src } => {
+                    let address = int(&values, base) + i64::from(offset);
+                    let bits = match values[src.index() as usize] {
+                        Value::Integer(value) => value as u64,
+                        Value::Float(value) => value.to_bits(),
+                    };
+                    unsafe { (address as *mut u64).write_unaligned(bits) };
+                    continue;
+                }
+                IrInstruction::FloatArith { op, lhs, rhs, .. } => {
+                    let (lhs, rhs) = (float(&values, lhs), float(&values, rhs));
+                    Value::Float(match op {
+                        FloatArithmeticOp::Addsd => lhs + rhs,
+                        FloatArithmeticOp::Subsd => lhs - rhs,
+                        FloatArithmeticOp::Mulsd => lhs * rhs,
+                        FloatArithmeticOp::Divsd => lhs / rhs,
+                        other => panic!("{other:?} is not supported"),
+                    })
+                }
+                IrInstruction::Bind(_) => continue,
+                IrInstruction::Jmp(target) => {
+                    pc = labels[target.index() as usize];
+                    continue;
+                }
+                IrInstruction::Branch {
+                    cond,
+                    lhs,
+                    rhs,
+                    target,
+                } => {
+                    if condition_holds(cond, int(&values, lhs), int(&values, rhs)) {
+                        pc = labels[target.index() as usize];
+                    }
+                    continue;
+                }
+                IrInstruction::Ret { value } => {
+                    return value.map(|reg| values[reg.index() as usize]);
+                }
+            };
+            values[instruction.defined().unwrap().index() as usize] = result;
+        }
+    }
+}
+
+use interpreter::Value;
+
+fn compile(function: &IrFunction) -> Result<AssembledCode, AssemblerError> {
+    let mut asm = Assembler::new();
+    function.compile(&mut asm)?;
+    asm.finish()
+}
+
+/// Small helper for building functions in tests.
+struct Builder {
+    function: IrFunction,
+}
+
+impl Builder {
+    fn new(convention: CallingConvention) -> Self {
+        Self {
+            function: IrFunction::new(convention),
+        }
+    }
+
+    fn int(&mut self) -> VReg {
+        self.function.create_vreg(ValueClass::Integer)
+    }
+
+    fn float(&mut self) -> VReg {
+        self.function.create_vreg(ValueClass::Float)
+    }
+
+    fn imm(&mut self, imm: i64) -> VReg {
+        let dst = self.int();
+        self.function.push(IrInstruction::MovImm { dst, imm });
+        dst
+    }
+
+    fn arith(&mut self, op: ArithmeticOp, lhs: VReg, rhs: VReg) -> VReg {
+        let dst = self.int();
+        self.function
+            .push(IrInstruction::Arith { op, dst, lhs, rhs });
+        dst
+    }
+
+    fn label(&mut self) -> IrLabel {
+        self.function.create_label()
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn run_int(function: &IrFunction, arguments: &[i64]) -> i64 {
+    let code = ExecutableCode::new(&compile(function).unwrap()).unwrap();
+    let expected = interpreter::run(
+        function,
+        &arguments
+            .iter()
+            .map(|v| Value::Integer(*v))
+            .collect::<Vec<_>>(),
+    );
+    let Some(Value::Integer(expected)) = expected else {
+        panic!("Function does not return an integer");
+    };
+
+    let actual = unsafe {
+        match (function.convention(), arguments) {
+            (CallingConvention::SystemV, []) => code.entry::<extern "C" fn() -> i64>()(),
+            (CallingConvention::SystemV, [a]) => {
+                code.entry::<extern "C" fn(i64) -> i64>()(*a)
+            }
+            (CallingConvention::SystemV, [a, b]) => {
+                code.entry::<extern "C" fn(i64, i64) -> i64>()(*a, *b)
+            }
+            (CallingConvention::SystemV, [a, b, c]) => {
+                code.entry::<extern "C" fn(i64, i64, i64) -> i64>()(*a, *b, *c)
+            }
+            (CallingConvention::SystemV, [a, b, c, d, e, f, g, h]) => code
+                .entry::<extern "C" fn(i64, i64, i64, i64, i64, i64, i64, i64) -> i64>()(
+                *a, *b, *c, *d, *e, *f, *g, *h,
+            ),
+            (CallingConvention::Win64, [a]) => {
+                code.entry::<extern "win64" fn(i64) -> i64>()(*a)
+            }
+            (CallingConvention::Win64, [a, b]) => {
+                code.entry::<extern "win64" fn(i64, i64) -> i64>()(*a, *b)
+            }
+            (CallingConvention::Win64, [a, b, c, d, e, f]) => code
+                .entry::<extern "win64" fn(i64, i64, i64, i64, i64, i64) -> i64>()(
+                *a, *b, *c, *d, *e, *f,
+            ),
+            _ => panic!("Unsupported signature"),
+        }
+    };
+    assert_eq!(actual, expected, "arguments: {arguments:?}");
+    actual
+}
+
+fn sum_to_n(convention: CallingConvention) -> IrFunction {
+    // sum = 0; i = 1; while i <= n { sum += i; i += 1 } return sum
+    let mut b = Builder::new(convention);
+    let n = b.function.add_argument(ValueClass::Integer);
+    let sum = b.imm(0);
+    let i = b.imm(1);
+    let one = b.imm(1);
+    let head = b.label();
+    let exit = b.label();
+    b.function.bind(head);
+    b.function.push(IrInstruction::Branch {
+        cond: Condition::Greater,
+        lhs: i,
+        rhs: n,
+        target: exit,
+    });
+    b.function.push(IrInstruction::Arith {
+        op: ArithmeticOp::Add,
+        dst: sum,
+        lhs: sum,
+        rhs: i,
+    });
+    b.function.push(IrInstruction::Arith {
+        op: ArithmeticOp::Add,
+        dst: i,
+        lhs: i,
+        rhs: one,
+    });
+    b.function.push(IrInstruction::Jmp(head));
+    b.function.bind(exit);
+    b.function.push(IrInstruction::Ret { value: Some(sum) });
+    b.function
+}
+
+#[test]
+fn test_loop_interval_covers_back_edge() -> Result<(), AssemblerError> {
+    let function = sum_to_n(CallingConvention::SystemV);
+    let intervals = function.live_intervals()?;
+    let n = function.arguments()[0];
+    let interval = intervals.iter().find(|i| i.vreg == n).unwrap();
+    // Used only by the branch at the loop head, but needed until the back edge,
+    // i.e. the `jmp` at index 7.
+    assert_eq!(interval.start, 0);
+    assert_eq!(interval.end, 8);
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_loop_matches_interpreter() {
+    for convention in [CallingConvention::SystemV, CallingConvention::Win64] {
+        let function = sum_to_n(convention);
+        for n in [0, 1, 10, 1000] {
+            assert_eq!(run_int(&function, &[n]), n * (n + 1) / 2);
+        }
+    }
+}
+
+fn division_with_live_values(convention: CallingConvention) -> IrFunction {
+    // Values live across `idiv` and shifts must not sit in RAX, RDX or RCX.
+    let mut b = Builder::new(convention);
+    let x = b.function.add_argument(ValueClass::Integer);
+    let y = b.function.add_argument(ValueClass::Integer);
+    let live: Vec<VReg> = (0..6).map(|i| b.imm(i * 7 + 3)).collect();
+    let quotient = b.int();
+    b.function.push(IrInstruction::Div {
+        dst: quotient,
+        lhs: x,
+        rhs: y,
+    });
+    let remainder = b.int();
+    b.function.push(IrInstruction::Rem {
+        dst: remainder,
+        lhs: x,
+        rhs: y,
+    });
+    let shifted = b.int();
+    b.function.push(IrInstruction::Shift {
+        op: ShiftOp::Shl,
+        dst: shifted,
+        lhs: quotient,
+        amount: live[1],
+    });
+    let mut result = b.arith(ArithmeticOp::Xor, shifted, remainder);
+    for reg in live {
+        result = b.arith(ArithmeticOp::Add, result, reg);
+    }
+    b.function.push(IrInstruction::Ret {
+        value: Some(result),
+    });
+    b.function
+}
+
+#[test]
+fn test_fixed_register_constraints() -> Result<(), AssemblerError> {
+    let function = division_with_live_values(CallingConvention::SystemV);
+    let allocation = function.allocate()?;
+    for interval in function.live_intervals()? {
+        let Some(Location::Gpr(reg)) = allocation.location(interval.vreg) else {
+            continue;
+        };
+        // The division is the 7th instruction, the shift the 9th.
+        if interval.contains(7) || interval.contains(8) {
+            assert!(
+                reg != GPR::RAX && reg != GPR::RDX,
+                "{interval:?} in {reg:?}"
+            );
+        }
+        if interval.contains(9) {
+            assert!(reg != GPR::RCX, "{interval:?} in {reg:?}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_division_matches_interpreter() {
+    let function = division_with_live_values(CallingConvention::SystemV);
+    for (x, y) in [(100, 7), (-100, 7), (100, -7), (i64::MAX, 3), (0, 1)] {
+        run_int(&function, &[x, y]);
+    }
+    let function = division_with_live_values(CallingConvention::Win64);
+    for (x, y) in [(100, 7), (-100, 7), (12345, -1000)] {
+        run_int(&function, &[x, y]);
+    }
+}
+
+fn register_pressure(values: i64) -> IrFunction {
+    // All values stay live until the end, which forces spills.
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let seed = b.function.add_argument(ValueClass::Integer);
+    let mut live = Vec::new();
+    for i in 0..values {
+        let constant = b.imm(i * 0x1_0001 + 1);
+        let value = b.int();
+        b.function.push(IrInstruction::Imul {
+            dst: value,
+            lhs: seed,
+            rhs: constant,
+        });
+        live.push(value);
+    }
+    let mut result = b.imm(0);
+    for (index, reg) in live.into_iter().enumerate() {
+        let op = if index % 2 == 0 {
+            ArithmeticOp::Add
+        } else {
+            ArithmeticOp::Xor
+        };
+        result = b.arith(op, result, reg);
+    }
+    b.function.push(IrInstruction::Ret {
+        value: Some(result),
+    });
+    b.function
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_spills_match_interpreter() -> Result<(), AssemblerError> {
+    let function = register_pressure(40);
+    let allocation = function.allocate()?;
+    assert!(allocation.spill_slot_count() > 0);
+    for seed in [0, 1, -3, 0x1234_5678_9ABC] {
+        run_int(&function, &[seed]);
+    }
+
+    let function = register_pressure(5);
+    assert_eq!(function.allocate()?.spill_slot_count(), 0);
+    run_int(&function, &[17]);
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_stack_arguments_match_interpreter() {
+    // Sums arguments with alternating signs, to detect any mix-up.
+    let build = |convention, count| {
+        let mut b = Builder::new(convention);
+        let arguments: Vec<VReg> = (0..count)
+            .map(|_| b.function.add_argument(ValueClass::Integer))
+            .collect();
+        let mut result = b.imm(0);
+        for (index, reg) in arguments.into_iter().enumerate() {
+            let op = if index % 2 == 0 {
+                ArithmeticOp::Add
+            } else {
+                ArithmeticOp::Sub
+            };
+            let scale = b.imm(i64::try_from(index).unwrap() + 1);
+            let scaled = b.int();
+            b.function.push(IrInstruction::Imul {
+                dst: scaled,
+                lhs: reg,
+                rhs: scale,
+            });
+            result = b.arith(op, result, scaled);
+        }
+        b.function.push(IrInstruction::Ret {
+            value: Some(result),
+        });
+        b.function
+    };
+
+    let function = build(CallingConvention::SystemV, 8);
+    run_int(
+        &function,
+        &[1, 20, 300, 4000, 50_000, 600_000, 7_000_000, 80_000_000],
+    );
+    let function = build(CallingConvention::Win64, 6);
+    run_int(&function, &[1, 20, 300, 4000, 50_000, 600_000]);
+    let function = build(CallingConvention::Win64, 2);
+    run_int(&function, &[-5, 9]);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_memory_and_floats_match_interpreter() {
+    // buffer[2] = buffer[0] * buffer[1] + 0.5 - buffer[0] / 4.0; return buffer[2]
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let buffer = b.function.add_argument(ValueClass::Integer);
+    let [x, y, product, sum, quarter, result] = [(); 6].map(|()| b.float());
+    let half = b.float();
+    let four = b.float();
+    let code = [
+        IrInstruction::Load {
+            dst: x,
+            base: buffer,
+            offset: 0,
+        },
+        IrInstruction::Load {
+            dst: y,
+            base: buffer,
+            offset: 8,
+        },
+        IrInstruction::MovF64 {
+            dst: half,
+            value: 0.5,
+        },
+        IrInstruction::MovF64 {
+            dst: four,
+            value: 4.0,
+        },
+        IrInstruction::FloatArith {
+            op: FloatArithmeticOp::Mulsd,
+            dst: product,
+            lhs: x,
+            rhs: y,
+        },
+        IrInstruction::FloatArith {
+            op: FloatArithmeticOp::Addsd,
+            dst: sum,
+            lhs: product,
+            rhs: half,
+        },
+        IrInstruction::FloatArith {
+            op: FloatArithmeticOp::Divsd,
+            dst: quarter,
+            lhs: x,
+            rhs: four,
+        },
+        IrInstruction::FloatArith {
+            op: FloatArithmeticOp::Subsd,
+            dst: result,
+            lhs: sum,
+            rhs: quarter,
+        },
+        IrInstruction::Store {
+            base: buffer,
+            offset: 16,
+            src: result,
+        },
+        IrInstruction::Ret {
+            value: Some(result),
+        },
+    ];
+    for instruction in code {
+        b.function.push(instruction);
+    }
+    let function = b.function;
+
+    let mut expected_buffer = [3.0_f64, -1.25, 0.0];
+    let expected = interpreter::run(
+        &function,
+        &[Value::Integer(expected_buffer.as_mut_ptr() as i64)],
+    );
+    let Some(Value::Float(expected)) = expected else {
+        panic!("Function does not return a float");
+    };
+
+    let code = ExecutableCode::new(&compile(&function).unwrap()).unwrap();
+    let mut buffer = [3.0_f64, -1.25, 0.0];
+    let actual =
+        unsafe { code.entry::<extern "C" fn(*mut f64) -> f64>()(buffer.as_mut_ptr()) };
+    assert_eq!(actual.to_bits(), expected.to_bits());
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(actual, 3.0 * -1.25 + 0.5 - 0.75);
+}
+
+/// Generates a random straight-line function of `length` operations over three
+/// arguments. Values are picked at random, so many of them stay live long.
+fn random_function(rng: &mut impl Rng, length: usize) -> IrFunction {
+    const OPS: [ArithmeticOp; 5] = [
+        ArithmeticOp::Add,
+        ArithmeticOp::Or,
+        ArithmeticOp::And,
+        ArithmeticOp::Sub,
+        ArithmeticOp::Xor,
+    ];
+    const SHIFTS: [ShiftOp; 5] = [
+        ShiftOp::Rol,
+        ShiftOp::Ror,
+        ShiftOp::Shl,
+        ShiftOp::Shr,
+        ShiftOp::Sar,
+    ];
+
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let mut values: Vec<VReg> = (0..3)
+        .map(|_| b.function.add_argument(ValueClass::Integer))
+        .collect();
+    for _ in 0..length {
+        let lhs = values[rng.gen_range(0..values.len())];
+        let rhs = values[rng.gen_range(0..values.len())];
+        let dst = b.int();
+        let instruction = match rng.gen_range(0..5) {
+            0 => IrInstruction::Arith {
+                op: OPS[rng.gen_range(0..OPS.len())],
+                dst,
+                lhs,
+                rhs,
+            },
+            1 => IrInstruction::Imul { dst, lhs, rhs },
+            2 => IrInstruction::Shift {
+                op: SHIFTS[rng.gen_range(0..SHIFTS.len())],
+                dst,
+                lhs,
+                amount: rhs,
+            },
+            3 => {
+                // Positive divisors below 256 avoid traps.
+                let mask = b.imm(0xFF);
+                let masked = b.arith(ArithmeticOp::And, rhs, mask);
+                let one = b.imm(1);
+                let divisor = b.arith(ArithmeticOp::Or, masked, one);
+                if rng.gen_bool(0.5) {
+                    IrInstruction::Div {
+                        dst,
+                        lhs,
+                        rhs: divisor,
+                    }
+                } else {
+                    IrInstruction::Rem {
+                        dst,
+                        lhs,
+                        rhs: divisor,
+                    }
+                }
+            }
+            _ => IrInstruction::MovImm {
+                dst,
+                imm: rng.gen(),
+            },
+        };
+        b.function.push(instruction);
+        values.push(dst);
+    }
+
+    // Keep a random subset of values live until the end.
+    let mut result = *values.last().unwrap();
+    for _ in 0..length / 2 {
+        let reg = values[rng.gen_range(0..values.len())];
+        result = b.arith(ArithmeticOp::Xor, result, reg);
+    }
+    b.function.push(IrInstruction::Ret {
+        value: Some(result),
+    });
+    b.function
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_random_functions_match_interpreter() {
+    let mut rng = rand::thread_rng();
+    for length in [1, 5, 20, 60, 150] {
+        for _ in 0..20 {
+            let function = random_function(&mut rng, length);
+            let arguments: [i64; 3] = rng.gen();
+            run_int(&function, &arguments);
+        }
+    }
+}
+
+#[test]
+fn test_invalid_ir() {
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let x = b.int();
+    let f = b.float();
+    b.function.push(IrInstruction::Mov { dst: x, src: f });
+    b.function.push(IrInstruction::Ret { value: None });
+    assert_eq!(b.function.allocate(), Err(AssemblerError::InvalidIr));
+
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let x = b.imm(1);
+    b.function.push(IrInstruction::Arith {
+        op: ArithmeticOp::Cmp,
+        dst: x,
+        lhs: x,
+        rhs: x,
+    });
+    b.function.push(IrInstruction::Ret { value: None });
+    assert_eq!(b.function.allocate(), Err(AssemblerError::InvalidIr));
+
+    let mut b = Builder::new(CallingConvention::SystemV);
+    let label = b.label();
+    b.function.push(IrInstruction::Jmp(label));
+    assert_eq!(b.function.allocate(), Err(AssemblerError::InvalidIr));
+
+    let mut b = Builder::new(CallingConvention::SystemV);
+    b.imm(1);
+    assert_eq!(b.function.allocate(), Err(AssemblerError::InvalidIr));
+}
@@ -0,0 +1,126 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+use osom_x64_asm::{
+    jit_debug::{JitSymbol, PerfMap},
+    AssembledCode, Assembler, AssemblerError, ExecutableCode,
+};
+use osom_x64_encoder::{
+    encoder::{misc, mov},
+    models::GPR,
+};
+
+/// Assembles two functions, returning the offset of the second one.
+fn build() -> Result<(AssembledCode, u32), AssemblerError> {
+    let mut asm = Assembler::new();
+    let second = asm.create_label();
+    asm.emit(mov::encode_mov_reg_imm(GPR::EAX, 1)?);
+    asm.emit(misc::encode_ret());
+    asm.bind_label(second)?;
+    asm.emit(misc::encode_ret());
+    let code = asm.finish()?;
+    let offset = code.label_offset(second).unwrap();
+    Ok((code, offset))
+}
+
+#[test]
+fn test_perf_map() -> Result<(), AssemblerError> {
+    let (code, second) = build()?;
+    let executable = ExecutableCode::new(&code).unwrap();
+    let symbols = [
+        JitSymbol::new("jit_first", 0, second),
+        JitSymbol::new("jit_second", second, 1),
+    ];
+    let mut map = PerfMap::open().unwrap();
+    map.add(&executable, &symbols).unwrap();
+
+    let content = std::fs::read_to_string(PerfMap::path()).unwrap();
+    std::fs::remove_file(PerfMap::path()).unwrap();
+    let start = executable.as_ptr() as u64;
+    let expected = format!(
+        "{start:x} {second:x} jit_first\n{:x} 1 jit_second\n",
+        start + u64::from(second)
+    );
+    assert!(content.contains(&expected), "{content}");
+    Ok(())
+}
+
+#[cfg(feature = "gdb-jit")]
+mod gdb {
+    use osom_x64_asm::jit_debug::GdbRegistration;
+
+    use super::*;
+
+    #[repr(C)]
+    struct JitCodeEntry {
+        next_entry: *const JitCodeEntry,
+        prev_entry: *const JitCodeEntry,
+        symfile_addr: *const u8,
+        symfile_size: u64,
+    }
+
+    #[repr(C)]
+    struct JitDescriptor {
+        version: u32,
+        action_flag: u32,
+        relevant_entry: *const JitCodeEntry,
+        first_entry: *const JitCodeEntry,
+    }
+
+    extern "C" {
+        static __jit_debug_descriptor: JitDescriptor;
+    }
+
+    /// Returns symbol files registered with GDB, as GDB would see them.
+    fn registered_files() -> Vec<Vec<u8>> {
+        let mut files = Vec::new();
+        unsafe {
+            let descriptor = &*core::ptr::addr_of!(__jit_debug_descriptor);
+            assert_eq!(descriptor.version, 1);
+            assert_eq!(descriptor.action_flag, 0);
+            let mut entry = descriptor.first_entry;
+            while !entry.is_null() {
+                let size = usize::try_from((*entry).symfile_size).unwrap();
+                files.push(
+                    core::slice::from_raw_parts((*entry).symfile_addr, size).to_vec(),
+                );
+                entry = (*entry).next_entry;
+            }
+        }
+        files
+    }
+
+    fn contains(file: &[u8], name: &str) -> bool {
+        let name = format!("\0{name}\0");
+        file.windows(name.len())
+            .any(|window| window == name.as_bytes())
+    }
+
+    #[test]
+    fn test_gdb_registration() -> Result<(), AssemblerError> {
+        let (code, second) = build()?;
+        let executable = ExecutableCode::new(&code).unwrap();
+        let first = GdbRegistration::register(
+            &executable,
+            &[JitSymbol::new("gdb_first", 0, second)],
+        );
+        let last = GdbRegistration::register(
+            &executable,
+            &[JitSymbol::new("gdb_second", second, 1)],
+        );
+
+        let files = registered_files();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].starts_with(b"\x7FELF"));
+        assert!(contains(&files[0], "gdb_second"));
+        assert!(contains(&files[1], "gdb_first"));
+
+        drop(last);
+        let files = registered_files();
+        assert_eq!(files.len(), 1);
+        assert!(contains(&files[0], "gdb_first"));
+
+        drop(first);
+        assert!(registered_files().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use osom_x64_asm::{Assembler, AssemblerError, ListingLine};
+use osom_x64_encoder::{
+    encoder::{arith, misc, mov},
+    formatter::Syntax,
+    models::{Condition, GPR},
+};
+
+#[test]
+fn test_listing_text() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let negative = asm.create_label();
+    asm.set_label_name(negative, "negative");
+    let value = asm.constant_u64(42);
+    let abort = asm.declare_symbol("abort");
+
+    asm.emit_comment("check the sign");
+    asm.emit(arith::encode_cmp_reg_imm(GPR::EDI, 0)?);
+    asm.emit_jcc(Condition::Less, negative);
+    asm.emit_comment("load the answer");
+    asm.emit_lea_label(GPR::RAX, value)?;
+    asm.emit(misc::encode_ret());
+    asm.bind_label(negative)?;
+    asm.emit_call_symbol(abort);
+    asm.emit_bytes(&[1, 2, 3]);
+    asm.emit_comment("trailing");
+
+    let (code, listing) = asm.finish_with_listing(Syntax::Intel)?;
+    let text = listing.to_string();
+    let expected = "\
+00000000  section .text
+00000000  83 ff 00                    cmp edi, 0x0  ; check the sign
+00000003  7c 08                       jl negative
+00000005  48 8d 05 14 00 00 00        lea rax, [rip+.L1]  ; load the answer
+0000000c  c3                          ret
+negative:
+";
+    assert!(text.starts_with(expected), "{text}");
+    assert!(text.contains("0000000d  e8 00 00 00 00              call abort\n"));
+    assert!(text.contains("db 0x01, 0x02, 0x03\n          ; trailing\n"));
+    assert!(text.contains("section .rodata\n.L1:\n"));
+    assert!(text.contains("2a 00 00 00 00 00 00 00     db 0x2a, 0x00"));
+
+    let mut end = 0;
+    for line in listing.lines() {
+        if let ListingLine::Bytes { offset, bytes, .. } = line {
+            assert_eq!(*offset, end);
+            assert_eq!(&code.as_slice()[*offset as usize..][..bytes.len()], bytes);
+            end = offset + u32::try_from(bytes.len()).unwrap();
+        }
+    }
+    assert_eq!(end as usize, code.as_slice().len());
+    Ok(())
+}
+
+#[test]
+fn test_listing_att_long_instruction() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    asm.emit(mov::encode_movabs_reg_imm(
+        GPR::RAX,
+        0x1122_3344_5566_7788i64,
+    )?);
+    asm.emit_db(0x90);
+
+    let (_, listing) = asm.finish_with_listing(Syntax::Att)?;
+    let lines = listing.to_string();
+    let mut lines = lines.lines();
+    assert_eq!(lines.next(), Some("00000000  section .text"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("00000000  48 b8 88 77 66 55 44 33     movabs"));
+    assert_eq!(lines.next(), Some("00000008  22 11"));
+    assert_eq!(
+        lines.next(),
+        Some("0000000a  90                          .byte 0x90")
+    );
+    Ok(())
+}
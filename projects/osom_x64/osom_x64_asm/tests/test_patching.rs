@@ -0,0 +1,157 @@
+use osom_x64_asm::{Assembler, AssemblerError, PatchKind, PATCH_WINDOW_SIZE};
+use osom_x64_encoder::{
+    decoder::{decode, Instruction},
+    encoder::{arith, misc, mov},
+    models::GPR,
+};
+
+#[test]
+fn test_patch_points_layout() -> Result<(), AssemblerError> {
+    for prefix in 0..16 {
+        let mut asm = Assembler::new();
+        let target = asm.create_label();
+        asm.emit_bytes(&vec![0xCC; prefix]);
+        let jump = asm.emit_patchable_jmp(target);
+        let mov = asm.emit_patchable_mov_imm64(GPR::R9, -7)?;
+        asm.bind_label(target)?;
+        asm.emit(misc::encode_ret());
+
+        let code = asm.finish()?;
+        let points = code.patch_points();
+        assert_eq!((jump, mov), (0, 1));
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].kind(), PatchKind::Jump);
+        assert_eq!(points[1].kind(), PatchKind::MovImm64);
+        for point in points {
+            let first = point.field_offset() / PATCH_WINDOW_SIZE;
+            let last = (point.end_offset() - 1) / PATCH_WINDOW_SIZE;
+            assert_eq!(first, last);
+        }
+
+        let bytes = code.as_slice();
+        let decoded = decode(&bytes[points[0].offset() as usize..]).unwrap();
+        let rel = code.label_offset(target).unwrap() - points[0].end_offset();
+        assert_eq!(
+            decoded.instruction(),
+            Instruction::JmpRel32 { rel: rel as i32 }
+        );
+        let decoded = decode(&bytes[points[1].offset() as usize..]).unwrap();
+        assert_eq!(
+            decoded.instruction(),
+            Instruction::MovRegImm {
+                dst: GPR::R9,
+                imm: -7
+            }
+        );
+        assert_eq!(points[1].end_offset() as usize + 1, bytes.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_patchable_mov_invalid_register() {
+    let mut asm = Assembler::new();
+    assert!(matches!(
+        asm.emit_patchable_mov_imm64(GPR::EAX, 0),
+        Err(AssemblerError::Encoding(_))
+    ));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod executable {
+    use osom_x64_asm::{AssembledCode, ExecutableCode, Label, PatchError};
+
+    use super::*;
+
+    /// Assembles `fn() -> i64` returning `movabs` immediate plus 1 or 2,
+    /// depending on the patchable jump.
+    fn build() -> Result<(AssembledCode, [Label; 2]), AssemblerError> {
+        let mut asm = Assembler::new();
+        let targets = [asm.create_label(), asm.create_label()];
+        asm.emit_patchable_mov_imm64(GPR::RAX, 40)?;
+        asm.emit_patchable_jmp(targets[0]);
+        for (target, increment) in targets.into_iter().zip([1, 2]) {
+            asm.bind_label(target)?;
+            asm.emit(mov::encode_mov_reg_imm(GPR::RCX, increment)?);
+            asm.emit(arith::encode_add_reg_reg(GPR::RAX, GPR::RCX)?);
+            asm.emit(misc::encode_ret());
+        }
+        Ok((asm.finish()?, targets))
+    }
+
+    #[test]
+    fn test_patching() -> Result<(), AssemblerError> {
+        let (code, targets) = build()?;
+        let executable = ExecutableCode::new(&code).unwrap();
+        assert_eq!(executable.len(), code.as_slice().len());
+        let entry: extern "sysv64" fn() -> i64 = unsafe { executable.entry() };
+        assert_eq!(entry(), 41);
+
+        executable.patch_immediate(0, 100).unwrap();
+        assert_eq!(entry(), 101);
+
+        let second = code.label_offset(targets[1]).unwrap() as usize;
+        let target = executable.as_ptr().wrapping_add(second);
+        executable.patch_jump(1, target).unwrap();
+        assert_eq!(entry(), 102);
+
+        executable.patch_immediate(0, i64::MIN).unwrap();
+        assert_eq!(entry(), i64::MIN + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_patching_errors() -> Result<(), AssemblerError> {
+        let (code, _) = build()?;
+        let executable = ExecutableCode::new(&code).unwrap();
+        let start = executable.as_ptr();
+        assert_eq!(
+            executable.patch_jump(0, start),
+            Err(PatchError::InvalidPatchPoint)
+        );
+        assert_eq!(
+            executable.patch_immediate(1, 0),
+            Err(PatchError::InvalidPatchPoint)
+        );
+        assert_eq!(
+            executable.patch_immediate(2, 0),
+            Err(PatchError::InvalidPatchPoint)
+        );
+        let far = start.wrapping_add(1 << 32);
+        assert_eq!(
+            executable.patch_jump(1, far),
+            Err(PatchError::TargetOutOfRange)
+        );
+        let entry: extern "sysv64" fn() -> i64 = unsafe { executable.entry() };
+        assert_eq!(entry(), 41);
+        Ok(())
+    }
+
+    #[test]
+    fn test_patching_while_running() -> Result<(), AssemblerError> {
+        let (code, targets) = build()?;
+        let executable = ExecutableCode::new(&code).unwrap();
+        let entry: extern "sysv64" fn() -> i64 = unsafe { executable.entry() };
+        let offsets = targets.map(|target| code.label_offset(target).unwrap() as usize);
+
+        std::thread::scope(|scope| {
+            let runner = scope.spawn(|| {
+                for _ in 0..100_000 {
+                    let result = entry();
+                    assert!(
+                        (41..=42).contains(&result) || (1001..=1002).contains(&result)
+                    );
+                }
+            });
+            for round in 0..1_000 {
+                let offset = offsets[round % 2];
+                let target = executable.as_ptr().wrapping_add(offset);
+                executable.patch_jump(1, target).unwrap();
+                let value = if round % 3 == 0 { 1000 } else { 40 };
+                executable.patch_immediate(0, value).unwrap();
+            }
+            runner.join().unwrap();
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use osom_x64_asm::{
+    Assembler, AssemblerError, PeepholeConfig, PeepholeRule, PeepholeStatistics,
+};
+use osom_x64_encoder::{
+    decoder::{Decoder, Instruction},
+    encoder::{arith, misc, mov, sse},
+    models::{ArithmeticOp, Condition, GPR, XMM},
+};
+
+fn optimize(
+    mut asm: Assembler,
+    config: PeepholeConfig,
+) -> Result<(Vec<Instruction>, PeepholeStatistics), AssemblerError> {
+    let statistics = asm.optimize(config);
+    let code = asm.finish()?;
+    let instructions = Decoder::new(code.as_slice())
+        .map(|item| item.unwrap().1.instruction())
+        .collect();
+    Ok((instructions, statistics))
+}
+
+#[test]
+fn test_redundant_moves() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    asm.emit(mov::encode_mov_reg_reg(GPR::RAX, GPR::RAX)?);
+    asm.emit(mov::encode_mov_reg_reg(GPR::R9W, GPR::R9W)?);
+    asm.emit(mov::encode_mov_reg_reg(GPR::EAX, GPR::EAX)?);
+    asm.emit(mov::encode_mov_reg_reg(GPR::RAX, GPR::RBX)?);
+    asm.emit(sse::encode_movaps_xmm_xmm(XMM::XMM3, XMM::XMM3));
+    asm.emit(sse::encode_movsd_xmm_xmm(XMM::XMM1, XMM::XMM1));
+    asm.emit(misc::encode_ret());
+
+    let (instructions, statistics) = optimize(asm, PeepholeConfig::all())?;
+    assert_eq!(
+        instructions,
+        vec![
+            // Clears the upper half of RAX.
+            Instruction::MovRegReg {
+                dst: GPR::EAX,
+                src: GPR::EAX
+            },
+            Instruction::MovRegReg {
+                dst: GPR::RAX,
+                src: GPR::RBX
+            },
+            Instruction::Ret,
+        ]
+    );
+    assert_eq!(statistics.applied(PeepholeRule::RedundantMove), 4);
+    assert_eq!(statistics.total(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_neutral_arithmetic_respects_flags() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let label = asm.create_label();
+    asm.emit(arith::encode_add_reg_imm(GPR::RAX, 0)?);
+    asm.emit(arith::encode_and_reg_imm(GPR::RCX, -1)?);
+    asm.emit(arith::encode_cmp_reg_reg(GPR::RAX, GPR::RCX)?);
+    // Sets flags read by the jump.
+    asm.emit(arith::encode_sub_reg_imm(GPR::RDX, 0)?);
+    asm.emit_jcc(Condition::Equal, label);
+    asm.emit(arith::encode_or_reg_imm(GPR::ESI, 0)?);
+    asm.bind_label(label)?;
+    asm.emit(arith::encode_xor_reg_imm(GPR::RDI, 0)?);
+    asm.emit(misc::encode_ret());
+
+    let (instructions, statistics) = optimize(asm, PeepholeConfig::all())?;
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::ArithRegReg {
+                op: ArithmeticOp::Cmp,
+                dst: GPR::RAX,
+                src: GPR::RCX
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Sub,
+                dst: GPR::RDX,
+                imm: 0
+            },
+            Instruction::JccRel8 {
+                cond: Condition::Equal,
+                rel: 3
+            },
+            Instruction::ArithRegImm {
+                op: ArithmeticOp::Or,
+                dst: GPR::ESI,
+                imm: 0
+            },
+            Instruction::Ret,
+        ]
+    );
+    assert_eq!(statistics.applied(PeepholeRule::NeutralArithmetic), 3);
+    Ok(())
+}
+
+#[test]
+fn test_zero_idiom() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let label = asm.create_label();
+    asm.emit(mov::encode_mov_reg_imm(GPR::R8, 0)?);
+    asm.emit(arith::encode_cmp_reg_reg(GPR::RAX, GPR::RCX)?);
+    // Classic pattern: zeroing between the comparison and the jump.
+    asm.emit(mov::encode_mov_reg_imm(GPR::EDX, 0)?);
+    asm.emit_jcc(Condition::Less, label);
+    asm.emit(misc::encode_ret());
+    asm.bind_label(label)?;
+    asm.emit(mov::encode_mov_reg_imm(GPR::AX, 0)?);
+    asm.emit(mov::encode_mov_reg_imm(GPR::RAX, 0)?);
+    asm.emit(misc::encode_ret());
+
+    let (instructions, statistics) = optimize(asm, PeepholeConfig::all())?;
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::ArithRegReg {
+                op: ArithmeticOp::Xor,
+                dst: GPR::R8D,
+                src: GPR::R8D
+            },
+            Instruction::ArithRegReg {
+                op: ArithmeticOp::Cmp,
+                dst: GPR::RAX,
+                src: GPR::RCX
+            },
+            Instruction::MovRegImm {
+                dst: GPR::EDX,
+                imm: 0
+            },
+            Instruction::JccRel8 {
+                cond: Condition::Less,
+                rel: 1
+            },
+            Instruction::Ret,
+            Instruction::MovRegImm {
+                dst: GPR::AX,
+                imm: 0
+            },
+            Instruction::ArithRegReg {
+                op: ArithmeticOp::Xor,
+                dst: GPR::EAX,
+                src: GPR::EAX
+            },
+            Instruction::Ret,
+        ]
+    );
+    assert_eq!(statistics.applied(PeepholeRule::ZeroIdiom), 2);
+    Ok(())
+}
+
+#[test]
+fn test_jump_to_next_and_labels() -> Result<(), AssemblerError> {
+    // Removing the move makes the jump target the next instruction.
+    let mut asm = Assembler::new();
+    let next = asm.create_label();
+    let end = asm.create_label();
+    asm.emit_jmp(next);
+    asm.emit(mov::encode_mov_reg_reg(GPR::RBX, GPR::RBX)?);
+    asm.bind_label(next)?;
+    asm.emit_jcc(Condition::NotEqual, end);
+    asm.bind_label(end)?;
+    asm.emit(misc::encode_ret());
+
+    let statistics = asm.optimize(PeepholeConfig::all());
+    assert_eq!(statistics.applied(PeepholeRule::RedundantMove), 1);
+    assert_eq!(statistics.applied(PeepholeRule::JumpToNext), 2);
+    let code = asm.finish()?;
+    assert_eq!(code.as_slice(), &[0xC3]);
+    assert_eq!(code.label_offset(next), Some(0));
+    assert_eq!(code.label_offset(end), Some(0));
+    Ok(())
+}
+
+#[test]
+fn test_disabled_rules() -> Result<(), AssemblerError> {
+    let build = || -> Result<Assembler, AssemblerError> {
+        let mut asm = Assembler::new();
+        asm.emit(mov::encode_mov_reg_imm(GPR::RAX, 0)?);
+        asm.emit(mov::encode_mov_reg_reg(GPR::RCX, GPR::RCX)?);
+        asm.emit(misc::encode_ret());
+        Ok(asm)
+    };
+
+    let config = PeepholeConfig::all().without_rule(PeepholeRule::ZeroIdiom);
+    assert!(!config.is_enabled(PeepholeRule::ZeroIdiom));
+    let (instructions, statistics) = optimize(build()?, config)?;
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::MovRegImm {
+                dst: GPR::RAX,
+                imm: 0
+            },
+            Instruction::Ret,
+        ]
+    );
+    assert_eq!(statistics.applied(PeepholeRule::ZeroIdiom), 0);
+    assert_eq!(statistics.applied(PeepholeRule::RedundantMove), 1);
+
+    let (instructions, statistics) = optimize(build()?, PeepholeConfig::none())?;
+    assert_eq!(instructions.len(), 3);
+    assert_eq!(statistics.total(), 0);
+
+    let config = PeepholeConfig::none().with_rule(PeepholeRule::ZeroIdiom);
+    let (instructions, _) = optimize(build()?, config)?;
+    assert_eq!(instructions.len(), 3);
+    assert!(matches!(
+        instructions[0],
+        Instruction::ArithRegReg {
+            op: ArithmeticOp::Xor,
+            ..
+        }
+    ));
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use osom_x64_asm::{Assembler, AssemblerError, Relocation, RelocationKind};
+use osom_x64_encoder::{
+    decoder::{decode, Instruction},
+    encoder::misc,
+    models::{Memory, GPR},
+};
+
+#[test]
+fn test_relocations_recorded() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let malloc = asm.declare_symbol("malloc");
+    let counter = asm.declare_symbol("counter");
+    assert_eq!(asm.declare_symbol("malloc"), malloc);
+    assert_ne!(malloc, counter);
+
+    // A long jump in front makes relocation offsets depend on layout.
+    let skip = asm.create_label();
+    asm.emit_jmp(skip);
+    asm.emit_bytes(&[0xCC; 200]);
+    asm.bind_label(skip)?;
+    asm.emit_call_symbol(malloc);
+    asm.emit_lea_symbol(GPR::RCX, counter)?;
+    asm.emit_mov_symbol_address(GPR::RDX, counter)?;
+    asm.emit_load_symbol_address(GPR::R8, malloc)?;
+    asm.emit_jmp_symbol(malloc);
+    asm.emit(misc::encode_ret());
+
+    let code = asm.finish()?;
+    assert_eq!(code.symbol_name(malloc), "malloc");
+    assert_eq!(code.symbol_name(counter), "counter");
+    let start = code.label_offset(skip).unwrap();
+    assert_eq!(start, 205);
+    let expected = [
+        (RelocationKind::Plt32, start + 1, malloc, -4),
+        (RelocationKind::Pc32, start + 8, counter, -4),
+        (RelocationKind::Abs64, start + 14, counter, 0),
+        (RelocationKind::GotPc32, start + 25, malloc, -4),
+        (RelocationKind::Plt32, start + 30, malloc, -4),
+    ];
+    let relocations: Vec<_> = code
+        .relocations()
+        .iter()
+        .map(|relocation: &Relocation| {
+            (
+                relocation.kind(),
+                relocation.offset(),
+                relocation.symbol(),
+                relocation.addend(),
+            )
+        })
+        .collect();
+    assert_eq!(relocations, expected);
+
+    let bytes = code.as_slice();
+    for relocation in code.relocations() {
+        let field = relocation.offset() as usize;
+        let size = relocation.kind().size() as usize;
+        assert!(bytes[field..field + size].iter().all(|byte| *byte == 0));
+    }
+    let load = decode(&bytes[start as usize + 22..]).unwrap();
+    assert_eq!(
+        load.instruction(),
+        Instruction::MovRegMem {
+            dst: GPR::R8,
+            src: Memory::rip_relative(0).unwrap()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_relocation_kinds() {
+    let kinds = [
+        RelocationKind::Pc32,
+        RelocationKind::Plt32,
+        RelocationKind::Abs64,
+        RelocationKind::GotPc32,
+    ];
+    let elf_types: Vec<_> = kinds.iter().map(|kind| kind.elf_type()).collect();
+    assert_eq!(elf_types, [2, 4, 1, 9]);
+    let sizes: Vec<_> = kinds.iter().map(|kind| kind.size()).collect();
+    assert_eq!(sizes, [4, 4, 8, 4]);
+}
+
+#[test]
+fn test_load_symbol_address_invalid_register() {
+    let mut asm = Assembler::new();
+    let symbol = asm.declare_symbol("symbol");
+    assert!(matches!(
+        asm.emit_load_symbol_address(GPR::EAX, symbol),
+        Err(AssemblerError::Encoding(_))
+    ));
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod executable {
+    use osom_x64_asm::{
+        Assembler, AssemblerError, ExecutableCode, PatchError, SymbolTable,
+    };
+    use osom_x64_encoder::{
+        encoder::{arith, call, misc, mov},
+        models::{Memory, GPR},
+    };
+
+    static COUNTER: i64 = 1000;
+
+    extern "C" fn add_one(value: i64) -> i64 {
+        value + 1
+    }
+
+    extern "C" fn add_ten(value: i64) -> i64 {
+        value + 10
+    }
+
+    fn symbols() -> SymbolTable {
+        let mut symbols = SymbolTable::new();
+        symbols
+            .define("add_one", add_one as *const u8)
+            .define("add_ten", add_ten as *const u8)
+            .define("counter", (&raw const COUNTER).cast());
+        symbols
+    }
+
+    /// Returns `add_ten(add_one(value)) + counter`.
+    fn build() -> Result<Assembler, AssemblerError> {
+        let mut asm = Assembler::new();
+        let add_one = asm.declare_symbol("add_one");
+        let add_ten = asm.declare_symbol("add_ten");
+        let counter = asm.declare_symbol("counter");
+        asm.emit(arith::encode_sub_reg_imm(GPR::RSP, 8)?);
+        asm.emit_call_symbol(add_one);
+        asm.emit(mov::encode_mov_reg_reg(GPR::RDI, GPR::RAX)?);
+        asm.emit_load_symbol_address(GPR::RCX, add_ten)?;
+        asm.emit(call::encode_call_reg(GPR::RCX)?);
+        asm.emit_mov_symbol_address(GPR::RCX, counter)?;
+        asm.emit(mov::encode_mov_reg_mem(
+            GPR::RCX,
+            Memory::based(GPR::RCX, 0)?,
+        )?);
+        asm.emit(arith::encode_add_reg_reg(GPR::RAX, GPR::RCX)?);
+        asm.emit(arith::encode_add_reg_imm(GPR::RSP, 8)?);
+        asm.emit(misc::encode_ret());
+        Ok(asm)
+    }
+
+    #[test]
+    fn test_link() -> Result<(), AssemblerError> {
+        let code = build()?.finish()?;
+        let executable = ExecutableCode::link(&code, &symbols()).unwrap();
+        let function: extern "C" fn(i64) -> i64 = unsafe { executable.entry() };
+        assert_eq!(function(5), 1016);
+        assert_eq!(function(-20), 991);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tail_call() -> Result<(), AssemblerError> {
+        let mut asm = Assembler::new();
+        let add_ten = asm.declare_symbol("add_ten");
+        asm.emit_jmp_symbol(add_ten);
+        let executable = ExecutableCode::link(&asm.finish()?, &symbols()).unwrap();
+        let function: extern "C" fn(i64) -> i64 = unsafe { executable.entry() };
+        assert_eq!(function(7), 17);
+        Ok(())
+    }
+
+    #[test]
+    fn test_link_errors() -> Result<(), AssemblerError> {
+        let code = build()?.finish()?;
+        assert!(matches!(
+            ExecutableCode::new(&code),
+            Err(PatchError::UndefinedSymbol)
+        ));
+        let mut partial = SymbolTable::new();
+        partial.define("add_one", add_one as *const u8);
+        assert!(matches!(
+            ExecutableCode::link(&code, &partial),
+            Err(PatchError::UndefinedSymbol)
+        ));
+
+        let mut asm = Assembler::new();
+        let far = asm.declare_symbol("far");
+        asm.emit_lea_symbol(GPR::RAX, far)?;
+        asm.emit(misc::encode_ret());
+        let mut symbols = SymbolTable::new();
+        symbols.define("far", core::ptr::null());
+        assert!(matches!(
+            ExecutableCode::link(&asm.finish()?, &symbols),
+            Err(PatchError::TargetOutOfRange)
+        ));
+        Ok(())
+    }
+}
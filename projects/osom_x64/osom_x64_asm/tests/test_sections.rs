@@ -0,0 +1,235 @@
+use osom_x64_asm::{
+    AssembledCode, Assembler, AssemblerError, PeepholeConfig, Section, SectionKind,
+};
+use osom_x64_encoder::{
+    decoder::{decode, Instruction},
+    encoder::{arith, misc, mov},
+    models::{Condition, Memory, GPR},
+};
+
+/// Builds a function returning `-1` for negative `edi` from a cold section, and
+/// `42` loaded from a read-only data section otherwise.
+fn build(
+    order: Option<&[Section]>,
+) -> Result<(AssembledCode, [Section; 3]), AssemblerError> {
+    let mut asm = Assembler::new();
+    let cold = asm.create_section(".text.cold", SectionKind::Code, 64)?;
+    let rodata = asm.create_section(".rodata", SectionKind::Data, 32)?;
+    let negative = asm.create_label();
+    let value = asm.create_label();
+
+    asm.emit(arith::encode_cmp_reg_imm(GPR::EDI, 0)?);
+    asm.emit_jcc(Condition::Less, negative);
+    asm.emit_lea_label(GPR::RAX, value)?;
+    asm.emit(mov::encode_mov_reg_mem(
+        GPR::EAX,
+        Memory::based(GPR::RAX, 0)?,
+    )?);
+    asm.emit(misc::encode_ret());
+
+    asm.switch_section(cold);
+    asm.bind_label(negative)?;
+    asm.emit(mov::encode_mov_reg_imm(GPR::EAX, -1)?);
+    asm.emit(misc::encode_ret());
+
+    asm.switch_section(rodata);
+    asm.bind_label(value)?;
+    asm.emit_dd(42);
+
+    if let Some(order) = order {
+        asm.set_section_order(order)?;
+    }
+    Ok((asm.finish()?, [Section::TEXT, cold, rodata]))
+}
+
+#[test]
+fn test_sections_layout() -> Result<(), AssemblerError> {
+    let (code, [text, cold, rodata]) = build(None)?;
+    assert_eq!(code.section_name(text), ".text");
+    assert_eq!(code.section_name(cold), ".text.cold");
+    assert_eq!(code.section_name(rodata), ".rodata");
+    assert_eq!(code.section_range(text), 0..15);
+    assert_eq!(code.section_range(cold), 64..70);
+    assert_eq!(code.section_range(rodata), 96..100);
+    assert_eq!(code.code().len(), 100);
+
+    let bytes = code.as_slice();
+    let mut offset = 15;
+    while offset < 64 {
+        let decoded = decode(&bytes[offset..]).unwrap();
+        assert!(matches!(decoded.instruction(), Instruction::Nop { .. }));
+        offset += decoded.len() as usize;
+    }
+    assert_eq!(offset, 64);
+    assert!(bytes[70..96].iter().all(|byte| *byte == 0));
+    assert_eq!(&bytes[96..100], &42u32.to_le_bytes());
+
+    let jump = decode(&bytes[3..]).unwrap();
+    assert_eq!(
+        jump.instruction(),
+        Instruction::JccRel8 {
+            cond: Condition::Less,
+            rel: 64 - 5
+        }
+    );
+    let lea = decode(&bytes[5..]).unwrap();
+    assert_eq!(
+        lea.instruction(),
+        Instruction::Lea {
+            dst: GPR::RAX,
+            src: Memory::rip_relative(96 - 12).unwrap()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_section_order() -> Result<(), AssemblerError> {
+    let (code, [text, cold, rodata]) = build(None)?;
+    let order = [rodata, cold, text];
+    let (reordered, _) = build(Some(&order))?;
+    assert_eq!(reordered.section_range(rodata), 0..4);
+    assert_eq!(reordered.section_range(cold), 64..70);
+    assert_eq!(reordered.section_range(text), 80..95);
+    // Each section is position independent, only cross-section offsets change.
+    assert_eq!(&reordered.as_slice()[64..70], &code.as_slice()[64..70]);
+
+    let jump = decode(&reordered.as_slice()[83..]).unwrap();
+    assert_eq!(
+        jump.instruction(),
+        Instruction::JccRel8 {
+            cond: Condition::Less,
+            rel: 64 - 85
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_section_order() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let cold = asm.create_section(".text.cold", SectionKind::Code, 16)?;
+    let mut other = Assembler::new();
+    for name in [".a", ".b", ".c"] {
+        other.create_section(name, SectionKind::Code, 16)?;
+    }
+    let foreign = other.create_section(".d", SectionKind::Code, 16)?;
+
+    for order in [
+        &[cold][..],
+        &[cold, cold],
+        &[Section::TEXT, cold, cold],
+        &[Section::TEXT, foreign],
+    ] {
+        assert_eq!(
+            asm.set_section_order(order),
+            Err(AssemblerError::InvalidSectionOrder)
+        );
+    }
+    assert_eq!(
+        asm.create_section(".x", SectionKind::Data, 3),
+        Err(AssemblerError::InvalidAlignment)
+    );
+
+    // Sections created after setting the order are appended.
+    asm.set_section_order(&[cold, Section::TEXT])?;
+    let data = asm.create_section(".data", SectionKind::Data, 8)?;
+    asm.emit(misc::encode_ret());
+    asm.switch_section(cold);
+    asm.emit(misc::encode_ret());
+    asm.switch_section(data);
+    asm.emit_db(1);
+    let code = asm.finish()?;
+    assert_eq!(code.section_range(cold), 0..1);
+    assert_eq!(code.section_range(Section::TEXT), 16..17);
+    assert_eq!(code.section_range(data), 24..25);
+    Ok(())
+}
+
+#[test]
+fn test_data_section_alignment_padding() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let data = asm.create_section(".data", SectionKind::Data, 8)?;
+    asm.switch_section(data);
+    asm.emit_db(0xAA);
+    asm.emit_align(8)?;
+    asm.emit_db(0xBB);
+    asm.switch_section(Section::TEXT);
+    asm.emit_db(0xCC);
+    asm.emit_align(4)?;
+    asm.emit(misc::encode_ret());
+
+    let code = asm.finish()?;
+    let bytes = code.as_slice();
+    assert_eq!(code.section_range(data), 8..17);
+    assert_eq!(&bytes[8..17], &[0xAA, 0, 0, 0, 0, 0, 0, 0, 0xBB]);
+    let nop = decode(&bytes[1..4]).unwrap();
+    assert_eq!(nop.instruction(), Instruction::Nop { size: 3 });
+    Ok(())
+}
+
+#[test]
+fn test_jump_to_other_section_is_kept() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let next = asm.create_section(".text.next", SectionKind::Code, 1)?;
+    let target = asm.create_label();
+    asm.emit_jmp(target);
+    asm.switch_section(next);
+    asm.bind_label(target)?;
+    asm.emit(misc::encode_ret());
+
+    let statistics = asm.optimize(PeepholeConfig::all());
+    assert_eq!(statistics.total(), 0);
+    let code = asm.finish()?;
+    assert_eq!(code.as_slice(), &[0xEB, 0x00, 0xC3]);
+    Ok(())
+}
+
+#[test]
+fn test_patch_points_follow_emission_order() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let cold = asm.create_section(".text.cold", SectionKind::Code, 16)?;
+    asm.set_section_order(&[cold, Section::TEXT])?;
+    let first = asm.emit_patchable_mov_imm64(GPR::RAX, 1)?;
+    asm.switch_section(cold);
+    let second = asm.emit_patchable_mov_imm64(GPR::RCX, 2)?;
+    assert_eq!((first, second), (0, 1));
+
+    let code = asm.finish()?;
+    let points = code.patch_points();
+    assert!(code
+        .section_range(Section::TEXT)
+        .contains(&points[0].offset()));
+    assert!(code.section_range(cold).contains(&points[1].offset()));
+    let decoded = decode(&code.as_slice()[points[0].offset() as usize..]).unwrap();
+    assert_eq!(
+        decoded.instruction(),
+        Instruction::MovRegImm {
+            dst: GPR::RAX,
+            imm: 1
+        }
+    );
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_execute_split_function() -> Result<(), AssemblerError> {
+    use osom_x64_asm::ExecutableCode;
+
+    let (_, [text, cold, rodata]) = build(None)?;
+    for order in [
+        [text, cold, rodata],
+        [rodata, cold, text],
+        [cold, text, rodata],
+    ] {
+        let (code, _) = build(Some(&order))?;
+        let entry = code.section_range(text).start as usize;
+        let executable = ExecutableCode::new(&code).unwrap();
+        let function: extern "C" fn(i32) -> i32 =
+            unsafe { core::mem::transmute(executable.as_ptr().add(entry)) };
+        assert_eq!(function(5), 42);
+        assert_eq!(function(-5), -1);
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use osom_x64_asm::{AssembledCode, Assembler, AssemblerError, Label};
+use osom_x64_encoder::{
+    decoder::{Decoder, Instruction},
+    encoder::{errors::EncodingError, misc, mov},
+    models::GPR,
+};
+use rand::{seq::SliceRandom, Rng};
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use osom_x64_asm::ExecutableCode;
+
+/// Result returned when no case matches.
+const DEFAULT_RESULT: i64 = -1;
+
+/// Assembles `fn(u32) -> i64` (System V) returning `results[i]` for the value
+/// of the `i`-th case and [`DEFAULT_RESULT`] otherwise.
+fn build_switch(
+    values: &[u32],
+    results: &[i64],
+) -> Result<AssembledCode, AssemblerError> {
+    let mut asm = Assembler::new();
+    let default = asm.create_label();
+    let labels: Vec<Label> = values.iter().map(|_| asm.create_label()).collect();
+    let cases: Vec<(u32, Label)> = values.iter().copied().zip(labels.clone()).collect();
+    asm.emit_switch(GPR::EDI, &cases, default, GPR::R11)?;
+
+    for (label, result) in labels.into_iter().zip(results) {
+        asm.bind_label(label)?;
+        asm.emit(mov::encode_mov_reg_imm(GPR::RAX, *result)?);
+        asm.emit(misc::encode_ret());
+    }
+    asm.bind_label(default)?;
+    asm.emit(mov::encode_mov_reg_imm(GPR::RAX, DEFAULT_RESULT)?);
+    asm.emit(misc::encode_ret());
+    asm.finish()
+}
+
+fn uses_jump_table(code: &AssembledCode) -> bool {
+    Decoder::new(code.code())
+        .map(|item| item.unwrap().1.instruction())
+        .any(|instruction| matches!(instruction, Instruction::JmpReg { .. }))
+}
+
+/// Returns values worth checking: all cases, their neighbours and the extremes.
+fn probes(values: &[u32]) -> Vec<u32> {
+    let mut probes = vec![0, 1, u32::MAX - 1, u32::MAX];
+    for &value in values {
+        probes.extend([value.wrapping_sub(1), value, value.wrapping_add(1)]);
+    }
+    probes
+}
+
+fn expected(values: &[u32], results: &[i64], probe: u32) -> i64 {
+    values
+        .iter()
+        .position(|value| *value == probe)
+        .map_or(DEFAULT_RESULT, |index| results[index])
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn check(values: &[u32], results: &[i64]) -> Result<AssembledCode, AssemblerError> {
+    let code = build_switch(values, results)?;
+    let executable = ExecutableCode::new(&code).unwrap();
+    let entry: extern "sysv64" fn(u32) -> i64 = unsafe { executable.entry() };
+    for probe in probes(values) {
+        assert_eq!(
+            entry(probe),
+            expected(values, results, probe),
+            "switch over {values:?} at {probe}"
+        );
+    }
+    Ok(code)
+}
+
+#[test]
+fn test_jump_table_contents() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let labels = [asm.create_label(), asm.create_label()];
+    asm.bind_label(labels[0])?;
+    asm.emit_bytes(&[0xCC; 5]);
+    asm.bind_label(labels[1])?;
+    asm.emit(misc::encode_ret());
+    let table = asm.jump_table(&[labels[1], labels[0], labels[1]]);
+    let same_table = asm.jump_table(&[labels[1], labels[0], labels[1]]);
+    let zeros = asm.constant_bytes(&[0; 12], 4)?;
+    assert_ne!(table, same_table);
+    assert_ne!(table, zeros);
+
+    let code = asm.finish()?;
+    for table in [table, same_table] {
+        let start = code.label_offset(table).unwrap() as usize;
+        assert_eq!(start % 4, 0);
+        let entries: Vec<i32> = code.as_slice()[start..start + 12]
+            .chunks(4)
+            .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        let start = start as i32;
+        assert_eq!(entries, vec![5 - start, -start, 5 - start]);
+    }
+    let zeros = code.label_offset(zeros).unwrap() as usize;
+    assert_eq!(&code.as_slice()[zeros..zeros + 12], &[0; 12]);
+    Ok(())
+}
+
+#[test]
+fn test_jump_table_unbound_target() {
+    let mut asm = Assembler::new();
+    let target = asm.create_label();
+    let _ = asm.jump_table(&[target]);
+    asm.emit(misc::encode_ret());
+    assert!(matches!(
+        asm.finish(),
+        Err(AssemblerError::UnboundLabel(label)) if label == target
+    ));
+}
+
+#[test]
+fn test_jump_table_dispatch_encoding() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let target = asm.create_label();
+    let table = asm.jump_table(&[target]);
+    asm.emit_jump_table_dispatch(GPR::RDI, table, GPR::R11)?;
+    asm.bind_label(target)?;
+    asm.emit(misc::encode_ret());
+
+    let code = asm.finish()?;
+    let decoded: Vec<Instruction> = Decoder::new(code.code())
+        .map(|item| item.unwrap().1.instruction())
+        .collect();
+    assert!(matches!(
+        decoded.as_slice(),
+        [
+            Instruction::Lea { dst: GPR::R11, .. },
+            Instruction::MovsxdRegMem { dst: GPR::RDI, .. },
+            Instruction::ArithRegReg {
+                dst: GPR::R11,
+                src: GPR::RDI,
+                ..
+            },
+            Instruction::JmpReg { target: GPR::R11 },
+            Instruction::Ret,
+        ]
+    ));
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_dense_switch() -> Result<(), AssemblerError> {
+    let values = [10, 11, 12, 14, 15, 17];
+    let code = check(&values, &[100, 110, 120, 140, 150, 170])?;
+    assert!(uses_jump_table(&code));
+
+    let values = [3, 0, 2, 1];
+    let code = check(&values, &[30, 0, 20, 10])?;
+    assert!(uses_jump_table(&code));
+
+    let values = [u32::MAX - 3, u32::MAX, u32::MAX - 1, u32::MAX - 2];
+    let code = check(&values, &[3, 0, 1, 2])?;
+    assert!(uses_jump_table(&code));
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_sparse_switch() -> Result<(), AssemblerError> {
+    let values = [1, 100, 1000, 7, 50_000, u32::MAX, 0, 3, 9_999];
+    let code = check(&values, &[1, 2, 3, 4, 5, 6, 7, 8, 9])?;
+    assert!(!uses_jump_table(&code));
+
+    // Too few cases for a jump table, however dense.
+    let code = check(&[5, 6, 7], &[50, 60, 70])?;
+    assert!(!uses_jump_table(&code));
+
+    let code = check(&[], &[])?;
+    assert!(!uses_jump_table(&code));
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn test_random_switches() -> Result<(), AssemblerError> {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let count = rng.gen_range(1..40);
+        let base: u32 = rng.gen();
+        let range = rng.gen_range(count..count * 4) as u32;
+        let mut values: Vec<u32> = (0..range).map(|i| base.wrapping_add(i)).collect();
+        values.shuffle(&mut rng);
+        values.truncate(count);
+        let results: Vec<i64> = (0..count).map(|_| rng.gen_range(0..i64::MAX)).collect();
+        check(&values, &results)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_switch_errors() {
+    let mut asm = Assembler::new();
+    let default = asm.create_label();
+    let target = asm.create_label();
+    let cases = [(1, target), (1, default)];
+    assert_eq!(
+        asm.emit_switch(GPR::EDI, &cases, default, GPR::R11),
+        Err(AssemblerError::InvalidSwitch)
+    );
+    let cases = [(1, target)];
+    assert_eq!(
+        asm.emit_switch(GPR::EDI, &cases, default, GPR::RDI),
+        Err(AssemblerError::InvalidSwitch)
+    );
+    assert_eq!(
+        asm.emit_switch(GPR::RDI, &cases, default, GPR::R11),
+        Err(AssemblerError::Encoding(EncodingError::InvalidRegisterSize))
+    );
+    assert_eq!(
+        asm.emit_switch(GPR::EDI, &cases, default, GPR::R11D),
+        Err(AssemblerError::Encoding(EncodingError::InvalidRegisterSize))
+    );
+
+    let table = asm.jump_table(&[target]);
+    assert_eq!(
+        asm.emit_jump_table_dispatch(GPR::RSP, table, GPR::R11),
+        Err(AssemblerError::Encoding(
+            EncodingError::InvalidMemoryOperand
+        ))
+    );
+    assert_eq!(
+        asm.emit_jump_table_dispatch(GPR::RAX, table, GPR::RAX),
+        Err(AssemblerError::InvalidSwitch)
+    );
+}
+
+#[test]
+fn test_switch_errors_emit_nothing() -> Result<(), AssemblerError> {
+    let mut asm = Assembler::new();
+    let default = asm.create_label();
+    let cases: Vec<(u32, Label)> = (0..8).map(|value| (value, default)).collect();
+    assert_eq!(
+        asm.emit_switch(GPR::ESP, &cases, default, GPR::R11),
+        Err(AssemblerError::Encoding(
+            EncodingError::InvalidMemoryOperand
+        ))
+    );
+    asm.bind_label(default)?;
+    let code = asm.finish()?;
+    assert!(code.code().is_empty());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+use osom_x64_asm::{Assembler, ExecutableCode};
+use osom_x64_encoder::{
+    encoder::{misc, mov},
+    models::{Memory, GPR},
+};
+
+fn thread_pointer() -> u64 {
+    let result: u64;
+    unsafe {
+        core::arch::asm!("mov {}, qword ptr fs:[0]", out(reg) result);
+    }
+    result
+}
+
+#[test]
+fn test_thread_pointer() {
+    let mut asm = Assembler::new();
+    asm.emit(mov::encode_mov_thread_pointer(GPR::RAX).unwrap());
+    asm.emit(misc::encode_ret());
+    let code = asm.finish().unwrap();
+
+    let executable = ExecutableCode::new(&code).unwrap();
+    let entry: extern "sysv64" fn() -> u64 = unsafe { executable.entry() };
+    let expected = thread_pointer();
+    assert_ne!(expected, 0);
+    assert_eq!(entry(), expected);
+
+    // The thread pointer differs between threads.
+    let other = std::thread::spawn(move || entry()).join().unwrap();
+    assert_ne!(other, expected);
+    assert_eq!(entry(), expected);
+}
+
+#[test]
+fn test_thread_local_read() {
+    let mut asm = Assembler::new();
+    asm.emit(
+        mov::encode_mov_reg_mem(GPR::RAX, Memory::thread_local(0).unwrap()).unwrap(),
+    );
+    asm.emit(
+        mov::encode_mov_reg_mem(GPR::RAX, Memory::based(GPR::RAX, 0).unwrap()).unwrap(),
+    );
+    asm.emit(misc::encode_ret());
+    let code = asm.finish().unwrap();
+
+    // The thread control block starts with a pointer to itself.
+    let executable = ExecutableCode::new(&code).unwrap();
+    let entry: extern "sysv64" fn() -> u64 = unsafe { executable.entry() };
+    assert_eq!(entry(), thread_pointer());
+}
@@ -0,0 +1,194 @@
+use osom_x64_asm::{
+    abi::{CallingConvention, FrameLayout, FunctionDescription},
+    unwind::{CfiDirective, EhFrameBuilder},
+    Assembler, AssemblerError,
+};
+use osom_x64_encoder::models::GPR;
+
+fn frame_cfi(
+    function: &FunctionDescription,
+) -> Result<Vec<(u32, CfiDirective)>, AssemblerError> {
+    let layout = FrameLayout::new(function)?;
+    let mut asm = Assembler::new();
+    layout.emit_prologue(&mut asm)?;
+    layout.emit_epilogue(&mut asm)?;
+    Ok(asm.finish()?.cfi_directives().to_vec())
+}
+
+#[test]
+fn test_frame_pointer_cfi() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::R15])
+        .with_frame_pointer()
+        .with_calls();
+    let expected = vec![
+        (1, CfiDirective::DefCfaOffset(16)),
+        (
+            1,
+            CfiDirective::Offset {
+                register: GPR::RBP,
+                offset: -16,
+            },
+        ),
+        (4, CfiDirective::DefCfaRegister(GPR::RBP)),
+        (
+            6,
+            CfiDirective::Offset {
+                register: GPR::R15,
+                offset: -24,
+            },
+        ),
+        (10, CfiDirective::RememberState),
+        (16, CfiDirective::Restore(GPR::R15)),
+        (
+            17,
+            CfiDirective::DefCfa {
+                register: GPR::RSP,
+                offset: 8,
+            },
+        ),
+        (17, CfiDirective::Restore(GPR::RBP)),
+        (18, CfiDirective::RestoreState),
+    ];
+    assert_eq!(frame_cfi(&function)?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_stack_pointer_cfi() -> Result<(), AssemblerError> {
+    let function = FunctionDescription::new(CallingConvention::SystemV)
+        .with_clobbered(&[GPR::RBX, GPR::R12])
+        .with_locals_size(20)
+        .with_calls();
+    let expected = vec![
+        (1, CfiDirective::DefCfaOffset(16)),
+        (
+            1,
+            CfiDirective::Offset {
+                register: GPR::RBX,
+                offset: -16,
+            },
+        ),
+        (3, CfiDirective::DefCfaOffset(24)),
+        (
+            3,
+            CfiDirective::Offset {
+                register: GPR::R12,
+                offset: -24,
+            },
+        ),
+        (7, CfiDirective::DefCfaOffset(64)),
+        (7, CfiDirective::RememberState),
+        (11, CfiDirective::DefCfaOffset(24)),
+        (13, CfiDirective::DefCfaOffset(16)),
+        (13, CfiDirective::Restore(GPR::R12)),
+        (14, CfiDirective::DefCfaOffset(8)),
+        (14, CfiDirective::Restore(GPR::RBX)),
+        (15, CfiDirective::RestoreState),
+    ];
+    assert_eq!(frame_cfi(&function)?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_leaf_function_has_no_cfi() -> Result<(), AssemblerError> {
+    let function =
+        FunctionDescription::new(CallingConvention::SystemV).with_locals_size(16);
+    let directives = frame_cfi(&function)?;
+    assert_eq!(
+        directives,
+        vec![
+            (0, CfiDirective::RememberState),
+            (1, CfiDirective::RestoreState)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_eh_frame_layout() {
+    let mut builder = EhFrameBuilder::new();
+    builder
+        .add_function(0x1000, 0x10, &[(1, CfiDirective::DefCfaOffset(16))])
+        .add_function(0x1010, 0x20, &[]);
+    let bytes = builder.finish();
+
+    let mut offset = 0;
+    let mut entries = Vec::new();
+    loop {
+        let length = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        if length == 0 {
+            break;
+        }
+        let id = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap());
+        entries.push((offset, id));
+        offset += 4 + length as usize;
+        assert_eq!(offset % 8, 0);
+    }
+    assert_eq!(offset + 4, bytes.len());
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0], (0, 0));
+    // FDEs point back to the CIE, relative to their CIE pointer field.
+    for (offset, id) in &entries[1..] {
+        assert_eq!(*id as usize, offset + 4);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod executable {
+    use std::panic::catch_unwind;
+
+    use osom_x64_asm::{
+        abi::{CallingConvention, FrameLayout, FunctionDescription},
+        unwind::FrameRegistration,
+        Assembler, AssemblerError, ExecutableCode,
+    };
+    use osom_x64_encoder::{
+        encoder::{call, mov},
+        models::GPR,
+    };
+
+    extern "C-unwind" fn callback(value: i64) -> i64 {
+        assert!(value >= 0, "Negative value.");
+        value * 2
+    }
+
+    /// Builds a function saving registers and calling [`callback`].
+    fn build(frame_pointer: bool) -> Result<ExecutableCode, AssemblerError> {
+        let mut function = FunctionDescription::new(CallingConvention::SystemV)
+            .with_clobbered(&[GPR::RBX, GPR::R12, GPR::R13])
+            .with_locals_size(24)
+            .with_calls();
+        if frame_pointer {
+            function = function.with_frame_pointer();
+        }
+        let layout = FrameLayout::new(&function)?;
+        let mut asm = Assembler::new();
+        layout.emit_prologue(&mut asm)?;
+        asm.emit(mov::encode_mov_reg_reg(GPR::RBX, GPR::RDI)?);
+        asm.emit(mov::encode_movabs_reg_imm(
+            GPR::RAX,
+            callback as *const () as i64,
+        )?);
+        asm.emit(call::encode_call_reg(GPR::RAX)?);
+        layout.emit_epilogue(&mut asm)?;
+        Ok(ExecutableCode::new(&asm.finish()?).unwrap())
+    }
+
+    #[test]
+    fn test_panic_unwinds_through_generated_code() -> Result<(), AssemblerError> {
+        for frame_pointer in [false, true] {
+            let code = build(frame_pointer)?;
+            let registration = FrameRegistration::register(&code);
+            assert!(!registration.eh_frame().is_empty());
+
+            let function: extern "C-unwind" fn(i64) -> i64 = unsafe { code.entry() };
+            assert_eq!(function(21), 42);
+            let result = catch_unwind(|| function(-1));
+            let message = result.unwrap_err();
+            assert_eq!(message.downcast_ref::<&str>(), Some(&"Negative value."));
+            assert_eq!(function(5), 10);
+        }
+        Ok(())
+    }
+}
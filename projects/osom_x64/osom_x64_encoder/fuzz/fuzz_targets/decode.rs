@@ -0,0 +1,15 @@
+//! Feeds arbitrary bytes to the decoder. It must never panic, and never report
+//! an instruction longer than the input or than `MAX_INSTRUCTION_SIZE`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use osom_x64_encoder::{constants::MAX_INSTRUCTION_SIZE, decoder::decode};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(decoded) = decode(data) {
+        let len = usize::from(decoded.len());
+        assert!(len <= MAX_INSTRUCTION_SIZE);
+        assert!(len <= data.len());
+        assert_eq!(decode(&data[..len]), Ok(decoded));
+    }
+});
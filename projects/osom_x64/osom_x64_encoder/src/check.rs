@@ -1,3 +1,4 @@
+#[allow(clippy::assertions_on_constants, clippy::eq_op)]
 const _: () = {
     // Note: A bunch of assumptions that we operate on. This is mostly guaranteed by
     // the Rust standard, but better safe than sorry.
@@ -6,5 +6,6 @@ pub const JMP_REL8_SIZE: usize = 2;
 pub const JMP_REL32_SIZE: usize = 5;
 pub const JCC_REL8_SIZE: usize = 2;
 pub const JCC_REL32_SIZE: usize = 6;
+pub const CALL_REL32_SIZE: usize = 5;
 
 pub const MAX_INSTRUCTION_SIZE: usize = 15;
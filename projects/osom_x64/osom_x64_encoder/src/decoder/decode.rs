@@ -0,0 +1,985 @@
+use crate::{
+    constants::MAX_INSTRUCTION_SIZE,
+    models::{
+        ArithmeticOp, BitCountOp, Condition, FloatArithmeticOp, MachineSize, Memory,
+        Scale, Segment, ShiftOp, GPR, XMM,
+    },
+};
+
+use super::{errors::DecodingError, DecodedInstruction, Instruction};
+
+const OPERAND_SIZE_PREFIX: u8 = 0x66;
+const SCALAR_DOUBLE_PREFIX: u8 = 0xF2;
+const SCALAR_SINGLE_PREFIX: u8 = 0xF3;
+
+const REX_W: u8 = 0b1000;
+const REX_R: u8 = 0b0100;
+const REX_X: u8 = 0b0010;
+const REX_B: u8 = 0b0001;
+
+/// Inverse of the opcode mapping used by `jcc` encoders. Only the low nibble
+/// of the opcode is taken into account.
+const fn map_opcode_to_cond(opcode: u8) -> Condition {
+    match opcode & 0x0F {
+        0x0 => Condition::Overflow,
+        0x1 => Condition::NotOverflow,
+        0x2 => Condition::Below,
+        0x3 => Condition::AboveOrEqual,
+        0x4 => Condition::Equal,
+        0x5 => Condition::NotEqual,
+        0x6 => Condition::BelowOrEqual,
+        0x7 => Condition::Above,
+        0x8 => Condition::Sign,
+        0x9 => Condition::NotSign,
+        0xA => Condition::Parity,
+        0xB => Condition::NotParity,
+        0xC => Condition::Less,
+        0xD => Condition::GreaterOrEqual,
+        0xE => Condition::LessOrEqual,
+        _ => Condition::Greater,
+    }
+}
+
+const fn map_opcode_to_arithmetic_op(bits: u8) -> ArithmeticOp {
+    match bits & 0b111 {
+        0 => ArithmeticOp::Add,
+        1 => ArithmeticOp::Or,
+        2 => ArithmeticOp::Adc,
+        3 => ArithmeticOp::Sbb,
+        4 => ArithmeticOp::And,
+        5 => ArithmeticOp::Sub,
+        6 => ArithmeticOp::Xor,
+        _ => ArithmeticOp::Cmp,
+    }
+}
+
+const fn map_extension_to_shift_op(bits: u8) -> Option<ShiftOp> {
+    match bits & 0b111 {
+        0 => Some(ShiftOp::Rol),
+        1 => Some(ShiftOp::Ror),
+        4 => Some(ShiftOp::Shl),
+        5 => Some(ShiftOp::Shr),
+        7 => Some(ShiftOp::Sar),
+        _ => None,
+    }
+}
+
+const fn map_opcode_to_float_op(
+    opcode: u8,
+    is_double: bool,
+) -> Option<FloatArithmeticOp> {
+    let op = match (opcode, is_double) {
+        (0x58, true) => FloatArithmeticOp::Addsd,
+        (0x59, true) => FloatArithmeticOp::Mulsd,
+        (0x5C, true) => FloatArithmeticOp::Subsd,
+        (0x5E, true) => FloatArithmeticOp::Divsd,
+        (0x58, false) => FloatArithmeticOp::Addss,
+        (0x59, false) => FloatArithmeticOp::Mulss,
+        (0x5C, false) => FloatArithmeticOp::Subss,
+        (0x5E, false) => FloatArithmeticOp::Divss,
+        _ => return None,
+    };
+    Some(op)
+}
+
+const fn map_index_to_scale(bits: u8) -> Scale {
+    match bits & 0b11 {
+        0 => Scale::Scale1,
+        1 => Scale::Scale2,
+        2 => Scale::Scale4,
+        _ => Scale::Scale8,
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Prefixes {
+    operand_size: bool,
+    scalar_double: bool,
+    scalar_single: bool,
+    segment: Option<Segment>,
+    rex: Option<u8>,
+}
+
+impl Prefixes {
+    #[inline(always)]
+    const fn is_empty(self) -> bool {
+        !self.operand_size
+            && !self.scalar_double
+            && !self.scalar_single
+            && self.segment.is_none()
+            && self.rex.is_none()
+    }
+
+    /// Returns `true` if only the operand size prefix and REX may be present.
+    #[inline(always)]
+    const fn is_general_purpose(self) -> bool {
+        !self.scalar_double && !self.scalar_single
+    }
+
+    /// Returns the operand size of a general purpose instruction, given
+    /// whether its opcode is the 8-bit variant.
+    #[inline(always)]
+    const fn operand_size(self, is_byte: bool) -> MachineSize {
+        if is_byte {
+            MachineSize::Byte
+        } else if self.rex_bit(REX_W) != 0 {
+            MachineSize::QWord
+        } else if self.operand_size {
+            MachineSize::Word
+        } else {
+            MachineSize::DWord
+        }
+    }
+
+    /// Returns the general purpose register of `size` and encoded `index`, i.e.
+    /// `ah`, `ch`, `dh` and `bh` for byte registers 4 to 7 addressed without REX.
+    #[inline(always)]
+    fn gpr(self, size: MachineSize, index: u8) -> GPR {
+        const HIGH_BYTES: [GPR; 4] = [GPR::AH, GPR::CH, GPR::DH, GPR::BH];
+
+        if matches!(size, MachineSize::Byte)
+            && self.rex.is_none()
+            && (4..8).contains(&index)
+        {
+            return HIGH_BYTES[index as usize - 4];
+        }
+        unsafe { GPR::new_unchecked(size, index) }
+    }
+
+    #[inline(always)]
+    const fn rex_bit(self, bit: u8) -> u8 {
+        match self.rex {
+            Some(rex) if rex & bit != 0 => 0b1000,
+            _ => 0,
+        }
+    }
+}
+
+/// The decoded r/m part of `ModRM` byte together with the `ModRM.reg` field.
+struct ModRm {
+    reg: u8,
+    rm: Rm,
+}
+
+enum Rm {
+    Register(u8),
+    Memory(Memory),
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+
+    /// Set once the segment override is applied to a memory operand.
+    segment_used: bool,
+}
+
+impl<'a> Reader<'a> {
+    #[inline(always)]
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            segment_used: false,
+        }
+    }
+
+    #[inline(always)]
+    fn peek_u8(&self) -> Option<u8> {
+        if self.position >= MAX_INSTRUCTION_SIZE {
+            return None;
+        }
+        self.bytes.get(self.position).copied()
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodingError> {
+        let end = self.position + N;
+        if end > MAX_INSTRUCTION_SIZE {
+            return Err(DecodingError::InstructionTooLong);
+        }
+        let Some(slice) = self.bytes.get(self.position..end) else {
+            return Err(DecodingError::UnexpectedEnd);
+        };
+        self.position = end;
+        let mut result = [0u8; N];
+        result.copy_from_slice(slice);
+        Ok(result)
+    }
+
+    #[inline(always)]
+    fn read_u8(&mut self) -> Result<u8, DecodingError> {
+        let [value] = self.read_array::<1>()?;
+        Ok(value)
+    }
+
+    #[inline(always)]
+    fn read_i8(&mut self) -> Result<i8, DecodingError> {
+        Ok(i8::from_le_bytes(self.read_array()?))
+    }
+
+    #[inline(always)]
+    fn read_i16(&mut self) -> Result<i16, DecodingError> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    #[inline(always)]
+    fn read_i32(&mut self) -> Result<i32, DecodingError> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    #[inline(always)]
+    fn read_i64(&mut self) -> Result<i64, DecodingError> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a sign extended immediate of `size`, where 64-bit operands use
+    /// 32-bit immediates unless `full` is set.
+    fn read_immediate(
+        &mut self,
+        size: MachineSize,
+        full: bool,
+    ) -> Result<i64, DecodingError> {
+        match size {
+            MachineSize::Byte => Ok(i64::from(self.read_i8()?)),
+            MachineSize::Word => Ok(i64::from(self.read_i16()?)),
+            MachineSize::QWord if full => self.read_i64(),
+            _ => Ok(i64::from(self.read_i32()?)),
+        }
+    }
+
+    fn read_prefixes(&mut self) -> Prefixes {
+        let mut prefixes = Prefixes::default();
+        loop {
+            match self.peek_u8() {
+                Some(OPERAND_SIZE_PREFIX) => prefixes.operand_size = true,
+                Some(SCALAR_DOUBLE_PREFIX) => prefixes.scalar_double = true,
+                Some(SCALAR_SINGLE_PREFIX) => prefixes.scalar_single = true,
+                Some(byte) => match Segment::from_override_prefix(byte) {
+                    Some(segment) => prefixes.segment = Some(segment),
+                    None => break,
+                },
+                None => break,
+            }
+            self.position += 1;
+        }
+
+        // REX has to immediately precede the opcode.
+        if let Some(rex @ 0x40..=0x4F) = self.peek_u8() {
+            prefixes.rex = Some(rex);
+            self.position += 1;
+        }
+
+        prefixes
+    }
+
+    /// Reads `ModRM` byte together with the optional `SIB` byte and displacement.
+    fn read_modrm(&mut self, prefixes: Prefixes) -> Result<ModRm, DecodingError> {
+        let modrm = self.read_u8()?;
+        let mod_bits = modrm >> 6;
+        let reg = ((modrm >> 3) & 0b111) | prefixes.rex_bit(REX_R);
+        let rm_bits = modrm & 0b111;
+
+        if mod_bits == 0b11 {
+            let rm = Rm::Register(rm_bits | prefixes.rex_bit(REX_B));
+            return Ok(ModRm { reg, rm });
+        }
+
+        if mod_bits == 0b00 && rm_bits == 0b101 {
+            let displacement = self.read_i32()?;
+            let Ok(memory) = Memory::rip_relative(displacement) else {
+                return Err(DecodingError::UnsupportedInstruction);
+            };
+            let rm = Rm::Memory(self.apply_segment(memory, prefixes));
+            return Ok(ModRm { reg, rm });
+        }
+
+        let mut base = GPR::NO_REG;
+        let mut index = GPR::NO_REG;
+        let mut scale = Scale::Scale1;
+        let mut has_disp32 = mod_bits == 0b10;
+
+        if rm_bits == 0b100 {
+            let sib = self.read_u8()?;
+            let index_bits = ((sib >> 3) & 0b111) | prefixes.rex_bit(REX_X);
+            let base_bits = sib & 0b111;
+            if index_bits != 0b100 {
+                index = unsafe { GPR::new_unchecked(MachineSize::QWord, index_bits) };
+                scale = map_index_to_scale(sib >> 6);
+            }
+            if mod_bits == 0b00 && base_bits == 0b101 {
+                has_disp32 = true;
+            } else {
+                let base_index = base_bits | prefixes.rex_bit(REX_B);
+                base = unsafe { GPR::new_unchecked(MachineSize::QWord, base_index) };
+            }
+        } else {
+            let base_index = rm_bits | prefixes.rex_bit(REX_B);
+            base = unsafe { GPR::new_unchecked(MachineSize::QWord, base_index) };
+        }
+
+        let displacement = if has_disp32 {
+            self.read_i32()?
+        } else if mod_bits == 0b01 {
+            i32::from(self.read_i8()?)
+        } else {
+            0
+        };
+
+        let Ok(memory) = Memory::new(base, index, scale, displacement.into()) else {
+            return Err(DecodingError::UnsupportedInstruction);
+        };
+        Ok(ModRm {
+            reg,
+            rm: Rm::Memory(self.apply_segment(memory, prefixes)),
+        })
+    }
+
+    #[inline(always)]
+    fn apply_segment(&mut self, memory: Memory, prefixes: Prefixes) -> Memory {
+        match prefixes.segment {
+            Some(segment) => {
+                self.segment_used = true;
+                memory.with_segment(segment)
+            }
+            None => memory,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline(always)]
+    fn finish(&self, instruction: Instruction) -> DecodedInstruction {
+        DecodedInstruction::new(instruction, self.position as u8)
+    }
+}
+
+#[inline(always)]
+const fn xmm(index: u8) -> XMM {
+    unsafe { XMM::new_unchecked(MachineSize::XMMWord, index) }
+}
+
+fn decode_lea(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+) -> Result<DecodedInstruction, DecodingError> {
+    if prefixes.scalar_double || prefixes.scalar_single {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+
+    let size = if prefixes.rex_bit(REX_W) != 0 {
+        MachineSize::QWord
+    } else if prefixes.operand_size {
+        MachineSize::Word
+    } else {
+        MachineSize::DWord
+    };
+
+    let modrm = reader.read_modrm(prefixes)?;
+    let Rm::Memory(src) = modrm.rm else {
+        return Err(DecodingError::UnsupportedInstruction);
+    };
+    let dst = unsafe { GPR::new_unchecked(size, modrm.reg) };
+    Ok(reader.finish(Instruction::Lea { dst, src }))
+}
+
+/// Decodes `mov` and ALU instructions of `op r/m, reg` and `op reg, r/m` shape.
+/// The lowest two bits of the opcode select the operand size and direction.
+fn decode_rm_reg(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+    op: Option<ArithmeticOp>,
+) -> Result<DecodedInstruction, DecodingError> {
+    let size = prefixes.operand_size(opcode & 0b01 == 0);
+    let is_reg_destination = opcode & 0b10 != 0;
+    let modrm = reader.read_modrm(prefixes)?;
+    let reg = prefixes.gpr(size, modrm.reg);
+    let instruction = match (modrm.rm, is_reg_destination) {
+        (Rm::Register(rm), _) => {
+            let rm = prefixes.gpr(size, rm);
+            let (dst, src) = if is_reg_destination {
+                (reg, rm)
+            } else {
+                (rm, reg)
+            };
+            match op {
+                Some(op) => Instruction::ArithRegReg { op, dst, src },
+                None => Instruction::MovRegReg { dst, src },
+            }
+        }
+        (Rm::Memory(src), true) => match op {
+            Some(op) => Instruction::ArithRegMem { op, dst: reg, src },
+            None => Instruction::MovRegMem { dst: reg, src },
+        },
+        (Rm::Memory(dst), false) => match op {
+            Some(op) => Instruction::ArithMemReg { op, dst, src: reg },
+            None => Instruction::MovMemReg { dst, src: reg },
+        },
+    };
+    Ok(reader.finish(instruction))
+}
+
+/// Decodes `op reg, imm` in the accumulator short form.
+fn decode_arith_accumulator(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    let size = prefixes.operand_size(opcode & 0b01 == 0);
+    let dst = prefixes.gpr(size, 0);
+    let op = map_opcode_to_arithmetic_op(opcode >> 3);
+    let imm = reader.read_immediate(size, false)?;
+    #[allow(clippy::cast_possible_truncation)]
+    let imm = imm as i32;
+    Ok(reader.finish(Instruction::ArithRegImm { op, dst, imm }))
+}
+
+/// Decodes `op reg, imm` encoded with `0x80`, `0x81` and `0x83` opcodes.
+fn decode_arith_immediate(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    let size = prefixes.operand_size(opcode == 0x80);
+    let modrm = reader.read_modrm(prefixes)?;
+    let Rm::Register(rm) = modrm.rm else {
+        return Err(DecodingError::UnsupportedInstruction);
+    };
+    let dst = prefixes.gpr(size, rm);
+    let op = map_opcode_to_arithmetic_op(modrm.reg);
+    let imm = if opcode == 0x83 {
+        i64::from(reader.read_i8()?)
+    } else {
+        reader.read_immediate(size, false)?
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let imm = imm as i32;
+    Ok(reader.finish(Instruction::ArithRegImm { op, dst, imm }))
+}
+
+/// Decodes `mov reg, imm` encoded with `0xB0..=0xBF` and `0xC7` opcodes.
+fn decode_mov_immediate(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    let (size, index, full) = if opcode == 0xC7 {
+        let modrm = reader.read_modrm(prefixes)?;
+        let Rm::Register(rm) = modrm.rm else {
+            return Err(DecodingError::UnsupportedInstruction);
+        };
+        if modrm.reg != 0 {
+            return Err(DecodingError::UnsupportedInstruction);
+        }
+        (prefixes.operand_size(false), rm, false)
+    } else {
+        let size = prefixes.operand_size(opcode < 0xB8);
+        (size, (opcode & 0b111) | prefixes.rex_bit(REX_B), true)
+    };
+    let dst = prefixes.gpr(size, index);
+    let imm = reader.read_immediate(size, full)?;
+    Ok(reader.finish(Instruction::MovRegImm { dst, imm }))
+}
+
+/// Reads `ModRM` of an instruction operating on a single general purpose register
+/// given in r/m. Returns the opcode extension together with the register.
+fn read_register_operand(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    size: MachineSize,
+) -> Result<(u8, GPR), DecodingError> {
+    let modrm = reader.read_modrm(prefixes)?;
+    let Rm::Register(rm) = modrm.rm else {
+        return Err(DecodingError::UnsupportedInstruction);
+    };
+    Ok((modrm.reg, prefixes.gpr(size, rm)))
+}
+
+/// Decodes `movsxd` encoded with `0x63` opcode. Only the 64-bit destination form
+/// is supported.
+fn decode_movsxd(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+) -> Result<DecodedInstruction, DecodingError> {
+    if prefixes.rex_bit(REX_W) == 0 || !prefixes.is_general_purpose() {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+    let modrm = reader.read_modrm(prefixes)?;
+    let dst = prefixes.gpr(MachineSize::QWord, modrm.reg);
+    let instruction = match modrm.rm {
+        Rm::Register(rm) => Instruction::MovsxdRegReg {
+            dst,
+            src: prefixes.gpr(MachineSize::DWord, rm),
+        },
+        Rm::Memory(src) => Instruction::MovsxdRegMem { dst, src },
+    };
+    Ok(reader.finish(instruction))
+}
+
+/// Decodes `div` and `idiv` encoded with `0xF6` and `0xF7` opcodes.
+fn decode_division(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    let size = prefixes.operand_size(opcode == 0xF6);
+    let (extension, src) = read_register_operand(reader, prefixes, size)?;
+    let instruction = match extension {
+        6 => Instruction::Div { src },
+        7 => Instruction::Idiv { src },
+        _ => return Err(DecodingError::UnsupportedInstruction),
+    };
+    Ok(reader.finish(instruction))
+}
+
+/// Decodes shifts encoded with `0xC0`, `0xC1` and `0xD0..=0xD3` opcodes.
+fn decode_shift(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    let size = prefixes.operand_size(opcode & 0b01 == 0);
+    let (extension, dst) = read_register_operand(reader, prefixes, size)?;
+    let Some(op) = map_extension_to_shift_op(extension) else {
+        return Err(DecodingError::UnsupportedInstruction);
+    };
+    let instruction = match opcode {
+        0xC0 | 0xC1 => Instruction::ShiftRegImm {
+            op,
+            dst,
+            imm: reader.read_u8()?,
+        },
+        0xD0 | 0xD1 => Instruction::ShiftRegImm { op, dst, imm: 1 },
+        _ => Instruction::ShiftRegCl { op, dst },
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_imul(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+) -> Result<DecodedInstruction, DecodingError> {
+    if !prefixes.is_general_purpose() {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+    let size = prefixes.operand_size(false);
+    let modrm = reader.read_modrm(prefixes)?;
+    let dst = prefixes.gpr(size, modrm.reg);
+    let instruction = match modrm.rm {
+        Rm::Register(rm) => Instruction::ImulRegReg {
+            dst,
+            src: prefixes.gpr(size, rm),
+        },
+        Rm::Memory(src) => Instruction::ImulRegMem { dst, src },
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_bit_count(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    if !prefixes.scalar_single || prefixes.scalar_double {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+    let op = match opcode {
+        0xB8 => BitCountOp::Popcnt,
+        0xBC => BitCountOp::Tzcnt,
+        _ => BitCountOp::Lzcnt,
+    };
+    let size = prefixes.operand_size(false);
+    let modrm = reader.read_modrm(prefixes)?;
+    let dst = prefixes.gpr(size, modrm.reg);
+    let instruction = match modrm.rm {
+        Rm::Register(rm) => Instruction::BitCountRegReg {
+            op,
+            dst,
+            src: prefixes.gpr(size, rm),
+        },
+        Rm::Memory(src) => Instruction::BitCountRegMem { op, dst, src },
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_float_arith(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    if prefixes.operand_size
+        || prefixes.rex_bit(REX_W) != 0
+        || prefixes.scalar_double == prefixes.scalar_single
+    {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+    let Some(op) = map_opcode_to_float_op(opcode, prefixes.scalar_double) else {
+        return Err(DecodingError::UnsupportedInstruction);
+    };
+
+    let modrm = reader.read_modrm(prefixes)?;
+    let dst = xmm(modrm.reg);
+    let instruction = match modrm.rm {
+        Rm::Register(rm) => Instruction::FloatArithXmmXmm {
+            op,
+            dst,
+            src: xmm(rm),
+        },
+        Rm::Memory(src) => Instruction::FloatArithXmmMem { op, dst, src },
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_scalar_move(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    if prefixes.operand_size
+        || prefixes.rex_bit(REX_W) != 0
+        || prefixes.scalar_double == prefixes.scalar_single
+    {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+
+    let modrm = reader.read_modrm(prefixes)?;
+    let reg = xmm(modrm.reg);
+    let instruction = match (prefixes.scalar_double, opcode, modrm.rm) {
+        (true, 0x10, Rm::Register(rm)) => Instruction::MovsdXmmXmm {
+            dst: reg,
+            src: xmm(rm),
+        },
+        (true, 0x11, Rm::Register(rm)) => Instruction::MovsdXmmXmm {
+            dst: xmm(rm),
+            src: reg,
+        },
+        (true, 0x10, Rm::Memory(mem)) => Instruction::MovsdXmmMem { dst: reg, src: mem },
+        (true, _, Rm::Memory(mem)) => Instruction::MovsdMemXmm { dst: mem, src: reg },
+        (false, 0x10, Rm::Register(rm)) => Instruction::MovssXmmXmm {
+            dst: reg,
+            src: xmm(rm),
+        },
+        (false, 0x11, Rm::Register(rm)) => Instruction::MovssXmmXmm {
+            dst: xmm(rm),
+            src: reg,
+        },
+        (false, 0x10, Rm::Memory(mem)) => {
+            Instruction::MovssXmmMem { dst: reg, src: mem }
+        }
+        (false, _, Rm::Memory(mem)) => Instruction::MovssMemXmm { dst: mem, src: reg },
+        _ => return Err(DecodingError::UnsupportedInstruction),
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_movaps(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+    opcode: u8,
+) -> Result<DecodedInstruction, DecodingError> {
+    if prefixes.operand_size
+        || !prefixes.is_general_purpose()
+        || prefixes.rex_bit(REX_W) != 0
+    {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+
+    let modrm = reader.read_modrm(prefixes)?;
+    let reg = xmm(modrm.reg);
+    let instruction = match (opcode, modrm.rm) {
+        (0x28, Rm::Register(rm)) => Instruction::MovapsXmmXmm {
+            dst: reg,
+            src: xmm(rm),
+        },
+        (_, Rm::Register(rm)) => Instruction::MovapsXmmXmm {
+            dst: xmm(rm),
+            src: reg,
+        },
+        (0x28, Rm::Memory(mem)) => Instruction::MovapsXmmMem { dst: reg, src: mem },
+        (_, Rm::Memory(mem)) => Instruction::MovapsMemXmm { dst: mem, src: reg },
+    };
+    Ok(reader.finish(instruction))
+}
+
+fn decode_two_byte_opcode(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+) -> Result<DecodedInstruction, DecodingError> {
+    let opcode = reader.read_u8()?;
+    match opcode {
+        0x80..=0x8F if prefixes.is_empty() => {
+            let cond = map_opcode_to_cond(opcode);
+            let rel = reader.read_i32()?;
+            Ok(reader.finish(Instruction::JccRel32 { cond, rel }))
+        }
+        0x10 | 0x11 => decode_scalar_move(reader, prefixes, opcode),
+        0x28 | 0x29 => decode_movaps(reader, prefixes, opcode),
+        0x58 | 0x59 | 0x5C | 0x5E => decode_float_arith(reader, prefixes, opcode),
+        0xAF => decode_imul(reader, prefixes),
+        0xB8 | 0xBC | 0xBD => decode_bit_count(reader, prefixes, opcode),
+        0x1F if !prefixes.scalar_double && !prefixes.scalar_single => {
+            let modrm = reader.read_modrm(prefixes)?;
+            if modrm.reg != 0 {
+                return Err(DecodingError::UnsupportedInstruction);
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            let size = reader.position as u8;
+            Ok(reader.finish(Instruction::Nop { size }))
+        }
+        _ => Err(DecodingError::UnsupportedInstruction),
+    }
+}
+
+/// Decodes a single instruction from the beginning of `bytes`. Trailing bytes
+/// are ignored.
+///
+/// # Errors
+/// * [`DecodingError::UnexpectedEnd`] if `bytes` ends in the middle of an instruction.
+/// * [`DecodingError::UnsupportedInstruction`] if the instruction is not (yet)
+///   understood by the decoder.
+/// * [`DecodingError::InstructionTooLong`] if the instruction would exceed
+///   [`MAX_INSTRUCTION_SIZE`] bytes.
+pub fn decode(bytes: &[u8]) -> Result<DecodedInstruction, DecodingError> {
+    let mut reader = Reader::new(bytes);
+    let prefixes = reader.read_prefixes();
+    let decoded = decode_instruction(&mut reader, prefixes)?;
+
+    // Segment override is only meaningful for memory operands.
+    if prefixes.segment.is_some() && !reader.segment_used {
+        return Err(DecodingError::UnsupportedInstruction);
+    }
+    Ok(decoded)
+}
+
+fn decode_instruction(
+    reader: &mut Reader,
+    prefixes: Prefixes,
+) -> Result<DecodedInstruction, DecodingError> {
+    let only_operand_size = Prefixes {
+        operand_size: false,
+        ..prefixes
+    }
+    .is_empty();
+
+    let opcode = reader.read_u8()?;
+    match opcode {
+        0x90 if only_operand_size => {
+            #[allow(clippy::cast_possible_truncation)]
+            let size = reader.position as u8;
+            Ok(reader.finish(Instruction::Nop { size }))
+        }
+        0xC3 if prefixes.is_empty() => Ok(reader.finish(Instruction::Ret)),
+        0xEB if prefixes.is_empty() => {
+            let rel = reader.read_i8()?;
+            Ok(reader.finish(Instruction::JmpRel8 { rel }))
+        }
+        0xE9 if prefixes.is_empty() => {
+            let rel = reader.read_i32()?;
+            Ok(reader.finish(Instruction::JmpRel32 { rel }))
+        }
+        0x70..=0x7F if prefixes.is_empty() => {
+            let cond = map_opcode_to_cond(opcode);
+            let rel = reader.read_i8()?;
+            Ok(reader.finish(Instruction::JccRel8 { cond, rel }))
+        }
+        0x8D => decode_lea(reader, prefixes),
+        0x50..=0x5F if !prefixes.operand_size && prefixes.is_general_purpose() => {
+            let index = (opcode & 0b111) | prefixes.rex_bit(REX_B);
+            let reg = unsafe { GPR::new_unchecked(MachineSize::QWord, index) };
+            let instruction = if opcode < 0x58 {
+                Instruction::Push { reg }
+            } else {
+                Instruction::Pop { reg }
+            };
+            Ok(reader.finish(instruction))
+        }
+        0x00..=0x3F if opcode & 0b111 < 4 && prefixes.is_general_purpose() => {
+            let op = map_opcode_to_arithmetic_op(opcode >> 3);
+            decode_rm_reg(reader, prefixes, opcode, Some(op))
+        }
+        0x00..=0x3F if opcode & 0b111 < 6 && prefixes.is_general_purpose() => {
+            decode_arith_accumulator(reader, prefixes, opcode)
+        }
+        0x80 | 0x81 | 0x83 if prefixes.is_general_purpose() => {
+            decode_arith_immediate(reader, prefixes, opcode)
+        }
+        0x88..=0x8B if prefixes.is_general_purpose() => {
+            decode_rm_reg(reader, prefixes, opcode, None)
+        }
+        0x99 if !prefixes.operand_size && prefixes.is_general_purpose() => {
+            let instruction = if prefixes.rex_bit(REX_W) != 0 {
+                Instruction::Cqo
+            } else {
+                Instruction::Cdq
+            };
+            Ok(reader.finish(instruction))
+        }
+        0x63 => decode_movsxd(reader, prefixes),
+        0xE8 if prefixes.is_empty() => {
+            let rel = reader.read_i32()?;
+            Ok(reader.finish(Instruction::CallRel32 { rel }))
+        }
+        0xFF if !prefixes.operand_size && prefixes.is_general_purpose() => {
+            let (extension, target) =
+                read_register_operand(reader, prefixes, MachineSize::QWord)?;
+            let instruction = match extension {
+                2 => Instruction::CallReg { target },
+                4 => Instruction::JmpReg { target },
+                _ => return Err(DecodingError::UnsupportedInstruction),
+            };
+            Ok(reader.finish(instruction))
+        }
+        0xF6 | 0xF7 if prefixes.is_general_purpose() => {
+            decode_division(reader, prefixes, opcode)
+        }
+        0xC0 | 0xC1 | 0xD0..=0xD3 if prefixes.is_general_purpose() => {
+            decode_shift(reader, prefixes, opcode)
+        }
+        0xB0..=0xBF | 0xC7 if prefixes.is_general_purpose() => {
+            decode_mov_immediate(reader, prefixes, opcode)
+        }
+        0x0F => decode_two_byte_opcode(reader, prefixes),
+        _ => Err(DecodingError::UnsupportedInstruction),
+    }
+}
+
+/// Iterator decoding consecutive instructions from a byte slice. Yields pairs
+/// of the instruction offset and the instruction itself.
+///
+/// # Notes
+/// The iteration stops after the first error.
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Returns the offset of the next instruction to decode.
+    #[must_use]
+    #[inline(always)]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Result<(usize, DecodedInstruction), DecodingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.bytes.len() {
+            return None;
+        }
+
+        let offset = self.offset;
+        match decode(&self.bytes[offset..]) {
+            Ok(decoded) => {
+                self.offset += decoded.len() as usize;
+                Some(Ok((offset, decoded)))
+            }
+            Err(err) => {
+                self.offset = self.bytes.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&[0xEB])]
+    #[case(&[0xE9, 0x00, 0x00, 0x00])]
+    #[case(&[0x0F])]
+    #[case(&[0x0F, 0x84, 0x00])]
+    #[case(&[0x0F, 0x1F, 0x84, 0x00])]
+    fn test_unexpected_end(#[case] bytes: &[u8]) {
+        assert_eq!(decode(bytes), Err(DecodingError::UnexpectedEnd));
+    }
+
+    #[rstest]
+    #[case(&[0x06])]
+    #[case(&[0x0F, 0x0B])]
+    #[case(&[0x0F, 0x1F, 0x08])]
+    #[case(&[0x66, 0xC3])]
+    #[case(&[0x41, 0x90])]
+    #[case(&[0x48, 0x8D, 0xC0])]
+    #[case(&[0xF2, 0xF3, 0x0F, 0x10, 0xC0])]
+    #[case(&[0x0F, 0xBD, 0xC1])]
+    #[case(&[0x64, 0xC3])]
+    #[case(&[0x65, 0x48, 0x89, 0xD8])]
+    #[case(&[0x64, 0x50])]
+    fn test_unsupported(#[case] bytes: &[u8]) {
+        assert_eq!(decode(bytes), Err(DecodingError::UnsupportedInstruction));
+    }
+
+    #[test]
+    fn test_too_long() {
+        let mut bytes = [OPERAND_SIZE_PREFIX; 16];
+        bytes[15] = 0x90;
+        assert_eq!(decode(&bytes), Err(DecodingError::InstructionTooLong));
+
+        bytes[14] = 0x90;
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.len(), 15);
+        assert_eq!(decoded.instruction(), Instruction::Nop { size: 15 });
+    }
+
+    #[rstest]
+    #[case(&[0x88, 0xE0], GPR::AL, GPR::AH)]
+    #[case(&[0x88, 0xFC], GPR::AH, GPR::BH)]
+    #[case(&[0x40, 0x88, 0xE0], GPR::AL, GPR::SPL)]
+    #[case(&[0x41, 0x88, 0xE0], GPR::R8B, GPR::SPL)]
+    fn test_byte_registers(#[case] bytes: &[u8], #[case] dst: GPR, #[case] src: GPR) {
+        let decoded = decode(bytes).unwrap();
+        assert_eq!(decoded.instruction(), Instruction::MovRegReg { dst, src });
+    }
+
+    #[rstest]
+    #[case(&[0x64, 0x48, 0x8B, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00], Memory::thread_local(0).unwrap())]
+    #[case(&[0x65, 0x48, 0x8B, 0x40, 0x08], Memory::based(GPR::RAX, 8).unwrap().with_segment(Segment::Gs))]
+    #[case(&[0x64, 0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00], Memory::rip_relative(0x10).unwrap().with_segment(Segment::Fs))]
+    fn test_segment_override(#[case] bytes: &[u8], #[case] src: Memory) {
+        let decoded = decode(bytes).unwrap();
+        let dst = GPR::RAX;
+        assert_eq!(decoded.instruction(), Instruction::MovRegMem { dst, src });
+        assert_eq!(usize::from(decoded.len()), bytes.len());
+    }
+
+    #[test]
+    fn test_decoder_iterator() {
+        let bytes = [0x90, 0x74, 0x02, 0xE9, 0x10, 0x00, 0x00, 0x00, 0xC3, 0x06];
+        let decoded: Vec<_> = Decoder::new(&bytes).collect();
+        assert_eq!(
+            decoded,
+            vec![
+                Ok((0, DecodedInstruction::new(Instruction::Nop { size: 1 }, 1))),
+                Ok((
+                    1,
+                    DecodedInstruction::new(
+                        Instruction::JccRel8 {
+                            cond: Condition::Equal,
+                            rel: 2
+                        },
+                        2
+                    )
+                )),
+                Ok((
+                    3,
+                    DecodedInstruction::new(Instruction::JmpRel32 { rel: 16 }, 5)
+                )),
+                Ok((8, DecodedInstruction::new(Instruction::Ret, 1))),
+                Err(DecodingError::UnsupportedInstruction),
+            ]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+#![allow(clippy::len_without_is_empty)]
+
+use crate::{
+    constants::MAX_INSTRUCTION_SIZE,
+    models::{
+        ArithmeticOp, BitCountOp, Condition, FloatArithmeticOp, Memory, ShiftOp, GPR,
+        XMM,
+    },
+};
+
+/// Represents a structured x64 instruction, as understood by the decoder.
+///
+/// # Notes
+/// Some encodings are shared between several [`Condition`] values (e.g. `jb` and `jc`).
+/// The decoder always returns the first one listed in the encoder's opcode table,
+/// i.e. [`Condition::Below`], [`Condition::AboveOrEqual`], [`Condition::Equal`] and
+/// [`Condition::NotEqual`].
+///
+/// Immediates are always sign extended from their encoded width, e.g. `mov al, 0xFF`
+/// is decoded with `imm` equal to `-1`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Instruction {
+    Ret,
+
+    /// NOP of `size` bytes, including all prefixes and operands.
+    Nop {
+        size: u8,
+    },
+
+    JmpRel8 {
+        rel: i8,
+    },
+
+    JmpRel32 {
+        rel: i32,
+    },
+
+    JccRel8 {
+        cond: Condition,
+        rel: i8,
+    },
+
+    JccRel32 {
+        cond: Condition,
+        rel: i32,
+    },
+
+    /// Indirect jump to the address held in `target`.
+    JmpReg {
+        target: GPR,
+    },
+
+    CallRel32 {
+        rel: i32,
+    },
+
+    /// Indirect call of the address held in `target`.
+    CallReg {
+        target: GPR,
+    },
+
+    Lea {
+        dst: GPR,
+        src: Memory,
+    },
+
+    Push {
+        reg: GPR,
+    },
+
+    Pop {
+        reg: GPR,
+    },
+
+    MovRegReg {
+        dst: GPR,
+        src: GPR,
+    },
+
+    MovRegImm {
+        dst: GPR,
+        imm: i64,
+    },
+
+    MovRegMem {
+        dst: GPR,
+        src: Memory,
+    },
+
+    MovMemReg {
+        dst: Memory,
+        src: GPR,
+    },
+
+    MovsxdRegReg {
+        dst: GPR,
+        src: GPR,
+    },
+
+    MovsxdRegMem {
+        dst: GPR,
+        src: Memory,
+    },
+
+    ArithRegReg {
+        op: ArithmeticOp,
+        dst: GPR,
+        src: GPR,
+    },
+
+    ArithRegImm {
+        op: ArithmeticOp,
+        dst: GPR,
+        imm: i32,
+    },
+
+    ArithRegMem {
+        op: ArithmeticOp,
+        dst: GPR,
+        src: Memory,
+    },
+
+    ArithMemReg {
+        op: ArithmeticOp,
+        dst: Memory,
+        src: GPR,
+    },
+
+    ImulRegReg {
+        dst: GPR,
+        src: GPR,
+    },
+
+    ImulRegMem {
+        dst: GPR,
+        src: Memory,
+    },
+
+    Div {
+        src: GPR,
+    },
+
+    Idiv {
+        src: GPR,
+    },
+
+    Cqo,
+    Cdq,
+
+    ShiftRegCl {
+        op: ShiftOp,
+        dst: GPR,
+    },
+
+    ShiftRegImm {
+        op: ShiftOp,
+        dst: GPR,
+        imm: u8,
+    },
+
+    BitCountRegReg {
+        op: BitCountOp,
+        dst: GPR,
+        src: GPR,
+    },
+
+    BitCountRegMem {
+        op: BitCountOp,
+        dst: GPR,
+        src: Memory,
+    },
+
+    MovsdXmmXmm {
+        dst: XMM,
+        src: XMM,
+    },
+
+    MovsdXmmMem {
+        dst: XMM,
+        src: Memory,
+    },
+
+    MovsdMemXmm {
+        dst: Memory,
+        src: XMM,
+    },
+
+    MovssXmmXmm {
+        dst: XMM,
+        src: XMM,
+    },
+
+    MovssXmmMem {
+        dst: XMM,
+        src: Memory,
+    },
+
+    MovssMemXmm {
+        dst: Memory,
+        src: XMM,
+    },
+
+    FloatArithXmmXmm {
+        op: FloatArithmeticOp,
+        dst: XMM,
+        src: XMM,
+    },
+
+    FloatArithXmmMem {
+        op: FloatArithmeticOp,
+        dst: XMM,
+        src: Memory,
+    },
+
+    MovapsXmmXmm {
+        dst: XMM,
+        src: XMM,
+    },
+
+    MovapsXmmMem {
+        dst: XMM,
+        src: Memory,
+    },
+
+    MovapsMemXmm {
+        dst: Memory,
+        src: XMM,
+    },
+}
+
+/// Represents a decoded x64 instruction together with the number of bytes it occupied.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct DecodedInstruction {
+    instruction: Instruction,
+    len: u8,
+}
+
+impl DecodedInstruction {
+    #[must_use]
+    #[inline(always)]
+    pub(super) const fn new(instruction: Instruction, len: u8) -> Self {
+        debug_assert!(len as usize <= MAX_INSTRUCTION_SIZE);
+        Self { instruction, len }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn instruction(&self) -> Instruction {
+        self.instruction
+    }
+
+    /// Returns the length of the instruction in bytes. Always in `1..=15` range.
+    #[must_use]
+    #[inline(always)]
+    pub const fn len(&self) -> u8 {
+        self.len
+    }
+}
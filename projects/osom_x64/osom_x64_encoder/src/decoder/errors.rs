@@ -0,0 +1,12 @@
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DecodingError {
+    /// The byte slice ended in the middle of an instruction.
+    UnexpectedEnd,
+
+    /// The bytes do not form an instruction supported by the decoder.
+    UnsupportedInstruction,
+
+    /// The instruction would exceed [`MAX_INSTRUCTION_SIZE`][crate::constants::MAX_INSTRUCTION_SIZE].
+    InstructionTooLong,
+}
@@ -0,0 +1,542 @@
+//! Registers, flags and memory touched by [`Instruction`]s.
+
+use crate::models::{
+    ArithmeticOp, CpuFeature, Flags, GprSet, MachineSize, Memory, ShiftOp, XmmSet, GPR,
+    XMM,
+};
+
+use super::Instruction;
+
+/// How an instruction accesses memory through its operands, or the stack.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum MemoryAccess {
+    #[default]
+    None,
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl MemoryAccess {
+    #[must_use]
+    #[inline(always)]
+    pub const fn reads(self) -> bool {
+        matches!(self, MemoryAccess::Read | MemoryAccess::ReadWrite)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn writes(self) -> bool {
+        matches!(self, MemoryAccess::Write | MemoryAccess::ReadWrite)
+    }
+}
+
+/// How an instruction changes the control flow.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum ControlFlow {
+    /// Continues with the next instruction.
+    #[default]
+    None,
+
+    /// Unconditional jump to a relative target.
+    Jump,
+
+    /// Conditional jump to a relative target, otherwise continues with the next
+    /// instruction.
+    ConditionalJump,
+
+    /// Unconditional jump to the address held in a register.
+    IndirectJump,
+
+    /// Call, continues with the next instruction when the callee returns.
+    Call,
+
+    Return,
+}
+
+/// Describes what an [`Instruction`] reads and writes, as returned by
+/// [`Instruction::info`].
+///
+/// # Notes
+/// Registers are reported by index, including implicit ones, e.g. `rdx` and `rax`
+/// for `div`, and `rsp` for `push`. Writes of 8-bit and 16-bit registers keep the
+/// remaining bits, thus such registers are reported as read too. Registers used
+/// to compute memory addresses are reported as read.
+///
+/// Flags left undefined by an instruction are reported as written. Flags kept
+/// intact depending on a runtime value, e.g. by shifts by `cl`, are reported as
+/// both read and written. Calls are described without the effects of the callee.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct InstructionInfo {
+    gprs_read: GprSet,
+    gprs_written: GprSet,
+    xmms_read: XmmSet,
+    xmms_written: XmmSet,
+    flags_read: Flags,
+    flags_written: Flags,
+    memory: MemoryAccess,
+    control_flow: ControlFlow,
+}
+
+impl InstructionInfo {
+    #[must_use]
+    #[inline(always)]
+    pub const fn gprs_read(&self) -> GprSet {
+        self.gprs_read
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn gprs_written(&self) -> GprSet {
+        self.gprs_written
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn xmms_read(&self) -> XmmSet {
+        self.xmms_read
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn xmms_written(&self) -> XmmSet {
+        self.xmms_written
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn flags_read(&self) -> Flags {
+        self.flags_read
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn flags_written(&self) -> Flags {
+        self.flags_written
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn memory(&self) -> MemoryAccess {
+        self.memory
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn control_flow(&self) -> ControlFlow {
+        self.control_flow
+    }
+
+    /// Returns `true` if the instruction may continue anywhere but the next
+    /// instruction, calls included.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_branch(&self) -> bool {
+        !matches!(self.control_flow(), ControlFlow::None)
+    }
+
+    /// Returns `true` if the instruction never continues with the next one,
+    /// i.e. ends a basic block without a fallthrough.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_terminator(&self) -> bool {
+        matches!(
+            self.control_flow(),
+            ControlFlow::Jump | ControlFlow::IndirectJump | ControlFlow::Return
+        )
+    }
+
+    fn read(&mut self, reg: GPR) -> &mut Self {
+        self.gprs_read = self.gprs_read.with(reg);
+        self
+    }
+
+    /// Records a write of `reg`, and a read for registers narrower than 32 bits.
+    fn write(&mut self, reg: GPR) -> &mut Self {
+        if matches!(reg.size(), MachineSize::Byte | MachineSize::Word) {
+            self.read(reg);
+        }
+        self.gprs_written = self.gprs_written.with(reg);
+        self
+    }
+
+    fn read_xmm(&mut self, reg: XMM) -> &mut Self {
+        self.xmms_read = self.xmms_read.with(reg);
+        self
+    }
+
+    fn write_xmm(&mut self, reg: XMM) -> &mut Self {
+        self.xmms_written = self.xmms_written.with(reg);
+        self
+    }
+
+    /// Records registers of the `memory` address, and the access itself.
+    fn access(&mut self, memory: Memory, access: MemoryAccess) -> &mut Self {
+        if memory.has_base() {
+            self.read(memory.base());
+        }
+        if memory.has_index() {
+            self.read(memory.index());
+        }
+        self.memory = access;
+        self
+    }
+
+    /// Records the `memory` address without accessing memory, as in `lea`.
+    fn address(&mut self, memory: Memory) -> &mut Self {
+        self.access(memory, MemoryAccess::None)
+    }
+
+    fn stack(&mut self, access: MemoryAccess) -> &mut Self {
+        self.read(GPR::RSP).write(GPR::RSP);
+        self.memory = access;
+        self
+    }
+
+    fn flags(&mut self, read: Flags, written: Flags) -> &mut Self {
+        self.flags_read = read;
+        self.flags_written = written;
+        self
+    }
+
+    fn branch(&mut self, control_flow: ControlFlow) -> &mut Self {
+        self.control_flow = control_flow;
+        self
+    }
+}
+
+/// Returns flags read by arithmetic `op`.
+const fn arithmetic_flags_read(op: ArithmeticOp) -> Flags {
+    match op {
+        ArithmeticOp::Adc | ArithmeticOp::Sbb => Flags::CARRY,
+        _ => Flags::NONE,
+    }
+}
+
+/// Returns flags read and written by shift `op` of `dst` by `count`, or by `cl`
+/// if `count` is [`None`].
+const fn shift_flags(op: ShiftOp, dst: GPR, count: Option<u8>) -> (Flags, Flags) {
+    let written = match op {
+        ShiftOp::Rol | ShiftOp::Ror => Flags::CARRY.union(Flags::OVERFLOW),
+        ShiftOp::Shl | ShiftOp::Shr | ShiftOp::Sar => Flags::STATUS,
+    };
+    let mask = if matches!(dst.size(), MachineSize::QWord) {
+        0x3F
+    } else {
+        0x1F
+    };
+    match count {
+        Some(count) if count & mask == 0 => (Flags::NONE, Flags::NONE),
+        Some(_) => (Flags::NONE, written),
+        None => (written, written),
+    }
+}
+
+impl Instruction {
+    /// Returns registers, flags and memory read and written by the instruction,
+    /// and its effect on the control flow.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn info(&self) -> InstructionInfo {
+        let mut info = InstructionInfo::default();
+        match *self {
+            Instruction::Nop { .. } => {}
+            Instruction::Ret => {
+                info.stack(MemoryAccess::Read).branch(ControlFlow::Return);
+            }
+            Instruction::JmpRel8 { .. } | Instruction::JmpRel32 { .. } => {
+                info.branch(ControlFlow::Jump);
+            }
+            Instruction::JccRel8 { cond, .. } | Instruction::JccRel32 { cond, .. } => {
+                info.flags(cond.flags(), Flags::NONE)
+                    .branch(ControlFlow::ConditionalJump);
+            }
+            Instruction::JmpReg { target } => {
+                info.read(target).branch(ControlFlow::IndirectJump);
+            }
+            Instruction::CallRel32 { .. } => {
+                info.stack(MemoryAccess::Write).branch(ControlFlow::Call);
+            }
+            Instruction::CallReg { target } => {
+                info.read(target)
+                    .stack(MemoryAccess::Write)
+                    .branch(ControlFlow::Call);
+            }
+            Instruction::Lea { dst, src } => {
+                info.address(src).write(dst);
+            }
+            Instruction::Push { reg } => {
+                info.read(reg).stack(MemoryAccess::Write);
+            }
+            Instruction::Pop { reg } => {
+                info.stack(MemoryAccess::Read).write(reg);
+            }
+            Instruction::MovRegReg { dst, src }
+            | Instruction::MovsxdRegReg { dst, src } => {
+                info.read(src).write(dst);
+            }
+            Instruction::MovRegImm { dst, .. } => {
+                info.write(dst);
+            }
+            Instruction::MovRegMem { dst, src }
+            | Instruction::MovsxdRegMem { dst, src } => {
+                info.access(src, MemoryAccess::Read).write(dst);
+            }
+            Instruction::MovMemReg { dst, src } => {
+                info.read(src).access(dst, MemoryAccess::Write);
+            }
+            Instruction::ArithRegReg { op, dst, src } => {
+                info.read(dst)
+                    .read(src)
+                    .flags(arithmetic_flags_read(op), Flags::STATUS);
+                if !matches!(op, ArithmeticOp::Cmp) {
+                    info.write(dst);
+                }
+            }
+            Instruction::ArithRegImm { op, dst, .. } => {
+                info.read(dst)
+                    .flags(arithmetic_flags_read(op), Flags::STATUS);
+                if !matches!(op, ArithmeticOp::Cmp) {
+                    info.write(dst);
+                }
+            }
+            Instruction::ArithRegMem { op, dst, src } => {
+                info.read(dst)
+                    .access(src, MemoryAccess::Read)
+                    .flags(arithmetic_flags_read(op), Flags::STATUS);
+                if !matches!(op, ArithmeticOp::Cmp) {
+                    info.write(dst);
+                }
+            }
+            Instruction::ArithMemReg { op, dst, src } => {
+                let access = if matches!(op, ArithmeticOp::Cmp) {
+                    MemoryAccess::Read
+                } else {
+                    MemoryAccess::ReadWrite
+                };
+                info.read(src)
+                    .access(dst, access)
+                    .flags(arithmetic_flags_read(op), Flags::STATUS);
+            }
+            Instruction::ImulRegReg { dst, src } => {
+                info.read(dst)
+                    .read(src)
+                    .write(dst)
+                    .flags(Flags::NONE, Flags::STATUS);
+            }
+            Instruction::ImulRegMem { dst, src } => {
+                info.read(dst)
+                    .access(src, MemoryAccess::Read)
+                    .write(dst)
+                    .flags(Flags::NONE, Flags::STATUS);
+            }
+            Instruction::Div { src } | Instruction::Idiv { src } => {
+                // 8-bit division uses `ax` only, wider ones `rdx:rax`.
+                info.read(src).read(GPR::RAX).write(GPR::RAX);
+                if !matches!(src.size(), MachineSize::Byte) {
+                    info.read(GPR::RDX).write(GPR::RDX);
+                }
+                info.flags(Flags::NONE, Flags::STATUS);
+            }
+            Instruction::Cqo => {
+                info.read(GPR::RAX).write(GPR::RDX);
+            }
+            Instruction::Cdq => {
+                info.read(GPR::EAX).write(GPR::EDX);
+            }
+            Instruction::ShiftRegCl { op, dst } => {
+                let (read, written) = shift_flags(op, dst, None);
+                info.read(dst).read(GPR::CL).write(dst).flags(read, written);
+            }
+            Instruction::ShiftRegImm { op, dst, imm } => {
+                let (read, written) = shift_flags(op, dst, Some(imm));
+                info.read(dst).write(dst).flags(read, written);
+            }
+            Instruction::BitCountRegReg { dst, src, .. } => {
+                info.read(src).write(dst).flags(Flags::NONE, Flags::STATUS);
+            }
+            Instruction::BitCountRegMem { dst, src, .. } => {
+                info.access(src, MemoryAccess::Read)
+                    .write(dst)
+                    .flags(Flags::NONE, Flags::STATUS);
+            }
+            // Register to register scalar moves keep the upper part of `dst`.
+            Instruction::MovsdXmmXmm { dst, src }
+            | Instruction::MovssXmmXmm { dst, src }
+            | Instruction::FloatArithXmmXmm { dst, src, .. } => {
+                info.read_xmm(dst).read_xmm(src).write_xmm(dst);
+            }
+            Instruction::MovapsXmmXmm { dst, src } => {
+                info.read_xmm(src).write_xmm(dst);
+            }
+            Instruction::MovsdXmmMem { dst, src }
+            | Instruction::MovssXmmMem { dst, src }
+            | Instruction::MovapsXmmMem { dst, src } => {
+                info.access(src, MemoryAccess::Read).write_xmm(dst);
+            }
+            Instruction::FloatArithXmmMem { dst, src, .. } => {
+                info.read_xmm(dst)
+                    .access(src, MemoryAccess::Read)
+                    .write_xmm(dst);
+            }
+            Instruction::MovsdMemXmm { dst, src }
+            | Instruction::MovssMemXmm { dst, src }
+            | Instruction::MovapsMemXmm { dst, src } => {
+                info.read_xmm(src).access(dst, MemoryAccess::Write);
+            }
+        }
+        info
+    }
+
+    /// Returns the ISA extension the instruction depends on, or [`None`] for
+    /// instructions available on every x86-64 CPU, SSE and SSE2 aside.
+    #[must_use]
+    pub const fn required_feature(&self) -> Option<CpuFeature> {
+        match *self {
+            Instruction::BitCountRegReg { op, .. }
+            | Instruction::BitCountRegMem { op, .. } => Some(op.required_feature()),
+            Instruction::FloatArithXmmXmm { op, .. }
+            | Instruction::FloatArithXmmMem { op, .. } => {
+                if op.is_double() {
+                    Some(CpuFeature::Sse2)
+                } else {
+                    Some(CpuFeature::Sse)
+                }
+            }
+            Instruction::MovsdXmmXmm { .. }
+            | Instruction::MovsdXmmMem { .. }
+            | Instruction::MovsdMemXmm { .. } => Some(CpuFeature::Sse2),
+            Instruction::MovssXmmXmm { .. }
+            | Instruction::MovssXmmMem { .. }
+            | Instruction::MovssMemXmm { .. }
+            | Instruction::MovapsXmmXmm { .. }
+            | Instruction::MovapsXmmMem { .. }
+            | Instruction::MovapsMemXmm { .. } => Some(CpuFeature::Sse),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::models::{BitCountOp, Condition, Displacement, FloatArithmeticOp};
+
+    fn gprs(regs: &[GPR]) -> GprSet {
+        regs.iter().fold(GprSet::EMPTY, |set, reg| set.with(*reg))
+    }
+
+    #[rstest]
+    #[case(Instruction::Div { src: GPR::RCX }, &[GPR::RCX, GPR::RAX, GPR::RDX], &[GPR::RAX, GPR::RDX])]
+    #[case(Instruction::Idiv { src: GPR::BL }, &[GPR::RBX, GPR::RAX], &[GPR::RAX])]
+    #[case(Instruction::Cqo, &[GPR::RAX], &[GPR::RDX])]
+    #[case(Instruction::Push { reg: GPR::R12 }, &[GPR::R12, GPR::RSP], &[GPR::RSP])]
+    #[case(Instruction::Pop { reg: GPR::R12 }, &[GPR::RSP], &[GPR::RSP, GPR::R12])]
+    #[case(Instruction::MovRegReg { dst: GPR::EAX, src: GPR::ECX }, &[GPR::RCX], &[GPR::RAX])]
+    #[case(Instruction::MovRegReg { dst: GPR::AX, src: GPR::CX }, &[GPR::RCX, GPR::RAX], &[GPR::RAX])]
+    #[case(Instruction::ShiftRegCl { op: ShiftOp::Shl, dst: GPR::R9 }, &[GPR::R9, GPR::RCX], &[GPR::R9])]
+    #[case(
+        Instruction::Lea { dst: GPR::RAX, src: Memory::new(GPR::RBX, GPR::R11, crate::models::Scale::Scale8, Displacement::ZERO).unwrap() },
+        &[GPR::RBX, GPR::R11],
+        &[GPR::RAX]
+    )]
+    #[case(
+        Instruction::ArithRegReg { op: ArithmeticOp::Cmp, dst: GPR::RAX, src: GPR::RSI },
+        &[GPR::RAX, GPR::RSI],
+        &[]
+    )]
+    #[case(Instruction::MovRegMem { dst: GPR::RAX, src: Memory::rip_relative(0).unwrap() }, &[], &[GPR::RAX])]
+    fn test_gprs(
+        #[case] instruction: Instruction,
+        #[case] read: &[GPR],
+        #[case] written: &[GPR],
+    ) {
+        let info = instruction.info();
+        assert_eq!(info.gprs_read(), gprs(read));
+        assert_eq!(info.gprs_written(), gprs(written));
+    }
+
+    #[rstest]
+    #[case(Instruction::ArithRegImm { op: ArithmeticOp::Add, dst: GPR::RAX, imm: 1 }, Flags::NONE, Flags::STATUS)]
+    #[case(Instruction::ArithRegImm { op: ArithmeticOp::Adc, dst: GPR::RAX, imm: 1 }, Flags::CARRY, Flags::STATUS)]
+    #[case(Instruction::JccRel8 { cond: Condition::Greater, rel: 0 }, Flags::ZERO.union(Flags::SIGN).union(Flags::OVERFLOW), Flags::NONE)]
+    #[case(Instruction::ShiftRegImm { op: ShiftOp::Shl, dst: GPR::RAX, imm: 64 }, Flags::NONE, Flags::NONE)]
+    #[case(Instruction::ShiftRegImm { op: ShiftOp::Rol, dst: GPR::EAX, imm: 3 }, Flags::NONE, Flags::CARRY.union(Flags::OVERFLOW))]
+    #[case(Instruction::ShiftRegCl { op: ShiftOp::Sar, dst: GPR::EAX }, Flags::STATUS, Flags::STATUS)]
+    #[case(Instruction::MovRegImm { dst: GPR::RAX, imm: 0 }, Flags::NONE, Flags::NONE)]
+    fn test_flags(
+        #[case] instruction: Instruction,
+        #[case] read: Flags,
+        #[case] written: Flags,
+    ) {
+        let info = instruction.info();
+        assert_eq!(info.flags_read(), read);
+        assert_eq!(info.flags_written(), written);
+    }
+
+    #[test]
+    fn test_xmms_and_memory() {
+        let memory = Memory::based(GPR::RDI, 8).unwrap();
+        let info = Instruction::FloatArithXmmMem {
+            op: FloatArithmeticOp::Addsd,
+            dst: XMM::XMM3,
+            src: memory,
+        }
+        .info();
+        assert!(info.xmms_read().contains(XMM::XMM3));
+        assert!(info.xmms_written().contains(XMM::XMM3));
+        assert_eq!(info.gprs_read(), gprs(&[GPR::RDI]));
+        assert_eq!(info.memory(), MemoryAccess::Read);
+
+        let info = Instruction::MovapsXmmXmm {
+            dst: XMM::XMM1,
+            src: XMM::XMM2,
+        }
+        .info();
+        assert_eq!(info.xmms_read(), XmmSet::EMPTY.with(XMM::XMM2));
+        assert_eq!(info.memory(), MemoryAccess::None);
+
+        let info = Instruction::ArithMemReg {
+            op: ArithmeticOp::Add,
+            dst: memory,
+            src: GPR::RAX,
+        }
+        .info();
+        assert_eq!(info.memory(), MemoryAccess::ReadWrite);
+    }
+
+    #[rstest]
+    #[case(Instruction::BitCountRegReg { op: BitCountOp::Lzcnt, dst: GPR::RAX, src: GPR::RCX }, Some(CpuFeature::Lzcnt))]
+    #[case(Instruction::BitCountRegReg { op: BitCountOp::Tzcnt, dst: GPR::EAX, src: GPR::ECX }, Some(CpuFeature::Bmi1))]
+    #[case(Instruction::FloatArithXmmXmm { op: FloatArithmeticOp::Mulss, dst: XMM::XMM0, src: XMM::XMM1 }, Some(CpuFeature::Sse))]
+    #[case(Instruction::MovsdXmmMem { dst: XMM::XMM0, src: Memory::rip_relative(0).unwrap() }, Some(CpuFeature::Sse2))]
+    #[case(Instruction::MovRegReg { dst: GPR::RAX, src: GPR::RCX }, None)]
+    fn test_required_feature(
+        #[case] instruction: Instruction,
+        #[case] expected: Option<CpuFeature>,
+    ) {
+        assert_eq!(instruction.required_feature(), expected);
+    }
+
+    #[rstest]
+    #[case(Instruction::Ret, ControlFlow::Return, true)]
+    #[case(Instruction::JmpRel8 { rel: 0 }, ControlFlow::Jump, true)]
+    #[case(Instruction::JmpReg { target: GPR::RAX }, ControlFlow::IndirectJump, true)]
+    #[case(Instruction::JccRel32 { cond: Condition::Equal, rel: 0 }, ControlFlow::ConditionalJump, false)]
+    #[case(Instruction::CallRel32 { rel: 0 }, ControlFlow::Call, false)]
+    #[case(Instruction::Nop { size: 1 }, ControlFlow::None, false)]
+    fn test_control_flow(
+        #[case] instruction: Instruction,
+        #[case] control_flow: ControlFlow,
+        #[case] is_terminator: bool,
+    ) {
+        let info = instruction.info();
+        assert_eq!(info.control_flow(), control_flow);
+        assert_eq!(info.is_branch(), control_flow != ControlFlow::None);
+        assert_eq!(info.is_terminator(), is_terminator);
+    }
+}
@@ -0,0 +1,9 @@
+mod decode;
+mod decoded_instruction;
+mod metadata;
+
+pub use decode::*;
+pub use decoded_instruction::*;
+pub use metadata::*;
+
+pub mod errors;
@@ -0,0 +1,336 @@
+//! Two-operand ALU instructions: `add`, `or`, `adc`, `sbb`, `and`, `sub`, `xor`
+//! and `cmp`.
+
+use crate::models::{ArithmeticOp, Immediate, MachineSize, Memory, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{
+        encode_opcode_with_reg, force_rex, OperandSize, RmInstruction, RmOperand,
+    },
+    EncodedInstruction,
+};
+
+/// Opcode offsets relative to `op << 3`.
+const RM_REG: u8 = 0b000;
+const REG_RM: u8 = 0b010;
+const ACCUMULATOR_IMM: u8 = 0b100;
+
+#[inline(always)]
+const fn opcode(op: ArithmeticOp, form: u8, size: OperandSize) -> u8 {
+    let base = (op.as_u8() << 3) | form;
+    if size.is_byte {
+        base
+    } else {
+        base | 1
+    }
+}
+
+#[inline(always)]
+fn encode_rm(
+    size: OperandSize,
+    force_rex: bool,
+    opcode: u8,
+    reg: u8,
+    rm: RmOperand,
+) -> EncodedInstruction {
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex,
+        opcode: &[opcode],
+        reg,
+        rm,
+        immediate: &[],
+    };
+    instruction.encode()
+}
+
+/// Encodes `op dst, src`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+/// * [`EncodingError::RegistersSizeMismatch`] if `dst` and `src` have different sizes.
+/// * [`EncodingError::HighByteRegisterWithRex`] if a high byte register is combined
+///   with a register requiring REX.
+pub fn encode_arith_reg_reg(
+    op: ArithmeticOp,
+    dst: GPR,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of_pair(dst, src)?;
+    Ok(encode_rm(
+        size,
+        force_rex(&[dst, src], None)?,
+        opcode(op, RM_REG, size),
+        src.encoding_index(),
+        RmOperand::Register(dst.encoding_index()),
+    ))
+}
+
+/// Encodes `op dst, imm`. Sign extended 8-bit immediate is used whenever possible,
+/// and the short accumulator form otherwise, when `dst` is `al`/`ax`/`eax`/`rax`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+/// * [`EncodingError::ArgumentOutOfRange`] if `imm` does not fit in `dst`. Both
+///   signed and unsigned interpretations are accepted, except for 64-bit `dst`,
+///   where the immediate has to fit in sign extended 32 bits.
+pub fn encode_arith_reg_imm(
+    op: ArithmeticOp,
+    dst: GPR,
+    imm: impl Into<Immediate>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let imm = imm.into();
+    let size = OperandSize::of(dst)?;
+    let (imm_size, fits) = match dst.size() {
+        MachineSize::QWord => (
+            MachineSize::DWord,
+            imm.fits_sign_extended(MachineSize::DWord),
+        ),
+        size @ (MachineSize::Byte | MachineSize::Word) => (size, imm.fits(size)),
+        _ => (MachineSize::DWord, imm.fits(MachineSize::DWord)),
+    };
+    if !fits {
+        return Err(EncodingError::ArgumentOutOfRange);
+    }
+
+    let bytes = imm.to_le_bytes();
+    // Unsigned immediates are sign extended from the operand size, e.g. `0xFFFF`
+    // is the same as `-1` for 16-bit registers.
+    let is_short = !size.is_byte
+        && imm
+            .truncated(imm_size)
+            .fits_sign_extended(MachineSize::Byte);
+    let (opcode, immediate): (u8, &[u8]) = if is_short {
+        (0x83, &bytes[..1])
+    } else {
+        let opcode = if size.is_byte { 0x80 } else { 0x81 };
+        (opcode, &bytes[..imm_size.as_bytes() as usize])
+    };
+
+    if !is_short && dst.encoding_index() == 0 {
+        let opcode = (op.as_u8() << 3) | ACCUMULATOR_IMM | u8::from(!size.is_byte);
+        let encoded =
+            encode_opcode_with_reg(size.prefix, size.rex_w, false, opcode, 0, immediate);
+        return Ok(encoded);
+    }
+
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex: dst.needs_rex(),
+        opcode: &[opcode],
+        reg: op.as_u8(),
+        rm: RmOperand::Register(dst.encoding_index()),
+        immediate,
+    };
+    Ok(instruction.encode())
+}
+
+/// Encodes `op dst, [src]`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose
+///   register.
+/// * [`EncodingError::HighByteRegisterWithRex`] if `dst` is a high byte register
+///   and `src` uses an extended register.
+pub fn encode_arith_reg_mem(
+    op: ArithmeticOp,
+    dst: GPR,
+    src: Memory,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(dst)?;
+    Ok(encode_rm(
+        size,
+        force_rex(&[dst], Some(src))?,
+        opcode(op, REG_RM, size),
+        dst.encoding_index(),
+        RmOperand::Memory(src),
+    ))
+}
+
+/// Encodes `op [dst], src`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `src` is not a general purpose
+///   register.
+/// * [`EncodingError::HighByteRegisterWithRex`] if `src` is a high byte register
+///   and `dst` uses an extended register.
+pub fn encode_arith_mem_reg(
+    op: ArithmeticOp,
+    dst: Memory,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(src)?;
+    Ok(encode_rm(
+        size,
+        force_rex(&[src], Some(dst))?,
+        opcode(op, RM_REG, size),
+        src.encoding_index(),
+        RmOperand::Memory(dst),
+    ))
+}
+
+macro_rules! arith_shortcuts {
+    ( $( $name: ident => $op: ident ),* $(,)? ) => {
+        paste::paste! {
+            $(
+                #[doc = "Shortcut for [`encode_arith_reg_reg`] with [`ArithmeticOp::" $op "`]."]
+                ///
+                /// # Errors
+                /// See [`encode_arith_reg_reg`].
+                #[inline(always)]
+                pub fn [<encode_ $name _reg_reg>](
+                    dst: GPR,
+                    src: GPR,
+                ) -> Result<EncodedInstruction, EncodingError> {
+                    encode_arith_reg_reg(ArithmeticOp::$op, dst, src)
+                }
+
+                #[doc = "Shortcut for [`encode_arith_reg_imm`] with [`ArithmeticOp::" $op "`]."]
+                ///
+                /// # Errors
+                /// See [`encode_arith_reg_imm`].
+                #[inline(always)]
+                pub fn [<encode_ $name _reg_imm>](
+                    dst: GPR,
+                    imm: impl Into<Immediate>,
+                ) -> Result<EncodedInstruction, EncodingError> {
+                    encode_arith_reg_imm(ArithmeticOp::$op, dst, imm)
+                }
+
+                #[doc = "Shortcut for [`encode_arith_reg_mem`] with [`ArithmeticOp::" $op "`]."]
+                ///
+                /// # Errors
+                /// See [`encode_arith_reg_mem`].
+                #[inline(always)]
+                pub fn [<encode_ $name _reg_mem>](
+                    dst: GPR,
+                    src: Memory,
+                ) -> Result<EncodedInstruction, EncodingError> {
+                    encode_arith_reg_mem(ArithmeticOp::$op, dst, src)
+                }
+
+                #[doc = "Shortcut for [`encode_arith_mem_reg`] with [`ArithmeticOp::" $op "`]."]
+                ///
+                /// # Errors
+                /// See [`encode_arith_mem_reg`].
+                #[inline(always)]
+                pub fn [<encode_ $name _mem_reg>](
+                    dst: Memory,
+                    src: GPR,
+                ) -> Result<EncodedInstruction, EncodingError> {
+                    encode_arith_mem_reg(ArithmeticOp::$op, dst, src)
+                }
+            )*
+        }
+    };
+}
+
+arith_shortcuts!(
+    add => Add,
+    or => Or,
+    adc => Adc,
+    sbb => Sbb,
+    and => And,
+    sub => Sub,
+    xor => Xor,
+    cmp => Cmp,
+);
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(ArithmeticOp::Add, GPR::RAX, GPR::RBX, &[0x48, 0x01, 0xD8])]
+    #[case(ArithmeticOp::Add, GPR::SPL, GPR::AL, &[0x40, 0x00, 0xC4])]
+    #[case(ArithmeticOp::Sub, GPR::R9D, GPR::EDX, &[0x41, 0x29, 0xD1])]
+    #[case(ArithmeticOp::Xor, GPR::AX, GPR::AX, &[0x66, 0x31, 0xC0])]
+    #[case(ArithmeticOp::Cmp, GPR::RCX, GPR::R12, &[0x4C, 0x39, 0xE1])]
+    #[case(ArithmeticOp::Add, GPR::BH, GPR::CH, &[0x00, 0xEF])]
+    fn test_arith_reg_reg(
+        #[case] op: ArithmeticOp,
+        #[case] dst: GPR,
+        #[case] src: GPR,
+        #[case] expected: &[u8],
+    ) {
+        assert_eq!(
+            encode_arith_reg_reg(op, dst, src).unwrap().as_slice(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(ArithmeticOp::Add, GPR::RAX, 8, &[0x48, 0x83, 0xC0, 0x08])]
+    #[case(ArithmeticOp::Add, GPR::RAX, 0x1000, &[0x48, 0x05, 0x00, 0x10, 0x00, 0x00])]
+    #[case(ArithmeticOp::Add, GPR::RCX, 0x1000, &[0x48, 0x81, 0xC1, 0x00, 0x10, 0x00, 0x00])]
+    #[case(ArithmeticOp::Add, GPR::EAX, 0x1000, &[0x05, 0x00, 0x10, 0x00, 0x00])]
+    #[case(ArithmeticOp::Add, GPR::AX, 0x1000, &[0x66, 0x05, 0x00, 0x10])]
+    #[case(ArithmeticOp::Add, GPR::CX, 0xFFFF, &[0x66, 0x83, 0xC1, 0xFF])]
+    #[case(ArithmeticOp::Add, GPR::AL, 0x10, &[0x04, 0x10])]
+    #[case(ArithmeticOp::Add, GPR::CL, 0x10, &[0x80, 0xC1, 0x10])]
+    #[case(ArithmeticOp::Add, GPR::AH, 0x10, &[0x80, 0xC4, 0x10])]
+    #[case(ArithmeticOp::Cmp, GPR::RCX, 127, &[0x48, 0x83, 0xF9, 0x7F])]
+    #[case(ArithmeticOp::Cmp, GPR::RCX, 128, &[0x48, 0x81, 0xF9, 0x80, 0x00, 0x00, 0x00])]
+    #[case(ArithmeticOp::And, GPR::R15, -16, &[0x49, 0x83, 0xE7, 0xF0])]
+    fn test_arith_reg_imm(
+        #[case] op: ArithmeticOp,
+        #[case] dst: GPR,
+        #[case] imm: i32,
+        #[case] expected: &[u8],
+    ) {
+        assert_eq!(
+            encode_arith_reg_imm(op, dst, imm).unwrap().as_slice(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_arith_immediate_types() {
+        let encoded = encode_add_reg_imm(GPR::EAX, u32::MAX).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x83, 0xC0, 0xFF]);
+        let encoded = encode_cmp_reg_imm(GPR::AL, 0xFFu8).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x3C, 0xFF]);
+        let encoded = encode_and_reg_imm(GPR::RCX, -1i64).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x48, 0x83, 0xE1, 0xFF]);
+        let encoded = encode_or_reg_imm(GPR::DX, Immediate::new(0x8000)).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x66, 0x81, 0xCA, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_arith_memory() {
+        let memory = Memory::based(GPR::RBX, 0).unwrap();
+        let encoded = encode_add_reg_mem(GPR::RAX, memory).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x48, 0x03, 0x03]);
+        let encoded = encode_add_mem_reg(memory, GPR::RAX).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x48, 0x01, 0x03]);
+    }
+
+    #[test]
+    fn test_arith_errors() {
+        assert!(matches!(
+            encode_add_reg_imm(GPR::AL, 256),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+        assert!(matches!(
+            encode_add_reg_imm(GPR::CX, -32769),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+        assert!(matches!(
+            encode_add_reg_imm(GPR::RAX, 0xFFFF_FFFFu32),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+        assert!(matches!(
+            encode_sub_reg_reg(GPR::RAX, GPR::CL),
+            Err(EncodingError::RegistersSizeMismatch)
+        ));
+        assert!(matches!(
+            encode_xor_reg_reg(GPR::AH, GPR::R15B),
+            Err(EncodingError::HighByteRegisterWithRex)
+        ));
+    }
+}
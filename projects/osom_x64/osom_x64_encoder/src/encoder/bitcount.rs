@@ -0,0 +1,138 @@
+//! Bit counting instructions: `popcnt`, `lzcnt` and `tzcnt`.
+
+use crate::models::{BitCountOp, CpuRequirement, Memory, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{InstructionBuffer, OperandSize, RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+const BIT_COUNT_PREFIX: u8 = 0xF3;
+
+#[inline(always)]
+fn encode_bit_count(
+    op: BitCountOp,
+    size: OperandSize,
+    reg: u8,
+    rm: RmOperand,
+) -> Result<EncodedInstruction, EncodingError> {
+    if size.is_byte {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    let instruction = RmInstruction {
+        prefix: Some(BIT_COUNT_PREFIX),
+        rex_w: size.rex_w,
+        force_rex: false,
+        opcode: &[0x0F, op.as_u8()],
+        reg,
+        rm,
+        immediate: &[],
+    };
+    let requirement = CpuRequirement::Feature(op.required_feature());
+    let encoded = instruction.encode();
+    let Some(prefix) = size.prefix else {
+        return Ok(encoded.with_cpu_requirement(requirement));
+    };
+
+    // The operand size prefix goes before the mandatory `F3` one, but after the
+    // segment override.
+    let (segment, rest) = encoded
+        .as_slice()
+        .split_at(usize::from(rm.segment().is_some()));
+    let mut buffer = InstructionBuffer::new();
+    buffer.extend(segment);
+    buffer.push(prefix);
+    buffer.extend(rest);
+    Ok(buffer.finish().with_cpu_requirement(requirement))
+}
+
+/// Encodes `op dst, src`, e.g. `lzcnt rax, rcx`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a 16, 32 or 64-bit
+///   register.
+/// * [`EncodingError::RegistersSizeMismatch`] if `dst` and `src` have different sizes.
+///
+/// # Notes
+/// The instruction requires [`BitCountOp::required_feature`].
+pub fn encode_bit_count_reg_reg(
+    op: BitCountOp,
+    dst: GPR,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of_pair(dst, src)?;
+    encode_bit_count(op, size, dst.index(), RmOperand::Register(src.index()))
+}
+
+/// Encodes `op dst, [src]`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 16, 32 or 64-bit register.
+///
+/// # Notes
+/// The instruction requires [`BitCountOp::required_feature`].
+pub fn encode_bit_count_reg_mem(
+    op: BitCountOp,
+    dst: GPR,
+    src: Memory,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(dst)?;
+    encode_bit_count(op, size, dst.index(), RmOperand::Memory(src))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::models::Segment;
+
+    #[rstest]
+    #[case(
+        encode_bit_count_reg_reg(BitCountOp::Lzcnt, GPR::RAX, GPR::RCX),
+        &[0xF3, 0x48, 0x0F, 0xBD, 0xC1]
+    )]
+    #[case(
+        encode_bit_count_reg_reg(BitCountOp::Tzcnt, GPR::R8D, GPR::EBX),
+        &[0xF3, 0x44, 0x0F, 0xBC, 0xC3]
+    )]
+    #[case(
+        encode_bit_count_reg_reg(BitCountOp::Popcnt, GPR::AX, GPR::CX),
+        &[0x66, 0xF3, 0x0F, 0xB8, 0xC1]
+    )]
+    #[case(
+        encode_bit_count_reg_mem(BitCountOp::Lzcnt, GPR::R9, Memory::based(GPR::RAX, 0).unwrap()),
+        &[0xF3, 0x4C, 0x0F, 0xBD, 0x08]
+    )]
+    #[case(
+        encode_bit_count_reg_mem(
+            BitCountOp::Popcnt,
+            GPR::AX,
+            Memory::based(GPR::RAX, 0).unwrap().with_segment(Segment::Fs),
+        ),
+        &[0x64, 0x66, 0xF3, 0x0F, 0xB8, 0x00]
+    )]
+    fn test_encode_bit_count(
+        #[case] encoded: Result<EncodedInstruction, EncodingError>,
+        #[case] expected: &[u8],
+    ) {
+        assert_eq!(encoded.unwrap().as_slice(), expected);
+    }
+
+    #[rstest]
+    #[case(
+        encode_bit_count_reg_reg(BitCountOp::Popcnt, GPR::AL, GPR::CL),
+        EncodingError::InvalidRegisterSize
+    )]
+    #[case(
+        encode_bit_count_reg_reg(BitCountOp::Lzcnt, GPR::RAX, GPR::ECX),
+        EncodingError::RegistersSizeMismatch
+    )]
+    fn test_encode_bit_count_errors(
+        #[case] encoded: Result<EncodedInstruction, EncodingError>,
+        #[case] expected: EncodingError,
+    ) {
+        assert_eq!(encoded.err(), Some(expected));
+    }
+}
@@ -0,0 +1,99 @@
+//! `call` instructions.
+
+use crate::{
+    constants::CALL_REL32_SIZE,
+    models::{Displacement, MachineSize, GPR},
+};
+
+use super::{
+    errors::EncodingError,
+    operands::{RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+/// Encodes `call target`, where `target` is relative to the start of this
+/// instruction, i.e. `$+target` in Intel syntax. There is no short form, thus it
+/// is always `call rel32`.
+///
+/// # Errors
+/// [`EncodingError::ArgumentOutOfRange`] if `target` is not reachable by `call rel32`.
+pub fn encode_call(
+    target: impl Into<Displacement>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let rel = target.into().relative_to_end(CALL_REL32_SIZE)?;
+    Ok(encode_call_rel32(rel))
+}
+
+/// Encodes `call rel32`. Returns [`EncodedInstruction`] of length 5.
+///
+/// # Notes
+/// It does not manipulate `rel`. In particular `rel` is relative to RIP, meaning
+/// next instruction after this one.
+#[must_use]
+pub fn encode_call_rel32(rel: i32) -> EncodedInstruction {
+    const OPCODE: u8 = 0xE8;
+    let mut buffer = [OPCODE; CALL_REL32_SIZE];
+    buffer[1..].copy_from_slice(&rel.to_le_bytes());
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked(buffer) }
+}
+
+/// Encodes `call target`, i.e. an indirect call of the address held in `target`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `target` is not a 64-bit register.
+pub fn encode_call_reg(target: GPR) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(target.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    let instruction = RmInstruction {
+        prefix: None,
+        rex_w: false,
+        force_rex: false,
+        opcode: &[0xFF],
+        reg: 2,
+        rm: RmOperand::Register(target.index()),
+        immediate: &[],
+    };
+    Ok(instruction.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_call_rel32() {
+        let encoded = encode_call_rel32(-5);
+        assert_eq!(encoded.as_slice(), &[0xE8, 0xFB, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_call() {
+        let encoded = encode_call(0).unwrap();
+        assert_eq!(encoded.as_slice(), &[0xE8, 0xFB, 0xFF, 0xFF, 0xFF]);
+        let encoded = encode_call(0x100).unwrap();
+        assert_eq!(encoded.as_slice(), &[0xE8, 0xFB, 0x00, 0x00, 0x00]);
+        assert!(matches!(
+            encode_call(i64::from(i32::MIN)),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, &[0xFF, 0xD0])]
+    #[case(GPR::RBP, &[0xFF, 0xD5])]
+    #[case(GPR::R12, &[0x41, 0xFF, 0xD4])]
+    fn test_call_reg(#[case] target: GPR, #[case] expected: &[u8]) {
+        assert_eq!(encode_call_reg(target).unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_call_reg_invalid_size() {
+        assert!(matches!(
+            encode_call_reg(GPR::EAX),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
+}
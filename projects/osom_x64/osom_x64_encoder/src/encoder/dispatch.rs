@@ -0,0 +1,291 @@
+//! Dynamic entry point dispatching a [`Mnemonic`] with [`Operand`]s to the
+//! specialized encoders.
+
+use crate::models::{Mnemonic, Operand, GPR};
+
+use super::{
+    arith, bitcount, call,
+    errors::{EncodingError, InvalidOperands},
+    jcc, jmp, lea, misc, mov, muldiv, shift, sse, stack, EncodedInstruction,
+};
+
+/// Encodes `mnemonic` with `operands`, given in Intel order (destination first),
+/// e.g. `encode(Mnemonic::Mov, &[GPR::RAX.into(), GPR::RCX.into()])`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidOperands`] if `mnemonic` has no form taking operands
+///   of the given kinds.
+/// * [`EncodingError::ArgumentOutOfRange`] if a [`Operand::Relative`] target is
+///   not reachable by any branch form.
+/// * Errors of the specialized encoder otherwise, e.g.
+///   [`EncodingError::RegistersSizeMismatch`].
+///
+/// # Notes
+/// [`Operand::Relative`] targets are relative to the start of the instruction.
+/// Branches use the short form when the target is in its range.
+pub fn encode(
+    mnemonic: Mnemonic,
+    operands: &[Operand],
+) -> Result<EncodedInstruction, EncodingError> {
+    use Operand::{Gpr, Immediate as Imm, Memory as Mem, Relative as Rel, Xmm};
+
+    match (mnemonic, operands) {
+        (Mnemonic::Arith(op), [Gpr(dst), Gpr(src)]) => {
+            arith::encode_arith_reg_reg(op, *dst, *src)
+        }
+        (Mnemonic::Arith(op), [Gpr(dst), Imm(imm)]) => {
+            arith::encode_arith_reg_imm(op, *dst, *imm)
+        }
+        (Mnemonic::Arith(op), [Gpr(dst), Mem(src)]) => {
+            arith::encode_arith_reg_mem(op, *dst, *src)
+        }
+        (Mnemonic::Arith(op), [Mem(dst), Gpr(src)]) => {
+            arith::encode_arith_mem_reg(op, *dst, *src)
+        }
+        (Mnemonic::Shift(op), [Gpr(dst), Gpr(GPR::CL)]) => {
+            shift::encode_shift_reg_cl(op, *dst)
+        }
+        (Mnemonic::Shift(op), [Gpr(dst), Imm(imm)]) => {
+            shift::encode_shift_reg_imm(op, *dst, *imm)
+        }
+        (Mnemonic::BitCount(op), [Gpr(dst), Gpr(src)]) => {
+            bitcount::encode_bit_count_reg_reg(op, *dst, *src)
+        }
+        (Mnemonic::BitCount(op), [Gpr(dst), Mem(src)]) => {
+            bitcount::encode_bit_count_reg_mem(op, *dst, *src)
+        }
+        (Mnemonic::FloatArith(op), [Xmm(dst), Xmm(src)]) => {
+            Ok(sse::encode_float_arith_xmm_xmm(op, *dst, *src))
+        }
+        (Mnemonic::FloatArith(op), [Xmm(dst), Mem(src)]) => {
+            Ok(sse::encode_float_arith_xmm_mem(op, *dst, *src))
+        }
+        (Mnemonic::Jcc(cond), [Rel(target)]) => jcc::encode_jcc(cond, *target),
+        (Mnemonic::Mov, [Gpr(dst), Gpr(src)]) => mov::encode_mov_reg_reg(*dst, *src),
+        (Mnemonic::Mov, [Gpr(dst), Imm(imm)]) => mov::encode_mov_reg_imm(*dst, *imm),
+        (Mnemonic::Mov, [Gpr(dst), Mem(src)]) => mov::encode_mov_reg_mem(*dst, *src),
+        (Mnemonic::Mov, [Mem(dst), Gpr(src)]) => mov::encode_mov_mem_reg(*dst, *src),
+        (Mnemonic::Movabs, [Gpr(dst), Imm(imm)]) => {
+            mov::encode_movabs_reg_imm(*dst, *imm)
+        }
+        (Mnemonic::Movsxd, [Gpr(dst), Gpr(src)]) => {
+            mov::encode_movsxd_reg_reg(*dst, *src)
+        }
+        (Mnemonic::Movsxd, [Gpr(dst), Mem(src)]) => {
+            mov::encode_movsxd_reg_mem(*dst, *src)
+        }
+        (Mnemonic::Lea, [Gpr(dst), Mem(src)]) => lea::encode_lea(*dst, *src),
+        (Mnemonic::Imul, [Gpr(dst), Gpr(src)]) => {
+            muldiv::encode_imul_reg_reg(*dst, *src)
+        }
+        (Mnemonic::Imul, [Gpr(dst), Mem(src)]) => {
+            muldiv::encode_imul_reg_mem(*dst, *src)
+        }
+        (Mnemonic::Div, [Gpr(src)]) => muldiv::encode_div(*src),
+        (Mnemonic::Idiv, [Gpr(src)]) => muldiv::encode_idiv(*src),
+        (Mnemonic::Cdq, []) => Ok(muldiv::encode_cdq()),
+        (Mnemonic::Cqo, []) => Ok(muldiv::encode_cqo()),
+        (Mnemonic::Push, [Gpr(reg)]) => stack::encode_push(*reg),
+        (Mnemonic::Pop, [Gpr(reg)]) => stack::encode_pop(*reg),
+        (Mnemonic::Jmp, [Gpr(target)]) => jmp::encode_jmp_reg(*target),
+        (Mnemonic::Jmp, [Rel(target)]) => jmp::encode_jmp(*target),
+        (Mnemonic::Call, [Gpr(target)]) => call::encode_call_reg(*target),
+        (Mnemonic::Call, [Rel(target)]) => call::encode_call(*target),
+        (Mnemonic::Ret, []) => Ok(misc::encode_ret()),
+        (Mnemonic::Nop, []) => misc::encode_nop(1),
+        (Mnemonic::Movsd, [Xmm(dst), Xmm(src)]) => {
+            Ok(sse::encode_movsd_xmm_xmm(*dst, *src))
+        }
+        (Mnemonic::Movsd, [Xmm(dst), Mem(src)]) => {
+            Ok(sse::encode_movsd_xmm_mem(*dst, *src))
+        }
+        (Mnemonic::Movsd, [Mem(dst), Xmm(src)]) => {
+            Ok(sse::encode_movsd_mem_xmm(*dst, *src))
+        }
+        (Mnemonic::Movss, [Xmm(dst), Xmm(src)]) => {
+            Ok(sse::encode_movss_xmm_xmm(*dst, *src))
+        }
+        (Mnemonic::Movss, [Xmm(dst), Mem(src)]) => {
+            Ok(sse::encode_movss_xmm_mem(*dst, *src))
+        }
+        (Mnemonic::Movss, [Mem(dst), Xmm(src)]) => {
+            Ok(sse::encode_movss_mem_xmm(*dst, *src))
+        }
+        (Mnemonic::Movaps, [Xmm(dst), Xmm(src)]) => {
+            Ok(sse::encode_movaps_xmm_xmm(*dst, *src))
+        }
+        (Mnemonic::Movaps, [Xmm(dst), Mem(src)]) => {
+            Ok(sse::encode_movaps_xmm_mem(*dst, *src))
+        }
+        (Mnemonic::Movaps, [Mem(dst), Xmm(src)]) => {
+            Ok(sse::encode_movaps_mem_xmm(*dst, *src))
+        }
+        _ => Err(EncodingError::InvalidOperands(InvalidOperands::new(
+            mnemonic, operands,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::models::{
+        ArithmeticOp, Condition, Displacement, FloatArithmeticOp, Immediate,
+        MachineSize, Memory, OperandKind, ShiftOp, XMM, YMM,
+    };
+
+    fn check(mnemonic: Mnemonic, operands: &[Operand], expected: &EncodedInstruction) {
+        let encoded = encode(mnemonic, operands).unwrap();
+        assert_eq!(encoded.as_slice(), expected.as_slice(), "{mnemonic}");
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let rax = Operand::Gpr(GPR::RAX);
+        let ecx = Operand::Gpr(GPR::ECX);
+        let memory = Memory::based(GPR::RBX, 8).unwrap();
+        let mem = Operand::Memory(memory);
+        let imm = Operand::Immediate(Immediate::new(-3));
+        let xmm = Operand::Xmm(XMM::XMM2);
+        let add = Mnemonic::Arith(ArithmeticOp::Add);
+
+        check(
+            add,
+            &[rax, rax],
+            &arith::encode_add_reg_reg(GPR::RAX, GPR::RAX).unwrap(),
+        );
+        check(
+            add,
+            &[rax, imm],
+            &arith::encode_add_reg_imm(GPR::RAX, -3).unwrap(),
+        );
+        check(
+            add,
+            &[rax, mem],
+            &arith::encode_add_reg_mem(GPR::RAX, memory).unwrap(),
+        );
+        check(
+            add,
+            &[mem, rax],
+            &arith::encode_add_mem_reg(memory, GPR::RAX).unwrap(),
+        );
+        check(
+            Mnemonic::Shift(ShiftOp::Shl),
+            &[ecx, Operand::Gpr(GPR::CL)],
+            &shift::encode_shift_reg_cl(ShiftOp::Shl, GPR::ECX).unwrap(),
+        );
+        check(
+            Mnemonic::Mov,
+            &[rax, Operand::Immediate(Immediate::new(1 << 40))],
+            &mov::encode_mov_reg_imm(GPR::RAX, 1i64 << 40).unwrap(),
+        );
+        check(
+            Mnemonic::Lea,
+            &[rax, mem],
+            &lea::encode_lea(GPR::RAX, memory).unwrap(),
+        );
+        check(
+            Mnemonic::Push,
+            &[rax],
+            &stack::encode_push(GPR::RAX).unwrap(),
+        );
+        check(Mnemonic::Cqo, &[], &muldiv::encode_cqo());
+        check(Mnemonic::Ret, &[], &misc::encode_ret());
+        check(
+            Mnemonic::FloatArith(FloatArithmeticOp::Mulsd),
+            &[xmm, mem],
+            &sse::encode_float_arith_xmm_mem(
+                FloatArithmeticOp::Mulsd,
+                XMM::XMM2,
+                memory,
+            ),
+        );
+        check(
+            Mnemonic::Movaps,
+            &[mem, xmm],
+            &sse::encode_movaps_mem_xmm(memory, XMM::XMM2),
+        );
+    }
+
+    #[rstest]
+    #[case(Mnemonic::Jmp, 2, jmp::encode_jmp_rel8(0))]
+    #[case(Mnemonic::Jmp, -126, jmp::encode_jmp_rel8(-128))]
+    #[case(Mnemonic::Jmp, -127, jmp::encode_jmp_rel32(-132))]
+    #[case(Mnemonic::Jmp, 129, jmp::encode_jmp_rel8(127))]
+    #[case(Mnemonic::Jmp, 130, jmp::encode_jmp_rel32(125))]
+    #[case(Mnemonic::Jcc(Condition::Less), 0, jcc::encode_jcc_rel8(Condition::Less, -2))]
+    #[case(
+        Mnemonic::Jcc(Condition::Less),
+        0x1000,
+        jcc::encode_jcc_rel32(Condition::Less, 0x1000 - 6)
+    )]
+    #[case(Mnemonic::Call, 0, call::encode_call_rel32(-5))]
+    fn test_relative(
+        #[case] mnemonic: Mnemonic,
+        #[case] rel: i64,
+        #[case] expected: EncodedInstruction,
+    ) {
+        check(mnemonic, &[Operand::Relative(rel.into())], &expected);
+    }
+
+    #[test]
+    fn test_relative_out_of_range() {
+        for rel in [i64::MIN, i64::from(i32::MAX) + 6, i64::from(i32::MIN)] {
+            assert!(matches!(
+                encode(Mnemonic::Jmp, &[Operand::Relative(rel.into())]),
+                Err(EncodingError::ArgumentOutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_operands() {
+        let operands = [Operand::Gpr(GPR::EAX), Operand::Ymm(YMM::YMM1)];
+        let Err(EncodingError::InvalidOperands(details)) =
+            encode(Mnemonic::Mov, &operands)
+        else {
+            panic!("Expected invalid operands");
+        };
+        assert_eq!(details.mnemonic(), Mnemonic::Mov);
+        assert_eq!(details.operand_count(), 2);
+        let kinds: Vec<_> = details.operand_kinds().collect();
+        assert_eq!(
+            kinds,
+            [OperandKind::Gpr(MachineSize::DWord), OperandKind::Ymm]
+        );
+
+        let operands = [Operand::Relative(Displacement::ZERO); 6];
+        let Err(EncodingError::InvalidOperands(details)) =
+            encode(Mnemonic::Ret, &operands)
+        else {
+            panic!("Expected invalid operands");
+        };
+        assert_eq!(details.operand_count(), 6);
+        assert_eq!(details.operand_kinds().count(), InvalidOperands::MAX_KINDS);
+
+        let operands = [Operand::Relative(Displacement::ZERO); 300];
+        let Err(EncodingError::InvalidOperands(details)) =
+            encode(Mnemonic::Ret, &operands)
+        else {
+            panic!("Expected invalid operands");
+        };
+        assert_eq!(details.operand_count(), usize::from(u8::MAX));
+
+        let Err(EncodingError::InvalidOperands(details)) = encode(Mnemonic::Push, &[])
+        else {
+            panic!("Expected invalid operands");
+        };
+        assert_eq!(details.operand_kinds().count(), 0);
+    }
+
+    #[test]
+    fn test_encoder_errors_propagate() {
+        let operands = [Operand::Gpr(GPR::RAX), Operand::Gpr(GPR::ECX)];
+        assert!(matches!(
+            encode(Mnemonic::Mov, &operands),
+            Err(EncodingError::RegistersSizeMismatch)
+        ));
+    }
+}
@@ -1,20 +1,40 @@
 #![allow(clippy::len_without_is_empty)]
 
-use crate::constants::MAX_INSTRUCTION_SIZE;
+use crate::{
+    constants::MAX_INSTRUCTION_SIZE,
+    models::{CpuFeature, CpuRequirement},
+};
 
 /// Represents an encoded x64 instruction. It is an array of 15 bytes plus 1 byte length, that
 /// is capable of holding any x64 instruction.
+///
+/// # Notes
+/// The instruction also carries its [`CpuRequirement`]. Encoders set it, while
+/// instructions built from raw bytes start as [`CpuRequirement::Unknown`].
 pub struct EncodedInstruction {
-    len: u8,
+    /// The length in the low nibble, the [`CpuRequirement`] in the high one.
+    header: u8,
     buffer: [u8; MAX_INSTRUCTION_SIZE],
 }
 
+const LEN_MASK: u8 = 0x0F;
+const REQUIREMENT_SHIFT: u32 = 4;
+const REQUIREMENT_UNKNOWN: u8 = 0;
+const REQUIREMENT_NO_FEATURE: u8 = 1;
+const REQUIREMENT_FIRST_FEATURE: u8 = 2;
+
 impl EncodedInstruction {
     const _CHECK: () = const {
         assert!(size_of::<EncodedInstruction>() <= 16);
+        assert!(MAX_INSTRUCTION_SIZE <= LEN_MASK as usize);
+        assert!(
+            REQUIREMENT_FIRST_FEATURE as usize + CpuFeature::ALL.len()
+                <= 1 << (8 - REQUIREMENT_SHIFT)
+        );
     };
 
-    /// Creates a new instance of [`EncodedInstruction`].
+    /// Creates a new instance of [`EncodedInstruction`], with
+    /// [`CpuRequirement::Unknown`].
     ///
     /// # Safety
     /// `len` has to be at most 15 and first `len` bytes in `buffer` has to contain
@@ -26,10 +46,14 @@ impl EncodedInstruction {
         buffer: [u8; MAX_INSTRUCTION_SIZE],
     ) -> Self {
         core::hint::assert_unchecked(len as usize <= MAX_INSTRUCTION_SIZE);
-        Self { len, buffer }
+        Self {
+            header: len | (REQUIREMENT_UNKNOWN << REQUIREMENT_SHIFT),
+            buffer,
+        }
     }
 
-    /// Creates a new instance of [`EncodedInstruction`].
+    /// Creates a new instance of [`EncodedInstruction`], with
+    /// [`CpuRequirement::Unknown`].
     ///
     /// # Safety
     /// `array` has to fully contain a valid x64 encoded instruction.
@@ -49,6 +73,19 @@ impl EncodedInstruction {
         Self::new_unchecked(T as u8, buffer)
     }
 
+    /// Creates a new instance of [`EncodedInstruction`], with
+    /// [`CpuRequirement::NoFeature`].
+    ///
+    /// # Safety
+    /// See [`EncodedInstruction::from_array_unchecked`].
+    #[must_use]
+    #[inline(always)]
+    pub(crate) const unsafe fn from_no_feature_array_unchecked<const T: usize>(
+        array: [u8; T],
+    ) -> Self {
+        Self::from_array_unchecked(array).with_cpu_requirement(CpuRequirement::NoFeature)
+    }
+
     #[must_use]
     #[inline(always)]
     pub const fn as_slice(&self) -> &[u8] {
@@ -58,9 +95,60 @@ impl EncodedInstruction {
     #[must_use]
     #[inline(always)]
     pub const fn len(&self) -> u8 {
+        let len = self.header & LEN_MASK;
         unsafe {
-            core::hint::assert_unchecked(self.len as usize <= MAX_INSTRUCTION_SIZE);
+            core::hint::assert_unchecked(len as usize <= MAX_INSTRUCTION_SIZE);
         };
-        self.len
+        len
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn cpu_requirement(&self) -> CpuRequirement {
+        match self.header >> REQUIREMENT_SHIFT {
+            REQUIREMENT_UNKNOWN => CpuRequirement::Unknown,
+            REQUIREMENT_NO_FEATURE => CpuRequirement::NoFeature,
+            bits => CpuRequirement::Feature(
+                CpuFeature::ALL[(bits - REQUIREMENT_FIRST_FEATURE) as usize],
+            ),
+        }
+    }
+
+    /// Returns the instruction with its requirement replaced, e.g. to classify an
+    /// instruction built from raw bytes.
+    #[must_use]
+    #[inline]
+    pub const fn with_cpu_requirement(mut self, requirement: CpuRequirement) -> Self {
+        let bits = match requirement {
+            CpuRequirement::Unknown => REQUIREMENT_UNKNOWN,
+            CpuRequirement::NoFeature => REQUIREMENT_NO_FEATURE,
+            CpuRequirement::Feature(feature) => {
+                REQUIREMENT_FIRST_FEATURE + feature as u8
+            }
+        };
+        self.header = (self.header & LEN_MASK) | (bits << REQUIREMENT_SHIFT);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_requirement() {
+        let raw = unsafe { EncodedInstruction::from_array_unchecked([0x0F, 0x0B]) };
+        assert_eq!(raw.cpu_requirement(), CpuRequirement::Unknown);
+
+        let requirements = CpuFeature::ALL
+            .map(CpuRequirement::Feature)
+            .into_iter()
+            .chain([CpuRequirement::NoFeature, CpuRequirement::Unknown]);
+        let mut instruction = raw;
+        for requirement in requirements {
+            instruction = instruction.with_cpu_requirement(requirement);
+            assert_eq!(instruction.cpu_requirement(), requirement);
+            assert_eq!(instruction.as_slice(), [0x0F, 0x0B]);
+        }
     }
 }
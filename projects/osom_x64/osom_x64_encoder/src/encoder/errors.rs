@@ -1,6 +1,73 @@
-#[repr(u8)]
+use crate::models::{CpuFeature, Mnemonic, Operand, OperandKind};
+
+// Every encoder returns `EncodingError`, keep it small.
+const _CHECK: () = const {
+    assert!(size_of::<EncodingError>() <= 8);
+};
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum EncodingError {
     ArgumentOutOfRange,
     RegistersSizeMismatch,
+
+    /// Register has a size the instruction cannot operate on.
+    InvalidRegisterSize,
+
+    /// Base, index and scale do not form a valid memory operand.
+    InvalidMemoryOperand,
+
+    /// AH, CH, DH or BH is combined with an operand requiring REX prefix, i.e.
+    /// SPL, BPL, SIL, DIL, an extended register, or a memory operand with an
+    /// extended base or index. With REX the high byte registers are not
+    /// addressable.
+    HighByteRegisterWithRex,
+
+    /// Instruction requires an ISA extension which is not enabled.
+    UnsupportedCpuFeature(CpuFeature),
+
+    /// Mnemonic has no form taking the given operands.
+    InvalidOperands(InvalidOperands),
+}
+
+/// Details of [`EncodingError::InvalidOperands`].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct InvalidOperands {
+    mnemonic: Mnemonic,
+    kinds: [Option<OperandKind>; Self::MAX_KINDS],
+    count: u8,
+}
+
+impl InvalidOperands {
+    /// Maximum number of recorded operand kinds.
+    pub const MAX_KINDS: usize = 4;
+
+    pub(crate) fn new(mnemonic: Mnemonic, operands: &[Operand]) -> Self {
+        let mut kinds = [None; Self::MAX_KINDS];
+        for (kind, operand) in kinds.iter_mut().zip(operands) {
+            *kind = Some(operand.kind());
+        }
+        Self {
+            mnemonic,
+            kinds,
+            count: u8::try_from(operands.len()).unwrap_or(u8::MAX),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn mnemonic(&self) -> Mnemonic {
+        self.mnemonic
+    }
+
+    /// Returns the number of given operands, saturated at [`u8::MAX`].
+    #[must_use]
+    #[inline(always)]
+    pub const fn operand_count(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Returns kinds of the given operands, up to [`Self::MAX_KINDS`] first ones.
+    pub fn operand_kinds(&self) -> impl Iterator<Item = OperandKind> + '_ {
+        self.kinds.iter().map_while(|kind| *kind)
+    }
 }
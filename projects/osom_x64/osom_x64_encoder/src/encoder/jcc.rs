@@ -1,11 +1,13 @@
-use crate::{constants::JCC_REL32_SIZE, models::Condition};
+use crate::{
+    constants::{JCC_REL32_SIZE, JCC_REL8_SIZE},
+    models::{Condition, Displacement},
+};
 
 const _CHECK: () = const {
-    use crate::constants::JCC_REL8_SIZE;
     assert!(JCC_REL8_SIZE == 2);
 };
 
-use super::EncodedInstruction;
+use super::{errors::EncodingError, EncodedInstruction};
 
 #[allow(clippy::match_same_arms)]
 const fn map_cond_to_opcode(cond: Condition) -> u8 {
@@ -33,6 +35,27 @@ const fn map_cond_to_opcode(cond: Condition) -> u8 {
     }
 }
 
+/// Encodes `jcc target`, where `target` is relative to the start of this
+/// instruction, i.e. `$+target` in Intel syntax. Picks `jcc rel8` if `target` is
+/// in its range, and `jcc rel32` otherwise. See [`encode_jcc_rel8`] and
+/// [`encode_jcc_rel32`] for the fixed width forms.
+///
+/// # Errors
+/// [`EncodingError::ArgumentOutOfRange`] if `target` is not reachable by `jcc rel32`.
+pub fn encode_jcc(
+    cond: Condition,
+    target: impl Into<Displacement>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let target = target.into();
+    if let Ok(rel) = i8::try_from(target.relative_to_end(JCC_REL8_SIZE)?) {
+        return Ok(encode_jcc_rel8(cond, rel));
+    }
+    Ok(encode_jcc_rel32(
+        cond,
+        target.relative_to_end(JCC_REL32_SIZE)?,
+    ))
+}
+
 /// Encodes `jcc rel8`. Returns [`EncodedInstruction`] of length 2 on success.
 ///
 /// # Notes
@@ -41,9 +64,9 @@ const fn map_cond_to_opcode(cond: Condition) -> u8 {
 #[must_use]
 #[inline]
 pub fn encode_jcc_rel8(cond: Condition, rel: i8) -> EncodedInstruction {
-    let val: u8 = unsafe { core::mem::transmute(rel) };
+    let val = rel.to_le_bytes()[0];
     let buffer = [map_cond_to_opcode(cond), val];
-    unsafe { EncodedInstruction::from_array_unchecked(buffer) }
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked(buffer) }
 }
 
 /// Encodes `jcc rel32`. Returns [`EncodedInstruction`] of length 6 on success.
@@ -64,5 +87,32 @@ pub fn encode_jcc_rel32(cond: Condition, rel: i32) -> EncodedInstruction {
         slice[2..6].copy_from_slice(&rel.to_le_bytes());
         buffer
     };
-    unsafe { EncodedInstruction::from_array_unchecked(buffer) }
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked(buffer) }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, &[0x7C, 0xFE])]
+    #[case(129, &[0x7C, 0x7F])]
+    #[case(-126, &[0x7C, 0x80])]
+    #[case(130, &[0x0F, 0x8C, 0x7C, 0x00, 0x00, 0x00])]
+    #[case(-127, &[0x0F, 0x8C, 0x7B, 0xFF, 0xFF, 0xFF])]
+    fn test_jcc(#[case] target: i32, #[case] expected: &[u8]) {
+        let encoded = encode_jcc(Condition::Less, target).unwrap();
+        assert_eq!(encoded.as_slice(), expected);
+    }
+
+    #[test]
+    fn test_jcc_out_of_range() {
+        let target = i64::from(i32::MAX) + 7;
+        assert!(matches!(
+            encode_jcc(Condition::Equal, target),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+    }
 }
@@ -1,11 +1,33 @@
-use crate::constants::JMP_REL32_SIZE;
+use crate::constants::{JMP_REL32_SIZE, JMP_REL8_SIZE};
 
 const _CHECK: () = const {
-    use crate::constants::JMP_REL8_SIZE;
     assert!(JMP_REL8_SIZE == 2);
 };
 
-use super::EncodedInstruction;
+use crate::models::{Displacement, MachineSize, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+/// Encodes `jmp target`, where `target` is relative to the start of this
+/// instruction, i.e. `$+target` in Intel syntax. Picks `jmp rel8` if `target` is
+/// in its range, and `jmp rel32` otherwise. Branches which have to keep their
+/// size, e.g. to be patched later, use [`encode_jmp_rel8`] or [`encode_jmp_rel32`].
+///
+/// # Errors
+/// [`EncodingError::ArgumentOutOfRange`] if `target` is not reachable by `jmp rel32`.
+pub fn encode_jmp(
+    target: impl Into<Displacement>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let target = target.into();
+    if let Ok(rel) = i8::try_from(target.relative_to_end(JMP_REL8_SIZE)?) {
+        return Ok(encode_jmp_rel8(rel));
+    }
+    Ok(encode_jmp_rel32(target.relative_to_end(JMP_REL32_SIZE)?))
+}
 
 /// Encodes `jmp rel8`. Returns [`EncodedInstruction`] of length 2 on success.
 ///
@@ -16,9 +38,9 @@ use super::EncodedInstruction;
 #[inline]
 pub fn encode_jmp_rel8(rel: i8) -> EncodedInstruction {
     const OPCODE: u8 = 0xEB;
-    let val: u8 = unsafe { core::mem::transmute(rel) };
+    let val = rel.to_le_bytes()[0];
     let buffer = [OPCODE, val];
-    unsafe { EncodedInstruction::from_array_unchecked(buffer) }
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked(buffer) }
 }
 
 /// Encodes `jmp rel32`. Returns [`EncodedInstruction`] of length 5 on success.
@@ -36,5 +58,68 @@ pub fn encode_jmp_rel32(rel: i32) -> EncodedInstruction {
         slice[1..5].copy_from_slice(&rel.to_le_bytes());
         buffer
     };
-    unsafe { EncodedInstruction::from_array_unchecked(buffer) }
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked(buffer) }
+}
+
+/// Encodes `jmp target`, i.e. an indirect jump to the address held in `target`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `target` is not a 64-bit register.
+pub fn encode_jmp_reg(target: GPR) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(target.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    let instruction = RmInstruction {
+        prefix: None,
+        rex_w: false,
+        force_rex: false,
+        opcode: &[0xFF],
+        reg: 4,
+        rm: RmOperand::Register(target.index()),
+        immediate: &[],
+    };
+    Ok(instruction.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(2, &[0xEB, 0x00])]
+    #[case(129, &[0xEB, 0x7F])]
+    #[case(-126, &[0xEB, 0x80])]
+    #[case(130, &[0xE9, 0x7D, 0x00, 0x00, 0x00])]
+    #[case(-127, &[0xE9, 0x7C, 0xFF, 0xFF, 0xFF])]
+    fn test_jmp(#[case] target: i32, #[case] expected: &[u8]) {
+        assert_eq!(encode_jmp(target).unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_jmp_out_of_range() {
+        for target in [i64::MIN, i64::from(i32::MAX) + 6, i64::from(i32::MIN)] {
+            assert!(matches!(
+                encode_jmp(target),
+                Err(EncodingError::ArgumentOutOfRange)
+            ));
+        }
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, &[0xFF, 0xE0])]
+    #[case(GPR::RSP, &[0xFF, 0xE4])]
+    #[case(GPR::R11, &[0x41, 0xFF, 0xE3])]
+    fn test_jmp_reg(#[case] target: GPR, #[case] expected: &[u8]) {
+        assert_eq!(encode_jmp_reg(target).unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_jmp_reg_invalid_size() {
+        assert!(matches!(
+            encode_jmp_reg(GPR::EAX),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
 }
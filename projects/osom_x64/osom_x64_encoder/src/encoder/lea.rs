@@ -0,0 +1,64 @@
+use crate::models::{MachineSize, Memory, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+/// Encodes `lea dst, [src]`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 16, 32 or 64-bit register.
+pub fn encode_lea(dst: GPR, src: Memory) -> Result<EncodedInstruction, EncodingError> {
+    let (prefix, rex_w) = match dst.size() {
+        MachineSize::Word => (Some(0x66), false),
+        MachineSize::DWord => (None, false),
+        MachineSize::QWord => (None, true),
+        _ => return Err(EncodingError::InvalidRegisterSize),
+    };
+
+    let instruction = RmInstruction {
+        prefix,
+        rex_w,
+        force_rex: false,
+        opcode: &[0x8D],
+        reg: dst.index(),
+        rm: RmOperand::Memory(src),
+        immediate: &[],
+    };
+    Ok(instruction.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::models::{Displacement, Scale};
+
+    #[rstest]
+    #[case(GPR::RAX, Memory::rip_relative(0x10).unwrap(), &[0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00])]
+    #[case(GPR::R12, Memory::based(GPR::RSP, 8).unwrap(), &[0x4C, 0x8D, 0x64, 0x24, 0x08])]
+    #[case(GPR::ECX, Memory::based(GPR::R13, 0).unwrap(), &[0x41, 0x8D, 0x4D, 0x00])]
+    #[case(GPR::DX, Memory::based(GPR::RBX, -200).unwrap(), &[0x66, 0x8D, 0x93, 0x38, 0xFF, 0xFF, 0xFF])]
+    #[case(
+        GPR::RSI,
+        Memory::new(GPR::RAX, GPR::R9, Scale::Scale8, Displacement::ZERO).unwrap(),
+        &[0x4A, 0x8D, 0x34, 0xC8]
+    )]
+    #[case(
+        GPR::RDI,
+        Memory::new(GPR::NO_REG, GPR::RCX, Scale::Scale4, Displacement::new(16)).unwrap(),
+        &[0x48, 0x8D, 0x3C, 0x8D, 0x10, 0x00, 0x00, 0x00]
+    )]
+    fn test_lea(#[case] dst: GPR, #[case] src: Memory, #[case] expected: &[u8]) {
+        assert_eq!(encode_lea(dst, src).unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_lea_invalid_size() {
+        let result = encode_lea(GPR::AL, Memory::rip_relative(0).unwrap());
+        assert!(matches!(result, Err(EncodingError::InvalidRegisterSize)));
+    }
+}
@@ -3,7 +3,7 @@ use super::{errors::EncodingError, EncodedInstruction};
 #[must_use]
 #[inline(always)]
 pub const fn encode_ret() -> EncodedInstruction {
-    unsafe { EncodedInstruction::from_array_unchecked([0xC3]) }
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked([0xC3]) }
 }
 
 /// Encodes NOP operation. Return [`EncodedInstruction`] of size `size` if
@@ -15,7 +15,7 @@ pub const fn encode_ret() -> EncodedInstruction {
 pub const fn encode_nop(size: u8) -> Result<EncodedInstruction, EncodingError> {
     macro_rules! encode {
         ( $array: expr ) => {
-            Ok(unsafe { EncodedInstruction::from_array_unchecked($array) })
+            Ok(unsafe { EncodedInstruction::from_no_feature_array_unchecked($array) })
         };
     }
 
@@ -1,8 +1,20 @@
+mod dispatch;
 mod encoded_instruction;
+mod operands;
 
+pub use dispatch::*;
 pub use encoded_instruction::*;
 
+pub mod arith;
+pub mod bitcount;
+pub mod call;
 pub mod errors;
 pub mod jcc;
 pub mod jmp;
+pub mod lea;
 pub mod misc;
+pub mod mov;
+pub mod muldiv;
+pub mod shift;
+pub mod sse;
+pub mod stack;
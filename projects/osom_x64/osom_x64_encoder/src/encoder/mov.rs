@@ -0,0 +1,388 @@
+//! General purpose `mov` instructions.
+
+use crate::models::{Immediate, MachineSize, Memory, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{
+        encode_opcode_with_reg, force_rex, OperandSize, RmInstruction, RmOperand,
+    },
+    EncodedInstruction,
+};
+
+#[inline(always)]
+fn encode_mov_rm(
+    size: OperandSize,
+    force_rex: bool,
+    opcode: u8,
+    reg: u8,
+    rm: RmOperand,
+) -> EncodedInstruction {
+    let opcode = if size.is_byte { opcode } else { opcode | 1 };
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex,
+        opcode: &[opcode],
+        reg,
+        rm,
+        immediate: &[],
+    };
+    instruction.encode()
+}
+
+/// Encodes `mov dst, src`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+/// * [`EncodingError::RegistersSizeMismatch`] if `dst` and `src` have different sizes.
+/// * [`EncodingError::HighByteRegisterWithRex`] if a high byte register is combined
+///   with a register requiring REX.
+pub fn encode_mov_reg_reg(
+    dst: GPR,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of_pair(dst, src)?;
+    Ok(encode_mov_rm(
+        size,
+        force_rex(&[dst, src], None)?,
+        0x88,
+        src.encoding_index(),
+        RmOperand::Register(dst.encoding_index()),
+    ))
+}
+
+/// Encodes `mov dst, imm`. 64-bit registers use, in order of preference:
+/// * sign extended 32-bit immediate, i.e. `mov r/m64, imm32`,
+/// * 32-bit immediate moved into the low half of the register, which the CPU
+///   zero extends, i.e. `mov r32, imm32`,
+/// * full 64-bit immediate (`movabs`).
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+/// * [`EncodingError::ArgumentOutOfRange`] if `imm` does not fit in `dst`. Both
+///   signed and unsigned interpretations are accepted, e.g. `255` and `-1` are
+///   valid for 8-bit registers.
+pub fn encode_mov_reg_imm(
+    dst: GPR,
+    imm: impl Into<Immediate>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let imm = imm.into();
+    let size = OperandSize::of(dst)?;
+    let bytes = imm.to_le_bytes();
+    let index = dst.encoding_index();
+    let encoded = match dst.size() {
+        MachineSize::Byte if imm.fits(MachineSize::Byte) => encode_opcode_with_reg(
+            None,
+            false,
+            dst.needs_rex(),
+            0xB0,
+            index,
+            &bytes[..1],
+        ),
+        MachineSize::Word if imm.fits(MachineSize::Word) => {
+            encode_opcode_with_reg(size.prefix, false, false, 0xB8, index, &bytes[..2])
+        }
+        MachineSize::DWord if imm.fits(MachineSize::DWord) => {
+            encode_opcode_with_reg(None, false, false, 0xB8, index, &bytes[..4])
+        }
+        MachineSize::QWord if imm.fits_sign_extended(MachineSize::DWord) => {
+            let instruction = RmInstruction {
+                prefix: None,
+                rex_w: true,
+                force_rex: false,
+                opcode: &[0xC7],
+                reg: 0,
+                rm: RmOperand::Register(index),
+                immediate: &bytes[..4],
+            };
+            instruction.encode()
+        }
+        MachineSize::QWord if imm.fits_zero_extended(MachineSize::DWord) => {
+            encode_opcode_with_reg(None, false, false, 0xB8, index, &bytes[..4])
+        }
+        MachineSize::QWord => {
+            encode_opcode_with_reg(None, true, false, 0xB8, index, &bytes)
+        }
+        _ => return Err(EncodingError::ArgumentOutOfRange),
+    };
+    Ok(encoded)
+}
+
+/// Encodes `movabs dst, imm`, i.e. `mov` with a full 64-bit immediate, even if
+/// a shorter encoding exists. The immediate occupies the last 8 bytes of the
+/// instruction, which makes it suitable for patching.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 64-bit register.
+pub fn encode_movabs_reg_imm(
+    dst: GPR,
+    imm: impl Into<Immediate>,
+) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(dst.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    Ok(encode_opcode_with_reg(
+        None,
+        true,
+        false,
+        0xB8,
+        dst.index(),
+        &imm.into().to_le_bytes(),
+    ))
+}
+
+/// Encodes `mov dst, [src]`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose
+///   register.
+/// * [`EncodingError::HighByteRegisterWithRex`] if `dst` is a high byte register
+///   and `src` uses an extended register.
+pub fn encode_mov_reg_mem(
+    dst: GPR,
+    src: Memory,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(dst)?;
+    Ok(encode_mov_rm(
+        size,
+        force_rex(&[dst], Some(src))?,
+        0x8A,
+        dst.encoding_index(),
+        RmOperand::Memory(src),
+    ))
+}
+
+/// Encodes `mov [dst], src`.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `src` is not a general purpose
+///   register.
+/// * [`EncodingError::HighByteRegisterWithRex`] if `src` is a high byte register
+///   and `dst` uses an extended register.
+pub fn encode_mov_mem_reg(
+    dst: Memory,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(src)?;
+    Ok(encode_mov_rm(
+        size,
+        force_rex(&[src], Some(dst))?,
+        0x88,
+        src.encoding_index(),
+        RmOperand::Memory(dst),
+    ))
+}
+
+/// Encodes `mov dst, qword ptr fs:[0]`, which loads the thread pointer as defined
+/// by the x86-64 Linux TLS ABI. Thread-local variables can then be addressed
+/// relative to `dst`, or directly through [`Memory::thread_local`].
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 64-bit register.
+pub fn encode_mov_thread_pointer(dst: GPR) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(dst.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    encode_mov_reg_mem(dst, Memory::thread_local(0)?)
+}
+
+#[inline(always)]
+fn encode_movsxd(dst: GPR, rm: RmOperand) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(dst.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    let instruction = RmInstruction {
+        prefix: None,
+        rex_w: true,
+        force_rex: false,
+        opcode: &[0x63],
+        reg: dst.index(),
+        rm,
+        immediate: &[],
+    };
+    Ok(instruction.encode())
+}
+
+/// Encodes `movsxd dst, src`, i.e. sign extends 32-bit `src` into 64-bit `dst`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 64-bit register or
+/// `src` is not a 32-bit register.
+pub fn encode_movsxd_reg_reg(
+    dst: GPR,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(src.size(), MachineSize::DWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    encode_movsxd(dst, RmOperand::Register(src.index()))
+}
+
+/// Encodes `movsxd dst, dword [src]`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 64-bit register.
+pub fn encode_movsxd_reg_mem(
+    dst: GPR,
+    src: Memory,
+) -> Result<EncodedInstruction, EncodingError> {
+    encode_movsxd(dst, RmOperand::Memory(src))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::models::{Displacement, Scale, Segment};
+
+    #[rstest]
+    #[case(GPR::RAX, GPR::RBX, &[0x48, 0x89, 0xD8])]
+    #[case(GPR::R8D, GPR::ECX, &[0x41, 0x89, 0xC8])]
+    #[case(GPR::SI, GPR::R15W, &[0x66, 0x44, 0x89, 0xFE])]
+    #[case(GPR::SPL, GPR::AL, &[0x40, 0x88, 0xC4])]
+    #[case(GPR::CL, GPR::DL, &[0x88, 0xD1])]
+    #[case(GPR::AH, GPR::BL, &[0x88, 0xDC])]
+    #[case(GPR::AL, GPR::BH, &[0x88, 0xF8])]
+    fn test_mov_reg_reg(#[case] dst: GPR, #[case] src: GPR, #[case] expected: &[u8]) {
+        assert_eq!(encode_mov_reg_reg(dst, src).unwrap().as_slice(), expected);
+    }
+
+    #[rstest]
+    #[case(GPR::EAX, 1, &[0xB8, 0x01, 0x00, 0x00, 0x00])]
+    #[case(GPR::RAX, 1, &[0x48, 0xC7, 0xC0, 0x01, 0x00, 0x00, 0x00])]
+    #[case(GPR::RAX, -1, &[0x48, 0xC7, 0xC0, 0xFF, 0xFF, 0xFF, 0xFF])]
+    #[case(GPR::RAX, 0xFFFF_FFFF, &[0xB8, 0xFF, 0xFF, 0xFF, 0xFF])]
+    #[case(GPR::R9, 0x8000_0000, &[0x41, 0xB9, 0x00, 0x00, 0x00, 0x80])]
+    #[case(GPR::RAX, 0x1_0000_0000, &[0x48, 0xB8, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00])]
+    #[case(GPR::R9, 0x1_0000_0000, &[0x49, 0xB9, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00])]
+    #[case(GPR::AL, 1, &[0xB0, 0x01])]
+    #[case(GPR::SIL, 1, &[0x40, 0xB6, 0x01])]
+    #[case(GPR::R10B, 0xFF, &[0x41, 0xB2, 0xFF])]
+    #[case(GPR::AH, 5, &[0xB4, 0x05])]
+    #[case(GPR::AX, 1, &[0x66, 0xB8, 0x01, 0x00])]
+    fn test_mov_reg_imm(#[case] dst: GPR, #[case] imm: i64, #[case] expected: &[u8]) {
+        assert_eq!(encode_mov_reg_imm(dst, imm).unwrap().as_slice(), expected);
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, 1, &[0x48, 0xB8, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00])]
+    #[case(GPR::R15, -2, &[0x49, 0xBF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])]
+    fn test_movabs_reg_imm(#[case] dst: GPR, #[case] imm: i64, #[case] expected: &[u8]) {
+        assert_eq!(
+            encode_movabs_reg_imm(dst, imm).unwrap().as_slice(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(GPR::AL, 256)]
+    #[case(GPR::AL, -129)]
+    #[case(GPR::AX, 0x1_0000)]
+    #[case(GPR::EAX, 0x1_0000_0000)]
+    fn test_mov_reg_imm_out_of_range(#[case] dst: GPR, #[case] imm: i64) {
+        assert!(matches!(
+            encode_mov_reg_imm(dst, imm),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_mov_reg_mem() {
+        let memory = Memory::based(GPR::RAX, 0).unwrap();
+        let encoded = encode_mov_reg_mem(GPR::R8, memory).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x4C, 0x8B, 0x00]);
+        let encoded = encode_mov_mem_reg(memory, GPR::R8).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x4C, 0x89, 0x00]);
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, GPR::ECX, &[0x48, 0x63, 0xC1])]
+    #[case(GPR::R9, GPR::R10D, &[0x4D, 0x63, 0xCA])]
+    #[case(GPR::RSP, GPR::ESP, &[0x48, 0x63, 0xE4])]
+    fn test_movsxd_reg_reg(#[case] dst: GPR, #[case] src: GPR, #[case] expected: &[u8]) {
+        assert_eq!(
+            encode_movsxd_reg_reg(dst, src).unwrap().as_slice(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_movsxd_reg_mem() {
+        let memory =
+            Memory::new(GPR::R11, GPR::RCX, Scale::Scale4, Displacement::ZERO).unwrap();
+        let encoded = encode_movsxd_reg_mem(GPR::RCX, memory).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x49, 0x63, 0x0C, 0x8B]);
+        assert!(matches!(
+            encode_movsxd_reg_mem(GPR::ECX, memory),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+        assert!(matches!(
+            encode_movsxd_reg_reg(GPR::RCX, GPR::RAX),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
+
+    #[test]
+    fn test_mov_errors() {
+        assert!(matches!(
+            encode_mov_reg_reg(GPR::RAX, GPR::EAX),
+            Err(EncodingError::RegistersSizeMismatch)
+        ));
+        assert!(matches!(
+            encode_mov_reg_imm(GPR::RIP, 0),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+        assert!(matches!(
+            encode_movabs_reg_imm(GPR::EAX, 0),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
+
+    #[test]
+    fn test_mov_segment() {
+        let encoded = encode_mov_thread_pointer(GPR::RAX).unwrap();
+        assert_eq!(
+            encoded.as_slice(),
+            &[0x64, 0x48, 0x8B, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00]
+        );
+        let memory = Memory::based(GPR::R8, 0).unwrap().with_segment(Segment::Gs);
+        let encoded = encode_mov_mem_reg(memory, GPR::AX).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x65, 0x66, 0x41, 0x89, 0x00]);
+        assert!(matches!(
+            encode_mov_thread_pointer(GPR::EAX),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
+
+    #[test]
+    fn test_mov_high_byte_with_rex() {
+        for (dst, src) in [
+            (GPR::AH, GPR::SPL),
+            (GPR::R8B, GPR::CH),
+            (GPR::DH, GPR::DIL),
+        ] {
+            assert!(matches!(
+                encode_mov_reg_reg(dst, src),
+                Err(EncodingError::HighByteRegisterWithRex)
+            ));
+        }
+
+        let extended = Memory::based(GPR::R8, 0).unwrap();
+        assert!(matches!(
+            encode_mov_reg_mem(GPR::AH, extended),
+            Err(EncodingError::HighByteRegisterWithRex)
+        ));
+        assert!(matches!(
+            encode_mov_mem_reg(extended, GPR::BH),
+            Err(EncodingError::HighByteRegisterWithRex)
+        ));
+
+        let legacy = Memory::based(GPR::RBX, 0).unwrap();
+        let encoded = encode_mov_reg_mem(GPR::AH, legacy).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x8A, 0x23]);
+        let encoded = encode_mov_reg_mem(GPR::SPL, extended).unwrap();
+        assert_eq!(encoded.as_slice(), &[0x41, 0x8A, 0x20]);
+    }
+}
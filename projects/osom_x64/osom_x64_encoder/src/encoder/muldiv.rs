@@ -0,0 +1,164 @@
+//! Multiplication and division instructions.
+
+use crate::models::{Memory, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{OperandSize, RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+#[inline(always)]
+fn encode_imul(
+    size: OperandSize,
+    force_rex: bool,
+    reg: u8,
+    rm: RmOperand,
+) -> EncodedInstruction {
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex,
+        opcode: &[0x0F, 0xAF],
+        reg,
+        rm,
+        immediate: &[],
+    };
+    instruction.encode()
+}
+
+/// Encodes `imul dst, src`, i.e. truncated signed multiplication.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a 16, 32 or 64-bit
+///   register.
+/// * [`EncodingError::RegistersSizeMismatch`] if `dst` and `src` have different sizes.
+pub fn encode_imul_reg_reg(
+    dst: GPR,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of_pair(dst, src)?;
+    if size.is_byte {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    Ok(encode_imul(
+        size,
+        false,
+        dst.index(),
+        RmOperand::Register(src.index()),
+    ))
+}
+
+/// Encodes `imul dst, [src]`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a 16, 32 or 64-bit register.
+pub fn encode_imul_reg_mem(
+    dst: GPR,
+    src: Memory,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(dst)?;
+    if size.is_byte {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    Ok(encode_imul(
+        size,
+        false,
+        dst.index(),
+        RmOperand::Memory(src),
+    ))
+}
+
+#[inline(always)]
+fn encode_division(
+    extension: u8,
+    src: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(src)?;
+    let opcode = if size.is_byte { 0xF6 } else { 0xF7 };
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex: src.needs_rex(),
+        opcode: &[opcode],
+        reg: extension,
+        rm: RmOperand::Register(src.encoding_index()),
+        immediate: &[],
+    };
+    Ok(instruction.encode())
+}
+
+/// Encodes `div src`, i.e. unsigned division of `rdx:rax` (or its narrower
+/// counterpart) by `src`. The quotient is stored in `rax` and the remainder
+/// in `rdx`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `src` is not a general purpose register.
+pub fn encode_div(src: GPR) -> Result<EncodedInstruction, EncodingError> {
+    encode_division(6, src)
+}
+
+/// Encodes `idiv src`, the signed counterpart of [`encode_div`].
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `src` is not a general purpose register.
+pub fn encode_idiv(src: GPR) -> Result<EncodedInstruction, EncodingError> {
+    encode_division(7, src)
+}
+
+/// Encodes `cqo`, i.e. sign extends `rax` into `rdx:rax`.
+#[must_use]
+#[inline(always)]
+pub const fn encode_cqo() -> EncodedInstruction {
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked([0x48, 0x99]) }
+}
+
+/// Encodes `cdq`, i.e. sign extends `eax` into `edx:eax`.
+#[must_use]
+#[inline(always)]
+pub const fn encode_cdq() -> EncodedInstruction {
+    unsafe { EncodedInstruction::from_no_feature_array_unchecked([0x99]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(encode_imul_reg_reg(GPR::RAX, GPR::RBX), &[0x48, 0x0F, 0xAF, 0xC3])]
+    #[case(encode_imul_reg_reg(GPR::CX, GPR::R10W), &[0x66, 0x41, 0x0F, 0xAF, 0xCA])]
+    #[case(
+        encode_imul_reg_mem(GPR::R9D, Memory::based(GPR::RSP, 8).unwrap()),
+        &[0x44, 0x0F, 0xAF, 0x4C, 0x24, 0x08]
+    )]
+    #[case(encode_idiv(GPR::RCX), &[0x48, 0xF7, 0xF9])]
+    #[case(encode_div(GPR::R11), &[0x49, 0xF7, 0xF3])]
+    #[case(encode_div(GPR::BL), &[0xF6, 0xF3])]
+    #[case(encode_idiv(GPR::SIL), &[0x40, 0xF6, 0xFE])]
+    fn test_muldiv(
+        #[case] encoded: Result<EncodedInstruction, EncodingError>,
+        #[case] expected: &[u8],
+    ) {
+        assert_eq!(encoded.unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_sign_extensions() {
+        assert_eq!(encode_cqo().as_slice(), &[0x48, 0x99]);
+        assert_eq!(encode_cdq().as_slice(), &[0x99]);
+    }
+
+    #[test]
+    fn test_imul_errors() {
+        assert!(matches!(
+            encode_imul_reg_reg(GPR::AL, GPR::BL),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+        assert!(matches!(
+            encode_imul_reg_reg(GPR::RAX, GPR::EBX),
+            Err(EncodingError::RegistersSizeMismatch)
+        ));
+    }
+}
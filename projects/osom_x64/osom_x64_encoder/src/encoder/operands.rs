@@ -0,0 +1,291 @@
+//! Shared machinery for instructions using `ModRM` (and possibly `SIB`) bytes.
+
+use crate::{
+    constants::MAX_INSTRUCTION_SIZE,
+    models::{CpuRequirement, MachineSize, Memory, Segment, GPR},
+};
+
+use super::{errors::EncodingError, EncodedInstruction};
+
+const REX: u8 = 0x40;
+const REX_W: u8 = 0b1000;
+const REX_R: u8 = 0b0100;
+const REX_X: u8 = 0b0010;
+const REX_B: u8 = 0b0001;
+
+/// The r/m part of `ModRM` byte: either a register (given by its 4-bit index)
+/// or a memory operand.
+#[derive(Clone, Copy)]
+pub(crate) enum RmOperand {
+    Register(u8),
+    Memory(Memory),
+}
+
+impl RmOperand {
+    /// Returns the segment override of a memory operand.
+    #[inline(always)]
+    pub(crate) const fn segment(self) -> Option<Segment> {
+        match self {
+            RmOperand::Register(_) => None,
+            RmOperand::Memory(memory) => memory.segment(),
+        }
+    }
+}
+
+pub(crate) const OPERAND_SIZE_PREFIX: u8 = 0x66;
+
+/// Describes how the size of a general purpose operand is encoded.
+#[derive(Clone, Copy)]
+pub(crate) struct OperandSize {
+    /// `0x66` for 16-bit operands.
+    pub prefix: Option<u8>,
+
+    /// Set for 64-bit operands.
+    pub rex_w: bool,
+
+    /// Set for 8-bit operands, which use opcodes with the lowest bit cleared.
+    pub is_byte: bool,
+}
+
+impl OperandSize {
+    /// Returns the encoding of `reg`'s size.
+    ///
+    /// # Errors
+    /// [`EncodingError::InvalidRegisterSize`] if `reg` is not a general purpose
+    /// register of size 8, 16, 32 or 64 bits.
+    pub(crate) const fn of(reg: GPR) -> Result<Self, EncodingError> {
+        let (prefix, rex_w, is_byte) = match reg.size() {
+            MachineSize::Byte => (None, false, true),
+            MachineSize::Word => (Some(OPERAND_SIZE_PREFIX), false, false),
+            MachineSize::DWord => (None, false, false),
+            MachineSize::QWord => (None, true, false),
+            _ => return Err(EncodingError::InvalidRegisterSize),
+        };
+        Ok(Self {
+            prefix,
+            rex_w,
+            is_byte,
+        })
+    }
+
+    /// Returns the same as [`OperandSize::of`], but additionally checks that
+    /// `other` has the same size as `reg`.
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidRegisterSize`] as in [`OperandSize::of`].
+    /// * [`EncodingError::RegistersSizeMismatch`] if sizes differ.
+    pub(crate) fn of_pair(reg: GPR, other: GPR) -> Result<Self, EncodingError> {
+        let size = Self::of(reg)?;
+        if reg.size() != other.size() {
+            return Err(EncodingError::RegistersSizeMismatch);
+        }
+        Ok(size)
+    }
+}
+
+/// Returns whether REX has to be forced for an instruction operating on `regs`
+/// and optionally on `memory`, i.e. whether one of `regs` is SPL, BPL, SIL or DIL.
+///
+/// # Errors
+/// [`EncodingError::HighByteRegisterWithRex`] if one of `regs` is AH, CH, DH or
+/// BH, and the instruction needs REX.
+pub(crate) fn force_rex(
+    regs: &[GPR],
+    memory: Option<Memory>,
+) -> Result<bool, EncodingError> {
+    let force_rex = regs.iter().any(GPR::needs_rex);
+    let needs_rex = force_rex || memory.is_some_and(|memory| memory.needs_rex());
+    if needs_rex && regs.iter().any(GPR::is_high_byte) {
+        return Err(EncodingError::HighByteRegisterWithRex);
+    }
+    Ok(force_rex)
+}
+
+/// Byte buffer with a fixed capacity of [`MAX_INSTRUCTION_SIZE`].
+pub(crate) struct InstructionBuffer {
+    buffer: [u8; MAX_INSTRUCTION_SIZE],
+    len: usize,
+}
+
+impl InstructionBuffer {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self {
+            buffer: [0u8; MAX_INSTRUCTION_SIZE],
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn push(&mut self, byte: u8) {
+        self.buffer[self.len] = byte;
+        self.len += 1;
+    }
+
+    #[inline(always)]
+    pub(crate) fn extend(&mut self, bytes: &[u8]) {
+        self.buffer[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline(always)]
+    pub(crate) fn finish(self) -> EncodedInstruction {
+        // SAFETY: push/extend panic on overflow, so len is at most MAX_INSTRUCTION_SIZE.
+        unsafe { EncodedInstruction::new_unchecked(self.len as u8, self.buffer) }
+            .with_cpu_requirement(CpuRequirement::NoFeature)
+    }
+}
+
+/// Describes an instruction of `[prefix] [REX] opcode ModRM [SIB] [disp] [imm]` shape.
+/// Memory operands with a segment override are additionally preceded by the
+/// override prefix.
+pub(crate) struct RmInstruction<'a> {
+    /// Legacy or mandatory prefix, e.g. `0x66` or `0xF2`.
+    pub prefix: Option<u8>,
+
+    pub rex_w: bool,
+
+    /// Emits REX even if no bits are set. Needed to address SPL, BPL, SIL and DIL.
+    pub force_rex: bool,
+
+    pub opcode: &'a [u8],
+
+    /// 4-bit register index or opcode extension placed in `ModRM.reg`.
+    pub reg: u8,
+
+    pub rm: RmOperand,
+
+    pub immediate: &'a [u8],
+}
+
+impl RmInstruction<'_> {
+    pub(crate) fn encode(&self) -> EncodedInstruction {
+        let mut rex = if self.rex_w { REX_W } else { 0 };
+        if self.reg & 0b1000 != 0 {
+            rex |= REX_R;
+        }
+
+        let reg_bits = (self.reg & 0b111) << 3;
+        let mut tail = InstructionBuffer::new();
+        match self.rm {
+            RmOperand::Register(index) => {
+                if index & 0b1000 != 0 {
+                    rex |= REX_B;
+                }
+                tail.push(0b1100_0000 | reg_bits | (index & 0b111));
+            }
+            RmOperand::Memory(memory) => {
+                rex |= encode_memory(&mut tail, reg_bits, memory);
+            }
+        }
+
+        let mut buffer = InstructionBuffer::new();
+        if let Some(segment) = self.rm.segment() {
+            buffer.push(segment.override_prefix());
+        }
+        if let Some(prefix) = self.prefix {
+            buffer.push(prefix);
+        }
+        if rex != 0 || self.force_rex {
+            buffer.push(REX | rex);
+        }
+        buffer.extend(self.opcode);
+        buffer.extend(&tail.buffer[..tail.len]);
+        buffer.extend(self.immediate);
+        buffer.finish()
+    }
+}
+
+/// Encodes instructions of `[prefix] [REX] (opcode + reg) [imm]` shape, where the
+/// low 3 bits of `reg` are added to the opcode.
+pub(crate) fn encode_opcode_with_reg(
+    prefix: Option<u8>,
+    rex_w: bool,
+    force_rex: bool,
+    opcode: u8,
+    reg: u8,
+    immediate: &[u8],
+) -> EncodedInstruction {
+    let mut rex = if rex_w { REX_W } else { 0 };
+    if reg & 0b1000 != 0 {
+        rex |= REX_B;
+    }
+
+    let mut buffer = InstructionBuffer::new();
+    if let Some(prefix) = prefix {
+        buffer.push(prefix);
+    }
+    if rex != 0 || force_rex {
+        buffer.push(REX | rex);
+    }
+    buffer.push(opcode + (reg & 0b111));
+    buffer.extend(immediate);
+    buffer.finish()
+}
+
+/// Encodes `ModRM`, `SIB` and displacement for `memory`. Returns REX bits
+/// needed for extended base and index registers.
+fn encode_memory(buffer: &mut InstructionBuffer, reg_bits: u8, memory: Memory) -> u8 {
+    const RM_SIB: u8 = 0b100;
+    const RM_DISP32: u8 = 0b101;
+    const SIB_NO_INDEX: u8 = 0b100;
+    const SIB_NO_BASE: u8 = 0b101;
+
+    let displacement = memory.displacement();
+    let displacement_bytes = displacement.to_le_bytes();
+
+    if memory.is_rip_relative() {
+        buffer.push(reg_bits | RM_DISP32);
+        buffer.extend(&displacement_bytes[..4]);
+        return 0;
+    }
+
+    let mut rex = 0;
+    let index_bits = if memory.has_index() {
+        let index = memory.index().index();
+        if index & 0b1000 != 0 {
+            rex |= REX_X;
+        }
+        index & 0b111
+    } else {
+        SIB_NO_INDEX
+    };
+
+    if !memory.has_base() {
+        let sib = (memory.scale().as_u8() << 6) | (index_bits << 3) | SIB_NO_BASE;
+        buffer.push(reg_bits | RM_SIB);
+        buffer.push(sib);
+        buffer.extend(&displacement_bytes[..4]);
+        return rex;
+    }
+
+    let base = memory.base().index();
+    if base & 0b1000 != 0 {
+        rex |= REX_B;
+    }
+    let base_bits = base & 0b111;
+
+    // RBP and R13 with mod=00 mean "no base", so they always need a displacement.
+    let mod_bits: u8 = match displacement.size() {
+        MachineSize::None if base_bits != 0b101 => 0b00,
+        MachineSize::None | MachineSize::Byte => 0b01,
+        _ => 0b10,
+    };
+
+    if memory.has_index() || base_bits == RM_SIB {
+        let sib = (memory.scale().as_u8() << 6) | (index_bits << 3) | base_bits;
+        buffer.push((mod_bits << 6) | reg_bits | RM_SIB);
+        buffer.push(sib);
+    } else {
+        buffer.push((mod_bits << 6) | reg_bits | base_bits);
+    }
+
+    match mod_bits {
+        0b01 => buffer.push(displacement_bytes[0]),
+        0b10 => buffer.extend(&displacement_bytes[..4]),
+        _ => {}
+    }
+
+    rex
+}
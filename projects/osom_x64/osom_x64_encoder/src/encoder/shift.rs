@@ -0,0 +1,97 @@
+//! Rotate and shift instructions.
+
+use crate::models::{Immediate, MachineSize, ShiftOp, GPR};
+
+use super::{
+    errors::EncodingError,
+    operands::{OperandSize, RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+#[inline(always)]
+fn encode_shift(
+    op: ShiftOp,
+    dst: GPR,
+    opcode: u8,
+    immediate: &[u8],
+) -> Result<EncodedInstruction, EncodingError> {
+    let size = OperandSize::of(dst)?;
+    let opcode = if size.is_byte { opcode } else { opcode | 1 };
+    let instruction = RmInstruction {
+        prefix: size.prefix,
+        rex_w: size.rex_w,
+        force_rex: dst.needs_rex(),
+        opcode: &[opcode],
+        reg: op.as_u8(),
+        rm: RmOperand::Register(dst.encoding_index()),
+        immediate,
+    };
+    Ok(instruction.encode())
+}
+
+/// Encodes `op dst, cl`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+pub fn encode_shift_reg_cl(
+    op: ShiftOp,
+    dst: GPR,
+) -> Result<EncodedInstruction, EncodingError> {
+    encode_shift(op, dst, 0xD2, &[])
+}
+
+/// Encodes `op dst, imm`. Shifts by 1 use the dedicated short form.
+///
+/// # Errors
+/// * [`EncodingError::InvalidRegisterSize`] if `dst` is not a general purpose register.
+/// * [`EncodingError::ArgumentOutOfRange`] if `imm` does not fit in 8 bits, either
+///   as a signed or as an unsigned value.
+///
+/// # Notes
+/// The CPU masks `imm` to 5 bits (6 bits for 64-bit operands), but it is
+/// encoded unchanged.
+pub fn encode_shift_reg_imm(
+    op: ShiftOp,
+    dst: GPR,
+    imm: impl Into<Immediate>,
+) -> Result<EncodedInstruction, EncodingError> {
+    let imm = imm.into();
+    if !imm.fits(MachineSize::Byte) {
+        return Err(EncodingError::ArgumentOutOfRange);
+    }
+    if imm.value() == 1 {
+        encode_shift(op, dst, 0xD0, &[])
+    } else {
+        encode_shift(op, dst, 0xC0, &imm.to_le_bytes()[..1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(encode_shift_reg_cl(ShiftOp::Shl, GPR::RAX), &[0x48, 0xD3, 0xE0])]
+    #[case(encode_shift_reg_cl(ShiftOp::Shr, GPR::R9D), &[0x41, 0xD3, 0xE9])]
+    #[case(encode_shift_reg_cl(ShiftOp::Rol, GPR::AL), &[0xD2, 0xC0])]
+    #[case(encode_shift_reg_imm(ShiftOp::Sar, GPR::RDX, 1), &[0x48, 0xD1, 0xFA])]
+    #[case(encode_shift_reg_imm(ShiftOp::Shl, GPR::R12, 5), &[0x49, 0xC1, 0xE4, 0x05])]
+    #[case(encode_shift_reg_imm(ShiftOp::Ror, GPR::SI, 3), &[0x66, 0xC1, 0xCE, 0x03])]
+    #[case(encode_shift_reg_imm(ShiftOp::Shr, GPR::EAX, 255u8), &[0xC1, 0xE8, 0xFF])]
+    fn test_shift(
+        #[case] encoded: Result<EncodedInstruction, EncodingError>,
+        #[case] expected: &[u8],
+    ) {
+        assert_eq!(encoded.unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn test_shift_out_of_range() {
+        assert!(matches!(
+            encode_shift_reg_imm(ShiftOp::Shl, GPR::RAX, 256),
+            Err(EncodingError::ArgumentOutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+//! SSE data movement instructions.
+
+use crate::models::{CpuFeature, CpuRequirement, FloatArithmeticOp, Memory, XMM};
+
+use super::{
+    operands::{RmInstruction, RmOperand},
+    EncodedInstruction,
+};
+
+const SCALAR_DOUBLE_PREFIX: u8 = 0xF2;
+const SCALAR_SINGLE_PREFIX: u8 = 0xF3;
+
+#[inline(always)]
+fn encode_sse(
+    prefix: Option<u8>,
+    opcode: u8,
+    reg: u8,
+    rm: RmOperand,
+) -> EncodedInstruction {
+    let instruction = RmInstruction {
+        prefix,
+        rex_w: false,
+        force_rex: false,
+        opcode: &[0x0F, opcode],
+        reg,
+        rm,
+        immediate: &[],
+    };
+    // Double precision instructions came with SSE2, single precision ones with SSE.
+    let feature = if prefix == Some(SCALAR_DOUBLE_PREFIX) {
+        CpuFeature::Sse2
+    } else {
+        CpuFeature::Sse
+    };
+    instruction
+        .encode()
+        .with_cpu_requirement(CpuRequirement::Feature(feature))
+}
+
+/// Encodes `movsd dst, src`, i.e. moves the low 64 bits of `src` into `dst`.
+#[must_use]
+#[inline]
+pub fn encode_movsd_xmm_xmm(dst: XMM, src: XMM) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_DOUBLE_PREFIX),
+        0x10,
+        dst.index(),
+        RmOperand::Register(src.index()),
+    )
+}
+
+/// Encodes `movsd dst, qword ptr [src]`.
+#[must_use]
+#[inline]
+pub fn encode_movsd_xmm_mem(dst: XMM, src: Memory) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_DOUBLE_PREFIX),
+        0x10,
+        dst.index(),
+        RmOperand::Memory(src),
+    )
+}
+
+/// Encodes `movsd qword ptr [dst], src`.
+#[must_use]
+#[inline]
+pub fn encode_movsd_mem_xmm(dst: Memory, src: XMM) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_DOUBLE_PREFIX),
+        0x11,
+        src.index(),
+        RmOperand::Memory(dst),
+    )
+}
+
+/// Encodes `movss dst, src`, i.e. moves the low 32 bits of `src` into `dst`.
+#[must_use]
+#[inline]
+pub fn encode_movss_xmm_xmm(dst: XMM, src: XMM) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_SINGLE_PREFIX),
+        0x10,
+        dst.index(),
+        RmOperand::Register(src.index()),
+    )
+}
+
+/// Encodes `movss dst, dword ptr [src]`.
+#[must_use]
+#[inline]
+pub fn encode_movss_xmm_mem(dst: XMM, src: Memory) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_SINGLE_PREFIX),
+        0x10,
+        dst.index(),
+        RmOperand::Memory(src),
+    )
+}
+
+/// Encodes `movss dword ptr [dst], src`.
+#[must_use]
+#[inline]
+pub fn encode_movss_mem_xmm(dst: Memory, src: XMM) -> EncodedInstruction {
+    encode_sse(
+        Some(SCALAR_SINGLE_PREFIX),
+        0x11,
+        src.index(),
+        RmOperand::Memory(dst),
+    )
+}
+
+/// Encodes `movaps dst, src`, i.e. moves all 128 bits of `src` into `dst`.
+#[must_use]
+#[inline]
+pub fn encode_movaps_xmm_xmm(dst: XMM, src: XMM) -> EncodedInstruction {
+    encode_sse(None, 0x28, dst.index(), RmOperand::Register(src.index()))
+}
+
+/// Encodes `movaps dst, xmmword ptr [src]`.
+///
+/// # Notes
+/// The address has to be 16-byte aligned at runtime, otherwise the instruction
+/// raises #GP.
+#[must_use]
+#[inline]
+pub fn encode_movaps_xmm_mem(dst: XMM, src: Memory) -> EncodedInstruction {
+    encode_sse(None, 0x28, dst.index(), RmOperand::Memory(src))
+}
+
+/// Encodes `movaps xmmword ptr [dst], src`.
+///
+/// # Notes
+/// The address has to be 16-byte aligned at runtime, otherwise the instruction
+/// raises #GP.
+#[must_use]
+#[inline]
+pub fn encode_movaps_mem_xmm(dst: Memory, src: XMM) -> EncodedInstruction {
+    encode_sse(None, 0x29, src.index(), RmOperand::Memory(dst))
+}
+
+/// Encodes scalar arithmetic `op dst, src`, e.g. `addsd xmm0, xmm1`.
+#[must_use]
+#[inline]
+pub fn encode_float_arith_xmm_xmm(
+    op: FloatArithmeticOp,
+    dst: XMM,
+    src: XMM,
+) -> EncodedInstruction {
+    encode_sse(
+        Some(op.prefix()),
+        op.opcode(),
+        dst.index(),
+        RmOperand::Register(src.index()),
+    )
+}
+
+/// Encodes scalar arithmetic `op dst, [src]`, e.g. `addsd xmm0, qword ptr [rax]`.
+#[must_use]
+#[inline]
+pub fn encode_float_arith_xmm_mem(
+    op: FloatArithmeticOp,
+    dst: XMM,
+    src: Memory,
+) -> EncodedInstruction {
+    encode_sse(
+        Some(op.prefix()),
+        op.opcode(),
+        dst.index(),
+        RmOperand::Memory(src),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GPR;
+
+    #[test]
+    fn test_movsd() {
+        let encoded =
+            encode_movsd_xmm_mem(XMM::XMM0, Memory::rip_relative(0x20).unwrap());
+        assert_eq!(
+            encoded.as_slice(),
+            &[0xF2, 0x0F, 0x10, 0x05, 0x20, 0x00, 0x00, 0x00]
+        );
+
+        let memory = Memory::based(GPR::RBP, -8).unwrap();
+        let encoded = encode_movsd_mem_xmm(memory, XMM::XMM9);
+        assert_eq!(encoded.as_slice(), &[0xF2, 0x44, 0x0F, 0x11, 0x4D, 0xF8]);
+
+        let encoded = encode_movsd_xmm_xmm(XMM::XMM1, XMM::XMM15);
+        assert_eq!(encoded.as_slice(), &[0xF2, 0x41, 0x0F, 0x10, 0xCF]);
+    }
+
+    #[test]
+    fn test_movss() {
+        let encoded = encode_movss_xmm_mem(XMM::XMM3, Memory::rip_relative(-4).unwrap());
+        assert_eq!(
+            encoded.as_slice(),
+            &[0xF3, 0x0F, 0x10, 0x1D, 0xFC, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn test_movaps() {
+        let memory = Memory::based(GPR::RSP, 16).unwrap();
+        let encoded = encode_movaps_xmm_mem(XMM::XMM0, memory);
+        assert_eq!(encoded.as_slice(), &[0x0F, 0x28, 0x44, 0x24, 0x10]);
+
+        let memory = Memory::based(GPR::RSP, 0).unwrap();
+        let encoded = encode_movaps_mem_xmm(memory, XMM::XMM9);
+        assert_eq!(encoded.as_slice(), &[0x44, 0x0F, 0x29, 0x0C, 0x24]);
+
+        let encoded = encode_movaps_xmm_xmm(XMM::XMM2, XMM::XMM3);
+        assert_eq!(encoded.as_slice(), &[0x0F, 0x28, 0xD3]);
+    }
+
+    #[test]
+    fn test_float_arith() {
+        let encoded =
+            encode_float_arith_xmm_xmm(FloatArithmeticOp::Addsd, XMM::XMM0, XMM::XMM9);
+        assert_eq!(encoded.as_slice(), &[0xF2, 0x41, 0x0F, 0x58, 0xC1]);
+
+        let memory = Memory::based(GPR::RAX, 0).unwrap();
+        let encoded =
+            encode_float_arith_xmm_mem(FloatArithmeticOp::Mulsd, XMM::XMM1, memory);
+        assert_eq!(encoded.as_slice(), &[0xF2, 0x0F, 0x59, 0x08]);
+
+        let encoded =
+            encode_float_arith_xmm_xmm(FloatArithmeticOp::Subss, XMM::XMM15, XMM::XMM2);
+        assert_eq!(encoded.as_slice(), &[0xF3, 0x44, 0x0F, 0x5C, 0xFA]);
+
+        let encoded =
+            encode_float_arith_xmm_xmm(FloatArithmeticOp::Divsd, XMM::XMM3, XMM::XMM4);
+        assert_eq!(encoded.as_slice(), &[0xF2, 0x0F, 0x5E, 0xDC]);
+    }
+}
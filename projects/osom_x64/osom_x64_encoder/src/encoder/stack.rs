@@ -0,0 +1,67 @@
+//! Stack manipulation instructions.
+
+use crate::models::{MachineSize, GPR};
+
+use super::{
+    errors::EncodingError, operands::encode_opcode_with_reg, EncodedInstruction,
+};
+
+#[inline(always)]
+fn encode_stack_op(opcode: u8, reg: GPR) -> Result<EncodedInstruction, EncodingError> {
+    if !matches!(reg.size(), MachineSize::QWord) {
+        return Err(EncodingError::InvalidRegisterSize);
+    }
+    Ok(encode_opcode_with_reg(
+        None,
+        false,
+        false,
+        opcode,
+        reg.index(),
+        &[],
+    ))
+}
+
+/// Encodes `push reg`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `reg` is not a 64-bit register.
+pub fn encode_push(reg: GPR) -> Result<EncodedInstruction, EncodingError> {
+    encode_stack_op(0x50, reg)
+}
+
+/// Encodes `pop reg`.
+///
+/// # Errors
+/// [`EncodingError::InvalidRegisterSize`] if `reg` is not a 64-bit register.
+pub fn encode_pop(reg: GPR) -> Result<EncodedInstruction, EncodingError> {
+    encode_stack_op(0x58, reg)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(GPR::RBX, &[0x53], &[0x5B])]
+    #[case(GPR::RAX, &[0x50], &[0x58])]
+    #[case(GPR::R12, &[0x41, 0x54], &[0x41, 0x5C])]
+    #[case(GPR::R15, &[0x41, 0x57], &[0x41, 0x5F])]
+    fn test_push_pop(#[case] reg: GPR, #[case] push: &[u8], #[case] pop: &[u8]) {
+        assert_eq!(encode_push(reg).unwrap().as_slice(), push);
+        assert_eq!(encode_pop(reg).unwrap().as_slice(), pop);
+    }
+
+    #[test]
+    fn test_push_invalid_size() {
+        assert!(matches!(
+            encode_push(GPR::EAX),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+        assert!(matches!(
+            encode_pop(GPR::RIP),
+            Err(EncodingError::InvalidRegisterSize)
+        ));
+    }
+}
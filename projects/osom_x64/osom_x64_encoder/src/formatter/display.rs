@@ -0,0 +1,551 @@
+use core::fmt;
+
+use crate::{
+    decoder::{decode, DecodedInstruction, Instruction},
+    encoder::EncodedInstruction,
+    models::{Condition, MachineSize, Memory, GPR, XMM},
+};
+
+use super::Syntax;
+
+/// Operand of a decoded instruction, as seen by the formatter.
+enum Operand {
+    Gpr(GPR),
+    Xmm(XMM),
+    Memory(Memory, MachineSize),
+    Immediate(i64),
+
+    /// Branch target relative to the end of the instruction.
+    Target(i64),
+}
+
+#[derive(Clone, Copy)]
+enum Content<'a> {
+    Decoded(DecodedInstruction),
+    Raw(&'a [u8]),
+}
+
+/// Renders an instruction as text. Created through [`DecodedInstruction::display`]
+/// or [`EncodedInstruction::display`].
+///
+/// # Notes
+/// Branch targets are printed relative to the start of the instruction (`$+8` or
+/// `.+8`), unless an address was given with [`InstructionDisplay::with_address`],
+/// in which case they are printed as absolute addresses. A label given with
+/// [`InstructionDisplay::with_target_label`] takes precedence over both.
+///
+/// Encoded instructions the decoder does not understand are printed as data
+/// directives.
+#[derive(Clone, Copy)]
+pub struct InstructionDisplay<'a> {
+    content: Content<'a>,
+    syntax: Syntax,
+    address: Option<u64>,
+    target_label: Option<&'a str>,
+}
+
+impl<'a> InstructionDisplay<'a> {
+    #[must_use]
+    #[inline(always)]
+    const fn new(content: Content<'a>, syntax: Syntax) -> Self {
+        Self {
+            content,
+            syntax,
+            address: None,
+            target_label: None,
+        }
+    }
+
+    /// Sets the address of the instruction, so that branch targets are
+    /// printed as absolute addresses.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_address(mut self, address: u64) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Sets the label printed in place of the branch target.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_target_label(mut self, label: &'a str) -> Self {
+        self.target_label = Some(label);
+        self
+    }
+
+    fn write_target(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        len: u8,
+        rel: i64,
+    ) -> fmt::Result {
+        if let Some(label) = self.target_label {
+            return f.write_str(label);
+        }
+
+        let offset = i64::from(len) + rel;
+        match self.address {
+            Some(address) => write!(f, "{:#x}", address.wrapping_add_signed(offset)),
+            None => self.syntax.write_relative(f, offset),
+        }
+    }
+
+    fn write_operand(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        len: u8,
+        operand: &Operand,
+    ) -> fmt::Result {
+        match operand {
+            Operand::Gpr(gpr) => self.syntax.write_gpr(f, *gpr),
+            Operand::Xmm(xmm) => self.syntax.write_xmm(f, *xmm),
+            Operand::Memory(memory, size) => {
+                self.syntax
+                    .write_memory(f, *memory, *size, self.target_label)
+            }
+            Operand::Immediate(imm) => self.syntax.write_immediate(f, *imm),
+            Operand::Target(rel) => self.write_target(f, len, *rel),
+        }
+    }
+
+    /// Writes `mnemonic` followed by `operands`. The `operands` are expected in Intel
+    /// order (destination first) and are reversed for AT&T syntax.
+    fn write_instruction(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        len: u8,
+        mnemonic: &str,
+        operands: &[Operand],
+    ) -> fmt::Result {
+        f.write_str(mnemonic)?;
+        let mut separator = " ";
+        let mut write_one = |f: &mut fmt::Formatter<'_>, operand| {
+            f.write_str(separator)?;
+            separator = ", ";
+            self.write_operand(f, len, operand)
+        };
+        match self.syntax {
+            Syntax::Intel => operands.iter().try_for_each(|op| write_one(f, op)),
+            Syntax::Att => operands.iter().rev().try_for_each(|op| write_one(f, op)),
+        }
+    }
+
+    /// Writes a jump or call through a register, marked with `*` in AT&T syntax.
+    fn write_indirect(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        mnemonic: &str,
+        target: GPR,
+    ) -> fmt::Result {
+        f.write_str(mnemonic)?;
+        match self.syntax {
+            Syntax::Intel => f.write_str(" ")?,
+            Syntax::Att => f.write_str(" *")?,
+        }
+        self.syntax.write_gpr(f, target)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn write_decoded(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        decoded: DecodedInstruction,
+    ) -> fmt::Result {
+        const QWORD: MachineSize = MachineSize::QWord;
+        const DWORD: MachineSize = MachineSize::DWord;
+        const XMMWORD: MachineSize = MachineSize::XMMWord;
+
+        let len = decoded.len();
+        let movsxd = match self.syntax {
+            Syntax::Intel => "movsxd",
+            Syntax::Att => "movslq",
+        };
+        let mut jcc_mnemonic = [0u8; 3];
+        let mut write = |mnemonic: &str, operands: &[Operand]| {
+            self.write_instruction(f, len, mnemonic, operands)
+        };
+
+        match decoded.instruction() {
+            Instruction::Ret => write("ret", &[]),
+            Instruction::Nop { .. } => write("nop", &[]),
+            Instruction::JmpRel8 { rel } => write("jmp", &[Operand::Target(rel.into())]),
+            Instruction::JmpRel32 { rel } => {
+                write("jmp", &[Operand::Target(rel.into())])
+            }
+            Instruction::JccRel8 { cond, rel } => {
+                write(jcc(&mut jcc_mnemonic, cond), &[Operand::Target(rel.into())])
+            }
+            Instruction::JccRel32 { cond, rel } => {
+                write(jcc(&mut jcc_mnemonic, cond), &[Operand::Target(rel.into())])
+            }
+            Instruction::JmpReg { target } => self.write_indirect(f, "jmp", target),
+            Instruction::CallRel32 { rel } => {
+                write("call", &[Operand::Target(rel.into())])
+            }
+            Instruction::CallReg { target } => self.write_indirect(f, "call", target),
+            Instruction::Lea { dst, src } => write(
+                "lea",
+                &[Operand::Gpr(dst), Operand::Memory(src, MachineSize::None)],
+            ),
+            Instruction::Push { reg } => write("push", &[Operand::Gpr(reg)]),
+            Instruction::Pop { reg } => write("pop", &[Operand::Gpr(reg)]),
+            Instruction::MovRegReg { dst, src } => {
+                write("mov", &[Operand::Gpr(dst), Operand::Gpr(src)])
+            }
+            Instruction::MovRegImm { dst, imm } => {
+                let mnemonic = if i32::try_from(imm).is_ok() || dst.size() != QWORD {
+                    "mov"
+                } else {
+                    "movabs"
+                };
+                write(mnemonic, &[Operand::Gpr(dst), Operand::Immediate(imm)])
+            }
+            Instruction::MovRegMem { dst, src } => write(
+                "mov",
+                &[Operand::Gpr(dst), Operand::Memory(src, dst.size())],
+            ),
+            Instruction::MovMemReg { dst, src } => write(
+                "mov",
+                &[Operand::Memory(dst, src.size()), Operand::Gpr(src)],
+            ),
+            Instruction::MovsxdRegReg { dst, src } => {
+                write(movsxd, &[Operand::Gpr(dst), Operand::Gpr(src)])
+            }
+            Instruction::MovsxdRegMem { dst, src } => {
+                write(movsxd, &[Operand::Gpr(dst), Operand::Memory(src, DWORD)])
+            }
+            Instruction::ArithRegReg { op, dst, src } => {
+                write(op.mnemonic(), &[Operand::Gpr(dst), Operand::Gpr(src)])
+            }
+            Instruction::ArithRegImm { op, dst, imm } => write(
+                op.mnemonic(),
+                &[Operand::Gpr(dst), Operand::Immediate(imm.into())],
+            ),
+            Instruction::ArithRegMem { op, dst, src } => write(
+                op.mnemonic(),
+                &[Operand::Gpr(dst), Operand::Memory(src, dst.size())],
+            ),
+            Instruction::ArithMemReg { op, dst, src } => write(
+                op.mnemonic(),
+                &[Operand::Memory(dst, src.size()), Operand::Gpr(src)],
+            ),
+            Instruction::ImulRegReg { dst, src } => {
+                write("imul", &[Operand::Gpr(dst), Operand::Gpr(src)])
+            }
+            Instruction::ImulRegMem { dst, src } => write(
+                "imul",
+                &[Operand::Gpr(dst), Operand::Memory(src, dst.size())],
+            ),
+            Instruction::Div { src } => write("div", &[Operand::Gpr(src)]),
+            Instruction::Idiv { src } => write("idiv", &[Operand::Gpr(src)]),
+            Instruction::Cqo => write("cqo", &[]),
+            Instruction::Cdq => write("cdq", &[]),
+            Instruction::ShiftRegCl { op, dst } => {
+                write(op.mnemonic(), &[Operand::Gpr(dst), Operand::Gpr(GPR::CL)])
+            }
+            Instruction::ShiftRegImm { op, dst, imm } => write(
+                op.mnemonic(),
+                &[Operand::Gpr(dst), Operand::Immediate(imm.into())],
+            ),
+            Instruction::BitCountRegReg { op, dst, src } => {
+                write(op.mnemonic(), &[Operand::Gpr(dst), Operand::Gpr(src)])
+            }
+            Instruction::BitCountRegMem { op, dst, src } => write(
+                op.mnemonic(),
+                &[Operand::Gpr(dst), Operand::Memory(src, dst.size())],
+            ),
+            Instruction::FloatArithXmmXmm { op, dst, src } => {
+                write(op.mnemonic(), &[Operand::Xmm(dst), Operand::Xmm(src)])
+            }
+            Instruction::FloatArithXmmMem { op, dst, src } => {
+                let size = if op.is_double() { QWORD } else { DWORD };
+                write(
+                    op.mnemonic(),
+                    &[Operand::Xmm(dst), Operand::Memory(src, size)],
+                )
+            }
+            Instruction::MovsdXmmXmm { dst, src } => {
+                write("movsd", &[Operand::Xmm(dst), Operand::Xmm(src)])
+            }
+            Instruction::MovsdXmmMem { dst, src } => {
+                write("movsd", &[Operand::Xmm(dst), Operand::Memory(src, QWORD)])
+            }
+            Instruction::MovsdMemXmm { dst, src } => {
+                write("movsd", &[Operand::Memory(dst, QWORD), Operand::Xmm(src)])
+            }
+            Instruction::MovssXmmXmm { dst, src } => {
+                write("movss", &[Operand::Xmm(dst), Operand::Xmm(src)])
+            }
+            Instruction::MovssXmmMem { dst, src } => {
+                write("movss", &[Operand::Xmm(dst), Operand::Memory(src, DWORD)])
+            }
+            Instruction::MovssMemXmm { dst, src } => {
+                write("movss", &[Operand::Memory(dst, DWORD), Operand::Xmm(src)])
+            }
+            Instruction::MovapsXmmXmm { dst, src } => {
+                write("movaps", &[Operand::Xmm(dst), Operand::Xmm(src)])
+            }
+            Instruction::MovapsXmmMem { dst, src } => write(
+                "movaps",
+                &[Operand::Xmm(dst), Operand::Memory(src, XMMWORD)],
+            ),
+            Instruction::MovapsMemXmm { dst, src } => write(
+                "movaps",
+                &[Operand::Memory(dst, XMMWORD), Operand::Xmm(src)],
+            ),
+        }
+    }
+}
+
+/// Writes `j<cc>` mnemonic into `buffer` and returns it as `&str`.
+fn jcc(buffer: &mut [u8; 3], cond: Condition) -> &str {
+    let suffix = cond.suffix().as_bytes();
+    buffer[0] = b'j';
+    buffer[1..=suffix.len()].copy_from_slice(suffix);
+    // SAFETY: both `j` and the suffix are ASCII.
+    unsafe { core::str::from_utf8_unchecked(&buffer[..=suffix.len()]) }
+}
+
+impl fmt::Display for InstructionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.content {
+            Content::Decoded(decoded) => self.write_decoded(f, decoded),
+            Content::Raw(bytes) => self.syntax.write_bytes(f, bytes),
+        }
+    }
+}
+
+impl DecodedInstruction {
+    /// Returns an object rendering the instruction in the given `syntax`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn display(&self, syntax: Syntax) -> InstructionDisplay<'static> {
+        InstructionDisplay::new(Content::Decoded(*self), syntax)
+    }
+}
+
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(Syntax::Intel).fmt(f)
+    }
+}
+
+impl EncodedInstruction {
+    /// Returns an object rendering the instruction in the given `syntax`.
+    #[must_use]
+    pub fn display(&self, syntax: Syntax) -> InstructionDisplay<'_> {
+        let content = match decode(self.as_slice()) {
+            Ok(decoded) => Content::Decoded(decoded),
+            Err(_) => Content::Raw(self.as_slice()),
+        };
+        InstructionDisplay::new(content, syntax)
+    }
+}
+
+impl fmt::Display for EncodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(Syntax::Intel).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        encoder::{arith, bitcount, lea, mov, muldiv, shift, sse, stack},
+        encoder::{call, jcc, jmp, misc},
+        models::{
+            BitCountOp, Condition, Displacement, FloatArithmeticOp, Memory, Scale,
+            Segment, ShiftOp, GPR, XMM, YMM,
+        },
+    };
+
+    #[rstest]
+    #[case(misc::encode_ret(), Syntax::Intel, "ret")]
+    #[case(misc::encode_nop(5).unwrap(), Syntax::Att, "nop")]
+    #[case(jmp::encode_jmp_rel8(-2), Syntax::Intel, "jmp $+0")]
+    #[case(jmp::encode_jmp_rel32(16), Syntax::Att, "jmp .+21")]
+    #[case(jcc::encode_jcc_rel8(Condition::NotEqual, -10), Syntax::Intel, "jne $-8")]
+    #[case(
+        jcc::encode_jcc_rel32(Condition::AboveOrEqual, 0),
+        Syntax::Att,
+        "jae .+6"
+    )]
+    #[case(
+        lea::encode_lea(GPR::RAX, Memory::based(GPR::RBP, -8).unwrap()).unwrap(),
+        Syntax::Intel,
+        "lea rax, [rbp-0x8]"
+    )]
+    #[case(
+        lea::encode_lea(GPR::RAX, Memory::based(GPR::RBP, -8).unwrap()).unwrap(),
+        Syntax::Att,
+        "lea -0x8(%rbp), %rax"
+    )]
+    #[case(
+        lea::encode_lea(
+            GPR::R8D,
+            Memory::new(GPR::RSI, GPR::R9, Scale::Scale4, Displacement::new(0x10)).unwrap()
+        )
+        .unwrap(),
+        Syntax::Intel,
+        "lea r8d, [rsi+r9*4+0x10]"
+    )]
+    #[case(
+        lea::encode_lea(
+            GPR::R8D,
+            Memory::new(GPR::NO_REG, GPR::R9, Scale::Scale4, Displacement::new(0x10)).unwrap()
+        )
+        .unwrap(),
+        Syntax::Att,
+        "lea 0x10(,%r9,4), %r8d"
+    )]
+    #[case(
+        sse::encode_movsd_xmm_mem(XMM::XMM1, Memory::rip_relative(0x20).unwrap()),
+        Syntax::Intel,
+        "movsd xmm1, qword ptr [rip+0x20]"
+    )]
+    #[case(
+        sse::encode_movss_mem_xmm(Memory::based(GPR::RSP, 0).unwrap(), XMM::XMM12),
+        Syntax::Att,
+        "movss %xmm12, (%rsp)"
+    )]
+    #[case(
+        mov::encode_mov_reg_reg(GPR::RAX, GPR::R12).unwrap(),
+        Syntax::Att,
+        "mov %r12, %rax"
+    )]
+    #[case(
+        mov::encode_mov_reg_imm(GPR::RAX, 0x1_0000_0000i64).unwrap(),
+        Syntax::Intel,
+        "movabs rax, 0x100000000"
+    )]
+    #[case(
+        mov::encode_mov_reg_mem(GPR::ECX, Memory::based(GPR::RBP, -4).unwrap()).unwrap(),
+        Syntax::Intel,
+        "mov ecx, dword ptr [rbp-0x4]"
+    )]
+    #[case(mov::encode_mov_thread_pointer(GPR::RAX).unwrap(), Syntax::Intel, "mov rax, qword ptr fs:[0x0]")]
+    #[case(mov::encode_mov_thread_pointer(GPR::RAX).unwrap(), Syntax::Att, "mov %fs:0x0, %rax")]
+    #[case(
+        mov::encode_mov_mem_reg(Memory::based(GPR::RBX, 8).unwrap().with_segment(Segment::Gs), GPR::ECX).unwrap(),
+        Syntax::Att,
+        "mov %ecx, %gs:0x8(%rbx)"
+    )]
+    #[case(arith::encode_add_reg_imm(GPR::RSP, 8).unwrap(), Syntax::Intel, "add rsp, 0x8")]
+    #[case(arith::encode_sub_reg_imm(GPR::RSP, -8).unwrap(), Syntax::Att, "sub $-0x8, %rsp")]
+    #[case(
+        arith::encode_cmp_mem_reg(Memory::based(GPR::RDI, 0).unwrap(), GPR::SIL).unwrap(),
+        Syntax::Att,
+        "cmp %sil, (%rdi)"
+    )]
+    #[case(stack::encode_push(GPR::R13).unwrap(), Syntax::Intel, "push r13")]
+    #[case(stack::encode_pop(GPR::RBP).unwrap(), Syntax::Att, "pop %rbp")]
+    #[case(
+        sse::encode_movaps_mem_xmm(Memory::based(GPR::RSP, 0x20).unwrap(), XMM::XMM6),
+        Syntax::Intel,
+        "movaps xmmword ptr [rsp+0x20], xmm6"
+    )]
+    #[case(muldiv::encode_idiv(GPR::RCX).unwrap(), Syntax::Intel, "idiv rcx")]
+    #[case(muldiv::encode_cqo(), Syntax::Att, "cqo")]
+    #[case(jmp::encode_jmp_reg(GPR::R11).unwrap(), Syntax::Intel, "jmp r11")]
+    #[case(jmp::encode_jmp_reg(GPR::RAX).unwrap(), Syntax::Att, "jmp *%rax")]
+    #[case(call::encode_call_reg(GPR::R12).unwrap(), Syntax::Intel, "call r12")]
+    #[case(call::encode_call_reg(GPR::RAX).unwrap(), Syntax::Att, "call *%rax")]
+    #[case(call::encode_call_rel32(0), Syntax::Intel, "call $+5")]
+    #[case(
+        mov::encode_movsxd_reg_mem(
+            GPR::RCX,
+            Memory::new(GPR::R11, GPR::RCX, Scale::Scale4, Displacement::ZERO).unwrap()
+        )
+        .unwrap(),
+        Syntax::Intel,
+        "movsxd rcx, dword ptr [r11+rcx*4]"
+    )]
+    #[case(
+        mov::encode_movsxd_reg_reg(GPR::RAX, GPR::ECX).unwrap(),
+        Syntax::Att,
+        "movslq %ecx, %rax"
+    )]
+    #[case(
+        shift::encode_shift_reg_cl(ShiftOp::Sar, GPR::R8).unwrap(),
+        Syntax::Att,
+        "sar %cl, %r8"
+    )]
+    #[case(
+        sse::encode_float_arith_xmm_mem(
+            FloatArithmeticOp::Divsd,
+            XMM::XMM2,
+            Memory::based(GPR::RAX, 8).unwrap()
+        ),
+        Syntax::Intel,
+        "divsd xmm2, qword ptr [rax+0x8]"
+    )]
+    #[case(
+        bitcount::encode_bit_count_reg_mem(
+            BitCountOp::Popcnt,
+            GPR::R10W,
+            Memory::based(GPR::RSI, 2).unwrap()
+        )
+        .unwrap(),
+        Syntax::Intel,
+        "popcnt r10w, word ptr [rsi+0x2]"
+    )]
+    #[case(
+        bitcount::encode_bit_count_reg_reg(BitCountOp::Lzcnt, GPR::RAX, GPR::R9).unwrap(),
+        Syntax::Att,
+        "lzcnt %r9, %rax"
+    )]
+    fn test_display(
+        #[case] encoded: EncodedInstruction,
+        #[case] syntax: Syntax,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(encoded.display(syntax).to_string(), expected);
+    }
+
+    #[test]
+    fn test_display_with_address_and_label() {
+        let encoded = jcc::encode_jcc_rel8(Condition::Less, 4);
+        let display = encoded.display(Syntax::Intel).with_address(0x1000);
+        assert_eq!(display.to_string(), "jl 0x1006");
+        assert_eq!(display.with_target_label(".L3").to_string(), "jl .L3");
+    }
+
+    #[test]
+    fn test_display_rip_relative_label() {
+        let encoded =
+            sse::encode_movsd_xmm_mem(XMM::XMM0, Memory::rip_relative(0).unwrap());
+        let intel = encoded.display(Syntax::Intel).with_target_label(".LC0");
+        assert_eq!(intel.to_string(), "movsd xmm0, qword ptr [rip+.LC0]");
+        let att = encoded.display(Syntax::Att).with_target_label(".LC0");
+        assert_eq!(att.to_string(), "movsd .LC0(%rip), %xmm0");
+    }
+
+    #[test]
+    fn test_display_raw_bytes() {
+        let encoded = unsafe { EncodedInstruction::from_array_unchecked([0x0F, 0x0B]) };
+        assert_eq!(encoded.to_string(), "db 0x0f, 0x0b");
+        assert_eq!(encoded.display(Syntax::Att).to_string(), ".byte 0x0f, 0x0b");
+    }
+
+    #[test]
+    fn test_register_names() {
+        struct Reg(&'static str, Syntax);
+        impl fmt::Display for Reg {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.1.write_register(f, self.0)
+            }
+        }
+
+        assert_eq!(GPR::R10D.name(), "r10d");
+        assert_eq!(GPR::SPL.name(), "spl");
+        assert_eq!(GPR::AX.name(), "ax");
+        assert_eq!(GPR::RIP.name(), "rip");
+        assert_eq!(XMM::XMM15.name(), "xmm15");
+        assert_eq!(YMM::YMM0.name(), "ymm0");
+        assert_eq!(Reg(GPR::RAX.name(), Syntax::Intel).to_string(), "rax");
+        assert_eq!(Reg(GPR::RAX.name(), Syntax::Att).to_string(), "%rax");
+    }
+}
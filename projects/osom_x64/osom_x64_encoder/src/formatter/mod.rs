@@ -0,0 +1,7 @@
+//! Textual rendering of instructions in Intel or AT&T syntax.
+
+mod display;
+mod syntax;
+
+pub use display::*;
+pub use syntax::*;
@@ -0,0 +1,225 @@
+use core::fmt;
+
+use crate::models::{MachineSize, Memory, GPR, XMM, YMM};
+
+/// Represents assembly syntax flavour used when rendering instructions.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum Syntax {
+    /// Destination first, bare register names, e.g. `mov rax, rbx`.
+    #[default]
+    Intel,
+
+    /// Source first, `%`-prefixed register names, e.g. `mov %rbx, %rax`. Mnemonics
+    /// have no size suffixes.
+    Att,
+}
+
+impl Syntax {
+    /// Writes register `name` in this syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    #[inline]
+    pub fn write_register(self, f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+        match self {
+            Syntax::Intel => f.write_str(name),
+            Syntax::Att => write!(f, "%{name}"),
+        }
+    }
+
+    /// Writes `gpr` in this syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    #[inline(always)]
+    pub fn write_gpr(self, f: &mut fmt::Formatter<'_>, gpr: GPR) -> fmt::Result {
+        self.write_register(f, gpr.name())
+    }
+
+    /// Writes `xmm` in this syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    #[inline(always)]
+    pub fn write_xmm(self, f: &mut fmt::Formatter<'_>, xmm: XMM) -> fmt::Result {
+        self.write_register(f, xmm.name())
+    }
+
+    /// Writes `ymm` in this syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    #[inline(always)]
+    pub fn write_ymm(self, f: &mut fmt::Formatter<'_>, ymm: YMM) -> fmt::Result {
+        self.write_register(f, ymm.name())
+    }
+
+    /// Writes a signed offset relative to the start of the current instruction,
+    /// i.e. `$+8` in Intel syntax and `.+8` in AT&T syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    pub fn write_relative(self, f: &mut fmt::Formatter<'_>, offset: i64) -> fmt::Result {
+        let anchor = match self {
+            Syntax::Intel => '$',
+            Syntax::Att => '.',
+        };
+        if offset < 0 {
+            write!(f, "{anchor}-{}", offset.unsigned_abs())
+        } else {
+            write!(f, "{anchor}+{offset}")
+        }
+    }
+
+    /// Writes an immediate, i.e. `0x8` in Intel syntax and `$0x8` in AT&T syntax.
+    /// Negative values are written with a minus sign.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    pub fn write_immediate(self, f: &mut fmt::Formatter<'_>, imm: i64) -> fmt::Result {
+        if matches!(self, Syntax::Att) {
+            f.write_str("$")?;
+        }
+        if imm < 0 {
+            write!(f, "-{:#x}", imm.unsigned_abs())
+        } else {
+            write!(f, "{imm:#x}")
+        }
+    }
+
+    /// Writes `memory` operand in this syntax. `size` is only used by Intel syntax
+    /// (`qword ptr`), and can be [`MachineSize::None`] to omit it. If `label` is
+    /// given, it replaces the displacement of RIP-relative operands.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    pub fn write_memory(
+        self,
+        f: &mut fmt::Formatter<'_>,
+        memory: Memory,
+        size: MachineSize,
+        label: Option<&str>,
+    ) -> fmt::Result {
+        let label = label.filter(|_| memory.is_rip_relative());
+        match self {
+            Syntax::Intel => write_intel_memory(f, memory, size, label),
+            Syntax::Att => write_att_memory(f, memory, label),
+        }
+    }
+
+    /// Writes raw bytes as a data directive, i.e. `db 0x0f, 0x0b` in Intel syntax
+    /// and `.byte 0x0f, 0x0b` in AT&T syntax.
+    ///
+    /// # Errors
+    /// Propagates errors from `f`.
+    pub fn write_bytes(self, f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+        let directive = match self {
+            Syntax::Intel => "db",
+            Syntax::Att => ".byte",
+        };
+        f.write_str(directive)?;
+        let mut separator = " ";
+        for byte in bytes {
+            write!(f, "{separator}{byte:#04x}")?;
+            separator = ", ";
+        }
+        Ok(())
+    }
+}
+
+const fn size_keyword(size: MachineSize) -> Option<&'static str> {
+    match size {
+        MachineSize::None => None,
+        MachineSize::Byte => Some("byte"),
+        MachineSize::Word => Some("word"),
+        MachineSize::DWord => Some("dword"),
+        MachineSize::QWord => Some("qword"),
+        MachineSize::XMMWord => Some("xmmword"),
+        MachineSize::YMMWord => Some("ymmword"),
+    }
+}
+
+#[inline(always)]
+const fn scale_factor(memory: Memory) -> u8 {
+    1 << memory.scale().as_u8()
+}
+
+fn write_displacement(f: &mut fmt::Formatter<'_>, displacement: i64) -> fmt::Result {
+    if displacement < 0 {
+        write!(f, "-{:#x}", displacement.unsigned_abs())
+    } else {
+        write!(f, "{displacement:#x}")
+    }
+}
+
+fn write_intel_memory(
+    f: &mut fmt::Formatter<'_>,
+    memory: Memory,
+    size: MachineSize,
+    label: Option<&str>,
+) -> fmt::Result {
+    if let Some(keyword) = size_keyword(size) {
+        write!(f, "{keyword} ptr ")?;
+    }
+    if let Some(segment) = memory.segment() {
+        write!(f, "{}:", segment.name())?;
+    }
+
+    f.write_str("[")?;
+    let mut is_first = true;
+    if memory.is_rip_relative() || memory.has_base() {
+        f.write_str(memory.base().name())?;
+        is_first = false;
+    }
+
+    if memory.has_index() {
+        if !is_first {
+            f.write_str("+")?;
+        }
+        write!(f, "{}*{}", memory.index().name(), scale_factor(memory))?;
+        is_first = false;
+    }
+
+    let displacement = memory.displacement().value();
+    if let Some(label) = label {
+        write!(f, "+{label}")?;
+    } else if is_first || displacement < 0 {
+        write_displacement(f, displacement)?;
+    } else if displacement > 0 {
+        write!(f, "+{displacement:#x}")?;
+    }
+    f.write_str("]")
+}
+
+fn write_att_memory(
+    f: &mut fmt::Formatter<'_>,
+    memory: Memory,
+    label: Option<&str>,
+) -> fmt::Result {
+    let has_registers =
+        memory.is_rip_relative() || memory.has_base() || memory.has_index();
+    let displacement = memory.displacement().value();
+
+    if let Some(segment) = memory.segment() {
+        write!(f, "%{}:", segment.name())?;
+    }
+    if let Some(label) = label {
+        f.write_str(label)?;
+    } else if displacement != 0 || !has_registers {
+        write_displacement(f, displacement)?;
+    }
+
+    if !has_registers {
+        return Ok(());
+    }
+
+    f.write_str("(")?;
+    if memory.is_rip_relative() || memory.has_base() {
+        write!(f, "%{}", memory.base().name())?;
+    }
+    if memory.has_index() {
+        write!(f, ",%{},{}", memory.index().name(), scale_factor(memory))?;
+    }
+    f.write_str(")")
+}
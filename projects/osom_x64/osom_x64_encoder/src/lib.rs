@@ -2,5 +2,7 @@
 #![allow(clippy::inline_always, clippy::module_name_repetitions)]
 mod check;
 pub mod constants;
+pub mod decoder;
 pub mod encoder;
+pub mod formatter;
 pub mod models;
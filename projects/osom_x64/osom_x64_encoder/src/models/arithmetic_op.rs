@@ -0,0 +1,52 @@
+/// Represents the classic two-operand ALU instructions sharing the same encoding
+/// scheme. The discriminant is the opcode extension used in `ModRM.reg`.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ArithmeticOp {
+    Add = 0,
+    Or = 1,
+    Adc = 2,
+    Sbb = 3,
+    And = 4,
+    Sub = 5,
+    Xor = 6,
+    Cmp = 7,
+}
+
+impl ArithmeticOp {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    pub const ALL: [Self; 8] = [
+        Self::Add,
+        Self::Or,
+        Self::Adc,
+        Self::Sbb,
+        Self::And,
+        Self::Sub,
+        Self::Xor,
+        Self::Cmp,
+    ];
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the mnemonic of the operation, e.g. `"add"`.
+    #[must_use]
+    pub const fn mnemonic(self) -> &'static str {
+        match self {
+            ArithmeticOp::Add => "add",
+            ArithmeticOp::Or => "or",
+            ArithmeticOp::Adc => "adc",
+            ArithmeticOp::Sbb => "sbb",
+            ArithmeticOp::And => "and",
+            ArithmeticOp::Sub => "sub",
+            ArithmeticOp::Xor => "xor",
+            ArithmeticOp::Cmp => "cmp",
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use super::CpuFeature;
+
+/// Represents bit counting instructions. The discriminant is the second opcode
+/// byte, following `F3 0F`.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum BitCountOp {
+    Popcnt = 0xB8,
+    Tzcnt = 0xBC,
+    Lzcnt = 0xBD,
+}
+
+impl BitCountOp {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    pub const ALL: [Self; 3] = [Self::Popcnt, Self::Tzcnt, Self::Lzcnt];
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the mnemonic of the operation, e.g. `"lzcnt"`.
+    #[must_use]
+    pub const fn mnemonic(self) -> &'static str {
+        match self {
+            BitCountOp::Popcnt => "popcnt",
+            BitCountOp::Tzcnt => "tzcnt",
+            BitCountOp::Lzcnt => "lzcnt",
+        }
+    }
+
+    /// Returns the ISA extension introducing the operation.
+    ///
+    /// # Notes
+    /// CPUs without the extension silently execute `tzcnt` as `bsf` and `lzcnt`
+    /// as `bsr`, which return different results.
+    #[must_use]
+    #[inline(always)]
+    pub const fn required_feature(self) -> CpuFeature {
+        match self {
+            BitCountOp::Popcnt => CpuFeature::Popcnt,
+            BitCountOp::Tzcnt => CpuFeature::Bmi1,
+            BitCountOp::Lzcnt => CpuFeature::Lzcnt,
+        }
+    }
+}
@@ -1,17 +1,19 @@
+use super::Flags;
+
 /// Represents conditions used in `CMOVcc` and `Jcc` instructions.
 #[repr(u8)]
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Condition {
-    /// In signed sense.
+    /// In unsigned sense.
     Above,
 
-    /// In signed sense.
+    /// In unsigned sense.
     AboveOrEqual,
 
-    /// In signed sense.
+    /// In unsigned sense.
     Below,
 
-    /// In signed sense.
+    /// In unsigned sense.
     BelowOrEqual,
 
     Carry,
@@ -19,16 +21,16 @@ pub enum Condition {
     Equal,
     NotEqual,
 
-    /// In unsigned sense.
+    /// In signed sense.
     Greater,
 
-    /// In unsigned sense.
+    /// In signed sense.
     GreaterOrEqual,
 
-    /// In unsigned sense.
+    /// In signed sense.
     Less,
 
-    /// In unsigned sense.
+    /// In signed sense.
     LessOrEqual,
     Overflow,
     NotOverflow,
@@ -44,4 +46,318 @@ impl Condition {
     const _CHECK: () = const {
         assert!(size_of::<Self>() == 1);
     };
+
+    /// All conditions, aliases included, in declaration order.
+    pub const ALL: [Self; 20] = [
+        Condition::Above,
+        Condition::AboveOrEqual,
+        Condition::Below,
+        Condition::BelowOrEqual,
+        Condition::Carry,
+        Condition::NotCarry,
+        Condition::Equal,
+        Condition::NotEqual,
+        Condition::Greater,
+        Condition::GreaterOrEqual,
+        Condition::Less,
+        Condition::LessOrEqual,
+        Condition::Overflow,
+        Condition::NotOverflow,
+        Condition::Parity,
+        Condition::NotParity,
+        Condition::Sign,
+        Condition::NotSign,
+        Condition::Zero,
+        Condition::NotZero,
+    ];
+
+    /// Returns the mnemonic suffix of the condition, e.g. `"ae"` for
+    /// [`Condition::AboveOrEqual`]. Prepend it with `j`, `set` or `cmov` to get
+    /// the full instruction mnemonic.
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Condition::Above => "a",
+            Condition::AboveOrEqual => "ae",
+            Condition::Below => "b",
+            Condition::BelowOrEqual => "be",
+            Condition::Carry => "c",
+            Condition::NotCarry => "nc",
+            Condition::Equal => "e",
+            Condition::NotEqual => "ne",
+            Condition::Greater => "g",
+            Condition::GreaterOrEqual => "ge",
+            Condition::Less => "l",
+            Condition::LessOrEqual => "le",
+            Condition::Overflow => "o",
+            Condition::NotOverflow => "no",
+            Condition::Parity => "p",
+            Condition::NotParity => "np",
+            Condition::Sign => "s",
+            Condition::NotSign => "ns",
+            Condition::Zero => "z",
+            Condition::NotZero => "nz",
+        }
+    }
+
+    /// Returns status flags tested by the condition.
+    #[must_use]
+    pub const fn flags(self) -> Flags {
+        match self {
+            Condition::Overflow | Condition::NotOverflow => Flags::OVERFLOW,
+            Condition::Below
+            | Condition::AboveOrEqual
+            | Condition::Carry
+            | Condition::NotCarry => Flags::CARRY,
+            Condition::Equal
+            | Condition::NotEqual
+            | Condition::Zero
+            | Condition::NotZero => Flags::ZERO,
+            Condition::BelowOrEqual | Condition::Above => {
+                Flags::CARRY.union(Flags::ZERO)
+            }
+            Condition::Sign | Condition::NotSign => Flags::SIGN,
+            Condition::Parity | Condition::NotParity => Flags::PARITY,
+            Condition::Less | Condition::GreaterOrEqual => {
+                Flags::SIGN.union(Flags::OVERFLOW)
+            }
+            Condition::LessOrEqual | Condition::Greater => {
+                Flags::ZERO.union(Flags::SIGN).union(Flags::OVERFLOW)
+            }
+        }
+    }
+
+    /// Returns the condition holding exactly when `self` does not, e.g.
+    /// [`Condition::BelowOrEqual`] for [`Condition::Above`]. Aliases map to
+    /// aliases, e.g. [`Condition::Carry`] to [`Condition::NotCarry`].
+    #[must_use]
+    pub const fn negate(self) -> Self {
+        match self {
+            Condition::Above => Condition::BelowOrEqual,
+            Condition::AboveOrEqual => Condition::Below,
+            Condition::Below => Condition::AboveOrEqual,
+            Condition::BelowOrEqual => Condition::Above,
+            Condition::Carry => Condition::NotCarry,
+            Condition::NotCarry => Condition::Carry,
+            Condition::Equal => Condition::NotEqual,
+            Condition::NotEqual => Condition::Equal,
+            Condition::Greater => Condition::LessOrEqual,
+            Condition::GreaterOrEqual => Condition::Less,
+            Condition::Less => Condition::GreaterOrEqual,
+            Condition::LessOrEqual => Condition::Greater,
+            Condition::Overflow => Condition::NotOverflow,
+            Condition::NotOverflow => Condition::Overflow,
+            Condition::Parity => Condition::NotParity,
+            Condition::NotParity => Condition::Parity,
+            Condition::Sign => Condition::NotSign,
+            Condition::NotSign => Condition::Sign,
+            Condition::Zero => Condition::NotZero,
+            Condition::NotZero => Condition::Zero,
+        }
+    }
+
+    /// Returns the condition to test after `cmp rhs, lhs` to get the same result
+    /// as testing `self` after `cmp lhs, rhs`, e.g. [`Condition::Greater`] for
+    /// [`Condition::Less`].
+    ///
+    /// Returns [`None`] for overflow, parity and sign conditions, which have no
+    /// such counterpart.
+    ///
+    /// # Notes
+    /// [`Condition::Carry`] and [`Condition::NotCarry`] are treated as
+    /// [`Condition::Below`] and [`Condition::AboveOrEqual`] respectively.
+    #[must_use]
+    pub const fn swap_operands(self) -> Option<Self> {
+        let swapped = match self {
+            Condition::Above => Condition::Below,
+            Condition::AboveOrEqual | Condition::NotCarry => Condition::BelowOrEqual,
+            Condition::Below | Condition::Carry => Condition::Above,
+            Condition::BelowOrEqual => Condition::AboveOrEqual,
+            Condition::Greater => Condition::Less,
+            Condition::GreaterOrEqual => Condition::LessOrEqual,
+            Condition::Less => Condition::Greater,
+            Condition::LessOrEqual => Condition::GreaterOrEqual,
+            Condition::Equal
+            | Condition::NotEqual
+            | Condition::Zero
+            | Condition::NotZero => self,
+            Condition::Overflow
+            | Condition::NotOverflow
+            | Condition::Parity
+            | Condition::NotParity
+            | Condition::Sign
+            | Condition::NotSign => return None,
+        };
+        Some(swapped)
+    }
+
+    /// Returns `true` for comparisons of signed integers, i.e. greater and less
+    /// conditions.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Condition::Greater
+                | Condition::GreaterOrEqual
+                | Condition::Less
+                | Condition::LessOrEqual
+        )
+    }
+
+    /// Returns `true` for comparisons of unsigned integers, i.e. above and below
+    /// conditions, [`Condition::Carry`] and [`Condition::NotCarry`] included.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_unsigned(self) -> bool {
+        matches!(
+            self,
+            Condition::Above
+                | Condition::AboveOrEqual
+                | Condition::Below
+                | Condition::BelowOrEqual
+                | Condition::Carry
+                | Condition::NotCarry
+        )
+    }
+
+    /// Returns `true` if the condition holds when `flags` are set, and the
+    /// remaining status flags are cleared.
+    #[must_use]
+    pub const fn evaluate(self, flags: Flags) -> bool {
+        let carry = flags.contains(Flags::CARRY);
+        let zero = flags.contains(Flags::ZERO);
+        let sign = flags.contains(Flags::SIGN);
+        let overflow = flags.contains(Flags::OVERFLOW);
+        let parity = flags.contains(Flags::PARITY);
+        match self {
+            Condition::Above => !carry && !zero,
+            Condition::AboveOrEqual | Condition::NotCarry => !carry,
+            Condition::Below | Condition::Carry => carry,
+            Condition::BelowOrEqual => carry || zero,
+            Condition::Equal | Condition::Zero => zero,
+            Condition::NotEqual | Condition::NotZero => !zero,
+            Condition::Greater => !zero && sign == overflow,
+            Condition::GreaterOrEqual => sign == overflow,
+            Condition::Less => sign != overflow,
+            Condition::LessOrEqual => zero || sign != overflow,
+            Condition::Overflow => overflow,
+            Condition::NotOverflow => !overflow,
+            Condition::Parity => parity,
+            Condition::NotParity => !parity,
+            Condition::Sign => sign,
+            Condition::NotSign => !sign,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::jcc::encode_jcc_rel8;
+
+    /// Returns flags set by `cmp lhs, rhs`.
+    #[allow(clippy::cast_sign_loss)]
+    fn compare(lhs: i8, rhs: i8) -> Flags {
+        let (result, overflow) = lhs.overflowing_sub(rhs);
+        let flags = [
+            (Flags::CARRY, (lhs as u8) < (rhs as u8)),
+            (Flags::ZERO, result == 0),
+            (Flags::SIGN, result < 0),
+            (Flags::OVERFLOW, overflow),
+            (Flags::PARITY, result.count_ones() % 2 == 0),
+        ];
+        flags
+            .into_iter()
+            .filter(|(_, set)| *set)
+            .fold(Flags::NONE, |flags, (flag, _)| flags.union(flag))
+    }
+
+    #[test]
+    fn test_negate_flips_opcode_low_bit() {
+        for cond in Condition::ALL {
+            let opcode = encode_jcc_rel8(cond, 0).as_slice()[0];
+            let negated = encode_jcc_rel8(cond.negate(), 0).as_slice()[0];
+            assert_eq!(negated, opcode ^ 1, "{cond:?}");
+            assert_eq!(cond.negate().negate(), cond);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_all_flags() {
+        for bits in 0..32u32 {
+            let flags = [
+                Flags::CARRY,
+                Flags::ZERO,
+                Flags::SIGN,
+                Flags::OVERFLOW,
+                Flags::PARITY,
+            ]
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| bits & (1 << index) != 0)
+            .fold(Flags::NONE, |flags, (_, flag)| flags.union(flag));
+            for cond in Condition::ALL {
+                assert_ne!(cond.evaluate(flags), cond.negate().evaluate(flags));
+                let ignored = Flags::STATUS.difference(cond.flags());
+                assert_eq!(
+                    cond.evaluate(flags),
+                    cond.evaluate(flags.union(ignored)),
+                    "{cond:?} depends on flags it does not test"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_sign_loss)]
+    fn test_evaluate_comparisons() {
+        for lhs in i8::MIN..=i8::MAX {
+            for rhs in i8::MIN..=i8::MAX {
+                let flags = compare(lhs, rhs);
+                let (ulhs, urhs) = (lhs as u8, rhs as u8);
+                let expected = [
+                    (Condition::Equal, lhs == rhs),
+                    (Condition::NotEqual, lhs != rhs),
+                    (Condition::Greater, lhs > rhs),
+                    (Condition::GreaterOrEqual, lhs >= rhs),
+                    (Condition::Less, lhs < rhs),
+                    (Condition::LessOrEqual, lhs <= rhs),
+                    (Condition::Above, ulhs > urhs),
+                    (Condition::AboveOrEqual, ulhs >= urhs),
+                    (Condition::Below, ulhs < urhs),
+                    (Condition::BelowOrEqual, ulhs <= urhs),
+                ];
+                for (cond, expected) in expected {
+                    assert_eq!(cond.evaluate(flags), expected, "{lhs} {cond:?} {rhs}");
+                }
+
+                let swapped_flags = compare(rhs, lhs);
+                for cond in Condition::ALL {
+                    if let Some(swapped) = cond.swap_operands() {
+                        assert_eq!(
+                            cond.evaluate(flags),
+                            swapped.evaluate(swapped_flags),
+                            "{lhs} {cond:?} {rhs}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_signedness() {
+        for cond in Condition::ALL {
+            assert!(!(cond.is_signed() && cond.is_unsigned()));
+            assert_eq!(cond.negate().is_signed(), cond.is_signed());
+            assert_eq!(cond.negate().is_unsigned(), cond.is_unsigned());
+            if cond.is_signed() || cond.is_unsigned() {
+                assert_eq!(cond.swap_operands().unwrap().is_signed(), cond.is_signed());
+            }
+        }
+        assert!(Condition::Less.is_signed());
+        assert!(Condition::Below.is_unsigned());
+        assert!(!Condition::Equal.is_signed() && !Condition::Equal.is_unsigned());
+    }
 }
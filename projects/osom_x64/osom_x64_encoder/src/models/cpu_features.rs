@@ -0,0 +1,239 @@
+/// Represents an ISA extension, which instructions may depend on.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum CpuFeature {
+    Sse,
+    Sse2,
+    Sse3,
+    Ssse3,
+    Sse41,
+    Sse42,
+    Popcnt,
+    Lzcnt,
+    Bmi1,
+    Bmi2,
+    Avx,
+    Avx2,
+    Fma,
+}
+
+impl CpuFeature {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    /// All features, in declaration order.
+    pub const ALL: [Self; 13] = [
+        CpuFeature::Sse,
+        CpuFeature::Sse2,
+        CpuFeature::Sse3,
+        CpuFeature::Ssse3,
+        CpuFeature::Sse41,
+        CpuFeature::Sse42,
+        CpuFeature::Popcnt,
+        CpuFeature::Lzcnt,
+        CpuFeature::Bmi1,
+        CpuFeature::Bmi2,
+        CpuFeature::Avx,
+        CpuFeature::Avx2,
+        CpuFeature::Fma,
+    ];
+
+    /// Returns the name of the feature as used by `/proc/cpuinfo` and
+    /// `target_feature`, e.g. `"sse4.1"`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            CpuFeature::Sse => "sse",
+            CpuFeature::Sse2 => "sse2",
+            CpuFeature::Sse3 => "sse3",
+            CpuFeature::Ssse3 => "ssse3",
+            CpuFeature::Sse41 => "sse4.1",
+            CpuFeature::Sse42 => "sse4.2",
+            CpuFeature::Popcnt => "popcnt",
+            CpuFeature::Lzcnt => "lzcnt",
+            CpuFeature::Bmi1 => "bmi1",
+            CpuFeature::Bmi2 => "bmi2",
+            CpuFeature::Avx => "avx",
+            CpuFeature::Avx2 => "avx2",
+            CpuFeature::Fma => "fma",
+        }
+    }
+}
+
+/// Represents what an encoded instruction requires from the CPU.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum CpuRequirement {
+    /// The instruction was not produced by an encoder, e.g. it was built from
+    /// raw bytes, thus its requirements are not known.
+    Unknown,
+
+    /// The instruction is supported by every x86-64 CPU.
+    NoFeature,
+
+    /// The instruction requires the given feature.
+    Feature(CpuFeature),
+}
+
+impl CpuRequirement {
+    /// Returns `true` if the requirement is known and met by `features`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_met_by(self, features: CpuFeatures) -> bool {
+        match self {
+            CpuRequirement::Unknown => false,
+            CpuRequirement::NoFeature => true,
+            CpuRequirement::Feature(feature) => features.contains(feature),
+        }
+    }
+}
+
+/// Represents a set of [`CpuFeature`]s, e.g. supported by the CPU the code is
+/// going to run on.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct CpuFeatures {
+    bits: u16,
+}
+
+impl CpuFeatures {
+    pub const NONE: Self = Self { bits: 0 };
+
+    /// Features every x86-64 CPU supports, i.e. SSE and SSE2.
+    pub const BASELINE: Self = Self::NONE.with(CpuFeature::Sse).with(CpuFeature::Sse2);
+
+    pub const ALL: Self = Self {
+        bits: (1 << CpuFeature::ALL.len()) - 1,
+    };
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn with(self, feature: CpuFeature) -> Self {
+        Self {
+            bits: self.bits | (1 << feature as u8),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn without(self, feature: CpuFeature) -> Self {
+        Self {
+            bits: self.bits & !(1 << feature as u8),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn contains(self, feature: CpuFeature) -> bool {
+        self.bits & (1 << feature as u8) != 0
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns an iterator over features in the set, in declaration order.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = CpuFeature> {
+        CpuFeature::ALL
+            .into_iter()
+            .filter(move |feature| self.contains(*feature))
+    }
+
+    /// Returns features supported by the current CPU, as reported by `cpuid`.
+    ///
+    /// # Notes
+    /// AVX, AVX2 and FMA are reported only if the operating system saves YMM
+    /// registers on context switches, as reported by `xgetbv`.
+    #[cfg(target_arch = "x86_64")]
+    #[must_use]
+    #[allow(unused_unsafe)]
+    pub fn detect() -> Self {
+        use core::arch::x86_64::{__cpuid, __cpuid_count, _xgetbv};
+
+        const fn bit(register: u32, bit: u32) -> bool {
+            register & (1 << bit) != 0
+        }
+
+        // SAFETY: `cpuid` is available on every x86-64 CPU, leaves above the
+        // maximal one are not queried.
+        let (leaf1, leaf7, extended) = unsafe {
+            let max_leaf = __cpuid(0).eax;
+            let max_extended_leaf = __cpuid(0x8000_0000).eax;
+            let leaf7 = if max_leaf >= 7 {
+                __cpuid_count(7, 0).ebx
+            } else {
+                0
+            };
+            let extended = if max_extended_leaf >= 0x8000_0001 {
+                __cpuid(0x8000_0001).ecx
+            } else {
+                0
+            };
+            (__cpuid(1), leaf7, extended)
+        };
+
+        // SAFETY: `xgetbv` is available when OSXSAVE is set.
+        let saves_ymm = bit(leaf1.ecx, 27) && unsafe { _xgetbv(0) } & 0b110 == 0b110;
+
+        let detected = [
+            (CpuFeature::Sse, bit(leaf1.edx, 25)),
+            (CpuFeature::Sse2, bit(leaf1.edx, 26)),
+            (CpuFeature::Sse3, bit(leaf1.ecx, 0)),
+            (CpuFeature::Ssse3, bit(leaf1.ecx, 9)),
+            (CpuFeature::Sse41, bit(leaf1.ecx, 19)),
+            (CpuFeature::Sse42, bit(leaf1.ecx, 20)),
+            (CpuFeature::Popcnt, bit(leaf1.ecx, 23)),
+            (CpuFeature::Lzcnt, bit(extended, 5)),
+            (CpuFeature::Bmi1, bit(leaf7, 3)),
+            (CpuFeature::Bmi2, bit(leaf7, 8)),
+            (CpuFeature::Avx, saves_ymm && bit(leaf1.ecx, 28)),
+            (CpuFeature::Avx2, saves_ymm && bit(leaf7, 5)),
+            (CpuFeature::Fma, saves_ymm && bit(leaf1.ecx, 12)),
+        ];
+        detected
+            .into_iter()
+            .filter(|(_, supported)| *supported)
+            .fold(Self::NONE, |features, (feature, _)| features.with(feature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_operations() {
+        let features = CpuFeatures::BASELINE.with(CpuFeature::Avx2);
+        assert!(features.contains(CpuFeature::Sse2));
+        assert!(features.contains(CpuFeature::Avx2));
+        assert!(!features.contains(CpuFeature::Avx));
+        assert_eq!(
+            features.iter().collect::<Vec<_>>(),
+            [CpuFeature::Sse, CpuFeature::Sse2, CpuFeature::Avx2]
+        );
+        assert_eq!(features.without(CpuFeature::Avx2), CpuFeatures::BASELINE);
+        assert!(CpuFeatures::NONE.is_empty());
+        assert_eq!(CpuFeatures::ALL.iter().count(), CpuFeature::ALL.len());
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_detect() {
+        let features = CpuFeatures::detect();
+        assert_eq!(features.union(CpuFeatures::BASELINE), features);
+        if features.contains(CpuFeature::Avx2) {
+            assert!(features.contains(CpuFeature::Avx));
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::encoder::errors::EncodingError;
+
+use super::{Immediate, MachineSize};
+
+/// Represents the displacement of a [`Memory`] operand, or the target of a
+/// relative branch.
+///
+/// # Notes
+/// The value is kept as `i64`. `ModRM` encodes displacements as sign extended
+/// 8-bit or 32-bit values, the encoder picks the shortest one. The same holds
+/// for `rel8` and `rel32` branch forms.
+///
+/// [`Memory`]: super::Memory
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Displacement {
+    value: i64,
+}
+
+impl Displacement {
+    pub const ZERO: Self = Self::new(0);
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(value: i64) -> Self {
+        Self { value }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn value(self) -> i64 {
+        self.value
+    }
+
+    /// Returns the smallest encodable size of the displacement:
+    /// * [`MachineSize::None`] for zero, which needs no displacement bytes,
+    /// * [`MachineSize::Byte`] for sign extended 8-bit displacements,
+    /// * [`MachineSize::DWord`] for sign extended 32-bit displacements,
+    /// * [`MachineSize::QWord`] otherwise, which `ModRM` cannot encode.
+    #[must_use]
+    pub const fn size(self) -> MachineSize {
+        if self.value == 0 {
+            return MachineSize::None;
+        }
+        match Immediate::new(self.value).sign_extended_size() {
+            MachineSize::Byte => MachineSize::Byte,
+            MachineSize::Word | MachineSize::DWord => MachineSize::DWord,
+            _ => MachineSize::QWord,
+        }
+    }
+
+    /// Returns `true` if the displacement fits in sign extended 32 bits, i.e. it
+    /// is encodable in `ModRM`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_encodable(self) -> bool {
+        !matches!(self.size(), MachineSize::QWord)
+    }
+
+    /// Converts the displacement, relative to the start of an instruction of
+    /// `size` bytes, into the 32-bit offset relative to its end, as encoded by
+    /// relative branches.
+    pub(crate) fn relative_to_end(self, size: usize) -> Result<i32, EncodingError> {
+        let size = i64::try_from(size).map_err(|_| EncodingError::ArgumentOutOfRange)?;
+        self.value
+            .checked_sub(size)
+            .and_then(|rel| i32::try_from(rel).ok())
+            .ok_or(EncodingError::ArgumentOutOfRange)
+    }
+
+    /// Returns little endian bytes of the value. Its first `n` bytes are the value
+    /// encoded in `n` bytes.
+    #[must_use]
+    #[inline(always)]
+    pub const fn to_le_bytes(self) -> [u8; 8] {
+        self.value.to_le_bytes()
+    }
+}
+
+macro_rules! displacement_from {
+    ( $( $ty: ty ),* ) => {
+        $(
+            impl From<$ty> for Displacement {
+                #[inline(always)]
+                fn from(value: $ty) -> Self {
+                    Self::new(i64::from(value))
+                }
+            }
+        )*
+    };
+}
+
+displacement_from!(i8, i16, i32, i64, u8, u16, u32);
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, MachineSize::None)]
+    #[case(1, MachineSize::Byte)]
+    #[case(-128, MachineSize::Byte)]
+    #[case(128, MachineSize::DWord)]
+    #[case(-129, MachineSize::DWord)]
+    #[case(0x7FFF_FFFF, MachineSize::DWord)]
+    #[case(-0x8000_0000, MachineSize::DWord)]
+    #[case(0x8000_0000, MachineSize::QWord)]
+    #[case(i64::MIN, MachineSize::QWord)]
+    fn test_size(#[case] value: i64, #[case] expected: MachineSize) {
+        let displacement = Displacement::new(value);
+        assert_eq!(displacement.size(), expected);
+        assert_eq!(
+            displacement.is_encodable(),
+            !matches!(expected, MachineSize::QWord)
+        );
+    }
+}
@@ -0,0 +1,94 @@
+/// Represents a set of RFLAGS status bits, stored at their RFLAGS positions.
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Flags {
+    bits: u16,
+}
+
+impl Flags {
+    pub const NONE: Self = Self { bits: 0 };
+    pub const CARRY: Self = Self { bits: 1 << 0 };
+    pub const PARITY: Self = Self { bits: 1 << 2 };
+    pub const AUXILIARY: Self = Self { bits: 1 << 4 };
+    pub const ZERO: Self = Self { bits: 1 << 6 };
+    pub const SIGN: Self = Self { bits: 1 << 7 };
+    pub const OVERFLOW: Self = Self { bits: 1 << 11 };
+
+    /// All status flags: CF, PF, AF, ZF, SF and OF.
+    pub const STATUS: Self = Self::CARRY
+        .union(Self::PARITY)
+        .union(Self::AUXILIARY)
+        .union(Self::ZERO)
+        .union(Self::SIGN)
+        .union(Self::OVERFLOW);
+
+    /// Extracts status flags from the value of RFLAGS, e.g. as stored by `pushfq`.
+    /// Other bits are ignored.
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_rflags(rflags: u64) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            bits: rflags as u16 & Self::STATUS.bits,
+        }
+    }
+
+    /// Returns the flags as RFLAGS bits.
+    #[must_use]
+    #[inline(always)]
+    pub const fn bits(self) -> u16 {
+        self.bits
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn difference(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & !other.bits,
+        }
+    }
+
+    /// Returns `true` if all flags in `other` are in `self`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn contains(self, other: Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(Flags::STATUS.bits(), 0x8D5);
+        assert_eq!(Flags::from_rflags(0x246), Flags::PARITY.union(Flags::ZERO));
+        assert!(Flags::STATUS.contains(Flags::SIGN.union(Flags::CARRY)));
+        assert!(!Flags::CARRY.contains(Flags::STATUS));
+        assert_eq!(Flags::STATUS.difference(Flags::STATUS), Flags::NONE);
+        assert!(Flags::CARRY.intersection(Flags::ZERO).is_empty());
+    }
+}
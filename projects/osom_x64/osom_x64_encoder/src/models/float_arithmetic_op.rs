@@ -0,0 +1,81 @@
+/// Represents scalar SSE arithmetic instructions, both on `f64` (`sd` suffix)
+/// and `f32` (`ss` suffix) values.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum FloatArithmeticOp {
+    Addsd,
+    Subsd,
+    Mulsd,
+    Divsd,
+    Addss,
+    Subss,
+    Mulss,
+    Divss,
+}
+
+impl FloatArithmeticOp {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    pub const ALL: [Self; 8] = [
+        Self::Addsd,
+        Self::Subsd,
+        Self::Mulsd,
+        Self::Divsd,
+        Self::Addss,
+        Self::Subss,
+        Self::Mulss,
+        Self::Divss,
+    ];
+
+    /// Returns `true` for operations on `f64` values.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_double(self) -> bool {
+        matches!(
+            self,
+            FloatArithmeticOp::Addsd
+                | FloatArithmeticOp::Subsd
+                | FloatArithmeticOp::Mulsd
+                | FloatArithmeticOp::Divsd
+        )
+    }
+
+    /// Returns the mandatory prefix of the operation, `0xF2` or `0xF3`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn prefix(self) -> u8 {
+        if self.is_double() {
+            0xF2
+        } else {
+            0xF3
+        }
+    }
+
+    /// Returns the second byte of the `0x0F`-prefixed opcode.
+    #[must_use]
+    pub const fn opcode(self) -> u8 {
+        match self {
+            FloatArithmeticOp::Addsd | FloatArithmeticOp::Addss => 0x58,
+            FloatArithmeticOp::Mulsd | FloatArithmeticOp::Mulss => 0x59,
+            FloatArithmeticOp::Subsd | FloatArithmeticOp::Subss => 0x5C,
+            FloatArithmeticOp::Divsd | FloatArithmeticOp::Divss => 0x5E,
+        }
+    }
+
+    /// Returns the mnemonic of the operation, e.g. `"addsd"`.
+    #[must_use]
+    pub const fn mnemonic(self) -> &'static str {
+        match self {
+            FloatArithmeticOp::Addsd => "addsd",
+            FloatArithmeticOp::Subsd => "subsd",
+            FloatArithmeticOp::Mulsd => "mulsd",
+            FloatArithmeticOp::Divsd => "divsd",
+            FloatArithmeticOp::Addss => "addss",
+            FloatArithmeticOp::Subss => "subss",
+            FloatArithmeticOp::Mulss => "mulss",
+            FloatArithmeticOp::Divss => "divss",
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use super::MachineSize;
+
+/// Represents an immediate operand.
+///
+/// # Notes
+/// The value is kept as `i64`, independently of the width it is encoded with.
+/// Encoders pick the shortest width preserving the value, so callers never
+/// have to.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Immediate {
+    value: i64,
+}
+
+impl Immediate {
+    #[must_use]
+    #[inline(always)]
+    pub const fn new(value: i64) -> Self {
+        Self { value }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn value(self) -> i64 {
+        self.value
+    }
+
+    /// Returns the smallest of [`MachineSize::Byte`], [`MachineSize::Word`],
+    /// [`MachineSize::DWord`] and [`MachineSize::QWord`], which sign extended
+    /// to 64 bits reproduces the value, e.g. `Byte` for `-1` and `Word` for `0xFF`.
+    #[must_use]
+    pub const fn sign_extended_size(self) -> MachineSize {
+        let value = self.value;
+        if value >= i8::MIN as i64 && value <= i8::MAX as i64 {
+            MachineSize::Byte
+        } else if value >= i16::MIN as i64 && value <= i16::MAX as i64 {
+            MachineSize::Word
+        } else if value >= i32::MIN as i64 && value <= i32::MAX as i64 {
+            MachineSize::DWord
+        } else {
+            MachineSize::QWord
+        }
+    }
+
+    /// Returns the smallest of [`MachineSize::Byte`], [`MachineSize::Word`],
+    /// [`MachineSize::DWord`] and [`MachineSize::QWord`], which zero extended
+    /// to 64 bits reproduces the value, e.g. `Byte` for `0xFF`. Negative values
+    /// always require `QWord`.
+    #[must_use]
+    pub const fn zero_extended_size(self) -> MachineSize {
+        let value = self.value;
+        if value < 0 {
+            MachineSize::QWord
+        } else if value <= u8::MAX as i64 {
+            MachineSize::Byte
+        } else if value <= u16::MAX as i64 {
+            MachineSize::Word
+        } else if value <= u32::MAX as i64 {
+            MachineSize::DWord
+        } else {
+            MachineSize::QWord
+        }
+    }
+
+    /// Returns `true` if the value sign extended from `size` is reproduced.
+    #[must_use]
+    #[inline(always)]
+    pub const fn fits_sign_extended(self, size: MachineSize) -> bool {
+        is_within(self.sign_extended_size(), size)
+    }
+
+    /// Returns `true` if the value zero extended from `size` is reproduced.
+    #[must_use]
+    #[inline(always)]
+    pub const fn fits_zero_extended(self, size: MachineSize) -> bool {
+        is_within(self.zero_extended_size(), size)
+    }
+
+    /// Returns `true` if the value is representable in `size` either as a signed
+    /// or as an unsigned integer, e.g. both `-1` and `0xFF` fit in a byte. This is
+    /// how immediates of the operand size are interpreted.
+    #[must_use]
+    #[inline(always)]
+    pub const fn fits(self, size: MachineSize) -> bool {
+        self.fits_sign_extended(size) || self.fits_zero_extended(size)
+    }
+
+    /// Returns the value truncated to `size` and sign extended back, e.g. `-1`
+    /// for `0xFFFF` truncated to [`MachineSize::Word`]. This is the value the CPU
+    /// operates on, when the immediate is used with `size` operands.
+    ///
+    /// # Notes
+    /// Sizes other than [`MachineSize::Byte`], [`MachineSize::Word`] and
+    /// [`MachineSize::DWord`] leave the value unchanged.
+    #[must_use]
+    pub const fn truncated(self, size: MachineSize) -> Self {
+        let shift = match size {
+            MachineSize::Byte | MachineSize::Word | MachineSize::DWord => {
+                64 - 8 * size.as_bytes() as u32
+            }
+            _ => 0,
+        };
+        Self::new((self.value << shift) >> shift)
+    }
+
+    /// Returns little endian bytes of the value. Its first `n` bytes are the value
+    /// encoded in `n` bytes.
+    #[must_use]
+    #[inline(always)]
+    pub const fn to_le_bytes(self) -> [u8; 8] {
+        self.value.to_le_bytes()
+    }
+}
+
+/// Returns `true` if `required` general purpose size is not larger than `size`.
+#[inline(always)]
+const fn is_within(required: MachineSize, size: MachineSize) -> bool {
+    match size {
+        MachineSize::Byte
+        | MachineSize::Word
+        | MachineSize::DWord
+        | MachineSize::QWord => required.as_bytes() <= size.as_bytes(),
+        _ => false,
+    }
+}
+
+macro_rules! immediate_from {
+    ( $( $ty: ty ),* ) => {
+        $(
+            impl From<$ty> for Immediate {
+                #[inline(always)]
+                fn from(value: $ty) -> Self {
+                    Self::new(i64::from(value))
+                }
+            }
+        )*
+    };
+}
+
+immediate_from!(i8, i16, i32, i64, u8, u16, u32);
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, MachineSize::Byte, MachineSize::Byte)]
+    #[case(-1, MachineSize::Byte, MachineSize::QWord)]
+    #[case(127, MachineSize::Byte, MachineSize::Byte)]
+    #[case(128, MachineSize::Word, MachineSize::Byte)]
+    #[case(255, MachineSize::Word, MachineSize::Byte)]
+    #[case(-129, MachineSize::Word, MachineSize::QWord)]
+    #[case(0xFFFF, MachineSize::DWord, MachineSize::Word)]
+    #[case(0x7FFF_FFFF, MachineSize::DWord, MachineSize::DWord)]
+    #[case(0xFFFF_FFFF, MachineSize::QWord, MachineSize::DWord)]
+    #[case(i64::from(i32::MIN), MachineSize::DWord, MachineSize::QWord)]
+    #[case(0x1_0000_0000, MachineSize::QWord, MachineSize::QWord)]
+    #[case(i64::MIN, MachineSize::QWord, MachineSize::QWord)]
+    fn test_sizes(
+        #[case] value: i64,
+        #[case] sign_extended: MachineSize,
+        #[case] zero_extended: MachineSize,
+    ) {
+        let imm = Immediate::new(value);
+        assert_eq!(imm.value(), value);
+        assert_eq!(imm.sign_extended_size(), sign_extended);
+        assert_eq!(imm.zero_extended_size(), zero_extended);
+        assert!(imm.fits_sign_extended(sign_extended));
+        assert!(imm.fits_zero_extended(zero_extended));
+        assert!(imm.fits(MachineSize::QWord));
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(Immediate::new(-1).fits(MachineSize::Byte));
+        assert!(Immediate::new(0xFF).fits(MachineSize::Byte));
+        assert!(!Immediate::new(0x100).fits(MachineSize::Byte));
+        assert!(!Immediate::new(-129).fits(MachineSize::Byte));
+        assert!(Immediate::new(0xFFFF_FFFF).fits(MachineSize::DWord));
+        assert!(!Immediate::new(0xFFFF_FFFF).fits_sign_extended(MachineSize::DWord));
+        assert!(!Immediate::new(0).fits(MachineSize::None));
+        assert!(!Immediate::new(0).fits(MachineSize::XMMWord));
+    }
+
+    #[rstest]
+    #[case(0xFFFF, MachineSize::Word, -1)]
+    #[case(0x1_0000, MachineSize::Word, 0)]
+    #[case(0x80, MachineSize::Byte, -128)]
+    #[case(0xFFFF_FFFF, MachineSize::DWord, -1)]
+    #[case(0xFFFF_FFFF, MachineSize::QWord, 0xFFFF_FFFF)]
+    #[case(-2, MachineSize::Byte, -2)]
+    fn test_truncated(
+        #[case] value: i64,
+        #[case] size: MachineSize,
+        #[case] expected: i64,
+    ) {
+        assert_eq!(Immediate::new(value).truncated(size).value(), expected);
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Immediate::from(-1i8).value(), -1);
+        assert_eq!(Immediate::from(u32::MAX).value(), 0xFFFF_FFFF);
+        assert_eq!(Immediate::from(0x1234u16).to_le_bytes()[..2], [0x34, 0x12]);
+    }
+}
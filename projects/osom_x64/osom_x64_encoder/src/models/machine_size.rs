@@ -3,7 +3,7 @@
 /// # Notes
 /// Fits in 8-bit integer.
 #[repr(u8)]
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum MachineSize {
     None = 0,
     Byte = 1,
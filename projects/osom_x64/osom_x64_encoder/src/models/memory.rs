@@ -0,0 +1,280 @@
+use crate::encoder::errors::EncodingError;
+
+use super::{Displacement, MachineSize, Scale, Segment, GPR};
+
+/// Represents a memory operand of the `[base + index * scale + displacement]` form.
+///
+/// # Notes
+/// `base` is either a 64-bit [`GPR`], [`GPR::RIP`] for RIP-relative addressing or
+/// [`GPR::NO_REG`] for absolute addressing. `index` is either a 64-bit [`GPR`] other
+/// than [`GPR::RSP`] or [`GPR::NO_REG`]. RIP-relative operands cannot have an index.
+/// The operand can optionally carry a [`Segment`] override.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Memory {
+    base: GPR,
+    index: GPR,
+    scale: Scale,
+    displacement: Displacement,
+    segment: Option<Segment>,
+}
+
+impl Memory {
+    /// Creates a new instance of [`Memory`].
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidMemoryOperand`] if `base` and `index` do not
+    ///   satisfy the constraints listed on [`Memory`].
+    /// * [`EncodingError::ArgumentOutOfRange`] if `displacement` does not fit in
+    ///   sign extended 32 bits.
+    pub const fn new(
+        base: GPR,
+        index: GPR,
+        scale: Scale,
+        displacement: Displacement,
+    ) -> Result<Self, EncodingError> {
+        if !displacement.is_encodable() {
+            return Err(EncodingError::ArgumentOutOfRange);
+        }
+        let base_is_valid = is_qword(base) || is_rip(base) || is_no_reg(base);
+        let index_is_valid = is_no_reg(index)
+            || (is_qword(index) && index.index() != GPR::RSP.index() && !is_rip(base));
+        if !base_is_valid || !index_is_valid {
+            return Err(EncodingError::InvalidMemoryOperand);
+        }
+
+        // Scale has no meaning without index. Normalize it, so that equal operands
+        // compare equal.
+        let scale = if is_no_reg(index) {
+            Scale::Scale1
+        } else {
+            scale
+        };
+
+        Ok(Self {
+            base,
+            index,
+            scale,
+            displacement,
+            segment: None,
+        })
+    }
+
+    /// Creates `[base + displacement]` operand.
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidMemoryOperand`] if `base` is not a 64-bit [`GPR`].
+    /// * [`EncodingError::ArgumentOutOfRange`] if `displacement` does not fit in
+    ///   sign extended 32 bits.
+    #[inline(always)]
+    pub fn based(
+        base: GPR,
+        displacement: impl Into<Displacement>,
+    ) -> Result<Self, EncodingError> {
+        if !is_qword(base) {
+            return Err(EncodingError::InvalidMemoryOperand);
+        }
+        Self::new(base, GPR::NO_REG, Scale::Scale1, displacement.into())
+    }
+
+    /// Creates `[rip + displacement]` operand. Note that `displacement` is relative
+    /// to the end of the instruction, and is always encoded in 32 bits.
+    ///
+    /// # Errors
+    /// [`EncodingError::ArgumentOutOfRange`] if `displacement` does not fit in sign
+    /// extended 32 bits.
+    #[inline(always)]
+    pub fn rip_relative(
+        displacement: impl Into<Displacement>,
+    ) -> Result<Self, EncodingError> {
+        Self::new(GPR::RIP, GPR::NO_REG, Scale::Scale1, displacement.into())
+    }
+
+    /// Creates `[displacement]` operand, i.e. an absolute address. The displacement
+    /// is always encoded in 32 bits.
+    ///
+    /// # Errors
+    /// [`EncodingError::ArgumentOutOfRange`] if `displacement` does not fit in sign
+    /// extended 32 bits.
+    #[inline(always)]
+    pub fn absolute(
+        displacement: impl Into<Displacement>,
+    ) -> Result<Self, EncodingError> {
+        Self::new(GPR::NO_REG, GPR::NO_REG, Scale::Scale1, displacement.into())
+    }
+
+    /// Creates `fs:[offset]` operand, addressing thread-local storage.
+    ///
+    /// # Notes
+    /// According to the x86-64 Linux TLS ABI, FS points to the thread control
+    /// block, which starts with a pointer to itself. Hence `fs:[0]` holds the
+    /// thread pointer, and thread-local variables of the executable live at
+    /// negative offsets.
+    ///
+    /// # Errors
+    /// [`EncodingError::ArgumentOutOfRange`] if `offset` does not fit in sign
+    /// extended 32 bits.
+    #[inline(always)]
+    pub fn thread_local(offset: impl Into<Displacement>) -> Result<Self, EncodingError> {
+        Ok(Self::absolute(offset)?.with_segment(Segment::Fs))
+    }
+
+    /// Returns a copy of the operand with `segment` override.
+    #[must_use]
+    #[inline(always)]
+    pub const fn with_segment(self, segment: Segment) -> Self {
+        Self {
+            segment: Some(segment),
+            ..self
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn base(&self) -> GPR {
+        self.base
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(&self) -> GPR {
+        self.index
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn displacement(&self) -> Displacement {
+        self.displacement
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn segment(&self) -> Option<Segment> {
+        self.segment
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_rip_relative(&self) -> bool {
+        is_rip(self.base)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn has_base(&self) -> bool {
+        is_qword(self.base)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn has_index(&self) -> bool {
+        !is_no_reg(self.index)
+    }
+
+    /// Returns `true` if the base or the index is an extended register, which
+    /// requires REX prefix.
+    #[must_use]
+    #[inline(always)]
+    pub const fn needs_rex(&self) -> bool {
+        (self.has_base() && self.base.is_extended())
+            || (self.has_index() && self.index.is_extended())
+    }
+}
+
+#[inline(always)]
+const fn is_qword(reg: GPR) -> bool {
+    matches!(reg.size(), MachineSize::QWord)
+}
+
+#[inline(always)]
+const fn is_rip(reg: GPR) -> bool {
+    matches!(reg.size(), MachineSize::None) && reg.index() == GPR::RIP.index()
+}
+
+#[inline(always)]
+const fn is_no_reg(reg: GPR) -> bool {
+    matches!(reg.size(), MachineSize::None) && reg.index() == GPR::NO_REG.index()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(GPR::RAX, GPR::RCX, Scale::Scale4)]
+    #[case(GPR::RSP, GPR::NO_REG, Scale::Scale1)]
+    #[case(GPR::NO_REG, GPR::R12, Scale::Scale8)]
+    #[case(GPR::RIP, GPR::NO_REG, Scale::Scale1)]
+    fn test_valid_memory(#[case] base: GPR, #[case] index: GPR, #[case] scale: Scale) {
+        let memory = Memory::new(base, index, scale, Displacement::new(-8)).unwrap();
+        assert_eq!(memory.base(), base);
+        assert_eq!(memory.index(), index);
+        assert_eq!(memory.scale(), scale);
+        assert_eq!(memory.displacement().value(), -8);
+    }
+
+    #[rstest]
+    #[case(GPR::EAX, GPR::NO_REG)]
+    #[case(GPR::RAX, GPR::RSP)]
+    #[case(GPR::RAX, GPR::ECX)]
+    #[case(GPR::RIP, GPR::RAX)]
+    fn test_invalid_memory(#[case] base: GPR, #[case] index: GPR) {
+        let result = Memory::new(base, index, Scale::Scale2, Displacement::ZERO);
+        assert_eq!(result, Err(EncodingError::InvalidMemoryOperand));
+    }
+
+    #[test]
+    fn test_displacement_range() {
+        let memory = Memory::based(GPR::RAX, i64::from(i32::MIN)).unwrap();
+        assert_eq!(memory.displacement().value(), i64::from(i32::MIN));
+        let memory = Memory::based(GPR::RAX, 0x8000_0000u32);
+        assert_eq!(memory, Err(EncodingError::ArgumentOutOfRange));
+        let displacement = Displacement::new(-0x8000_0001);
+        let memory = Memory::new(GPR::RAX, GPR::RCX, Scale::Scale2, displacement);
+        assert_eq!(memory, Err(EncodingError::ArgumentOutOfRange));
+        let memory = Memory::rip_relative(i64::from(i32::MAX) + 1);
+        assert_eq!(memory, Err(EncodingError::ArgumentOutOfRange));
+        let memory = Memory::absolute(0xFFFF_FFFFu32);
+        assert_eq!(memory, Err(EncodingError::ArgumentOutOfRange));
+    }
+
+    #[test]
+    fn test_const_memory() {
+        const MEMORY: Result<Memory, EncodingError> =
+            Memory::new(GPR::RIP, GPR::NO_REG, Scale::Scale1, Displacement::ZERO);
+        assert_eq!(MEMORY, Memory::rip_relative(0));
+        assert!(MEMORY.unwrap().is_rip_relative());
+    }
+
+    #[test]
+    fn test_scale_normalized() {
+        let left = Memory::new(GPR::RAX, GPR::NO_REG, Scale::Scale8, Displacement::ZERO)
+            .unwrap();
+        let right = Memory::based(GPR::RAX, 0).unwrap();
+        assert_eq!(left, right);
+        assert_ne!(GPR::NO_REG, GPR::AL);
+    }
+
+    #[test]
+    fn test_segment() {
+        let memory = Memory::based(GPR::RAX, 8).unwrap();
+        assert_eq!(memory.segment(), None);
+        let memory = memory.with_segment(Segment::Gs);
+        assert_eq!(memory.segment(), Some(Segment::Gs));
+        assert_eq!(memory.base(), GPR::RAX);
+        assert_eq!(memory.displacement().value(), 8);
+        assert_ne!(memory, Memory::based(GPR::RAX, 8).unwrap());
+
+        let memory = Memory::thread_local(-16).unwrap();
+        assert_eq!(memory.segment(), Some(Segment::Fs));
+        assert!(!memory.has_base() && !memory.has_index());
+        assert_eq!(memory.displacement().value(), -16);
+    }
+}
@@ -0,0 +1,220 @@
+use core::fmt;
+
+use super::{ArithmeticOp, BitCountOp, Condition, FloatArithmeticOp, ShiftOp};
+
+/// Represents instruction mnemonics accepted by [`encode`]. Families sharing an
+/// encoding scheme carry their operation, e.g. `add` is
+/// `Mnemonic::Arith(ArithmeticOp::Add)` and `jne` is
+/// `Mnemonic::Jcc(Condition::NotEqual)`.
+///
+/// [`encode`]: crate::encoder::encode
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Mnemonic {
+    Arith(ArithmeticOp),
+    Shift(ShiftOp),
+    BitCount(BitCountOp),
+    FloatArith(FloatArithmeticOp),
+    Jcc(Condition),
+    Mov,
+
+    /// `mov` with a full 64-bit immediate.
+    Movabs,
+    Movsxd,
+    Lea,
+    Imul,
+    Div,
+    Idiv,
+    Cdq,
+    Cqo,
+    Push,
+    Pop,
+    Jmp,
+    Call,
+    Ret,
+    Nop,
+    Movsd,
+    Movss,
+    Movaps,
+}
+
+impl Mnemonic {
+    const SIMPLE: [Self; 18] = [
+        Self::Mov,
+        Self::Movabs,
+        Self::Movsxd,
+        Self::Lea,
+        Self::Imul,
+        Self::Div,
+        Self::Idiv,
+        Self::Cdq,
+        Self::Cqo,
+        Self::Push,
+        Self::Pop,
+        Self::Jmp,
+        Self::Call,
+        Self::Ret,
+        Self::Nop,
+        Self::Movsd,
+        Self::Movss,
+        Self::Movaps,
+    ];
+
+    /// Returns the mnemonic named `name` in lowercase Intel syntax, e.g. `"add"`
+    /// or `"jne"`. Returns `None` if the name is not known.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let simple = Self::SIMPLE
+            .into_iter()
+            .find(|m| m.simple_name() == Some(name));
+        if simple.is_some() {
+            return simple;
+        }
+        let arith = ArithmeticOp::ALL
+            .into_iter()
+            .find(|op| op.mnemonic() == name);
+        if let Some(op) = arith {
+            return Some(Self::Arith(op));
+        }
+        let shift = ShiftOp::ALL.into_iter().find(|op| op.mnemonic() == name);
+        if let Some(op) = shift {
+            return Some(Self::Shift(op));
+        }
+        let bit_count = BitCountOp::ALL.into_iter().find(|op| op.mnemonic() == name);
+        if let Some(op) = bit_count {
+            return Some(Self::BitCount(op));
+        }
+        let float = FloatArithmeticOp::ALL
+            .into_iter()
+            .find(|op| op.mnemonic() == name);
+        if let Some(op) = float {
+            return Some(Self::FloatArith(op));
+        }
+        let suffix = name.strip_prefix('j')?;
+        Condition::ALL
+            .into_iter()
+            .find(|cond| cond.suffix() == suffix)
+            .map(Self::Jcc)
+    }
+
+    /// Returns the name of mnemonics without an operation, e.g. `"mov"`.
+    const fn simple_name(self) -> Option<&'static str> {
+        let name = match self {
+            Mnemonic::Arith(_)
+            | Mnemonic::Shift(_)
+            | Mnemonic::BitCount(_)
+            | Mnemonic::FloatArith(_)
+            | Mnemonic::Jcc(_) => return None,
+            Mnemonic::Mov => "mov",
+            Mnemonic::Movabs => "movabs",
+            Mnemonic::Movsxd => "movsxd",
+            Mnemonic::Lea => "lea",
+            Mnemonic::Imul => "imul",
+            Mnemonic::Div => "div",
+            Mnemonic::Idiv => "idiv",
+            Mnemonic::Cdq => "cdq",
+            Mnemonic::Cqo => "cqo",
+            Mnemonic::Push => "push",
+            Mnemonic::Pop => "pop",
+            Mnemonic::Jmp => "jmp",
+            Mnemonic::Call => "call",
+            Mnemonic::Ret => "ret",
+            Mnemonic::Nop => "nop",
+            Mnemonic::Movsd => "movsd",
+            Mnemonic::Movss => "movss",
+            Mnemonic::Movaps => "movaps",
+        };
+        Some(name)
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mnemonic::Arith(op) => f.write_str(op.mnemonic()),
+            Mnemonic::Shift(op) => f.write_str(op.mnemonic()),
+            Mnemonic::BitCount(op) => f.write_str(op.mnemonic()),
+            Mnemonic::FloatArith(op) => f.write_str(op.mnemonic()),
+            Mnemonic::Jcc(cond) => write!(f, "j{}", cond.suffix()),
+            _ => f.write_str(self.simple_name().unwrap_or_default()),
+        }
+    }
+}
+
+impl From<ArithmeticOp> for Mnemonic {
+    #[inline(always)]
+    fn from(value: ArithmeticOp) -> Self {
+        Self::Arith(value)
+    }
+}
+
+impl From<ShiftOp> for Mnemonic {
+    #[inline(always)]
+    fn from(value: ShiftOp) -> Self {
+        Self::Shift(value)
+    }
+}
+
+impl From<BitCountOp> for Mnemonic {
+    #[inline(always)]
+    fn from(value: BitCountOp) -> Self {
+        Self::BitCount(value)
+    }
+}
+
+impl From<FloatArithmeticOp> for Mnemonic {
+    #[inline(always)]
+    fn from(value: FloatArithmeticOp) -> Self {
+        Self::FloatArith(value)
+    }
+}
+
+impl From<Condition> for Mnemonic {
+    #[inline(always)]
+    fn from(value: Condition) -> Self {
+        Self::Jcc(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("add", Mnemonic::Arith(ArithmeticOp::Add))]
+    #[case("sar", Mnemonic::Shift(ShiftOp::Sar))]
+    #[case("tzcnt", Mnemonic::BitCount(BitCountOp::Tzcnt))]
+    #[case("divss", Mnemonic::FloatArith(FloatArithmeticOp::Divss))]
+    #[case("jae", Mnemonic::Jcc(Condition::AboveOrEqual))]
+    #[case("jnz", Mnemonic::Jcc(Condition::NotZero))]
+    #[case("jmp", Mnemonic::Jmp)]
+    #[case("movabs", Mnemonic::Movabs)]
+    #[case("ret", Mnemonic::Ret)]
+    fn test_name(#[case] name: &str, #[case] mnemonic: Mnemonic) {
+        assert_eq!(Mnemonic::from_name(name), Some(mnemonic));
+        assert_eq!(mnemonic.to_string(), name);
+    }
+
+    #[test]
+    fn test_unknown_name() {
+        for name in ["", "j", "jx", "ADD", "ud2", "movq"] {
+            assert_eq!(Mnemonic::from_name(name), None);
+        }
+    }
+
+    #[test]
+    fn test_all_names_roundtrip() {
+        let families = ArithmeticOp::ALL
+            .into_iter()
+            .map(Mnemonic::from)
+            .chain(ShiftOp::ALL.map(Mnemonic::from))
+            .chain(BitCountOp::ALL.map(Mnemonic::from))
+            .chain(FloatArithmeticOp::ALL.map(Mnemonic::from))
+            .chain(Condition::ALL.map(Mnemonic::from));
+        for mnemonic in families.chain(Mnemonic::SIMPLE) {
+            let name = mnemonic.to_string();
+            assert_eq!(Mnemonic::from_name(&name), Some(mnemonic), "{name}");
+        }
+    }
+}
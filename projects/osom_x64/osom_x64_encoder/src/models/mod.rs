@@ -1,9 +1,35 @@
+mod arithmetic_op;
+mod bit_count_op;
 mod condition;
+mod cpu_features;
+mod displacement;
+mod flags;
+mod float_arithmetic_op;
+mod immediate;
 mod machine_size;
+mod memory;
+mod mnemonic;
+mod operand;
+mod register_set;
 mod registers;
 mod scale;
+mod segment;
+mod shift_op;
 
+pub use arithmetic_op::*;
+pub use bit_count_op::*;
 pub use condition::*;
+pub use cpu_features::*;
+pub use displacement::*;
+pub use flags::*;
+pub use float_arithmetic_op::*;
+pub use immediate::*;
 pub use machine_size::*;
+pub use memory::*;
+pub use mnemonic::*;
+pub use operand::*;
+pub use register_set::*;
 pub use registers::*;
 pub use scale::*;
+pub use segment::*;
+pub use shift_op::*;
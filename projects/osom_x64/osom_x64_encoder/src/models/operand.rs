@@ -0,0 +1,88 @@
+use super::{Displacement, Immediate, MachineSize, Memory, GPR, XMM, YMM};
+
+/// Represents an instruction operand accepted by [`encode`].
+///
+/// # Notes
+/// Label-relative operands are given as [`Operand::Relative`], i.e. with the
+/// label already resolved to an offset. Labels are out of scope for the encoder:
+/// a label's offset depends on the size of every instruction between the
+/// branch and its target, including branches whose own size depends on their
+/// targets. Only an assembler laying out the whole function can resolve it, as
+/// `osom_x64_asm` does for `jmp`, `jcc` and RIP-relative operands referring to
+/// its labels.
+///
+/// [`encode`]: crate::encoder::encode
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Operand {
+    Gpr(GPR),
+    Xmm(XMM),
+    Ymm(YMM),
+    Memory(Memory),
+    Immediate(Immediate),
+
+    /// Branch target relative to the start of the instruction, i.e. `$+rel`
+    /// in Intel syntax, e.g. the offset of a resolved label minus the offset of
+    /// the branch. The encoder picks the shortest branch form reaching it.
+    Relative(Displacement),
+}
+
+impl Operand {
+    #[must_use]
+    pub const fn kind(&self) -> OperandKind {
+        match self {
+            Operand::Gpr(gpr) => OperandKind::Gpr(gpr.size()),
+            Operand::Xmm(_) => OperandKind::Xmm,
+            Operand::Ymm(_) => OperandKind::Ymm,
+            Operand::Memory(_) => OperandKind::Memory,
+            Operand::Immediate(_) => OperandKind::Immediate,
+            Operand::Relative(_) => OperandKind::Relative,
+        }
+    }
+}
+
+impl From<GPR> for Operand {
+    #[inline(always)]
+    fn from(value: GPR) -> Self {
+        Self::Gpr(value)
+    }
+}
+
+impl From<XMM> for Operand {
+    #[inline(always)]
+    fn from(value: XMM) -> Self {
+        Self::Xmm(value)
+    }
+}
+
+impl From<YMM> for Operand {
+    #[inline(always)]
+    fn from(value: YMM) -> Self {
+        Self::Ymm(value)
+    }
+}
+
+impl From<Memory> for Operand {
+    #[inline(always)]
+    fn from(value: Memory) -> Self {
+        Self::Memory(value)
+    }
+}
+
+impl From<Immediate> for Operand {
+    #[inline(always)]
+    fn from(value: Immediate) -> Self {
+        Self::Immediate(value)
+    }
+}
+
+/// Describes the kind of an [`Operand`], without its value. General purpose
+/// registers keep their size.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum OperandKind {
+    Gpr(MachineSize),
+    Xmm,
+    Ymm,
+    Memory,
+    Immediate,
+    Relative,
+}
@@ -0,0 +1,96 @@
+use super::{MachineSize, GPR, XMM};
+
+macro_rules! register_set {
+    ( $name: ident, $reg: ident, $size: ident, $doc: literal ) => {
+        #[doc = $doc]
+        ///
+        /// # Notes
+        /// Registers are identified by their index only, e.g. `eax` and `rax` are
+        /// the same member.
+        #[repr(transparent)]
+        #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+        pub struct $name {
+            bits: u16,
+        }
+
+        impl $name {
+            pub const EMPTY: Self = Self { bits: 0 };
+
+            /// Returns the set with `reg` added.
+            #[must_use]
+            #[inline(always)]
+            pub const fn with(self, reg: $reg) -> Self {
+                Self {
+                    bits: self.bits | (1 << reg.index()),
+                }
+            }
+
+            #[must_use]
+            #[inline(always)]
+            pub const fn union(self, other: Self) -> Self {
+                Self {
+                    bits: self.bits | other.bits,
+                }
+            }
+
+            #[must_use]
+            #[inline(always)]
+            pub const fn contains(self, reg: $reg) -> bool {
+                self.bits & (1 << reg.index()) != 0
+            }
+
+            #[must_use]
+            #[inline(always)]
+            pub const fn is_empty(self) -> bool {
+                self.bits == 0
+            }
+
+            #[must_use]
+            #[inline(always)]
+            pub const fn len(self) -> u32 {
+                self.bits.count_ones()
+            }
+
+            /// Iterates over members of the set, as full width registers in
+            /// ascending index order.
+            pub fn iter(self) -> impl Iterator<Item = $reg> {
+                (0..16)
+                    .filter(move |index| self.bits & (1 << index) != 0)
+                    .map(|index| unsafe {
+                        $reg::new_unchecked(MachineSize::$size, index)
+                    })
+            }
+        }
+    };
+}
+
+register_set!(
+    GprSet,
+    GPR,
+    QWord,
+    "Represents a set of general purpose registers."
+);
+register_set!(XmmSet, XMM, XMMWord, "Represents a set of XMM registers.");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpr_set() {
+        let set = GprSet::EMPTY.with(GPR::EAX).with(GPR::R9B).with(GPR::RAX);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(GPR::AL));
+        assert!(set.contains(GPR::R9));
+        assert!(!set.contains(GPR::RCX));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [GPR::RAX, GPR::R9]);
+        assert_eq!(set.union(GprSet::EMPTY.with(GPR::RCX)).len(), 3);
+    }
+
+    #[test]
+    fn test_xmm_set() {
+        let set = XmmSet::EMPTY.with(XMM::XMM15);
+        assert!(!set.is_empty());
+        assert_eq!(set.iter().collect::<Vec<_>>(), [XMM::XMM15]);
+    }
+}
@@ -1,3 +1,7 @@
+use core::fmt;
+
+use crate::encoder::errors::EncodingError;
+
 use super::MachineSize;
 
 macro_rules! reg_field {
@@ -8,7 +12,7 @@ macro_rules! reg_field {
 }
 
 macro_rules! reg_class {
-    ( $name: ident, $doc: literal ) => {
+    ( $name: ident, $doc: literal, $( $size: ident )|+ ) => {
         #[doc = $doc]
         #[repr(transparent)]
         #[derive(PartialEq, Eq, Hash, Clone, Copy)]
@@ -35,10 +39,29 @@ macro_rules! reg_class {
                 }
             }
 
+            /// Creates a new instance, checking that `size` and `index` denote a
+            /// register of this class.
+            ///
+            /// # Errors
+            /// * [`EncodingError::InvalidRegisterSize`] if registers of this class do
+            ///   not have `size`.
+            /// * [`EncodingError::ArgumentOutOfRange`] if `index` is not in `0..=15`
+            ///   range.
+            #[inline(always)]
+            pub const fn new(size: MachineSize, index: u8) -> Result<Self, EncodingError> {
+                if !matches!(size, $( MachineSize::$size )|+) {
+                    return Err(EncodingError::InvalidRegisterSize);
+                }
+                if index > 15 {
+                    return Err(EncodingError::ArgumentOutOfRange);
+                }
+                Ok(unsafe { Self::new_unchecked(size, index) })
+            }
+
             #[must_use]
             #[inline(always)]
             pub const fn size(&self) -> MachineSize {
-                unsafe { core::mem::transmute(self.val >> 4) }
+                unsafe { core::mem::transmute((self.val >> 4) & 0b111) }
             }
 
             #[must_use]
@@ -46,15 +69,42 @@ macro_rules! reg_class {
             pub const fn index(&self) -> u8 {
                 self.val & 0b1111
             }
+
+            /// Returns `true` for registers 8 to 15, which are encoded with an
+            /// additional bit in REX or VEX prefix.
+            #[must_use]
+            #[inline(always)]
+            pub const fn is_extended(&self) -> bool {
+                self.index() >= 8
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
         }
     };
 }
 
-reg_class!(GPR, "Represents general purpose registers.");
+/// Marks AH, CH, DH and BH in the otherwise unused highest bit.
+const HIGH_BYTE: u8 = 0b1000_0000;
+
+reg_class!(
+    GPR,
+    "Represents general purpose registers.",
+    Byte | Word | DWord | QWord
+);
 
 impl GPR {
     // Special.
-    reg_field!(NO_REG, None, 16);
+    reg_field!(NO_REG, None, 15);
     reg_field!(RIP, None, 0);
 
     // Byte registers.
@@ -75,6 +125,13 @@ impl GPR {
     reg_field!(R14B, Byte, 14);
     reg_field!(R15B, Byte, 15);
 
+    // High byte registers, i.e. bits 8 to 15 of RAX, RCX, RDX and RBX. They share
+    // indexes with their full registers, and are addressable only without REX.
+    pub const AH: Self = Self::high_byte(0);
+    pub const CH: Self = Self::high_byte(1);
+    pub const DH: Self = Self::high_byte(2);
+    pub const BH: Self = Self::high_byte(3);
+
     // Word registers.
     reg_field!(AX, Word, 0);
     reg_field!(CX, Word, 1);
@@ -128,9 +185,124 @@ impl GPR {
     reg_field!(R13, QWord, 13);
     reg_field!(R14, QWord, 14);
     reg_field!(R15, QWord, 15);
+
+    const NAMES: [[&'static str; 16]; 4] = [
+        [
+            "al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil", "r8b", "r9b", "r10b",
+            "r11b", "r12b", "r13b", "r14b", "r15b",
+        ],
+        [
+            "ax", "cx", "dx", "bx", "sp", "bp", "si", "di", "r8w", "r9w", "r10w",
+            "r11w", "r12w", "r13w", "r14w", "r15w",
+        ],
+        [
+            "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d",
+            "r10d", "r11d", "r12d", "r13d", "r14d", "r15d",
+        ],
+        [
+            "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10",
+            "r11", "r12", "r13", "r14", "r15",
+        ],
+    ];
+
+    #[inline(always)]
+    const fn high_byte(index: u8) -> Self {
+        Self {
+            val: HIGH_BYTE | ((MachineSize::Byte as u8) << 4) | index,
+        }
+    }
+
+    /// Returns `true` for AH, CH, DH and BH.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_high_byte(&self) -> bool {
+        self.val & HIGH_BYTE != 0
+    }
+
+    /// Returns the index encoded in instructions, i.e. 4 to 7 for AH, CH, DH and BH,
+    /// and [`GPR::index`] for other registers.
+    #[must_use]
+    #[inline(always)]
+    pub const fn encoding_index(&self) -> u8 {
+        if self.is_high_byte() {
+            self.index() + 4
+        } else {
+            self.index()
+        }
+    }
+
+    /// Returns the register with the same index and the given `size`, e.g.
+    /// [`GPR::EAX`] for [`GPR::RAX`] and [`MachineSize::DWord`], or [`GPR::AL`]
+    /// for [`GPR::AH`] and [`MachineSize::Byte`].
+    ///
+    /// # Errors
+    /// * [`EncodingError::InvalidRegisterSize`] if `size` is not a general
+    ///   purpose register size.
+    /// * [`EncodingError::ArgumentOutOfRange`] for [`GPR::RIP`] and [`GPR::NO_REG`].
+    #[inline(always)]
+    pub const fn resize(&self, size: MachineSize) -> Result<Self, EncodingError> {
+        if matches!(self.size(), MachineSize::None) {
+            return Err(EncodingError::ArgumentOutOfRange);
+        }
+        Self::new(size, self.index())
+    }
+
+    /// Returns `true` if the register can be encoded only with a REX prefix, i.e.
+    /// for extended registers, and SPL, BPL, SIL and DIL.
+    ///
+    /// # Notes
+    /// AH, CH, DH and BH are encoded as SPL, BPL, SIL and DIL are, but without REX.
+    /// Thus instructions using them cannot use REX at all.
+    #[must_use]
+    #[inline(always)]
+    pub const fn needs_rex(&self) -> bool {
+        self.is_extended()
+            || (matches!(self.size(), MachineSize::Byte) && self.index() >= 4)
+    }
+
+    /// Returns `true` for RAX, RCX, RDX and RBX in any size, high bytes included,
+    /// i.e. registers whose bits 8 to 15 are addressable as AH, CH, DH and BH.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_high_byte_capable(&self) -> bool {
+        !matches!(self.size(), MachineSize::None) && self.index() < 4
+    }
+
+    /// Returns an iterator over all 16 registers of `size`, ordered by index.
+    /// Empty if `size` is not a general purpose register size. AH, CH, DH and BH
+    /// are not included.
+    #[inline]
+    pub fn all(size: MachineSize) -> impl Iterator<Item = Self> {
+        (0..16).filter_map(move |index| Self::new(size, index).ok())
+    }
+
+    /// Returns the canonical (lowercase, Intel) name of the register,
+    /// e.g. `"r10d"` for [`GPR::R10D`].
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        const HIGH_BYTE_NAMES: [&str; 4] = ["ah", "ch", "dh", "bh"];
+
+        let index = self.index() as usize;
+        if self.is_high_byte() {
+            return HIGH_BYTE_NAMES[index];
+        }
+        match self.size() {
+            MachineSize::Byte => Self::NAMES[0][index],
+            MachineSize::Word => Self::NAMES[1][index],
+            MachineSize::DWord => Self::NAMES[2][index],
+            MachineSize::QWord => Self::NAMES[3][index],
+            _ => {
+                if index == 0 {
+                    "rip"
+                } else {
+                    "noreg"
+                }
+            }
+        }
+    }
 }
 
-reg_class!(XMM, "Represents XMM registers.");
+reg_class!(XMM, "Represents XMM registers.", XMMWord);
 
 impl XMM {
     reg_field!(XMM0, XMMWord, 0);
@@ -149,9 +321,28 @@ impl XMM {
     reg_field!(XMM13, XMMWord, 13);
     reg_field!(XMM14, XMMWord, 14);
     reg_field!(XMM15, XMMWord, 15);
+
+    const NAMES: [&'static str; 16] = [
+        "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7", "xmm8", "xmm9",
+        "xmm10", "xmm11", "xmm12", "xmm13", "xmm14", "xmm15",
+    ];
+
+    /// Returns an iterator over all 16 registers, ordered by index.
+    #[inline]
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..16).map(|index| unsafe { Self::new_unchecked(MachineSize::XMMWord, index) })
+    }
+
+    /// Returns the canonical (lowercase) name of the register, e.g. `"xmm3"`
+    /// for [`XMM::XMM3`].
+    #[must_use]
+    #[inline(always)]
+    pub const fn name(&self) -> &'static str {
+        Self::NAMES[self.index() as usize]
+    }
 }
 
-reg_class!(YMM, "Represents YMM registers.");
+reg_class!(YMM, "Represents YMM registers.", YMMWord);
 
 impl YMM {
     reg_field!(YMM0, YMMWord, 0);
@@ -170,4 +361,136 @@ impl YMM {
     reg_field!(YMM13, YMMWord, 13);
     reg_field!(YMM14, YMMWord, 14);
     reg_field!(YMM15, YMMWord, 15);
+
+    const NAMES: [&'static str; 16] = [
+        "ymm0", "ymm1", "ymm2", "ymm3", "ymm4", "ymm5", "ymm6", "ymm7", "ymm8", "ymm9",
+        "ymm10", "ymm11", "ymm12", "ymm13", "ymm14", "ymm15",
+    ];
+
+    /// Returns an iterator over all 16 registers, ordered by index.
+    #[inline]
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..16).map(|index| unsafe { Self::new_unchecked(MachineSize::YMMWord, index) })
+    }
+
+    /// Returns the canonical (lowercase) name of the register, e.g. `"ymm3"`
+    /// for [`YMM::YMM3`].
+    #[must_use]
+    #[inline(always)]
+    pub const fn name(&self) -> &'static str {
+        Self::NAMES[self.index() as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(MachineSize::QWord, 0, Ok(GPR::RAX))]
+    #[case(MachineSize::Byte, 6, Ok(GPR::SIL))]
+    #[case(MachineSize::Word, 15, Ok(GPR::R15W))]
+    #[case(MachineSize::DWord, 16, Err(EncodingError::ArgumentOutOfRange))]
+    #[case(MachineSize::XMMWord, 0, Err(EncodingError::InvalidRegisterSize))]
+    #[case(MachineSize::None, 0, Err(EncodingError::InvalidRegisterSize))]
+    fn test_gpr_new(
+        #[case] size: MachineSize,
+        #[case] index: u8,
+        #[case] expected: Result<GPR, EncodingError>,
+    ) {
+        assert_eq!(GPR::new(size, index), expected);
+    }
+
+    #[test]
+    fn test_vector_new() {
+        assert_eq!(XMM::new(MachineSize::XMMWord, 7), Ok(XMM::XMM7));
+        assert_eq!(
+            XMM::new(MachineSize::YMMWord, 7),
+            Err(EncodingError::InvalidRegisterSize)
+        );
+        assert_eq!(YMM::new(MachineSize::YMMWord, 12), Ok(YMM::YMM12));
+        assert_eq!(
+            YMM::new(MachineSize::YMMWord, 255),
+            Err(EncodingError::ArgumentOutOfRange)
+        );
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, MachineSize::DWord, Ok(GPR::EAX))]
+    #[case(GPR::R9B, MachineSize::QWord, Ok(GPR::R9))]
+    #[case(GPR::DI, MachineSize::Byte, Ok(GPR::DIL))]
+    #[case(GPR::RAX, MachineSize::None, Err(EncodingError::InvalidRegisterSize))]
+    #[case(GPR::RIP, MachineSize::QWord, Err(EncodingError::ArgumentOutOfRange))]
+    #[case(GPR::AH, MachineSize::Byte, Ok(GPR::AL))]
+    #[case(GPR::BH, MachineSize::QWord, Ok(GPR::RBX))]
+    fn test_resize(
+        #[case] reg: GPR,
+        #[case] size: MachineSize,
+        #[case] expected: Result<GPR, EncodingError>,
+    ) {
+        assert_eq!(reg.resize(size), expected);
+    }
+
+    #[rstest]
+    #[case(GPR::RAX, false, false, true)]
+    #[case(GPR::BL, false, false, true)]
+    #[case(GPR::SPL, true, false, false)]
+    #[case(GPR::SP, false, false, false)]
+    #[case(GPR::R8B, true, true, false)]
+    #[case(GPR::R15D, true, true, false)]
+    #[case(GPR::AH, false, false, true)]
+    fn test_gpr_properties(
+        #[case] reg: GPR,
+        #[case] needs_rex: bool,
+        #[case] is_extended: bool,
+        #[case] is_high_byte_capable: bool,
+    ) {
+        assert_eq!(reg.needs_rex(), needs_rex);
+        assert_eq!(reg.is_extended(), is_extended);
+        assert_eq!(reg.is_high_byte_capable(), is_high_byte_capable);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let dwords: Vec<_> = GPR::all(MachineSize::DWord).collect();
+        assert_eq!(dwords.len(), 16);
+        assert_eq!(dwords[0], GPR::EAX);
+        assert_eq!(dwords[15], GPR::R15D);
+        assert_eq!(GPR::all(MachineSize::XMMWord).count(), 0);
+        assert!(XMM::all().eq((0..16).map(|index| XMM::new(
+            MachineSize::XMMWord,
+            index
+        )
+        .unwrap())));
+        assert_eq!(YMM::all().last(), Some(YMM::YMM15));
+    }
+
+    #[test]
+    fn test_display_and_debug() {
+        assert_eq!(GPR::R10D.to_string(), "r10d");
+        assert_eq!(format!("{:?}", GPR::SPL), "spl");
+        assert_eq!(format!("{:?}", GPR::RIP), "rip");
+        assert_eq!(XMM::XMM15.to_string(), "xmm15");
+        assert_eq!(format!("{:?}", YMM::YMM2), "ymm2");
+        assert_eq!(format!("{:?}", [GPR::RAX, GPR::R8]), "[rax, r8]");
+        assert_eq!(GPR::DH.to_string(), "dh");
+    }
+
+    #[test]
+    fn test_high_bytes() {
+        for (reg, low) in [GPR::AH, GPR::CH, GPR::DH, GPR::BH]
+            .into_iter()
+            .zip(GPR::all(MachineSize::Byte))
+        {
+            assert!(reg.is_high_byte());
+            assert!(!low.is_high_byte());
+            assert_ne!(reg, low);
+            assert_eq!(reg.size(), MachineSize::Byte);
+            assert_eq!(reg.index(), low.index());
+            assert_eq!(reg.encoding_index(), low.index() + 4);
+        }
+        assert_eq!(GPR::SPL.encoding_index(), 4);
+    }
 }
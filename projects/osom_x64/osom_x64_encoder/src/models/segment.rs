@@ -0,0 +1,91 @@
+/// Represents segment registers. The discriminant is the register number.
+///
+/// # Notes
+/// In 64-bit mode the bases of ES, CS, SS and DS are treated as zero, so only
+/// FS and GS overrides change the effective address. On Linux FS points to
+/// the thread control block of the current thread, see [`Memory::thread_local`].
+///
+/// [`Memory::thread_local`]: super::Memory::thread_local
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Segment {
+    Es = 0,
+    Cs = 1,
+    Ss = 2,
+    Ds = 3,
+    Fs = 4,
+    Gs = 5,
+}
+
+impl Segment {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    pub const ALL: [Self; 6] =
+        [Self::Es, Self::Cs, Self::Ss, Self::Ds, Self::Fs, Self::Gs];
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the segment override prefix, e.g. `0x64` for FS.
+    #[must_use]
+    pub const fn override_prefix(self) -> u8 {
+        match self {
+            Segment::Es => 0x26,
+            Segment::Cs => 0x2E,
+            Segment::Ss => 0x36,
+            Segment::Ds => 0x3E,
+            Segment::Fs => 0x64,
+            Segment::Gs => 0x65,
+        }
+    }
+
+    /// Returns the segment selected by override `prefix`, or `None` if `prefix`
+    /// is not a segment override prefix.
+    #[must_use]
+    pub const fn from_override_prefix(prefix: u8) -> Option<Self> {
+        match prefix {
+            0x26 => Some(Segment::Es),
+            0x2E => Some(Segment::Cs),
+            0x36 => Some(Segment::Ss),
+            0x3E => Some(Segment::Ds),
+            0x64 => Some(Segment::Fs),
+            0x65 => Some(Segment::Gs),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the register, e.g. `"fs"`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Segment::Es => "es",
+            Segment::Cs => "cs",
+            Segment::Ss => "ss",
+            Segment::Ds => "ds",
+            Segment::Fs => "fs",
+            Segment::Gs => "gs",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_prefix() {
+        for segment in Segment::ALL {
+            let prefix = segment.override_prefix();
+            assert_eq!(Segment::from_override_prefix(prefix), Some(segment));
+        }
+        assert_eq!(Segment::Fs.override_prefix(), 0x64);
+        assert_eq!(Segment::Gs.override_prefix(), 0x65);
+        assert_eq!(Segment::from_override_prefix(0x66), None);
+        assert_eq!(Segment::from_override_prefix(0x40), None);
+    }
+}
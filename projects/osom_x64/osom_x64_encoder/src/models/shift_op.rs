@@ -0,0 +1,37 @@
+/// Represents rotate and shift instructions. The discriminant is the opcode
+/// extension used in `ModRM.reg`.
+#[repr(u8)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ShiftOp {
+    Rol = 0,
+    Ror = 1,
+    Shl = 4,
+    Shr = 5,
+    Sar = 7,
+}
+
+impl ShiftOp {
+    const _CHECK: () = const {
+        assert!(size_of::<Self>() == 1);
+    };
+
+    pub const ALL: [Self; 5] = [Self::Rol, Self::Ror, Self::Shl, Self::Shr, Self::Sar];
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the mnemonic of the operation, e.g. `"shl"`.
+    #[must_use]
+    pub const fn mnemonic(self) -> &'static str {
+        match self {
+            ShiftOp::Rol => "rol",
+            ShiftOp::Ror => "ror",
+            ShiftOp::Shl => "shl",
+            ShiftOp::Shr => "shr",
+            ShiftOp::Sar => "sar",
+        }
+    }
+}
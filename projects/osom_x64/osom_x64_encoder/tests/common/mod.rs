@@ -0,0 +1,18 @@
+use osom_x64_encoder::{
+    decoder::Instruction,
+    models::{MachineSize, GPR},
+};
+
+/// Returns the decoded form of `mov dst, imm`. 64-bit moves of zero extended
+/// 32-bit values are encoded as 32-bit ones.
+#[allow(clippy::cast_possible_truncation)]
+pub fn mov_reg_imm(dst: GPR, imm: i64) -> Instruction {
+    if dst.size() == MachineSize::QWord && (1 << 31..1 << 32).contains(&imm) {
+        let dst = dst.resize(MachineSize::DWord).unwrap();
+        return Instruction::MovRegImm {
+            dst,
+            imm: i64::from(imm as u32 as i32),
+        };
+    }
+    Instruction::MovRegImm { dst, imm }
+}
@@ -0,0 +1,299 @@
+#!/usr/bin/env python3
+"""Regenerates golden tables used by `tests/test_golden.rs`.
+
+Each table lists instructions in Intel syntax together with bytes produced by
+GNU `as` (binutils), the reference assembler. Requires `as` and `objdump`
+targeting x86-64 in `PATH`. Run from any directory:
+
+    python3 tests/golden/generate.py
+
+Tables are checked in, so that the tests run offline. Regenerate them only when
+extending the coverage, and review the diff.
+
+Notes:
+* `as` is run without optimizations (no `-O`), which keeps the encoding of
+  e.g. `mov rax, 1` as requested. Cases where `as` picks a different, but
+  equivalent, encoding than the encoder on purpose are not generated, e.g.
+  `shl reg, 1` for which `as` prefers the `D1` form, or `mov rax, 0x80000000`
+  for which `as` picks `movabs`.
+* There are no YMM encoders yet, thus YMM registers are not covered.
+"""
+
+import re
+import os
+import subprocess
+import tempfile
+
+DIRECTORY = os.path.dirname(os.path.abspath(__file__))
+
+GPR8 = ["al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil"] + [
+    f"r{i}b" for i in range(8, 16)
+]
+GPR16 = ["ax", "cx", "dx", "bx", "sp", "bp", "si", "di"] + [
+    f"r{i}w" for i in range(8, 16)
+]
+GPR32 = ["eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi"] + [
+    f"r{i}d" for i in range(8, 16)
+]
+GPR64 = ["rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi"] + [
+    f"r{i}" for i in range(8, 16)
+]
+GPRS = [GPR8, GPR16, GPR32, GPR64]
+HIGH8 = ["ah", "ch", "dh", "bh"]
+# Byte registers addressable without REX, thus combinable with high bytes.
+LEGACY8 = GPR8[:4] + HIGH8
+XMMS = [f"xmm{i}" for i in range(16)]
+
+PTR = {8: "byte", 16: "word", 32: "dword", 64: "qword"}
+
+ARITHMETIC = ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"]
+SHIFTS = ["rol", "ror", "shl", "shr", "sar"]
+BIT_COUNTS = ["popcnt", "tzcnt", "lzcnt"]
+FLOAT_ARITHMETIC = [
+    "addsd", "subsd", "mulsd", "divsd", "addss", "subss", "mulss", "divss",
+]
+
+SCALES = [1, 2, 4, 8]
+# No displacement, 8-bit boundaries and 32-bit boundaries.
+DISPLACEMENTS = [0, 127, -128, 128, -129, 2**31 - 1, -(2**31)]
+
+
+def memory(base, index=None, scale=1, displacement=0):
+    parts = []
+    if base is not None:
+        parts.append(base)
+    if index is not None:
+        parts.append(f"{index}*{scale}")
+    text = "+".join(parts)
+    if displacement or not parts:
+        if displacement < 0:
+            text += f"-{hex(-displacement)}"
+        else:
+            text += f"+{hex(displacement)}" if parts else hex(displacement)
+    return f"[{text}]"
+
+
+def gpr_table():
+    lines = []
+    for registers in GPRS:
+        for dst in registers:
+            for src in registers:
+                lines.append(f"mov {dst}, {src}")
+                lines.append(f"add {dst}, {src}")
+    for op in ARITHMETIC[1:]:
+        for registers in GPRS:
+            for index, dst in enumerate(registers):
+                for src in (registers[index], registers[(index * 5 + 3) % 16]):
+                    lines.append(f"{op} {dst}, {src}")
+    for registers in GPRS[1:]:
+        for dst in registers:
+            for src in registers:
+                lines.append(f"imul {dst}, {src}")
+    for dst in GPR64:
+        for src in GPR32:
+            lines.append(f"movsxd {dst}, {src}")
+    for op in BIT_COUNTS:
+        for registers in GPRS[1:]:
+            for index, dst in enumerate(registers):
+                for src in (registers[index], registers[(index * 7 + 5) % 16]):
+                    lines.append(f"{op} {dst}, {src}")
+    for registers in GPRS:
+        for register in registers:
+            lines.append(f"div {register}")
+            lines.append(f"idiv {register}")
+            for op in SHIFTS:
+                lines.append(f"{op} {register}, cl")
+    for dst in LEGACY8:
+        for src in LEGACY8:
+            if dst in HIGH8 or src in HIGH8:
+                lines.append(f"mov {dst}, {src}")
+                lines.append(f"{ARITHMETIC[len(lines) % 8]} {dst}, {src}")
+    for register in HIGH8:
+        lines.append(f"div {register}")
+        lines.append(f"idiv {register}")
+        for op in SHIFTS:
+            lines.append(f"{op} {register}, cl")
+    for register in GPR64:
+        lines.append(f"push {register}")
+        lines.append(f"pop {register}")
+        lines.append(f"jmp {register}")
+        lines.append(f"call {register}")
+    return lines
+
+
+def memory_table():
+    operands = []
+    for base in GPR64:
+        for index in GPR64:
+            if index == "rsp":
+                continue
+            for scale in SCALES:
+                for displacement in (0, -128, 128):
+                    operands.append(memory(base, index, scale, displacement))
+        for displacement in DISPLACEMENTS:
+            operands.append(memory(base, displacement=displacement))
+    for index in GPR64:
+        if index == "rsp":
+            continue
+        for scale in SCALES:
+            operands.append(memory(None, index, scale, 0x40))
+    for displacement in (0, 0x40, 2**31 - 1, -(2**31)):
+        operands.append(memory(None, displacement=displacement))
+        operands.append(memory("rip", displacement=displacement))
+
+    lines = []
+    for position, operand in enumerate(operands):
+        dst = GPR64[position % 16]
+        lines.append(f"mov {dst}, qword ptr {operand}")
+    for position, operand in enumerate(operands[::23]):
+        registers = GPRS[position % 4]
+        size = [8, 16, 32, 64][position % 4]
+        register = registers[(position * 3) % 16]
+        lines.append(f"mov {PTR[size]} ptr {operand}, {register}")
+        lines.append(f"mov {register}, {PTR[size]} ptr {operand}")
+        op = ARITHMETIC[position % 8]
+        lines.append(f"{op} {register}, {PTR[size]} ptr {operand}")
+        lines.append(f"{op} {PTR[size]} ptr {operand}, {register}")
+        lines.append(f"lea {GPR64[position % 16]}, {operand}")
+        if size != 8:
+            lines.append(f"imul {register}, {PTR[size]} ptr {operand}")
+        lines.append(f"movsxd {GPR64[position % 16]}, dword ptr {operand}")
+        if size != 8:
+            op = BIT_COUNTS[position % 3]
+            lines.append(f"{op} {register}, {PTR[size]} ptr {operand}")
+        xmm = XMMS[position % 16]
+        lines.append(f"movsd {xmm}, qword ptr {operand}")
+        lines.append(f"movsd qword ptr {operand}, {xmm}")
+        lines.append(f"movss {xmm}, dword ptr {operand}")
+        lines.append(f"movss dword ptr {operand}, {xmm}")
+        lines.append(f"movaps {xmm}, xmmword ptr {operand}")
+        lines.append(f"movaps xmmword ptr {operand}, {xmm}")
+        float_op = FLOAT_ARITHMETIC[position % 8]
+        float_size = "qword" if float_op.endswith("sd") else "dword"
+        lines.append(f"{float_op} {xmm}, {float_size} ptr {operand}")
+    legacy = [
+        operand for operand in operands if not re.search(r"\br(?:[89]|1[0-5])\b", operand)
+    ]
+    for position, operand in enumerate(legacy[::7]):
+        register = HIGH8[position % 4]
+        lines.append(f"mov {register}, byte ptr {operand}")
+        lines.append(f"mov byte ptr {operand}, {register}")
+        op = ARITHMETIC[position % 8]
+        lines.append(f"{op} {register}, byte ptr {operand}")
+        lines.append(f"{op} byte ptr {operand}, {register}")
+    for position, operand in enumerate(operands[::17]):
+        segment = ("fs", "gs")[position % 2]
+        registers = GPRS[position % 4]
+        size = [8, 16, 32, 64][position % 4]
+        register = registers[(position * 5) % 16]
+        lines.append(f"mov {register}, {PTR[size]} ptr {segment}:{operand}")
+        lines.append(f"mov {PTR[size]} ptr {segment}:{operand}, {register}")
+        op = ARITHMETIC[position % 8]
+        lines.append(f"{op} {register}, {PTR[size]} ptr {segment}:{operand}")
+        lines.append(f"popcnt {GPR16[position % 16]}, word ptr {segment}:{operand}")
+        xmm = f"xmm{position % 16}"
+        lines.append(f"movsd {xmm}, qword ptr {segment}:{operand}")
+    return lines
+
+
+def xmm_table():
+    lines = []
+    for op in ["movsd", "movss", "movaps"] + FLOAT_ARITHMETIC:
+        for dst in XMMS:
+            for src in XMMS:
+                lines.append(f"{op} {dst}, {src}")
+    return lines
+
+
+def boundaries(bits):
+    values = {0, 1, -1, 2 ** (bits - 1) - 1, -(2 ** (bits - 1)), 2**bits - 1}
+    for smaller in (8, 16, 32):
+        if smaller < bits:
+            values |= {
+                2 ** (smaller - 1) - 1,
+                2 ** (smaller - 1),
+                -(2 ** (smaller - 1)),
+                -(2 ** (smaller - 1)) - 1,
+            }
+    return sorted(values)
+
+
+def immediate_table():
+    lines = []
+    for bits, registers in zip([8, 16, 32, 64], GPRS):
+        for register in registers:
+            for value in boundaries(bits):
+                if bits == 64 and value >= 2**63:
+                    continue
+                # The encoder zero extends these from `mov r32, imm32`, while `as`
+                # falls back to `movabs`.
+                if bits == 64 and 2**31 <= value < 2**32:
+                    continue
+                lines.append(f"mov {register}, {value}")
+        for register in registers[:2] + registers[8:10]:
+            for op in ARITHMETIC:
+                for value in boundaries(min(bits, 32)):
+                    # Sign extended 32-bit immediates are the only option for
+                    # 64-bit registers.
+                    if bits == 64 and value >= 2**31:
+                        continue
+                    lines.append(f"{op} {register}, {value}")
+            for op in SHIFTS:
+                for value in (0, 2, bits - 1, 255):
+                    lines.append(f"{op} {register}, {value}")
+    for register in HIGH8:
+        for value in boundaries(8):
+            lines.append(f"mov {register}, {value}")
+            lines.append(f"{ARITHMETIC[len(lines) % 8]} {register}, {value}")
+        for op in SHIFTS:
+            lines.append(f"{op} {register}, 3")
+    for register in GPR64:
+        for value in (0, -1, 2**31 - 1, 2**31, 2**63 - 1, -(2**63)):
+            lines.append(f"movabs {register}, {value}")
+    return lines
+
+
+def assemble(lines):
+    source = ".intel_syntax noprefix\n" + "\n".join(lines) + "\n"
+    with tempfile.TemporaryDirectory() as directory:
+        source_path = os.path.join(directory, "input.s")
+        object_path = os.path.join(directory, "input.o")
+        with open(source_path, "w") as file:
+            file.write(source)
+        subprocess.run(["as", "--64", "-o", object_path, source_path], check=True)
+        dump = subprocess.run(
+            ["objdump", "-d", "--insn-width=16", object_path],
+            check=True,
+            capture_output=True,
+            text=True,
+        ).stdout
+
+    encodings = []
+    for line in dump.splitlines():
+        columns = line.split("\t")
+        if len(columns) < 2 or not columns[0].strip().endswith(":"):
+            continue
+        encodings.append(columns[1].strip())
+    assert len(encodings) == len(lines), (len(encodings), len(lines))
+    return encodings
+
+
+def write_table(name, lines):
+    encodings = assemble(lines)
+    path = os.path.join(DIRECTORY, f"{name}.txt")
+    with open(path, "w") as file:
+        file.write("# Generated by generate.py from GNU as output, do not edit.\n")
+        for line, encoding in zip(lines, encodings):
+            file.write(f"{line} = {encoding}\n")
+    print(f"{path}: {len(lines)} instructions")
+
+
+def main():
+    write_table("gpr", gpr_table())
+    write_table("memory", memory_table())
+    write_table("xmm", xmm_table())
+    write_table("immediate", immediate_table())
+
+
+if __name__ == "__main__":
+    main()
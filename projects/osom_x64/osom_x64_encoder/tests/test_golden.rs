@@ -0,0 +1,275 @@
+//! Differential tests against golden tables produced by a reference assembler,
+//! see `tests/golden/generate.py`. Each table line has the form
+//! `<instruction in Intel syntax> = <bytes in hex>`.
+
+use osom_x64_encoder::{
+    encoder::{
+        self, arith, bitcount, call, errors::EncodingError, jmp, lea, mov, muldiv,
+        shift, sse, stack, EncodedInstruction,
+    },
+    models::{
+        self, ArithmeticOp, BitCountOp, FloatArithmeticOp, MachineSize, Memory,
+        Mnemonic, Scale, Segment, ShiftOp, GPR, XMM,
+    },
+};
+use rstest::rstest;
+
+#[derive(Clone, Copy, Debug)]
+enum Operand {
+    Gpr(GPR),
+    Xmm(XMM),
+    Memory(Memory),
+    Immediate(i64),
+}
+
+fn parse_gpr(name: &str) -> Option<GPR> {
+    if name == "rip" {
+        return Some(GPR::RIP);
+    }
+    let high_bytes = [GPR::AH, GPR::CH, GPR::DH, GPR::BH];
+    if let Some(gpr) = high_bytes.into_iter().find(|gpr| gpr.name() == name) {
+        return Some(gpr);
+    }
+    let sizes = [
+        MachineSize::Byte,
+        MachineSize::Word,
+        MachineSize::DWord,
+        MachineSize::QWord,
+    ];
+    sizes
+        .into_iter()
+        .flat_map(GPR::all)
+        .find(|gpr| gpr.name() == name)
+}
+
+fn parse_xmm(name: &str) -> Option<XMM> {
+    XMM::all().find(|xmm| xmm.name() == name)
+}
+
+fn parse_integer(text: &str) -> i64 {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None => digits.parse::<i128>(),
+    }
+    .unwrap_or_else(|_| panic!("Invalid integer: {text}"));
+    let value = if negative { -value } else { value };
+    i64::try_from(value).unwrap_or_else(|_| panic!("Integer out of range: {text}"))
+}
+
+/// Parses `[base+index*scale+disp]`, with every part optional.
+fn parse_memory(text: &str) -> Memory {
+    let inner = text.trim_start_matches('[').trim_end_matches(']');
+    let mut base = GPR::NO_REG;
+    let mut index = GPR::NO_REG;
+    let mut scale = Scale::Scale1;
+    let mut displacement = 0;
+    let mut rest = inner;
+    while !rest.is_empty() {
+        let negative = rest.starts_with('-');
+        rest = rest.trim_start_matches(['+', '-']);
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let term = &rest[..end];
+        rest = &rest[end..];
+        if let Some((register, factor)) = term.split_once('*') {
+            index = parse_gpr(register).unwrap();
+            scale = match factor {
+                "1" => Scale::Scale1,
+                "2" => Scale::Scale2,
+                "4" => Scale::Scale4,
+                "8" => Scale::Scale8,
+                _ => panic!("Invalid scale: {text}"),
+            };
+        } else if let Some(register) = parse_gpr(term) {
+            base = register;
+        } else {
+            let value = parse_integer(term);
+            let value = if negative { -value } else { value };
+            displacement = i32::try_from(value).unwrap();
+        }
+    }
+    Memory::new(base, index, scale, displacement.into()).unwrap()
+}
+
+fn parse_operand(text: &str) -> Operand {
+    let text = match text.split_once(" ptr ") {
+        Some((_, operand)) => operand,
+        None => text,
+    };
+    if let Some((segment, memory)) = text.split_once(':') {
+        let segment = Segment::ALL
+            .into_iter()
+            .find(|s| s.name() == segment)
+            .unwrap();
+        Operand::Memory(parse_memory(memory).with_segment(segment))
+    } else if text.starts_with('[') {
+        Operand::Memory(parse_memory(text))
+    } else if let Some(gpr) = parse_gpr(text) {
+        Operand::Gpr(gpr)
+    } else if let Some(xmm) = parse_xmm(text) {
+        Operand::Xmm(xmm)
+    } else {
+        Operand::Immediate(parse_integer(text))
+    }
+}
+
+/// Encodes `instruction` with the matching encoder.
+fn encode(instruction: &str) -> Result<EncodedInstruction, EncodingError> {
+    use Operand::{Gpr, Immediate, Memory as Mem, Xmm};
+
+    let (mnemonic, operands) = instruction.split_once(' ').unwrap_or((instruction, ""));
+    let operands: Vec<_> = operands
+        .split(", ")
+        .filter(|operand| !operand.is_empty())
+        .map(parse_operand)
+        .collect();
+    let arithmetic = ArithmeticOp::ALL
+        .into_iter()
+        .find(|op| op.mnemonic() == mnemonic);
+    let shift = ShiftOp::ALL
+        .into_iter()
+        .find(|op| op.mnemonic() == mnemonic);
+    let bit_count = BitCountOp::ALL
+        .into_iter()
+        .find(|op| op.mnemonic() == mnemonic);
+    let float = FloatArithmeticOp::ALL
+        .into_iter()
+        .find(|op| op.mnemonic() == mnemonic);
+
+    match (mnemonic, operands.as_slice()) {
+        ("mov", [Gpr(dst), Gpr(src)]) => mov::encode_mov_reg_reg(*dst, *src),
+        ("mov", [Gpr(dst), Immediate(imm)]) => mov::encode_mov_reg_imm(*dst, *imm),
+        ("mov", [Gpr(dst), Mem(src)]) => mov::encode_mov_reg_mem(*dst, *src),
+        ("mov", [Mem(dst), Gpr(src)]) => mov::encode_mov_mem_reg(*dst, *src),
+        ("movabs", [Gpr(dst), Immediate(imm)]) => mov::encode_movabs_reg_imm(*dst, *imm),
+        ("movsxd", [Gpr(dst), Gpr(src)]) => mov::encode_movsxd_reg_reg(*dst, *src),
+        ("movsxd", [Gpr(dst), Mem(src)]) => mov::encode_movsxd_reg_mem(*dst, *src),
+        ("lea", [Gpr(dst), Mem(src)]) => lea::encode_lea(*dst, *src),
+        ("imul", [Gpr(dst), Gpr(src)]) => muldiv::encode_imul_reg_reg(*dst, *src),
+        ("imul", [Gpr(dst), Mem(src)]) => muldiv::encode_imul_reg_mem(*dst, *src),
+        ("div", [Gpr(src)]) => muldiv::encode_div(*src),
+        ("idiv", [Gpr(src)]) => muldiv::encode_idiv(*src),
+        ("push", [Gpr(reg)]) => stack::encode_push(*reg),
+        ("pop", [Gpr(reg)]) => stack::encode_pop(*reg),
+        ("jmp", [Gpr(target)]) => jmp::encode_jmp_reg(*target),
+        ("call", [Gpr(target)]) => call::encode_call_reg(*target),
+        ("movsd", [Xmm(dst), Xmm(src)]) => Ok(sse::encode_movsd_xmm_xmm(*dst, *src)),
+        ("movsd", [Xmm(dst), Mem(src)]) => Ok(sse::encode_movsd_xmm_mem(*dst, *src)),
+        ("movsd", [Mem(dst), Xmm(src)]) => Ok(sse::encode_movsd_mem_xmm(*dst, *src)),
+        ("movss", [Xmm(dst), Xmm(src)]) => Ok(sse::encode_movss_xmm_xmm(*dst, *src)),
+        ("movss", [Xmm(dst), Mem(src)]) => Ok(sse::encode_movss_xmm_mem(*dst, *src)),
+        ("movss", [Mem(dst), Xmm(src)]) => Ok(sse::encode_movss_mem_xmm(*dst, *src)),
+        ("movaps", [Xmm(dst), Xmm(src)]) => Ok(sse::encode_movaps_xmm_xmm(*dst, *src)),
+        ("movaps", [Xmm(dst), Mem(src)]) => Ok(sse::encode_movaps_xmm_mem(*dst, *src)),
+        ("movaps", [Mem(dst), Xmm(src)]) => Ok(sse::encode_movaps_mem_xmm(*dst, *src)),
+        (_, [Gpr(dst), Gpr(src)]) if arithmetic.is_some() => {
+            arith::encode_arith_reg_reg(arithmetic.unwrap(), *dst, *src)
+        }
+        (_, [Gpr(dst), Immediate(imm)]) if arithmetic.is_some() => {
+            arith::encode_arith_reg_imm(arithmetic.unwrap(), *dst, *imm)
+        }
+        (_, [Gpr(dst), Mem(src)]) if arithmetic.is_some() => {
+            arith::encode_arith_reg_mem(arithmetic.unwrap(), *dst, *src)
+        }
+        (_, [Mem(dst), Gpr(src)]) if arithmetic.is_some() => {
+            arith::encode_arith_mem_reg(arithmetic.unwrap(), *dst, *src)
+        }
+        (_, [Gpr(dst), Gpr(GPR::CL)]) if shift.is_some() => {
+            shift::encode_shift_reg_cl(shift.unwrap(), *dst)
+        }
+        (_, [Gpr(dst), Immediate(imm)]) if shift.is_some() => {
+            shift::encode_shift_reg_imm(shift.unwrap(), *dst, *imm)
+        }
+        (_, [Gpr(dst), Gpr(src)]) if bit_count.is_some() => {
+            bitcount::encode_bit_count_reg_reg(bit_count.unwrap(), *dst, *src)
+        }
+        (_, [Gpr(dst), Mem(src)]) if bit_count.is_some() => {
+            bitcount::encode_bit_count_reg_mem(bit_count.unwrap(), *dst, *src)
+        }
+        (_, [Xmm(dst), Xmm(src)]) if float.is_some() => {
+            Ok(sse::encode_float_arith_xmm_xmm(float.unwrap(), *dst, *src))
+        }
+        (_, [Xmm(dst), Mem(src)]) if float.is_some() => {
+            Ok(sse::encode_float_arith_xmm_mem(float.unwrap(), *dst, *src))
+        }
+        _ => panic!("No encoder for: {instruction}"),
+    }
+}
+
+/// Encodes `instruction` through the operand-generic [`encoder::encode`].
+fn encode_dynamic(instruction: &str) -> Result<EncodedInstruction, EncodingError> {
+    let (mnemonic, operands) = instruction.split_once(' ').unwrap_or((instruction, ""));
+    let mnemonic = Mnemonic::from_name(mnemonic)
+        .unwrap_or_else(|| panic!("Unknown mnemonic: {instruction}"));
+    let operands: Vec<_> = operands
+        .split(", ")
+        .filter(|operand| !operand.is_empty())
+        .map(|operand| match parse_operand(operand) {
+            Operand::Gpr(gpr) => models::Operand::Gpr(gpr),
+            Operand::Xmm(xmm) => models::Operand::Xmm(xmm),
+            Operand::Memory(memory) => models::Operand::Memory(memory),
+            Operand::Immediate(imm) => models::Operand::Immediate(imm.into()),
+        })
+        .collect();
+    encoder::encode(mnemonic, &operands)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let bytes: Vec<_> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    bytes.join(" ")
+}
+
+#[rstest]
+#[case::gpr(include_str!("golden/gpr.txt"))]
+#[case::memory(include_str!("golden/memory.txt"))]
+#[case::xmm(include_str!("golden/xmm.txt"))]
+#[case::immediate(include_str!("golden/immediate.txt"))]
+fn test_golden(#[case] table: &str) {
+    check_table(table, encode);
+}
+
+#[rstest]
+#[case::gpr(include_str!("golden/gpr.txt"))]
+#[case::memory(include_str!("golden/memory.txt"))]
+#[case::xmm(include_str!("golden/xmm.txt"))]
+#[case::immediate(include_str!("golden/immediate.txt"))]
+fn test_golden_dispatch(#[case] table: &str) {
+    check_table(table, encode_dynamic);
+}
+
+/// Checks that `encode` reproduces every line of the golden `table`.
+fn check_table(
+    table: &str,
+    encode: fn(&str) -> Result<EncodedInstruction, EncodingError>,
+) {
+    let mut failures = Vec::new();
+    let mut count = 0;
+    for line in table.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (instruction, expected) = line.split_once(" = ").unwrap();
+        count += 1;
+        match encode(instruction) {
+            Ok(encoded) if hex(encoded.as_slice()) == expected => {}
+            Ok(encoded) => failures.push(format!(
+                "{instruction}: expected {expected}, got {}",
+                hex(encoded.as_slice())
+            )),
+            Err(error) => {
+                failures
+                    .push(format!("{instruction}: expected {expected}, got {error:?}"));
+            }
+        }
+    }
+    assert!(count > 0);
+    assert!(
+        failures.is_empty(),
+        "{} of {count} instructions differ:\n{}",
+        failures.len(),
+        failures[..failures.len().min(20)].join("\n")
+    );
+}
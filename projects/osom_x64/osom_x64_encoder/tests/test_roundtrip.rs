@@ -0,0 +1,389 @@
+use osom_x64_encoder::{
+    decoder::{decode, Instruction},
+    encoder::{
+        arith, call, jcc, jmp, lea, misc, mov, muldiv, shift, sse, stack,
+        EncodedInstruction,
+    },
+    models::{
+        ArithmeticOp, Condition, FloatArithmeticOp, MachineSize, Memory, Scale, ShiftOp,
+        GPR, XMM,
+    },
+};
+use rstest::rstest;
+
+mod common;
+
+use common::mov_reg_imm;
+
+const GPR_SIZES: [MachineSize; 4] = [
+    MachineSize::Byte,
+    MachineSize::Word,
+    MachineSize::DWord,
+    MachineSize::QWord,
+];
+
+fn canonical(cond: Condition) -> Condition {
+    match cond {
+        Condition::Carry => Condition::Below,
+        Condition::NotCarry => Condition::AboveOrEqual,
+        Condition::Zero => Condition::Equal,
+        Condition::NotZero => Condition::NotEqual,
+        other => other,
+    }
+}
+
+fn roundtrip(encoded: &EncodedInstruction) -> Instruction {
+    let decoded = decode(encoded.as_slice()).unwrap();
+    assert_eq!(decoded.len(), encoded.len());
+    decoded.instruction()
+}
+
+#[test]
+fn test_ret_roundtrip() {
+    assert_eq!(roundtrip(&misc::encode_ret()), Instruction::Ret);
+}
+
+#[test]
+fn test_nop_roundtrip() {
+    for size in 1..=9 {
+        let encoded = misc::encode_nop(size).unwrap();
+        assert_eq!(roundtrip(&encoded), Instruction::Nop { size });
+    }
+}
+
+#[test]
+fn test_push_pop_roundtrip() {
+    for reg in GPR::all(MachineSize::QWord) {
+        let encoded = stack::encode_push(reg).unwrap();
+        assert_eq!(roundtrip(&encoded), Instruction::Push { reg });
+        let encoded = stack::encode_pop(reg).unwrap();
+        assert_eq!(roundtrip(&encoded), Instruction::Pop { reg });
+    }
+}
+
+#[test]
+fn test_jmp_reg_roundtrip() {
+    for target in GPR::all(MachineSize::QWord) {
+        let encoded = jmp::encode_jmp_reg(target).unwrap();
+        assert_eq!(roundtrip(&encoded), Instruction::JmpReg { target });
+        let encoded = call::encode_call_reg(target).unwrap();
+        assert_eq!(roundtrip(&encoded), Instruction::CallReg { target });
+    }
+}
+
+#[rstest]
+#[case(i8::MIN)]
+#[case(-1)]
+#[case(0)]
+#[case(1)]
+#[case(i8::MAX)]
+fn test_jmp_rel8_roundtrip(#[case] rel: i8) {
+    let encoded = jmp::encode_jmp_rel8(rel);
+    assert_eq!(roundtrip(&encoded), Instruction::JmpRel8 { rel });
+    for cond in Condition::ALL {
+        let encoded = jcc::encode_jcc_rel8(cond, rel);
+        let expected = Instruction::JccRel8 {
+            cond: canonical(cond),
+            rel,
+        };
+        assert_eq!(roundtrip(&encoded), expected);
+    }
+}
+
+#[rstest]
+#[case(i32::MIN)]
+#[case(-129)]
+#[case(0)]
+#[case(128)]
+#[case(i32::MAX)]
+fn test_jmp_rel32_roundtrip(#[case] rel: i32) {
+    let encoded = jmp::encode_jmp_rel32(rel);
+    assert_eq!(roundtrip(&encoded), Instruction::JmpRel32 { rel });
+    let encoded = call::encode_call_rel32(rel);
+    assert_eq!(roundtrip(&encoded), Instruction::CallRel32 { rel });
+    for cond in Condition::ALL {
+        let encoded = jcc::encode_jcc_rel32(cond, rel);
+        let expected = Instruction::JccRel32 {
+            cond: canonical(cond),
+            rel,
+        };
+        assert_eq!(roundtrip(&encoded), expected);
+    }
+}
+
+/// Returns interesting immediates representable in `size`, sign extended
+/// the same way the decoder does.
+fn immediates(size: MachineSize) -> Vec<i64> {
+    let mut result = vec![0, 1, -1, i64::from(i8::MIN), i64::from(i8::MAX)];
+    if size != MachineSize::Byte {
+        result.extend([128, -129, i64::from(i16::MIN), i64::from(i16::MAX)]);
+    }
+    if matches!(size, MachineSize::DWord | MachineSize::QWord) {
+        result.extend([i64::from(i32::MIN), i64::from(i32::MAX)]);
+    }
+    if size == MachineSize::QWord {
+        result.extend([i64::MIN, i64::MAX, 0xFFFF_FFFF, 0x1_0000_0000]);
+    }
+    result
+}
+
+fn xmms() -> impl Iterator<Item = XMM> {
+    XMM::all()
+}
+
+fn memory_operands() -> Vec<Memory> {
+    const SCALES: [Scale; 4] =
+        [Scale::Scale1, Scale::Scale2, Scale::Scale4, Scale::Scale8];
+    const DISPLACEMENTS: [i32; 5] = [0, -1, 127, 128, i32::MIN];
+
+    let mut result = Vec::new();
+    for displacement in DISPLACEMENTS {
+        result.push(Memory::rip_relative(displacement).unwrap());
+        for base in GPR::all(MachineSize::QWord).chain([GPR::NO_REG]) {
+            for index in GPR::all(MachineSize::QWord).chain([GPR::NO_REG]) {
+                for scale in SCALES {
+                    if let Ok(memory) =
+                        Memory::new(base, index, scale, displacement.into())
+                    {
+                        result.push(memory);
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn test_lea_roundtrip() {
+    let memory_operands = memory_operands();
+    for size in [MachineSize::Word, MachineSize::DWord, MachineSize::QWord] {
+        for dst in GPR::all(size) {
+            for &src in &memory_operands {
+                let encoded = lea::encode_lea(dst, src).unwrap();
+                assert_eq!(roundtrip(&encoded), Instruction::Lea { dst, src });
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scalar_move_roundtrip() {
+    let memory_operands = memory_operands();
+    for reg in xmms() {
+        for other in xmms() {
+            let encoded = sse::encode_movsd_xmm_xmm(reg, other);
+            let expected = Instruction::MovsdXmmXmm {
+                dst: reg,
+                src: other,
+            };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movaps_xmm_xmm(reg, other);
+            let expected = Instruction::MovapsXmmXmm {
+                dst: reg,
+                src: other,
+            };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movss_xmm_xmm(reg, other);
+            let expected = Instruction::MovssXmmXmm {
+                dst: reg,
+                src: other,
+            };
+            assert_eq!(roundtrip(&encoded), expected);
+        }
+
+        for &mem in &memory_operands {
+            let encoded = sse::encode_movsd_xmm_mem(reg, mem);
+            let expected = Instruction::MovsdXmmMem { dst: reg, src: mem };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movsd_mem_xmm(mem, reg);
+            let expected = Instruction::MovsdMemXmm { dst: mem, src: reg };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movss_xmm_mem(reg, mem);
+            let expected = Instruction::MovssXmmMem { dst: reg, src: mem };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movss_mem_xmm(mem, reg);
+            let expected = Instruction::MovssMemXmm { dst: mem, src: reg };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movaps_xmm_mem(reg, mem);
+            let expected = Instruction::MovapsXmmMem { dst: reg, src: mem };
+            assert_eq!(roundtrip(&encoded), expected);
+
+            let encoded = sse::encode_movaps_mem_xmm(mem, reg);
+            let expected = Instruction::MovapsMemXmm { dst: mem, src: reg };
+            assert_eq!(roundtrip(&encoded), expected);
+        }
+    }
+}
+
+#[test]
+fn test_mov_roundtrip() {
+    let memory_operands = memory_operands();
+    for size in GPR_SIZES {
+        for dst in GPR::all(size) {
+            for src in GPR::all(size) {
+                let encoded = mov::encode_mov_reg_reg(dst, src).unwrap();
+                assert_eq!(roundtrip(&encoded), Instruction::MovRegReg { dst, src });
+            }
+
+            for imm in immediates(size) {
+                let encoded = mov::encode_mov_reg_imm(dst, imm).unwrap();
+                assert_eq!(roundtrip(&encoded), mov_reg_imm(dst, imm));
+            }
+
+            for &mem in memory_operands.iter().step_by(7) {
+                let encoded = mov::encode_mov_reg_mem(dst, mem).unwrap();
+                let expected = Instruction::MovRegMem { dst, src: mem };
+                assert_eq!(roundtrip(&encoded), expected);
+
+                let encoded = mov::encode_mov_mem_reg(mem, dst).unwrap();
+                let expected = Instruction::MovMemReg { dst: mem, src: dst };
+                assert_eq!(roundtrip(&encoded), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_movabs_roundtrip() {
+    for dst in GPR::all(MachineSize::QWord) {
+        for imm in [0, 1, -1, i64::MIN, i64::MAX] {
+            let encoded = mov::encode_movabs_reg_imm(dst, imm).unwrap();
+            assert_eq!(encoded.len(), 10);
+            assert_eq!(roundtrip(&encoded), Instruction::MovRegImm { dst, imm });
+        }
+    }
+}
+
+#[test]
+fn test_movsxd_roundtrip() {
+    let memory_operands = memory_operands();
+    for dst in GPR::all(MachineSize::QWord) {
+        for src in GPR::all(MachineSize::DWord) {
+            let encoded = mov::encode_movsxd_reg_reg(dst, src).unwrap();
+            assert_eq!(roundtrip(&encoded), Instruction::MovsxdRegReg { dst, src });
+        }
+
+        for &mem in memory_operands.iter().step_by(11) {
+            let encoded = mov::encode_movsxd_reg_mem(dst, mem).unwrap();
+            let expected = Instruction::MovsxdRegMem { dst, src: mem };
+            assert_eq!(roundtrip(&encoded), expected);
+        }
+    }
+}
+
+#[test]
+fn test_arith_roundtrip() {
+    let memory_operands = memory_operands();
+    for op in ArithmeticOp::ALL {
+        for size in GPR_SIZES {
+            for dst in GPR::all(size) {
+                for src in GPR::all(size) {
+                    let encoded = arith::encode_arith_reg_reg(op, dst, src).unwrap();
+                    let expected = Instruction::ArithRegReg { op, dst, src };
+                    assert_eq!(roundtrip(&encoded), expected);
+                }
+
+                for imm in immediates(size) {
+                    let Ok(imm) = i32::try_from(imm) else {
+                        continue;
+                    };
+                    let encoded = arith::encode_arith_reg_imm(op, dst, imm).unwrap();
+                    let expected = Instruction::ArithRegImm { op, dst, imm };
+                    assert_eq!(roundtrip(&encoded), expected);
+                }
+
+                for &mem in memory_operands.iter().step_by(31) {
+                    let encoded = arith::encode_arith_reg_mem(op, dst, mem).unwrap();
+                    let expected = Instruction::ArithRegMem { op, dst, src: mem };
+                    assert_eq!(roundtrip(&encoded), expected);
+
+                    let encoded = arith::encode_arith_mem_reg(op, mem, dst).unwrap();
+                    let expected = Instruction::ArithMemReg {
+                        op,
+                        dst: mem,
+                        src: dst,
+                    };
+                    assert_eq!(roundtrip(&encoded), expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_muldiv_roundtrip() {
+    assert_eq!(roundtrip(&muldiv::encode_cqo()), Instruction::Cqo);
+    assert_eq!(roundtrip(&muldiv::encode_cdq()), Instruction::Cdq);
+
+    let memory_operands = memory_operands();
+    for size in GPR_SIZES {
+        for reg in GPR::all(size) {
+            let encoded = muldiv::encode_div(reg).unwrap();
+            assert_eq!(roundtrip(&encoded), Instruction::Div { src: reg });
+
+            let encoded = muldiv::encode_idiv(reg).unwrap();
+            assert_eq!(roundtrip(&encoded), Instruction::Idiv { src: reg });
+
+            if size == MachineSize::Byte {
+                continue;
+            }
+
+            for src in GPR::all(size) {
+                let encoded = muldiv::encode_imul_reg_reg(reg, src).unwrap();
+                let expected = Instruction::ImulRegReg { dst: reg, src };
+                assert_eq!(roundtrip(&encoded), expected);
+            }
+
+            for &mem in memory_operands.iter().step_by(31) {
+                let encoded = muldiv::encode_imul_reg_mem(reg, mem).unwrap();
+                let expected = Instruction::ImulRegMem { dst: reg, src: mem };
+                assert_eq!(roundtrip(&encoded), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shift_roundtrip() {
+    for op in ShiftOp::ALL {
+        for size in GPR_SIZES {
+            for dst in GPR::all(size) {
+                let encoded = shift::encode_shift_reg_cl(op, dst).unwrap();
+                assert_eq!(roundtrip(&encoded), Instruction::ShiftRegCl { op, dst });
+
+                for imm in [0, 1, 2, 31, 63, u8::MAX] {
+                    let encoded = shift::encode_shift_reg_imm(op, dst, imm).unwrap();
+                    let expected = Instruction::ShiftRegImm { op, dst, imm };
+                    assert_eq!(roundtrip(&encoded), expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_float_arith_roundtrip() {
+    let memory_operands = memory_operands();
+    for op in FloatArithmeticOp::ALL {
+        for dst in xmms() {
+            for src in xmms() {
+                let encoded = sse::encode_float_arith_xmm_xmm(op, dst, src);
+                let expected = Instruction::FloatArithXmmXmm { op, dst, src };
+                assert_eq!(roundtrip(&encoded), expected);
+            }
+
+            for &src in memory_operands.iter().step_by(13) {
+                let encoded = sse::encode_float_arith_xmm_mem(op, dst, src);
+                let expected = Instruction::FloatArithXmmMem { op, dst, src };
+                assert_eq!(roundtrip(&encoded), expected);
+            }
+        }
+    }
+}
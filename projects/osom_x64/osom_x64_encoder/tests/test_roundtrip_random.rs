@@ -0,0 +1,371 @@
+//! Property tests: random valid operands for each encoder, decoded back and
+//! compared with the input, and random bytes fed to the decoder.
+
+use osom_x64_encoder::{
+    constants::MAX_INSTRUCTION_SIZE,
+    decoder::{decode, Instruction},
+    encoder::{
+        arith, bitcount, call, jcc, jmp, lea, mov, muldiv, shift, sse, stack,
+        EncodedInstruction,
+    },
+    models::{
+        ArithmeticOp, BitCountOp, Condition, CpuRequirement, FloatArithmeticOp,
+        MachineSize, Memory, Scale, ShiftOp, GPR, XMM,
+    },
+};
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+mod common;
+
+use common::mov_reg_imm;
+
+const ITERATIONS: usize = 20_000;
+
+const GPR_SIZES: [MachineSize; 4] = [
+    MachineSize::Byte,
+    MachineSize::Word,
+    MachineSize::DWord,
+    MachineSize::QWord,
+];
+
+const SCALES: [Scale; 4] = [Scale::Scale1, Scale::Scale2, Scale::Scale4, Scale::Scale8];
+
+/// Conditions decoded as themselves, i.e. without aliases such as `Carry`.
+const CONDITIONS: [Condition; 16] = [
+    Condition::Overflow,
+    Condition::NotOverflow,
+    Condition::Below,
+    Condition::AboveOrEqual,
+    Condition::Equal,
+    Condition::NotEqual,
+    Condition::BelowOrEqual,
+    Condition::Above,
+    Condition::Sign,
+    Condition::NotSign,
+    Condition::Parity,
+    Condition::NotParity,
+    Condition::Less,
+    Condition::GreaterOrEqual,
+    Condition::LessOrEqual,
+    Condition::Greater,
+];
+
+fn roundtrip(encoded: &EncodedInstruction) -> Instruction {
+    let decoded = decode(encoded.as_slice()).unwrap();
+    assert_eq!(decoded.len(), encoded.len());
+    let requirement = match decoded.instruction().required_feature() {
+        Some(feature) => CpuRequirement::Feature(feature),
+        None => CpuRequirement::NoFeature,
+    };
+    assert_eq!(encoded.cpu_requirement(), requirement);
+    decoded.instruction()
+}
+
+fn random_gpr(rng: &mut ThreadRng, size: MachineSize) -> GPR {
+    GPR::new(size, rng.gen_range(0..16)).unwrap()
+}
+
+fn random_xmm(rng: &mut ThreadRng) -> XMM {
+    XMM::new(MachineSize::XMMWord, rng.gen_range(0..16)).unwrap()
+}
+
+/// Returns a random integer of at most `bits` bits, sign extended, biased
+/// towards small values and boundaries.
+fn random_signed(rng: &mut ThreadRng, bits: u32) -> i64 {
+    let bits = match rng.gen_range(0..4) {
+        0 => bits.min(8),
+        1 => bits.min(16),
+        _ => bits,
+    };
+    let max = i64::MAX >> (64 - bits);
+    let min = i64::MIN >> (64 - bits);
+    match rng.gen_range(0..8) {
+        0 => max,
+        1 => min,
+        2 => rng.gen_range(-2..=2),
+        _ => rng.gen_range(min..=max),
+    }
+}
+
+fn random_memory(rng: &mut ThreadRng) -> Memory {
+    #[allow(clippy::cast_possible_truncation)]
+    let displacement = random_signed(rng, 32) as i32;
+    if rng.gen_ratio(1, 16) {
+        return Memory::rip_relative(displacement).unwrap();
+    }
+    loop {
+        let base = if rng.gen_ratio(1, 8) {
+            GPR::NO_REG
+        } else {
+            random_gpr(rng, MachineSize::QWord)
+        };
+        let index = if rng.gen_ratio(1, 3) {
+            GPR::NO_REG
+        } else {
+            random_gpr(rng, MachineSize::QWord)
+        };
+        let scale = *SCALES.choose(rng).unwrap();
+        if let Ok(memory) = Memory::new(base, index, scale, displacement.into()) {
+            return memory;
+        }
+    }
+}
+
+/// Returns a random immediate accepted by `mov` to a register of `size`, as
+/// reported by the decoder, i.e. sign extended.
+fn random_immediate(rng: &mut ThreadRng, size: MachineSize) -> i64 {
+    random_signed(rng, u32::from(size.as_bytes()) * 8)
+}
+
+/// Encodes a random instruction with a random encoder, returning it together
+/// with its expected decoded form.
+#[allow(clippy::too_many_lines)]
+fn random_instruction(rng: &mut ThreadRng) -> (EncodedInstruction, Instruction) {
+    let size = *GPR_SIZES.choose(rng).unwrap();
+    let dst = random_gpr(rng, size);
+    let src = random_gpr(rng, size);
+    let mem = random_memory(rng);
+    let qword = random_gpr(rng, MachineSize::QWord);
+    let (xmm_dst, xmm_src) = (random_xmm(rng), random_xmm(rng));
+    match rng.gen_range(0..25) {
+        0 => (
+            mov::encode_mov_reg_reg(dst, src).unwrap(),
+            Instruction::MovRegReg { dst, src },
+        ),
+        1 => {
+            let imm = random_immediate(rng, size);
+            (
+                mov::encode_mov_reg_imm(dst, imm).unwrap(),
+                mov_reg_imm(dst, imm),
+            )
+        }
+        2 => (
+            mov::encode_mov_reg_mem(dst, mem).unwrap(),
+            Instruction::MovRegMem { dst, src: mem },
+        ),
+        3 => (
+            mov::encode_mov_mem_reg(mem, src).unwrap(),
+            Instruction::MovMemReg { dst: mem, src },
+        ),
+        4 => {
+            let imm = rng.gen();
+            (
+                mov::encode_movabs_reg_imm(qword, imm).unwrap(),
+                Instruction::MovRegImm { dst: qword, imm },
+            )
+        }
+        5 => {
+            let src = random_gpr(rng, MachineSize::DWord);
+            (
+                mov::encode_movsxd_reg_reg(qword, src).unwrap(),
+                Instruction::MovsxdRegReg { dst: qword, src },
+            )
+        }
+        6 => (
+            mov::encode_movsxd_reg_mem(qword, mem).unwrap(),
+            Instruction::MovsxdRegMem {
+                dst: qword,
+                src: mem,
+            },
+        ),
+        7 => {
+            let size = *GPR_SIZES[1..].choose(rng).unwrap();
+            let dst = random_gpr(rng, size);
+            (
+                lea::encode_lea(dst, mem).unwrap(),
+                Instruction::Lea { dst, src: mem },
+            )
+        }
+        8 => {
+            let op = *ArithmeticOp::ALL.choose(rng).unwrap();
+            (
+                arith::encode_arith_reg_reg(op, dst, src).unwrap(),
+                Instruction::ArithRegReg { op, dst, src },
+            )
+        }
+        9 => {
+            let op = *ArithmeticOp::ALL.choose(rng).unwrap();
+            let bits = u32::from(size.as_bytes().min(4)) * 8;
+            #[allow(clippy::cast_possible_truncation)]
+            let imm = random_signed(rng, bits) as i32;
+            (
+                arith::encode_arith_reg_imm(op, dst, imm).unwrap(),
+                Instruction::ArithRegImm { op, dst, imm },
+            )
+        }
+        10 => {
+            let op = *ArithmeticOp::ALL.choose(rng).unwrap();
+            (
+                arith::encode_arith_reg_mem(op, dst, mem).unwrap(),
+                Instruction::ArithRegMem { op, dst, src: mem },
+            )
+        }
+        11 => {
+            let op = *ArithmeticOp::ALL.choose(rng).unwrap();
+            (
+                arith::encode_arith_mem_reg(op, mem, src).unwrap(),
+                Instruction::ArithMemReg { op, dst: mem, src },
+            )
+        }
+        12 if size != MachineSize::Byte => (
+            muldiv::encode_imul_reg_reg(dst, src).unwrap(),
+            Instruction::ImulRegReg { dst, src },
+        ),
+        13 if size != MachineSize::Byte => (
+            muldiv::encode_imul_reg_mem(dst, mem).unwrap(),
+            Instruction::ImulRegMem { dst, src: mem },
+        ),
+        14 => (muldiv::encode_div(src).unwrap(), Instruction::Div { src }),
+        15 => (muldiv::encode_idiv(src).unwrap(), Instruction::Idiv { src }),
+        16 => {
+            let op = *ShiftOp::ALL.choose(rng).unwrap();
+            (
+                shift::encode_shift_reg_cl(op, dst).unwrap(),
+                Instruction::ShiftRegCl { op, dst },
+            )
+        }
+        17 => {
+            let op = *ShiftOp::ALL.choose(rng).unwrap();
+            let imm = rng.gen();
+            (
+                shift::encode_shift_reg_imm(op, dst, imm).unwrap(),
+                Instruction::ShiftRegImm { op, dst, imm },
+            )
+        }
+        18 => (
+            stack::encode_push(qword).unwrap(),
+            Instruction::Push { reg: qword },
+        ),
+        19 => (
+            stack::encode_pop(qword).unwrap(),
+            Instruction::Pop { reg: qword },
+        ),
+        20 => {
+            let cond = *CONDITIONS.choose(rng).unwrap();
+            let rel = rng.gen();
+            (
+                jcc::encode_jcc_rel32(cond, rel),
+                Instruction::JccRel32 { cond, rel },
+            )
+        }
+        21 => {
+            let target = qword;
+            if rng.gen() {
+                (
+                    jmp::encode_jmp_reg(target).unwrap(),
+                    Instruction::JmpReg { target },
+                )
+            } else {
+                (
+                    call::encode_call_reg(target).unwrap(),
+                    Instruction::CallReg { target },
+                )
+            }
+        }
+        22 => {
+            let op = *FloatArithmeticOp::ALL.choose(rng).unwrap();
+            (
+                sse::encode_float_arith_xmm_xmm(op, xmm_dst, xmm_src),
+                Instruction::FloatArithXmmXmm {
+                    op,
+                    dst: xmm_dst,
+                    src: xmm_src,
+                },
+            )
+        }
+        23 if size != MachineSize::Byte => {
+            let op = *BitCountOp::ALL.choose(rng).unwrap();
+            if rng.gen() {
+                (
+                    bitcount::encode_bit_count_reg_reg(op, dst, src).unwrap(),
+                    Instruction::BitCountRegReg { op, dst, src },
+                )
+            } else {
+                (
+                    bitcount::encode_bit_count_reg_mem(op, dst, mem).unwrap(),
+                    Instruction::BitCountRegMem { op, dst, src: mem },
+                )
+            }
+        }
+        _ => match rng.gen_range(0..4) {
+            0 => (
+                sse::encode_movsd_xmm_mem(xmm_dst, mem),
+                Instruction::MovsdXmmMem {
+                    dst: xmm_dst,
+                    src: mem,
+                },
+            ),
+            1 => (
+                sse::encode_movss_mem_xmm(mem, xmm_src),
+                Instruction::MovssMemXmm {
+                    dst: mem,
+                    src: xmm_src,
+                },
+            ),
+            2 => (
+                sse::encode_movaps_xmm_xmm(xmm_dst, xmm_src),
+                Instruction::MovapsXmmXmm {
+                    dst: xmm_dst,
+                    src: xmm_src,
+                },
+            ),
+            _ => {
+                let op = *FloatArithmeticOp::ALL.choose(rng).unwrap();
+                (
+                    sse::encode_float_arith_xmm_mem(op, xmm_dst, mem),
+                    Instruction::FloatArithXmmMem {
+                        op,
+                        dst: xmm_dst,
+                        src: mem,
+                    },
+                )
+            }
+        },
+    }
+}
+
+#[test]
+fn test_random_roundtrip() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..ITERATIONS {
+        let (encoded, expected) = random_instruction(&mut rng);
+        assert_eq!(
+            roundtrip(&encoded),
+            expected,
+            "bytes: {:02X?}",
+            encoded.as_slice()
+        );
+    }
+}
+
+#[test]
+fn test_random_roundtrip_truncated() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..ITERATIONS {
+        let (encoded, _) = random_instruction(&mut rng);
+        let bytes = encoded.as_slice();
+        let len = rng.gen_range(0..bytes.len());
+        assert!(
+            decode(&bytes[..len]).is_err(),
+            "bytes: {:02X?}",
+            &bytes[..len]
+        );
+    }
+}
+
+#[test]
+fn test_random_bytes_decode() {
+    let mut rng = rand::thread_rng();
+    let mut buffer = [0u8; 2 * MAX_INSTRUCTION_SIZE];
+    for _ in 0..ITERATIONS * 10 {
+        let len = rng.gen_range(0..=buffer.len());
+        rng.fill(&mut buffer[..len]);
+        if let Ok(decoded) = decode(&buffer[..len]) {
+            let decoded_len = usize::from(decoded.len());
+            assert!(decoded_len <= MAX_INSTRUCTION_SIZE.min(len));
+            // Bytes after the instruction do not change its meaning.
+            let again = decode(&buffer[..decoded_len]).unwrap();
+            assert_eq!(again.instruction(), decoded.instruction());
+        }
+    }
+}